@@ -0,0 +1,168 @@
+//! `--accessible`: output screen readers can follow. Nothing is animated or redrawn, so there are no
+//! spinners, and questions are plain lines asked one after the other, read a line at a time:
+//! choices are listed numbered and chosen by their number or name.
+//!
+//! It is turned on as well when a screen reader seems to be running: when `$ACCESSIBLE` is set, as
+//! for other terminal tools, when GNOME loads its accessibility bridge, or within Emacspeak.
+
+use anyhow::{bail, Result};
+use console::Term;
+use std::{
+    env,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::interactive::LIST_SEP;
+use crate::prompt_timeout;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns accessible output on if `accessible` is set or a screen reader is detected.
+pub fn configure(accessible: bool) {
+    ENABLED.store(accessible || screen_reader_detected(), Ordering::Relaxed);
+}
+
+/// Whether output is meant for screen readers.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Whether questions are asked as plain lines: for screen readers, and when they time out, see
+/// [`crate::prompt_timeout`].
+pub fn line_prompts() -> bool {
+    enabled() || prompt_timeout::asking()
+}
+
+fn screen_reader_detected() -> bool {
+    let set = |name: &str| env::var_os(name).is_some_and(|value| !value.is_empty());
+    set("ACCESSIBLE")
+        || set("EMACSPEAK_DIR")
+        || env::var("GTK_MODULES").is_ok_and(|modules| modules.contains("atk-bridge"))
+}
+
+/// Asks `prompt`, returning the line answered, or `default` if the answer is empty.
+pub fn input(prompt: &str, default: Option<&str>) -> Result<String> {
+    let answer = ask(prompt)?;
+    Ok(match default {
+        Some(default) if answer.is_empty() => default.to_string(),
+        _ => answer,
+    })
+}
+
+/// Asks to choose one of `items` by its number or name, `default` being chosen by an empty
+/// answer, returning the index of the chosen one.
+pub fn select(prompt: &str, items: &[String], default: usize) -> Result<usize> {
+    if items.is_empty() {
+        bail!("no items to choose from");
+    }
+    list(prompt, items)?;
+    loop {
+        let answer = ask(&format!(
+            "Number of your choice, {} by default:",
+            default + 1
+        ))?;
+        if answer.is_empty() {
+            return Ok(default);
+        }
+        match choice(items, &answer) {
+            Some(chosen) => return Ok(chosen),
+            None => not_a_choice(&answer, items.len())?,
+        }
+    }
+}
+
+/// Asks to choose any of `items` by their numbers or names, separated by commas, those `selected`
+/// being chosen by an empty answer, and none by `none`, returning the indices of the chosen ones.
+pub fn multi_select(prompt: &str, items: &[String], selected: &[bool]) -> Result<Vec<usize>> {
+    list(prompt, items)?;
+    let defaults = selected
+        .iter()
+        .enumerate()
+        .filter(|(_, selected)| **selected)
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let numbers = defaults
+        .iter()
+        .map(|index| (index + 1).to_string())
+        .collect::<Vec<_>>();
+    let question = if numbers.is_empty() {
+        "Numbers of your choices, separated by commas, none by default:".to_string()
+    } else {
+        format!(
+            "Numbers of your choices, separated by commas, {} by default, or none:",
+            numbers.join(", ")
+        )
+    };
+    'ask: loop {
+        let answer = ask(&question)?;
+        if answer.is_empty() {
+            return Ok(defaults);
+        }
+        if answer.eq_ignore_ascii_case("none") {
+            return Ok(vec![]);
+        }
+        let mut chosen = vec![];
+        for part in answer.split(LIST_SEP).map(str::trim).filter(|p| !p.is_empty()) {
+            match choice(items, part) {
+                Some(index) if !chosen.contains(&index) => chosen.push(index),
+                Some(_) => {}
+                None => {
+                    not_a_choice(part, items.len())?;
+                    continue 'ask;
+                }
+            }
+        }
+        chosen.sort_unstable();
+        return Ok(chosen);
+    }
+}
+
+fn list(prompt: &str, items: &[String]) -> Result<()> {
+    let term = Term::stderr();
+    term.write_line(prompt)?;
+    for (index, item) in items.iter().enumerate() {
+        term.write_line(&format!("{}) {item}", index + 1))?;
+    }
+    Ok(())
+}
+
+/// The index of the item of `items` numbered or named `answer`.
+fn choice(items: &[String], answer: &str) -> Option<usize> {
+    answer.parse::<usize>().map_or_else(
+        |_| items.iter().position(|item| item == answer),
+        |number| (1..=items.len()).contains(&number).then(|| number - 1),
+    )
+}
+
+fn not_a_choice(answer: &str, count: usize) -> Result<()> {
+    Term::stderr().write_line(&format!(
+        "{answer} is not a choice, answer a number from 1 to {count}, or a name."
+    ))?;
+    Ok(())
+}
+
+/// Writes `question` on a line of its own, reading the answer from the next line.
+fn ask(question: &str) -> Result<String> {
+    Term::stderr().write_line(question)?;
+    let mut answer = String::new();
+    if prompt_timeout::read_line(&mut answer)? == 0 {
+        bail!("no answer to `{question}`, the input has ended");
+    }
+    Ok(answer.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices_are_numbered_from_one_or_named() {
+        let items = ["MIT".to_string(), "Apache-2.0".to_string()];
+
+        assert_eq!(choice(&items, "1"), Some(0));
+        assert_eq!(choice(&items, "Apache-2.0"), Some(1));
+        assert_eq!(choice(&items, "0"), None);
+        assert_eq!(choice(&items, "3"), None);
+        assert_eq!(choice(&items, "GPL"), None);
+    }
+}
@@ -7,15 +7,22 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::{info, Vcs};
+use crate::{info, FetcherConfig, LimitsConfig, Vcs};
 
 pub const CONFIG_FILE_NAME: &str = "cargo-generate.toml";
+/// Answers to placeholders with `remember = true`, kept next to the application config
+pub const REMEMBERED_FILE_NAME: &str = "cargo-generate-remembered.toml";
 
 #[derive(Deserialize, Default, Debug)]
 pub struct AppConfig {
     pub defaults: Option<DefaultsConfig>,
     pub favorites: Option<HashMap<String, FavoriteConfig>>,
     pub values: Option<HashMap<String, toml::Value>>,
+    pub theme: Option<ThemeConfig>,
+    /// External commands fetching templates, by the URL scheme they are for
+    pub fetchers: Option<HashMap<String, FetcherConfig>>,
+    /// How much rendering a template may write, see [`crate::limits`]
+    pub limits: Option<LimitsConfig>,
 }
 
 impl AppConfig {
@@ -43,6 +50,21 @@ pub struct FavoriteConfig {
 pub struct DefaultsConfig {
     /// relates to `crate::Args::ssh_identity`
     pub ssh_identity: Option<PathBuf>,
+    /// relates to `crate::Args::values_url`
+    pub values_url: Option<String>,
+    /// relates to `crate::Args::require_signed`
+    pub require_signed: Option<bool>,
+    /// The SSH public keys a signed template may be signed by, like `ssh-ed25519 AAAA...`
+    pub trusted_keys: Option<Vec<String>>,
+}
+
+/// Styles of the output, in the dotted notation of `console`, e.g. `bold.cyan` or `208`
+#[derive(Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct ThemeConfig {
+    /// Defaults, values and the selected item of prompts
+    pub accent: Option<String>,
+    pub warning: Option<String>,
+    pub error: Option<String>,
 }
 
 impl TryFrom<&Path> for AppConfig {
@@ -63,6 +85,81 @@ impl TryFrom<&Path> for AppConfig {
     }
 }
 
+/// The last answers given to placeholders with `remember = true`, offered as their default the
+/// next time any template asks for them.
+///
+/// They live in a file of their own, so that the application config is never rewritten.
+#[derive(Default, Debug)]
+pub struct RememberedAnswers {
+    path: Option<PathBuf>,
+    answers: toml::Table,
+    changed: bool,
+    /// Answers given before in this run, offered for all placeholders, see [`Self::offer`]
+    offered: toml::Table,
+}
+
+impl RememberedAnswers {
+    /// Loads the answers stored next to the application config at `app_config_path`.
+    pub fn load(app_config_path: &Path) -> Result<Self> {
+        let path = app_config_path.with_file_name(REMEMBERED_FILE_NAME);
+        let answers = if path.exists() {
+            toml::from_str(&fs::read_to_string(&path)?)
+                .with_context(|| format!("Invalid remembered answers in {}", path.display()))?
+        } else {
+            toml::Table::new()
+        };
+
+        Ok(Self {
+            path: Some(path),
+            answers,
+            changed: false,
+            offered: toml::Table::new(),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&toml::Value> {
+        self.answers.get(name)
+    }
+
+    /// Offers `answers` as the defaults of their placeholders, whether they are remembered or not,
+    /// e.g. to change some of the answers given to a preview. They are not saved.
+    pub fn offer(&mut self, answers: toml::Table) {
+        self.offered = answers;
+    }
+
+    /// The answer offered as the default of the placeholder `name`: one offered, or the one
+    /// remembered if the placeholder is to `remember`.
+    pub fn default_for(&self, name: &str, remember: bool) -> Option<&toml::Value> {
+        self.offered
+            .get(name)
+            .or_else(|| remember.then(|| self.get(name)).flatten())
+    }
+
+    pub fn remember(&mut self, name: &str, value: &serde_json::Value) {
+        let Ok(value) = toml::Value::try_from(value) else {
+            return;
+        };
+        if self.answers.get(name) != Some(&value) {
+            self.answers.insert(name.to_string(), value);
+            self.changed = true;
+        }
+    }
+
+    /// Writes the answers back, if any of them changed.
+    pub fn save(&self) -> Result<()> {
+        match &self.path {
+            Some(path) if self.changed => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, toml::to_string(&self.answers)?)?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 /// # Panics
 pub fn app_config_path(path: &Option<PathBuf>) -> Result<PathBuf> {
     path.as_ref()
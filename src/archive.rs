@@ -0,0 +1,39 @@
+//! Gzipped tar archives of templates, made and unpacked by the system's `tar`.
+
+use anyhow::{bail, Context, Result};
+use std::{path::Path, process::Command};
+
+/// What never goes into an archive of a template.
+pub const EXCLUDED: [&str; 2] = [".git", "target"];
+
+fn tar(args: &mut Command) -> Result<()> {
+    let output = args
+        .output()
+        .context("Failed to run `tar`, is it installed?")?;
+    if !output.status.success() {
+        bail!(
+            "`tar` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Packs the contents of `dir` into the gzipped tar archive `archive`.
+pub fn pack(dir: &Path, archive: &Path) -> Result<()> {
+    let mut command = Command::new("tar");
+    command.arg("-czf").arg(archive);
+    for excluded in EXCLUDED {
+        command.arg(format!("--exclude=./{excluded}"));
+    }
+    tar(command.arg("-C").arg(dir).arg("."))
+}
+
+/// Unpacks the gzipped tar archive `archive` into `dir`.
+pub fn unpack(archive: &Path, dir: &Path) -> Result<()> {
+    tar(Command::new("tar")
+        .arg("-xzf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dir))
+}
@@ -4,7 +4,7 @@ use std::{
 };
 
 use anyhow::{anyhow, Result};
-use clap::{Args, Parser};
+use clap::{Args, Parser, Subcommand};
 use serde::Deserialize;
 
 use crate::git;
@@ -57,7 +57,15 @@ pub enum Cli {
 }
 
 #[derive(Clone, Debug, Args)]
-#[command(arg_required_else_help(true), version, about)]
+#[command(
+    arg_required_else_help(true),
+    args_conflicts_with_subcommands(true),
+    subcommand_negates_reqs(true),
+    disable_help_subcommand(true),
+    version,
+    about,
+    after_help = crate::exit_code::HELP
+)]
 pub struct GenerateArgs {
     #[command(flatten)]
     pub template_path: TemplatePath,
@@ -78,6 +86,7 @@ pub struct GenerateArgs {
             "define",
             "init",
             "template_values_file",
+            "values_url",
             "ssh_identity",
             "test",
         ])
@@ -113,16 +122,51 @@ pub struct GenerateArgs {
     #[arg(long, action)]
     pub continue_on_error: bool,
 
+    /// Fail on variables that are not defined, and on files or names that fail to render, rather
+    /// than rendering them as nothing or leaving them as they are
+    #[arg(long, action)]
+    pub strict: bool,
+
     /// Pass template values through a file. Values should be in the format `key=value`, one per
     /// line
     #[arg(long="values-file", value_parser, alias="template-values-file", value_name="FILE", help_heading = heading::OUTPUT_PARAMETERS)]
     pub template_values_file: Option<String>,
 
+    /// Download default template values from a URL, as a JSON or TOML document in the format of
+    /// the values file. Values given otherwise take precedence
+    #[arg(long, value_name = "URL", help_heading = heading::OUTPUT_PARAMETERS)]
+    pub values_url: Option<String>,
+
     /// If silent mode is set all variables will be extracted from the template_values_file. If a
     /// value is missing the project generation will fail
     #[arg(long, short, requires("name"), action)]
     pub silent: bool,
 
+    /// Take the default of every placeholder having one without asking, only asking for the
+    /// placeholders without a default
+    #[arg(long, action)]
+    pub defaults: bool,
+
+    /// Reuse the answers given when generating from the same template last failed, asking only
+    /// for those not given then
+    #[arg(long, action)]
+    pub resume: bool,
+
+    /// Take the default of a placeholder nobody answered within SECS seconds, and of all those
+    /// after it without asking, failing for placeholders without a default
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub prompt_timeout: Option<u64>,
+
+    /// Ask in the language LANG, like `de` or `pt-BR`, if the template has prompts in it. Defaults
+    /// to the language of the locale
+    #[arg(long, value_name = "LANG")]
+    pub lang: Option<String>,
+
+    /// Ask plain questions one after the other, with numbered choices, and show no spinners, for
+    /// screen readers. Also enabled when a screen reader is detected
+    #[arg(long, action)]
+    pub accessible: bool,
+
     /// Use specific configuration file. Defaults to $CARGO_HOME/cargo-generate or
     /// $HOME/.cargo/cargo-generate
     #[arg(short, long, value_parser)]
@@ -152,6 +196,11 @@ pub struct GenerateArgs {
     #[arg(long, short, number_of_values = 1, value_parser, help_heading = heading::OUTPUT_PARAMETERS)]
     pub define: Vec<String>,
 
+    /// A folder to look up templates `{% include %}`d or `{% import %}`ed by the template in, after
+    /// the template itself and its own include roots. Can be given several times
+    #[arg(long, number_of_values = 1, value_parser, value_name = "DIR", help_heading = heading::OUTPUT_PARAMETERS)]
+    pub include_root: Vec<PathBuf>,
+
     /// Generate the template directly into the current dir. No subfolder will be created and no vcs
     /// is initialized.
     #[arg(long, action, help_heading = heading::OUTPUT_PARAMETERS)]
@@ -171,6 +220,31 @@ pub struct GenerateArgs {
     #[arg(short, long, action, help_heading = heading::OUTPUT_PARAMETERS)]
     pub allow_commands: bool,
 
+    /// Don't run anything of the template: neither its hooks, postprocessing scripts, template
+    /// functions and `rhai` filters, nor the commands of its defaults. Variables the hooks would
+    /// set may be missing
+    #[arg(long, action, conflicts_with = "allow_commands", help_heading = heading::OUTPUT_PARAMETERS)]
+    pub no_scripts: bool,
+
+    /// Allows going online for checks, like whether the crate name is still free on crates.io, and
+    /// for hooks to download with `http::download` or generate from remote templates
+    #[arg(long, action, help_heading = heading::OUTPUT_PARAMETERS)]
+    pub allow_network: bool,
+
+    /// Refuse templates unless their `cargo-generate.toml` and hook scripts are signed by one of
+    /// the `trusted_keys` of the config file
+    #[arg(long, action, help_heading = heading::OUTPUT_PARAMETERS)]
+    pub require_signed: bool,
+
+    /// Show the files that would be generated, and some of their contents, before writing any,
+    /// offering to change the answers first
+    #[arg(long, action, conflicts_with = "silent", help_heading = heading::OUTPUT_PARAMETERS)]
+    pub preview: bool,
+
+    /// Keep what was generated when interrupted with Ctrl-C, rather than removing it
+    #[arg(long, action, help_heading = heading::OUTPUT_PARAMETERS)]
+    pub keep_partial: bool,
+
     /// Allow the template to overwrite existing files in the destination.
     #[arg(short, long, action, help_heading = heading::OUTPUT_PARAMETERS)]
     pub overwrite: bool,
@@ -179,9 +253,194 @@ pub struct GenerateArgs {
     #[arg(long, action, help_heading = heading::GIT_PARAMETERS)]
     pub skip_submodules: bool,
 
+    /// Unix timestamp used for the `current_*` date variables instead of the current time.
+    /// Defaults to $SOURCE_DATE_EPOCH, if set
+    #[arg(long, value_parser, value_name="SECONDS", help_heading = heading::OUTPUT_PARAMETERS)]
+    pub timestamp: Option<i64>,
+
+    /// UTC offset of the `current_*` date variables, e.g. `+02:00`. Defaults to UTC
+    #[arg(long, value_parser, value_name="OFFSET", allow_hyphen_values = true, help_heading = heading::OUTPUT_PARAMETERS)]
+    pub utc_offset: Option<String>,
+
+    /// Generate byte-identical output for the same template and answers: the date variables are
+    /// pinned to $SOURCE_DATE_EPOCH, or to the unix epoch, unless `--timestamp` is given
+    #[arg(long, action, help_heading = heading::OUTPUT_PARAMETERS)]
+    pub reproducible: bool,
+
+    /// Print plain ASCII symbols instead of emoji, also enabled by setting
+    /// $CARGO_GENERATE_NO_EMOJI
+    #[arg(long, action, help_heading = heading::OUTPUT_PARAMETERS)]
+    pub no_emoji: bool,
+
+    /// Record the template, its commit, the version of cargo-generate and a hash of the answers in
+    /// a `.cargo-generate.lock` in the generated project
+    #[arg(long, action, help_heading = heading::OUTPUT_PARAMETERS)]
+    pub lockfile: bool,
+
+    /// Write a `regenerate.sh` into the generated project, generating it again in place from the
+    /// same template and ref, with the same answers
+    #[arg(long, action, help_heading = heading::OUTPUT_PARAMETERS)]
+    pub regenerate_script: bool,
+
+    /// Time rendering each file, running each hook and each Rhai filter, and print the N slowest
+    /// of each (10 by default)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10", help_heading = heading::OUTPUT_PARAMETERS)]
+    pub profile_render: Option<usize>,
+
+    /// Print where the value of the variable NAME came from, and what overwrote it, once the
+    /// project is generated. May be given multiple times
+    #[arg(long, value_name = "NAME", help_heading = heading::OUTPUT_PARAMETERS)]
+    pub explain_var: Vec<String>,
+
+    /// When to use colors: `auto`, `always` or `never`. `auto` honors $NO_COLOR
+    #[arg(long, value_parser, default_value = "auto", value_name = "WHEN", help_heading = heading::OUTPUT_PARAMETERS)]
+    pub color: ColorChoice,
+
     /// All args after "--" on the command line.
     #[arg(skip)]
     pub other_args: Option<Vec<String>>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands run instead of generating a project.
+#[derive(Clone, Debug, Subcommand)]
+pub enum Command {
+    /// Publish a template to an OCI registry, to generate from with `oci://<REFERENCE>`
+    Publish(PublishArgs),
+    /// Bundle a template into a single archive, to generate from with `--path <ARCHIVE>`
+    Package(PackageArgs),
+    /// Remove the project generated last, if it is still as it was generated
+    Undo(UndoArgs),
+    /// Tell where a project generated with `--lockfile` came from, as its `.cargo-generate.lock`
+    /// records it
+    Info(InfoArgs),
+    /// Print a single file of a template as it would be generated, without generating anything
+    Render(Box<RenderArgs>),
+    /// Print the value of an expression, like a condition of `cargo-generate.toml`, with the values
+    /// of a template
+    Eval(Box<EvalArgs>),
+    /// Rewrite the `cargo-generate.toml` of a template written for an older config version, like
+    /// those of Liquid templates, to the current one
+    MigrateConfig(MigrateConfigArgs),
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct PublishArgs {
+    /// Where to publish the template, like `registry.example.com/templates/api:1.2.0`
+    #[arg(long, value_name = "REFERENCE")]
+    pub oci: String,
+
+    /// The directory of the template
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct PackageArgs {
+    /// The directory of the template
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Where to write the archive, by default `<DIRECTORY NAME>.tar.gz` in the current directory
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct MigrateConfigArgs {
+    /// The directory of the template, or its config file
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct UndoArgs {
+    /// Use specific configuration file. Defaults to $CARGO_HOME/cargo-generate or
+    /// $HOME/.cargo/cargo-generate
+    #[arg(short, long, value_parser)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct InfoArgs {
+    /// The directory of the generated project
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct RenderArgs {
+    /// The file to print, relative to the template, like `src/main.rs`
+    #[arg(long, value_name = "FILE")]
+    pub file: PathBuf,
+
+    #[command(flatten)]
+    pub context: ContextArgs,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct EvalArgs {
+    /// The expression to evaluate, like `license | lower` or `crate_type == "lib"`
+    #[arg(value_name = "EXPRESSION")]
+    pub expression: String,
+
+    #[command(flatten)]
+    pub context: ContextArgs,
+}
+
+/// The template and the values to render with, for the commands printing something rendered.
+#[derive(Clone, Debug, Args)]
+pub struct ContextArgs {
+    #[command(flatten)]
+    pub template_path: TemplatePath,
+
+    /// The project name to render with
+    #[arg(long, short, value_parser)]
+    pub name: Option<String>,
+
+    /// Define a value for use during template expansion. E.g `--define foo=bar`
+    #[arg(long, short, number_of_values = 1, value_parser)]
+    pub define: Vec<String>,
+
+    /// Pass template values through a file. Values should be in the format `key=value`, one per
+    /// line
+    #[arg(long="values-file", value_parser, alias="template-values-file", value_name="FILE")]
+    pub template_values_file: Option<String>,
+
+    /// Take all values from `--define` and the values file, or their defaults, without asking
+    #[arg(long, short, requires("name"), action)]
+    pub silent: bool,
+
+    /// Use specific configuration file. Defaults to $CARGO_HOME/cargo-generate or
+    /// $HOME/.cargo/cargo-generate
+    #[arg(short, long, value_parser)]
+    pub config: Option<PathBuf>,
+
+    /// Allows running system commands without being prompted
+    #[arg(short, long, action)]
+    pub allow_commands: bool,
+}
+
+impl ContextArgs {
+    /// The arguments to go through generating with until what is asked for is rendered,
+    /// generating into `destination` as with `--init`, as hooks may write to it.
+    pub fn generate_args(&self, destination: PathBuf) -> GenerateArgs {
+        GenerateArgs {
+            template_path: self.template_path.clone(),
+            name: self.name.clone(),
+            define: self.define.clone(),
+            template_values_file: self.template_values_file.clone(),
+            silent: self.silent,
+            config: self.config.clone(),
+            allow_commands: self.allow_commands,
+            init: true,
+            destination: Some(destination),
+            vcs: Some(Vcs::None),
+            ..GenerateArgs::default()
+        }
+    }
 }
 
 impl Default for GenerateArgs {
@@ -194,8 +453,15 @@ impl Default for GenerateArgs {
             verbose: false,
             quiet: false,
             continue_on_error: false,
+            strict: false,
             template_values_file: None,
+            values_url: None,
             silent: false,
+            defaults: false,
+            resume: false,
+            prompt_timeout: None,
+            lang: None,
+            accessible: false,
             config: None,
             vcs: None,
             lib: true,
@@ -203,13 +469,29 @@ impl Default for GenerateArgs {
             ssh_identity: None,
             gitconfig: None,
             define: Vec::default(),
+            include_root: Vec::default(),
             init: false,
             destination: None,
             force_git_init: false,
             allow_commands: false,
+            no_scripts: false,
+            allow_network: false,
+            preview: false,
+            keep_partial: false,
+            require_signed: false,
             overwrite: false,
             skip_submodules: false,
+            timestamp: None,
+            utc_offset: None,
+            reproducible: false,
+            no_emoji: false,
+            lockfile: false,
+            regenerate_script: false,
+            profile_render: None,
+            explain_var: Vec::default(),
+            color: ColorChoice::Auto,
             other_args: None,
+            command: None,
         }
     }
 }
@@ -333,13 +615,32 @@ impl FromStr for Vcs {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(anyhow!("Must be one of 'auto', 'always' or 'never'")),
+        }
+    }
+}
+
 impl Vcs {
     pub fn initialize(&self, project_dir: &Path, branch: Option<&str>, force: bool) -> Result<()> {
         match self {
             Self::None => Ok(()),
-            Self::Git => git::init(project_dir, branch, force)
-                .map(|_| ())
-                .map_err(anyhow::Error::from),
+            Self::Git => git::init(project_dir, branch, force),
         }
     }
 
@@ -0,0 +1,245 @@
+//! Case conversions of the template filters and the rhai functions, and ASCII slugs.
+//!
+//! ASCII text is converted by `heck`, so names keep converting the way they always did. Other text
+//! is split into words on its own, as `heck` neither separates caseless scripts like CJK from
+//! cased ones (`日本Project` would stay a single word), nor knows about titlecase letters.
+
+use heck::{
+    ToKebabCase, ToLowerCamelCase, ToPascalCase, ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase,
+    ToTitleCase, ToUpperCamelCase,
+};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+pub fn kebab_case(s: &str) -> String {
+    convert(s, ToKebabCase::to_kebab_case, "-", str::to_lowercase)
+}
+
+pub fn lower_camel_case(s: &str) -> String {
+    if s.is_ascii() {
+        return s.to_lower_camel_case();
+    }
+    let mut words = words(s).into_iter();
+    let first = words.next().map(str::to_lowercase).unwrap_or_default();
+    words.fold(first, |camel, word| camel + capitalize(word).as_str())
+}
+
+pub fn pascal_case(s: &str) -> String {
+    convert(s, ToPascalCase::to_pascal_case, "", capitalize)
+}
+
+pub fn shouty_kebab_case(s: &str) -> String {
+    convert(
+        s,
+        ToShoutyKebabCase::to_shouty_kebab_case,
+        "-",
+        str::to_uppercase,
+    )
+}
+
+pub fn shouty_snake_case(s: &str) -> String {
+    convert(
+        s,
+        ToShoutySnakeCase::to_shouty_snake_case,
+        "_",
+        str::to_uppercase,
+    )
+}
+
+pub fn snake_case(s: &str) -> String {
+    convert(s, ToSnakeCase::to_snake_case, "_", str::to_lowercase)
+}
+
+pub fn title_case(s: &str) -> String {
+    convert(s, ToTitleCase::to_title_case, " ", capitalize)
+}
+
+pub fn upper_camel_case(s: &str) -> String {
+    convert(s, ToUpperCamelCase::to_upper_camel_case, "", capitalize)
+}
+
+/// Transliterates `s` to lowercase ASCII words joined by `separator`, dropping everything
+/// without an ASCII equivalent, e.g. `Straße Café` becomes `strasse-cafe`.
+pub fn ascii_slug(s: &str, separator: &str) -> String {
+    let ascii = transliterate(s);
+    words(&ascii)
+        .into_iter()
+        .map(|word| {
+            word.chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// The name of the crate of a project, which must be ASCII to be valid.
+pub fn crate_name(project_name: &str) -> String {
+    if project_name.is_ascii() {
+        return project_name.to_snake_case();
+    }
+    match ascii_slug(project_name, "_") {
+        // nothing could be transliterated, let cargo complain about the name
+        slug if slug.is_empty() => snake_case(project_name),
+        slug => slug,
+    }
+}
+
+fn convert(
+    s: &str,
+    ascii: impl Fn(&str) -> String,
+    separator: &str,
+    word_case: impl Fn(&str) -> String,
+) -> String {
+    if s.is_ascii() {
+        return ascii(s);
+    }
+    words(s)
+        .into_iter()
+        .map(word_case)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| {
+        let rest = chars.flat_map(char::to_lowercase);
+        // a titlecase letter like `ǅ` already is the capital form at the start of a word
+        if kind(first) == Kind::Upper && !first.is_uppercase() {
+            std::iter::once(first).chain(rest).collect()
+        } else {
+            first.to_uppercase().chain(rest).collect()
+        }
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Upper,
+    Lower,
+    /// Letters of scripts without case, like CJK
+    Caseless,
+    /// Digits belong to the word they appear in
+    Neutral,
+}
+
+fn kind(c: char) -> Kind {
+    if c.is_lowercase() {
+        Kind::Lower
+    } else if c.is_uppercase() || c.to_lowercase().ne([c]) {
+        // titlecase letters like `ǅ` are neither upper- nor lowercase, but start a word
+        Kind::Upper
+    } else if c.is_alphabetic() {
+        Kind::Caseless
+    } else {
+        Kind::Neutral
+    }
+}
+
+/// Splits `s` into words at non-alphanumeric characters, at changes from lower- to uppercase, at
+/// the end of uppercase acronyms (`HTTPServer`) and between cased and caseless letters.
+fn words(s: &str) -> Vec<&str> {
+    let mut words = vec![];
+    let mut start = None;
+    let mut previous = Kind::Neutral;
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !c.is_alphanumeric() {
+            if let Some(start) = start.take() {
+                words.push(&s[start..i]);
+            }
+            continue;
+        }
+        let current = kind(c);
+        let next = chars.peek().map(|&(_, c)| kind(c));
+        let boundary = match (previous, current) {
+            (Kind::Lower, Kind::Upper) => true,
+            (Kind::Upper, Kind::Upper) => next == Some(Kind::Lower),
+            (Kind::Caseless, Kind::Upper | Kind::Lower)
+            | (Kind::Upper | Kind::Lower, Kind::Caseless) => true,
+            _ => false,
+        };
+        match start {
+            Some(word_start) if boundary => {
+                words.push(&s[word_start..i]);
+                start = Some(i);
+            }
+            Some(_) => {}
+            None => start = Some(i),
+        }
+        if current != Kind::Neutral {
+            previous = current;
+        }
+    }
+    if let Some(start) = start {
+        words.push(&s[start..]);
+    }
+    words
+}
+
+/// Decomposes accented letters into their base letter and drops the accents; letters that do not
+/// decompose are spelled out where there is a common ASCII spelling.
+fn transliterate(s: &str) -> String {
+    let mut ascii = String::with_capacity(s.len());
+    for c in s.nfkd().filter(|&c| !is_combining_mark(c)) {
+        match c {
+            'ß' => ascii.push_str("ss"),
+            'æ' => ascii.push_str("ae"),
+            'Æ' => ascii.push_str("Ae"),
+            'œ' => ascii.push_str("oe"),
+            'Œ' => ascii.push_str("Oe"),
+            'þ' => ascii.push_str("th"),
+            'Þ' => ascii.push_str("Th"),
+            'ø' => ascii.push('o'),
+            'Ø' => ascii.push('O'),
+            'đ' | 'ð' => ascii.push('d'),
+            'Đ' | 'Ð' => ascii.push('D'),
+            'ł' => ascii.push('l'),
+            'Ł' => ascii.push('L'),
+            'ı' => ascii.push('i'),
+            c => ascii.push(c),
+        }
+    }
+    ascii
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_words_across_scripts() {
+        assert_eq!(words("日本Project"), ["日本", "Project"]);
+        assert_eq!(
+            words("ÜberWächter-東京 tower"),
+            ["Über", "Wächter", "東京", "tower"]
+        );
+        assert_eq!(words("HTTPServerΩ2go"), ["HTTP", "Server", "Ω2go"]);
+        assert_eq!(pascal_case("日本project"), "日本Project");
+        assert_eq!(snake_case("ÜberWächter"), "über_wächter");
+        assert_eq!(lower_camel_case("Straße Café"), "straßeCafé");
+        assert_eq!(title_case("ǅemal bijedić"), "ǅemal Bijedić");
+    }
+
+    #[test]
+    fn ascii_text_converts_as_before() {
+        for s in ["lockFirmware", "HTTPServer", "foo-bar_baz 2", "ABC123Def"] {
+            assert_eq!(snake_case(s), s.to_snake_case());
+            assert_eq!(pascal_case(s), s.to_pascal_case());
+            assert_eq!(crate_name(s), s.to_snake_case());
+        }
+    }
+
+    #[test]
+    fn slugs_are_ascii() {
+        assert_eq!(ascii_slug("Überwächter", "-"), "uberwachter");
+        assert_eq!(ascii_slug("Straße Café", "_"), "strasse_cafe");
+        assert_eq!(ascii_slug("東京-tower", "-"), "tower");
+        assert_eq!(ascii_slug("ＡＢＣ Łódź", "-"), "abc-lodz");
+        assert_eq!(crate_name("Øresund Bro"), "oresund_bro");
+        assert_eq!(crate_name("日本語"), "日本語");
+    }
+}
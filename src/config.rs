@@ -1,33 +1,133 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use console::style;
 use indexmap::IndexMap;
+use regex::Regex;
 use semver::VersionReq;
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 use std::{collections::HashMap, fs};
 use std::{convert::TryFrom, io::ErrorKind};
 
+use crate::emoji;
+use crate::features::FeatureConfig;
+use crate::generators::{CiFeature, CiProvider, PreCommit};
+use crate::include_exclude::PatternSources;
+use crate::merge::MergeStrategy;
+use crate::migrate_config::{self, CONFIG_VERSION};
+use crate::postprocess::Postprocessor;
+use crate::targets::TargetConfig;
 use crate::Vcs;
 
 pub const CONFIG_FILE_NAME: &str = "cargo-generate.toml";
 
 #[derive(Deserialize, Debug, PartialEq, Default, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
+    /// The version of the schema of the config, 1 if not given, see [`crate::migrate_config`]
+    pub config_version: Option<u32>,
     pub template: Option<TemplateConfig>,
     pub placeholders: Option<TemplateSlotsTable>,
     pub hooks: Option<HooksConfig>,
     pub conditional: Option<HashMap<String, ConditionalConfig>>,
+    pub generators: Option<GeneratorsConfig>,
+    pub workspace_template: Option<WorkspaceTemplateConfig>,
+    /// Variables derived from the other variables, rendered in order after prompting
+    pub computed: Option<IndexMap<String, String>>,
+    /// Globs for some platforms only, by `cfg` expression, see [`crate::targets`]
+    pub target: Option<IndexMap<String, TargetConfig>>,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct HooksConfig {
-    pub init: Option<Vec<String>>,
-    pub pre: Option<Vec<String>>,
-    pub post: Option<Vec<String>>,
+    pub init: Option<Vec<HookScript>>,
+    pub pre: Option<Vec<HookScript>>,
+    pub post: Option<Vec<HookScript>>,
 }
 
+/// A hook, given by the path of its script, or as a table with the settings of the commands it
+/// runs, `{ script = "post.rhai", cwd = "dest", env = { RUST_LOG = "{{ crate_name }}" } }`.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum HookScript {
+    Script(String),
+    Declared(HookSettings),
+}
+
+impl HookScript {
+    pub fn script(&self) -> &str {
+        match self {
+            Self::Script(script) => script,
+            Self::Declared(settings) => &settings.script,
+        }
+    }
+}
+
+/// A hook script, where the commands it runs run, and with what environment.
 #[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HookSettings {
+    pub script: String,
+    /// `dest` for the destination folder, `template` for the template being expanded, the
+    /// default, or a path relative to it, rendered
+    pub cwd: Option<String>,
+    /// Variables added to the environment of the commands, with rendered values
+    pub env: Option<IndexMap<String, String>>,
+}
+
+/// Built-in generators a template can opt in to, see [`crate::generators`].
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GeneratorsConfig {
+    /// Emit license file(s) according to the `license` placeholder
+    pub license: Option<bool>,
+    /// Emit a `CODE_OF_CONDUCT.md`
+    pub code_of_conduct: Option<bool>,
+    /// Emit a `CONTRIBUTING.md`
+    pub contributing: Option<bool>,
+    /// Emit a CI pipeline for the given provider
+    pub ci: Option<CiConfig>,
+    /// Offer to install a pre-commit hook running `cargo fmt` and `cargo clippy`
+    pub pre_commit: Option<PreCommit>,
+    /// Offer to emit a `Dockerfile` and a devcontainer configuration
+    pub container: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CiConfig {
+    pub provider: CiProvider,
+    /// Jobs to include in the pipeline, defaults to `fmt`, `clippy` and `test`
+    pub features: Option<Vec<CiFeature>>,
+}
+
+/// A template generating a whole workspace, see [`crate::workspace_template`].
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceTemplateConfig {
+    pub members: Vec<WorkspaceMemberConfig>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceMemberConfig {
+    /// Folder of the member template, relative to the workspace template
+    pub template: String,
+    /// Placeholder whose value is the `project-name` of the member
+    pub name_placeholder: String,
+    /// Folder the member is generated into, defaults to the parent folder of `template`
+    pub directory: Option<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Default, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct TemplateConfig {
+    /// What the template generates, shown when choosing between the templates of a repository
+    pub description: Option<String>,
     pub sub_templates: Option<Vec<String>>,
+    pub kind: Option<TemplateKind>,
+    pub project_type: Option<ProjectType>,
 
     pub cargo_generate_version: Option<VersionReq>,
     pub include: Option<Vec<String>>,
@@ -38,6 +138,69 @@ pub struct TemplateConfig {
     /// If true, disables automatic whitespace stripping (trim_blocks and lstrip_blocks)
     /// Set to true to restore Liquid-like behavior where {%- and -%} are required for whitespace control
     pub preserve_whitespace: Option<bool>,
+    /// If true, generating fails on undefined variables and files that fail to render, see
+    /// [`crate::strict`]
+    pub strict: Option<bool>,
+    /// How generated files are combined with files already existing at the destination, by glob
+    pub merge: Option<IndexMap<String, MergeStrategy>>,
+    /// Processors run on the rendered files, by glob
+    pub postprocess: Option<IndexMap<String, Vec<Postprocessor>>>,
+    /// How many folders deep below the template root files are rendered, deeper ones are copied
+    /// as they are
+    pub max_depth: Option<usize>,
+    /// If true, symlinked folders within the template are replaced by copies of their targets,
+    /// which are rendered like any other folder
+    pub follow_symlinks: Option<bool>,
+    /// If false, hidden files and folders, those whose name starts with a dot, are copied as they
+    /// are instead of being rendered
+    pub render_hidden: Option<bool>,
+    /// Features the user picks from, each bringing placeholders, globs and `Cargo.toml` entries
+    pub features: Option<IndexMap<String, FeatureConfig>>,
+    /// Rhai scripts callable as template functions, by name, see [`crate::template_functions`]
+    pub functions: Option<IndexMap<String, String>>,
+    /// Folders `{% include %}` and `{% import %}` look up templates in, see
+    /// [`crate::template_includes`]
+    pub include_roots: Option<Vec<String>>,
+    /// Files rendered once all others are, by glob, e.g. to list what the others added to the
+    /// `state`, see [`crate::render_state`]
+    pub render_last: Option<Vec<String>>,
+    /// Template files kept as they are along with what they render to, by glob, see
+    /// [`crate::keep_source`]
+    pub keep_source: Option<IndexMap<String, bool>>,
+    /// Rhai scripts used as `rhai` filters, by glob, instead of those the template is found to use,
+    /// see [`crate::template_filters::rhai_filter_files`]
+    pub rhai_filters: Option<Vec<String>>,
+    /// Hooks declared along with the other scripts of the template, run after those of `[hooks]`
+    pub hooks: Option<HooksConfig>,
+    /// Other names for variables, by alias, see [`crate::variable_aliases`]
+    pub variable_aliases: Option<IndexMap<String, String>>,
+    /// Where the patterns of `include`, `exclude` and `ignore` merged from other sections come from
+    #[serde(skip)]
+    pub pattern_sources: PatternSources,
+}
+
+/// What a template generates.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TemplateKind {
+    /// A new project
+    #[default]
+    Project,
+    /// A feature added to an existing project, like CI or benchmarks, never replacing any of its
+    /// files, see [`crate::merge::OVERLAY_MERGE`]
+    Overlay,
+}
+
+/// What kind of project a template generates.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProjectType {
+    /// A crate, named after the project
+    #[default]
+    Rust,
+    /// Anything else: there is no crate name, the project is neither looked for in nor added to
+    /// a Cargo workspace, and its name is kept as given, so long as it names a folder
+    Generic,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
@@ -64,7 +227,7 @@ impl Config {
     pub(crate) fn from_path(path: &Option<impl AsRef<Path>>) -> Result<Self> {
         let mut config = match path {
             Some(path) => match fs::read_to_string(path) {
-                Ok(contents) => Self::try_from(contents)?,
+                Ok(contents) => Self::read(path.as_ref(), &contents)?,
                 Err(e) => match e.kind() {
                     ErrorKind::NotFound => Self::default(),
                     _ => anyhow::bail!(e),
@@ -76,33 +239,168 @@ impl Config {
         Ok(config)
     }
 
+    /// The config `contents` of the file at `path`, upgraded first if of an older version.
+    fn read(path: &Path, contents: &str) -> Result<Self> {
+        let table = toml::from_str(contents).map_err(|e| invalid_config(path, &e))?;
+        let contents = if migrate_config::version(&table)? < CONFIG_VERSION {
+            let upgraded = migrate_config::upgrade(contents);
+            if upgraded != contents {
+                migrate_config::warn_upgraded(path);
+            }
+            upgraded
+        } else {
+            contents.to_string()
+        };
+        Self::try_from(contents).map_err(|e| invalid_config(path, &e))
+    }
+
     pub fn get_init_hooks(&self) -> Vec<String> {
-        self.hooks
-            .as_ref()
-            .map(|h| h.init.clone().unwrap_or_default())
-            .unwrap_or_default()
+        self.get_hooks(|h| h.init.as_ref())
     }
 
     pub fn get_pre_hooks(&self) -> Vec<String> {
-        self.hooks
-            .as_ref()
-            .map(|h| h.pre.clone().unwrap_or_default())
-            .unwrap_or_default()
+        self.get_hooks(|h| h.pre.as_ref())
     }
 
     pub fn get_post_hooks(&self) -> Vec<String> {
+        self.get_hooks(|h| h.post.as_ref())
+    }
+
+    /// Whether the template has hooks, in `[hooks]` or `[template.hooks]`.
+    pub fn has_hooks(&self) -> bool {
+        self.all_hooks().next().is_some()
+    }
+
+    fn all_hooks(&self) -> impl Iterator<Item = &HooksConfig> {
         self.hooks
-            .as_ref()
-            .map(|h| h.post.clone().unwrap_or_default())
-            .unwrap_or_default()
+            .iter()
+            .chain(self.template.iter().flat_map(|t| t.hooks.as_ref()))
+    }
+
+    fn get_hooks(&self, kind: impl Fn(&HooksConfig) -> Option<&Vec<HookScript>>) -> Vec<String> {
+        let mut scripts: Vec<String> = vec![];
+        for hook in self.all_hooks().flat_map(|h| kind(h).into_iter().flatten()) {
+            if !scripts.iter().any(|script| script == hook.script()) {
+                scripts.push(hook.script().to_string());
+            }
+        }
+        scripts
+    }
+
+    /// The settings of the hooks declared with any, by script.
+    pub fn get_hook_settings(&self) -> IndexMap<String, HookSettings> {
+        let hooks = self.all_hooks().flat_map(|h| {
+            [&h.init, &h.pre, &h.post]
+                .into_iter()
+                .flatten()
+                .flatten()
+        });
+        hooks
+            .filter_map(|hook| match hook {
+                HookScript::Script(_) => None,
+                HookScript::Declared(settings) => Some((settings.script.clone(), settings.clone())),
+            })
+            .collect()
     }
 
     pub fn get_hook_files(&self) -> Vec<String> {
         let mut pre = self.get_init_hooks();
         pre.append(&mut self.get_pre_hooks());
         pre.append(&mut self.get_post_hooks());
+        pre.append(&mut self.get_postprocess_scripts());
+        pre.append(&mut self.get_function_scripts());
         pre
     }
+
+    pub fn get_function_scripts(&self) -> Vec<String> {
+        let mut scripts = vec![];
+        let functions = self
+            .template
+            .iter()
+            .flat_map(|t| t.functions.iter().flat_map(IndexMap::values));
+        for script in functions {
+            if !scripts.contains(script) {
+                scripts.push(script.clone());
+            }
+        }
+        scripts
+    }
+
+    pub fn get_postprocess_scripts(&self) -> Vec<String> {
+        let mut scripts = vec![];
+        let processors = self
+            .template
+            .iter()
+            .flat_map(|t| t.postprocess.iter().flat_map(|p| p.values().flatten()));
+        for script in processors.filter_map(Postprocessor::script) {
+            if !scripts.iter().any(|s| s == script) {
+                scripts.push(script.to_string());
+            }
+        }
+        scripts
+    }
+
+    /// Whether `name` is a placeholder of the template, conditional or not.
+    pub fn defines_placeholder(&self, name: &str) -> bool {
+        self.placeholders
+            .iter()
+            .chain(
+                self.conditional
+                    .iter()
+                    .flat_map(HashMap::values)
+                    .filter_map(|conditional| conditional.placeholders.as_ref()),
+            )
+            .any(|TemplateSlotsTable(placeholders)| placeholders.contains_key(name))
+    }
+}
+
+/// Matches the unknown key of a deserialization error, and the keys expected instead.
+static UNKNOWN_FIELD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"unknown field `([^`]+)`, expected (.*)").unwrap());
+
+/// The error of the config at `path` failing to parse, with the line of the error, and the key
+/// meant when a key is unknown.
+fn invalid_config(path: &Path, error: &toml::de::Error) -> anyhow::Error {
+    let hint = did_you_mean(error.message())
+        .map(|key| format!("\n{}", style(format!("Did you mean `{key}`?")).bold()))
+        .unwrap_or_default();
+    anyhow!(
+        "{} {} `{}`:\n{}{hint}",
+        emoji::ERROR,
+        style("Invalid template config").bold().red(),
+        style(path.display()).bold().yellow(),
+        error.to_string().trim_end(),
+    )
+}
+
+/// The expected key closest to the unknown one of the error `message`, if any is close enough to
+/// be a typo of it.
+fn did_you_mean(message: &str) -> Option<String> {
+    let captures = UNKNOWN_FIELD.captures(message)?;
+    let unknown = &captures[1];
+    captures[2]
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .map(|key| (edit_distance(unknown, key), key))
+        .filter(|(distance, _)| *distance <= (unknown.chars().count() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, key)| key.to_string())
+}
+
+/// The number of characters to insert, delete or replace to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let replaced = previous[j] + usize::from(a != *b);
+            current.push(replaced.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
 }
 
 /// Search through a folder structure for template configuration files, but look no deeper than
@@ -207,7 +505,10 @@ mod tests {
         assert_eq!(
             config.template,
             Some(TemplateConfig {
+                description: None,
                 sub_templates: None,
+                kind: None,
+                project_type: None,
                 cargo_generate_version: Some(VersionReq::from_str(">=0.8.0").unwrap()),
                 include: Some(vec!["Cargo.toml".into()]),
                 exclude: None,
@@ -215,11 +516,83 @@ mod tests {
                 vcs: None,
                 init: None,
                 preserve_whitespace: None,
+                strict: None,
+                merge: None,
+                postprocess: None,
+                max_depth: None,
+                follow_symlinks: None,
+                render_hidden: None,
+                features: None,
+                functions: None,
+                include_roots: None,
+                render_last: None,
+                keep_source: None,
+                rhai_filters: None,
+                hooks: None,
+                variable_aliases: None,
+                pattern_sources: Default::default(),
             })
         );
         assert!(config.placeholders.is_some());
     }
 
+    #[test]
+    fn hooks_of_the_template_table_run_after_the_others() {
+        let config = Config::try_from(
+            r#"
+            [hooks]
+            pre = ["a.rhai", "b.rhai"]
+            [template]
+            hooks = { init = ["init.rhai"], pre = ["b.rhai", "c.rhai"] }
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        assert!(config.has_hooks());
+        assert_eq!(config.get_init_hooks(), ["init.rhai"]);
+        assert_eq!(config.get_pre_hooks(), ["a.rhai", "b.rhai", "c.rhai"]);
+        assert!(config.get_post_hooks().is_empty());
+        assert!(!Config::default().has_hooks());
+    }
+
+    #[test]
+    fn hooks_can_be_declared_with_settings() {
+        let config = Config::try_from(
+            r#"
+            [hooks]
+            post = ["a.rhai", { script = "b.rhai", cwd = "dest", env = { A = "{{ x }}" } }]
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(config.get_post_hooks(), ["a.rhai", "b.rhai"]);
+        let settings = config.get_hook_settings();
+        assert_eq!(settings.keys().collect::<Vec<_>>(), ["b.rhai"]);
+        assert_eq!(settings["b.rhai"].cwd.as_deref(), Some("dest"));
+        assert_eq!(settings["b.rhai"].env.as_ref().unwrap()["A"], "{{ x }}");
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected_with_the_key_meant() {
+        let error = Config::try_from("[placeholder.foo]\ntype = \"string\"\n".to_string())
+            .unwrap_err();
+        assert_eq!(did_you_mean(error.message()).as_deref(), Some("placeholders"));
+
+        let error = Config::try_from("[template]\nexlude = [\"target\"]\n".to_string())
+            .unwrap_err();
+        assert_eq!(did_you_mean(error.message()).as_deref(), Some("exclude"));
+
+        let error = invalid_config(Path::new("cargo-generate.toml"), &error).to_string();
+        let error = console::strip_ansi_codes(&error);
+        assert!(error.contains("line 2"), "{error}");
+        assert!(error.ends_with("Did you mean `exclude`?"), "{error}");
+
+        let error = Config::try_from("[template]\nfoo = 1\n".to_string()).unwrap_err();
+        assert_eq!(did_you_mean(error.message()), None);
+    }
+
     #[test]
     fn config_try_from_handles_empty() {
         let result = Config::try_from("".to_string());
@@ -228,10 +601,15 @@ mod tests {
         assert_eq!(
             result,
             Config {
+                config_version: None,
                 template: None,
                 hooks: None,
                 placeholders: None,
                 conditional: Default::default(),
+                generators: None,
+                workspace_template: None,
+                computed: None,
+                target: None,
             }
         )
     }
@@ -308,4 +686,47 @@ mod tests {
         assert_eq!(b_type, Some(&Value::String("string".to_string())));
         assert_eq!(b_prompt, Some(&Value::String("bar".to_string())));
     }
+
+    #[test]
+    fn config_try_from_handles_ci_generator() {
+        let result = Config::try_from(
+            r#"
+            [generators.ci]
+            provider = "gitlab"
+            features = ["fmt", "release"]
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.generators.unwrap().ci,
+            Some(CiConfig {
+                provider: CiProvider::GitLab,
+                features: Some(vec![CiFeature::Fmt, CiFeature::Release]),
+            })
+        );
+    }
+
+    #[test]
+    fn config_try_from_handles_workspace_template() {
+        let result = Config::try_from(
+            r#"
+            [workspace_template]
+            members = [
+                { template = "members/core", name_placeholder = "core_name" },
+                { template = "members/cli", name_placeholder = "cli_name", directory = "bin" },
+            ]
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        let members = result.workspace_template.unwrap().members;
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].template, "members/core");
+        assert_eq!(members[0].directory, None);
+        assert_eq!(members[1].name_placeholder, "cli_name");
+        assert_eq!(members[1].directory.as_deref(), Some("bin"));
+    }
 }
@@ -3,8 +3,11 @@ use console::style;
 use log::{debug, warn};
 use std::{
     fs::{copy, read_dir, remove_file},
-    path::Path,
+    path::{Path, PathBuf},
 };
+use walkdir::WalkDir;
+
+use crate::merge::{merge_file, MergeRules};
 
 pub const TEMPLATE_SUFFIX: &str = ".liquid";
 
@@ -12,6 +15,7 @@ pub fn copy_files_recursively(
     src: impl AsRef<Path>,
     dst: impl AsRef<Path>,
     overwrite: bool,
+    merge_rules: &MergeRules,
 ) -> Result<()> {
     let dst_path = dst.as_ref();
 
@@ -29,9 +33,9 @@ pub fn copy_files_recursively(
             if !dst_dir.exists() {
                 std::fs::create_dir(&dst_dir)?;
             }
-            copy_files_recursively(src_entry.path(), dst_dir, overwrite)?;
+            copy_files_recursively(src_entry.path(), dst_dir, overwrite, merge_rules)?;
         } else if entry_type.is_file() {
-            copy_file(&src_entry.path(), dst_path, overwrite)?;
+            copy_file(&src_entry.path(), dst_path, overwrite, merge_rules)?;
         } else {
             // todo: maybe we better emit a warning but continue processing the other files
             warn!(
@@ -48,10 +52,79 @@ pub fn copy_files_recursively(
     Ok(())
 }
 
+/// The files and folders below `dst` that copying `src` into it touches, as they were before, so
+/// they can be restored if copying fails halfway, e.g. into an existing project with `--init`.
+pub struct Snapshot {
+    /// The contents of each file, `None` for files that didn't exist
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+    /// The folders that didn't exist, parents first
+    folders: Vec<PathBuf>,
+}
+
+impl Snapshot {
+    pub fn take(src: &Path, dst: &Path) -> Result<Self> {
+        let mut snapshot = Self {
+            files: vec![],
+            folders: vec![],
+        };
+        let entries = WalkDir::new(src)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|entry| entry.file_name() != ".git");
+        for entry in entries {
+            let entry = entry?;
+            let relative_path = entry.path().strip_prefix(src)?;
+            let relative_path = relative_path
+                .to_str()
+                .and_then(|path| path.strip_suffix(TEMPLATE_SUFFIX))
+                .map_or_else(|| relative_path.to_path_buf(), PathBuf::from);
+            let path = dst.join(relative_path);
+            if entry.file_type().is_dir() {
+                if !path.exists() {
+                    snapshot.folders.push(path);
+                }
+            } else if entry.file_type().is_file() {
+                let contents = path.is_file().then(|| std::fs::read(&path)).transpose()?;
+                snapshot.files.push((path, contents));
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Puts back the files as they were and removes the files and folders that didn't exist.
+    /// Whatever can't be restored is warned about.
+    pub fn restore(self) {
+        for (path, contents) in self.files {
+            let restored = match contents {
+                Some(contents) => std::fs::write(&path, contents),
+                None if path.exists() => remove_file(&path),
+                None => continue,
+            };
+            if let Err(error) = restored {
+                warn!(
+                    "{} `{}`: {error}",
+                    style("Failed restoring").bold().red(),
+                    style(path.display()).bold(),
+                );
+            }
+        }
+        for folder in self.folders.iter().rev() {
+            // anything left in it was not put there by copying
+            let _ = std::fs::remove_dir(folder);
+        }
+    }
+}
+
 /// move a file from src to dst, possibly overwriting existing files
 /// if overwrite is true skipping otherwise
 /// if the file has a .liquid suffix, the suffix will be removed in the destination, and overwritten if existing
-fn copy_file(src_path: &Path, dst: &Path, overwrite: bool) -> Result<()> {
+/// if a merge strategy matches an existing destination file, the file is merged into it instead
+fn copy_file(
+    src_path: &Path,
+    dst: &Path,
+    overwrite: bool,
+    merge_rules: &MergeRules,
+) -> Result<()> {
     let filename = src_path.file_name().unwrap().to_string_lossy().to_string();
     let dst_path = dst.join(&filename);
     let mut overwrite = overwrite;
@@ -66,6 +139,9 @@ fn copy_file(src_path: &Path, dst: &Path, overwrite: bool) -> Result<()> {
 
         // move the file to a new filename without the .liquid suffix, in any case
         let dst_path = dst.join(new_filename);
+        if merge_existing(src_path, &dst_path, merge_rules)? {
+            return Ok(());
+        }
         safe_copy(src_path, &dst_path, overwrite)?;
     } else if src_path
         .with_file_name(format!("{filename}{TEMPLATE_SUFFIX}"))
@@ -79,12 +155,27 @@ fn copy_file(src_path: &Path, dst: &Path, overwrite: bool) -> Result<()> {
         // if the file doesn't have a .liquid suffix, just copy it
         // and skip if flile with that name exists
         // possibly overwriting existing files if overwrite is true
+        if merge_existing(src_path, &dst_path, merge_rules)? {
+            return Ok(());
+        }
         safe_copy_skip_existing(src_path, &dst_path, overwrite)?;
     }
 
     Ok(())
 }
 
+/// Merges the file into an existing one, if the template has a merge strategy for it.
+/// Returns whether the file was merged.
+fn merge_existing(src_path: &Path, dst_path: &Path, merge_rules: &MergeRules) -> Result<bool> {
+    match merge_rules.strategy_for(dst_path) {
+        Some(strategy) if dst_path.exists() => {
+            merge_file(strategy, src_path, dst_path)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
 fn safe_copy(src_path: &Path, dst_path: &Path, overwrite: bool) -> Result<()> {
     if dst_path.exists() && !overwrite {
         bail!(
@@ -181,6 +272,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn snapshots_restore_what_copying_touched() {
+        let src = tempdir().unwrap();
+        std::fs::create_dir_all(src.path().join("src")).unwrap();
+        std::fs::write(src.path().join("README.md"), "new").unwrap();
+        std::fs::write(src.path().join("src/main.rs"), "new").unwrap();
+        let dst = tempdir().unwrap();
+        std::fs::write(dst.path().join("README.md"), "old").unwrap();
+
+        let snapshot = Snapshot::take(src.path(), dst.path()).unwrap();
+        copy_files_recursively(src.path(), dst.path(), true, &MergeRules::default()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dst.path().join("README.md")).unwrap(),
+            "new"
+        );
+
+        snapshot.restore();
+        assert_eq!(
+            std::fs::read_to_string(dst.path().join("README.md")).unwrap(),
+            "old"
+        );
+        assert!(!dst.path().join("src").exists());
+    }
+
     #[test]
     fn test_special_liquid_file_handling() {
         let tmp = tempdir().unwrap();
@@ -192,14 +307,14 @@ mod tests {
         let tmp2 = tempdir().unwrap();
 
         // copy the non liquid file first, should not copy anything
-        copy_file(f1.as_path(), tmp2.path(), false).unwrap();
+        copy_file(f1.as_path(), tmp2.path(), false, &MergeRules::default()).unwrap();
         assert!(
             !tmp2.path().join("README.md").exists(),
             "the file should not be copied"
         );
 
         // copy the liquid file, should copy the liquid file and remove the .liquid suffix
-        copy_file(f2.as_path(), tmp2.path(), false).unwrap();
+        copy_file(f2.as_path(), tmp2.path(), false, &MergeRules::default()).unwrap();
         assert!(
             tmp2.path().join("README.md").exists(),
             "the file should be copied and the .liquid suffix removed"
@@ -214,7 +329,7 @@ mod tests {
         let f4 = tmp2.path().join("README.md");
         std::fs::write(&f4, "Existing file, should be overwritten").unwrap();
         assert!(
-            copy_file(f2.as_path(), tmp2.path(), false).is_ok(),
+            copy_file(f2.as_path(), tmp2.path(), false, &MergeRules::default()).is_ok(),
             "the file should be copied"
         );
         assert_eq!(
@@ -0,0 +1,82 @@
+//! With `--allow-network`, checking that the crate name of a new project is still free on
+//! crates.io, as publishing it is often where a project is headed.
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use dialoguer::console::Term;
+use log::warn;
+use std::process::Command;
+
+use crate::{case, interactive, prompt_timeout::PromptTimeout};
+
+/// Overrides the API of crates.io, e.g. with a mirror or a registry for tests.
+pub const API_ENV: &str = "CARGO_GENERATE_CRATES_IO_API";
+const API: &str = "https://crates.io/api/v1";
+
+/// Whether a crate named `crate_name` is published on crates.io.
+fn is_taken(crate_name: &str) -> Result<bool> {
+    let api = std::env::var(API_ENV).unwrap_or_else(|_| API.to_string());
+    let output = Command::new("curl")
+        .args(["--silent", "--show-error", "--location", "--max-time", "10"])
+        // crates.io refuses requests without one
+        .args([
+            "--user-agent",
+            concat!("cargo-generate/", env!("CARGO_PKG_VERSION")),
+        ])
+        // the status follows the body on a line of its own, as there is no path discarding the body
+        // on every platform
+        .args(["--write-out", "\n%{http_code}"])
+        .arg(format!("{}/crates/{crate_name}", api.trim_end_matches('/')))
+        .output()
+        .context("Failed to run `curl`, is it installed?")?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.rsplit('\n').next().unwrap_or_default().trim() {
+        "200" => Ok(true),
+        "404" => Ok(false),
+        status => bail!("crates.io answered with status {status}"),
+    }
+}
+
+/// Warns when the crate name derived from `project_name` is taken on crates.io and, unless
+/// `silent`, asks for another name within `timeout`, returning the name to go on with.
+///
+/// Not being able to check is no reason to stop, it is only warned about.
+pub fn check_name_available(
+    mut project_name: String,
+    silent: bool,
+    timeout: &PromptTimeout,
+) -> Result<String> {
+    loop {
+        let crate_name = case::crate_name(&project_name);
+        match is_taken(&crate_name) {
+            Ok(false) => return Ok(project_name),
+            Ok(true) => {
+                warn!(
+                    "{} `{}` {}",
+                    style("The crate name").bold(),
+                    style(&crate_name).bold().yellow(),
+                    style("is already taken on crates.io").bold(),
+                );
+                if silent || !Term::stderr().is_term() {
+                    return Ok(project_name);
+                }
+                let another = interactive::another_name(&project_name, timeout)?;
+                if another == project_name {
+                    return Ok(project_name);
+                }
+                project_name = another;
+            }
+            Err(error) => {
+                warn!(
+                    "{} `{}`: {error}",
+                    style("Could not check crates.io for the crate name").bold(),
+                    style(&crate_name).bold().yellow(),
+                );
+                return Ok(project_name);
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! The `date` filter and `now` function of templates, formatting dates with the strftime
+//! specifiers of [`chrono`], e.g. `{{ now() | date("%A, %B %-d %Y") }}`.
+//!
+//! Dates are RFC 3339 or RFC 2822 strings, common forms like `2024-01-31`, `2024-01-31 12:30` or
+//! `31.01.2024`, unix timestamps, or in any form at all given its format, e.g.
+//! `{{ "01/31/24" | date("%Y", "%m/%d/%y") }}`. Dates without an offset are taken in the offset
+//! of the generation, UTC unless `--utc-offset` tells another. `now()` tells the moment of the
+//! generation in another offset or time zone, e.g. `{{ now("Europe/Paris") }}`.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use minijinja::value::ValueKind;
+use minijinja::{Environment, Error, ErrorKind, Value};
+use time::OffsetDateTime;
+
+/// Forms of dates and times with an offset that are recognized without being given, besides
+/// RFC 3339 and RFC 2822, e.g. the `2024-01-31 12:30:00 +0200` of liquid.
+const OFFSET_DATE_TIME_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f %z", "%Y-%m-%d %H:%M %z"];
+
+/// Forms of dates and times without an offset that are recognized without being given.
+const DATE_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Forms of dates that are recognized without being given.
+const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y"];
+
+/// Adds the `date` filter and the `now` function to `env`, `now()` being `generated_at`, the
+/// moment the project is generated at, whose offset dates without one are taken in.
+pub fn add_to(env: &mut Environment, generated_at: OffsetDateTime) {
+    let offset = FixedOffset::east_opt(generated_at.offset().whole_seconds()).unwrap_or(Utc.fix());
+    let generated_at =
+        DateTime::from_timestamp(generated_at.unix_timestamp(), generated_at.nanosecond())
+            .unwrap_or_default()
+            .with_timezone(&offset);
+    env.add_filter(
+        "date",
+        move |value: &Value, format: &str, input_format: Option<&str>| {
+            date(value, format, input_format, &offset)
+        },
+    );
+    env.add_function("now", move |offset: Option<&str>| {
+        now(&generated_at, offset)
+    });
+}
+
+/// `now()`, the moment `generated_at` as RFC 3339 date and time, in its offset or in `offset`:
+/// `Z`, `+09:00`, a time zone like `Europe/Paris`, or `local` for that of the system.
+fn now(generated_at: &DateTime<FixedOffset>, offset: Option<&str>) -> Result<String, Error> {
+    let now = match offset {
+        None => *generated_at,
+        Some(offset) => generated_at.with_timezone(&parse_offset(offset, generated_at)?),
+    };
+    Ok(now.to_rfc3339())
+}
+
+/// The `date` filter, formatting the date `value` with the strftime `format`, `value` being in
+/// `input_format` if given, and in `offset` unless it tells its own.
+fn date(
+    value: &Value,
+    format: &str,
+    input_format: Option<&str>,
+    offset: &FixedOffset,
+) -> Result<String, Error> {
+    let date = parse(value, input_format, offset)?;
+    let items = StrftimeItems::new(format).collect::<Vec<_>>();
+    if items.contains(&Item::Error) {
+        return Err(Error::new(
+            ErrorKind::InvalidOperation,
+            format!("`{format}` is no valid strftime format"),
+        ));
+    }
+    Ok(date.format_with_items(items.iter()).to_string())
+}
+
+fn parse(
+    value: &Value,
+    input_format: Option<&str>,
+    offset: &FixedOffset,
+) -> Result<DateTime<FixedOffset>, Error> {
+    let invalid = || {
+        let expected = input_format.map_or_else(
+            || "a date".to_string(),
+            |format| format!("a date in the format `{format}`"),
+        );
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("`{value}` is not {expected}"),
+        )
+    };
+
+    if value.kind() == ValueKind::Number {
+        let timestamp = i64::try_from(value.clone()).map_err(|_| invalid())?;
+        return DateTime::from_timestamp(timestamp, 0)
+            .map(|date| date.with_timezone(offset))
+            .ok_or_else(invalid);
+    }
+    let value = value.as_str().ok_or_else(invalid)?.trim();
+    let parsed = input_format.map_or_else(
+        || parse_known(value, offset),
+        |format| parse_with(value, format, offset),
+    );
+    parsed.ok_or_else(invalid)
+}
+
+/// The date `value` in one of the forms recognized without being given.
+fn parse_known(value: &str, offset: &FixedOffset) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_rfc2822(value))
+        .ok()
+        .or_else(|| {
+            OFFSET_DATE_TIME_FORMATS
+                .iter()
+                .find_map(|format| DateTime::parse_from_str(value, format).ok())
+        })
+        .or_else(|| {
+            DATE_TIME_FORMATS
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                .and_then(|date| in_offset(date, offset))
+        })
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+                .and_then(|date| in_offset(date.and_time(NaiveTime::MIN), offset))
+        })
+}
+
+/// The date `value` in the `format`, of a date and time with or without an offset, or of a date.
+fn parse_with(value: &str, format: &str, offset: &FixedOffset) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_str(value, format)
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, format)
+                .ok()
+                .and_then(|date| in_offset(date, offset))
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, format)
+                .ok()
+                .and_then(|date| in_offset(date.and_time(NaiveTime::MIN), offset))
+        })
+}
+
+fn in_offset(date: NaiveDateTime, offset: &FixedOffset) -> Option<DateTime<FixedOffset>> {
+    offset.from_local_datetime(&date).single()
+}
+
+/// The offset `offset` names at the moment `at`, `local` being the one of the system, and a time
+/// zone of the IANA database, like `Europe/Paris`, the one it has then.
+fn parse_offset(offset: &str, at: &DateTime<FixedOffset>) -> Result<FixedOffset, Error> {
+    let offset = offset.trim();
+    if offset.eq_ignore_ascii_case("local") {
+        return Ok(at.with_timezone(&Local).offset().fix());
+    }
+    if offset.eq_ignore_ascii_case("z") || offset.eq_ignore_ascii_case("utc") {
+        return Ok(Utc.fix());
+    }
+    if let Ok(zone) = offset.parse::<Tz>() {
+        return Ok(at.with_timezone(&zone).offset().fix());
+    }
+    offset.parse().map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!(
+                "`{offset}` is no UTC offset nor time zone, expected e.g. `+02:00`, `Z`, \
+                 `Europe/Paris` or `local`"
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(value: impl Into<Value>, format: &str, input_format: Option<&str>) -> String {
+        date(&value.into(), format, input_format, &Utc.fix()).unwrap()
+    }
+
+    #[test]
+    fn dates_are_formatted_with_strftime() {
+        assert_eq!(format("2024-01-05", "%Y", None), "2024");
+        assert_eq!(
+            format("2024-01-05T08:30:00+02:00", "%A, %B %-d %Y %H:%M %:z", None),
+            "Friday, January 5 2024 08:30 +02:00"
+        );
+        assert_eq!(
+            format("Fri, 05 Jan 2024 08:30:00 GMT", "%d/%m/%y", None),
+            "05/01/24"
+        );
+        assert_eq!(format("2024-01-05 08:30", "%j %I%p", None), "005 08AM");
+        assert_eq!(format("05.01.2024", "%F", None), "2024-01-05");
+        assert_eq!(
+            format("2024-01-05 08:30:00 +0200", "%H:%M %z", None),
+            "08:30 +0200"
+        );
+        assert_eq!(
+            format(1_704_443_400, "%+", None),
+            "2024-01-05T08:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn dates_are_parsed_in_the_format_given() {
+        assert_eq!(format("01/05/24", "%F", Some("%m/%d/%y")), "2024-01-05");
+        assert_eq!(
+            format("5 Jan 2024, 8:30", "%T", Some("%d %b %Y, %H:%M")),
+            "08:30:00"
+        );
+        assert!(date(&"2024-01-05".into(), "%F", Some("%m/%d/%y"), &Utc.fix()).is_err());
+    }
+
+    #[test]
+    fn invalid_dates_and_formats_fail() {
+        assert!(date(&"yesterday".into(), "%Y", None, &Utc.fix()).is_err());
+        assert!(date(&true.into(), "%Y", None, &Utc.fix()).is_err());
+        assert!(date(&"2024-01-05".into(), "%Q", None, &Utc.fix()).is_err());
+    }
+
+    #[test]
+    fn now_is_in_the_offset_asked() {
+        let at = DateTime::parse_from_rfc3339("2024-01-05T08:30:00Z").unwrap();
+        assert_eq!(
+            parse_offset("+05:30", &at).unwrap(),
+            FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap()
+        );
+        assert_eq!(parse_offset("UTC", &at).unwrap(), Utc.fix());
+        assert!(parse_offset("local", &at).is_ok());
+        assert!(parse_offset("Mars/Olympus_Mons", &at).is_err());
+        assert!(now(&at, Some("+09:00")).unwrap().ends_with("+09:00"));
+    }
+
+    #[test]
+    fn time_zones_have_the_offset_of_the_moment() {
+        let winter = DateTime::parse_from_rfc3339("2024-01-05T08:30:00Z").unwrap();
+        let summer = DateTime::parse_from_rfc3339("2024-07-05T08:30:00Z").unwrap();
+        assert_eq!(
+            parse_offset("Europe/Paris", &winter).unwrap(),
+            FixedOffset::east_opt(3600).unwrap()
+        );
+        assert_eq!(
+            parse_offset("Europe/Paris", &summer).unwrap(),
+            FixedOffset::east_opt(2 * 3600).unwrap()
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Asking where the project is generated when neither `--name` nor `--destination` tells, instead
+//! of using the current folder without a word: the folder the project folder is created in, the
+//! current one by default. It must be an existing folder that can be written to, and the tab key
+//! completes the names of folders.
+//!
+//! Nothing is asked when silent, testing, generating into an existing folder with `--init`, or
+//! without a terminal to ask on.
+
+use anyhow::Result;
+use dialoguer::console::Term;
+use dialoguer::{Completion, Input};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::{env, fs};
+
+use crate::project_variables::Prompt;
+use crate::prompt_timeout::PromptTimeout;
+use crate::user_parsed_input::UserParsedInput;
+use crate::{accessible, GenerateArgs, GenerateOptions};
+
+/// Asks for the destination of `user_parsed_input`, unless something pins it.
+pub fn ask_unless_pinned(
+    args: &GenerateArgs,
+    options: &GenerateOptions,
+    user_parsed_input: &mut UserParsedInput,
+    timeout: &PromptTimeout,
+) -> Result<()> {
+    if is_asked(args, options, user_parsed_input) {
+        let destination = ask(user_parsed_input.destination(), timeout)?;
+        user_parsed_input.set_destination(destination);
+    }
+    Ok(())
+}
+
+/// Whether the destination is asked for, as nothing pins it.
+fn is_asked(
+    args: &GenerateArgs,
+    options: &GenerateOptions,
+    user_parsed_input: &UserParsedInput,
+) -> bool {
+    args.name.is_none()
+        && args.destination.is_none()
+        && options.render_only.is_none()
+        && !user_parsed_input.init()
+        && !user_parsed_input.test()
+        && !user_parsed_input.silent()
+        && Term::stderr().is_term()
+}
+
+/// Asks for the folder to create the project folder in, `default` being the one answered by
+/// nothing, within `timeout`.
+fn ask(default: &Path, timeout: &PromptTimeout) -> Result<PathBuf> {
+    let default = default.display().to_string();
+    let answer = if timeout.active() {
+        timeout.ask("destination", Some(default.clone()), || ask_now(&default))?
+    } else {
+        ask_now(&default)?
+    };
+    Ok(resolve(&answer))
+}
+
+fn ask_now(default: &str) -> Result<String> {
+    let prompt = Prompt::new(
+        "Destination, the project folder is created within",
+        Some(default.to_string()),
+    );
+    if accessible::line_prompts() {
+        loop {
+            let answer = accessible::input(&prompt.with_default, Some(default))?;
+            match unusable_because(&resolve(&answer)) {
+                Some(reason) => Term::stderr().write_line(&reason)?,
+                None => return Ok(answer),
+            }
+        }
+    }
+    Ok(Input::<String>::new()
+        .with_prompt(&prompt.styled)
+        .default(default.to_string())
+        .completion_with(&FolderCompletion)
+        .validate_with(|answer: &String| unusable_because(&resolve(answer)).map_or(Ok(()), Err))
+        .interact_text()?)
+}
+
+/// The folder `answer` names, with a leading `~` for the home folder, relative to the current
+/// folder unless absolute.
+fn resolve(answer: &str) -> PathBuf {
+    let answer = answer.trim();
+    let home = || home::home_dir().unwrap_or_default();
+    let path = match answer.strip_prefix('~') {
+        Some("") => home(),
+        Some(rest) if rest.starts_with(['/', MAIN_SEPARATOR]) => home().join(&rest[1..]),
+        _ => PathBuf::from(answer),
+    };
+    env::current_dir().unwrap_or_default().join(path)
+}
+
+/// Why the project can't be created within `folder`, if it can't.
+fn unusable_because(folder: &Path) -> Option<String> {
+    if !folder.is_dir() {
+        return Some(format!("`{}` is no existing folder", folder.display()));
+    }
+    tempfile::tempfile_in(folder)
+        .err()
+        .map(|error| format!("`{}` can't be written to: {error}", folder.display()))
+}
+
+/// Completes the name of the folder being typed, as far as the folders starting alike agree.
+struct FolderCompletion;
+
+impl Completion for FolderCompletion {
+    fn get(&self, input: &str) -> Option<String> {
+        complete(input, &resolve)
+    }
+}
+
+/// `input` with the name of its last folder completed, the folders of the one before listed in
+/// the folder `resolve` finds for it.
+fn complete(input: &str, resolve: &dyn Fn(&str) -> PathBuf) -> Option<String> {
+    let split = input
+        .rfind(['/', MAIN_SEPARATOR])
+        .map_or(0, |index| index + 1);
+    let (parent, start) = input.split_at(split);
+    let mut names = fs::read_dir(resolve(parent))
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(start) && (!start.is_empty() || !name.starts_with('.')))
+        .collect::<Vec<_>>();
+    names.sort();
+    let completed = match names.as_slice() {
+        [] => return None,
+        [name] => format!("{name}/"),
+        [first, .., last] => first
+            .char_indices()
+            .zip(last.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(first.clone(), |((index, _), _)| first[..index].to_string()),
+    };
+    (completed != start).then(|| format!("{parent}{completed}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folder_names_are_completed_as_far_as_they_agree() {
+        let root = tempfile::tempdir().unwrap();
+        for folder in ["projects", "prototypes", "src/bin", ".hidden"] {
+            fs::create_dir_all(root.path().join(folder)).unwrap();
+        }
+        fs::write(root.path().join("projects.txt"), "").unwrap();
+        let complete = |input| complete(input, &|parent: &str| root.path().join(parent));
+
+        assert_eq!(complete("p").as_deref(), Some("pro"));
+        assert_eq!(complete("pro"), None);
+        assert_eq!(complete("proj").as_deref(), Some("projects/"));
+        assert_eq!(complete("s").as_deref(), Some("src/"));
+        assert_eq!(complete("src/").as_deref(), Some("src/bin/"));
+        assert_eq!(complete(".h").as_deref(), Some(".hidden/"));
+        assert_eq!(complete("x"), None);
+    }
+
+    #[test]
+    fn only_existing_folders_are_usable() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("file"), "").unwrap();
+
+        assert_eq!(unusable_because(root.path()), None);
+        assert!(unusable_because(&root.path().join("file")).is_some());
+        assert!(unusable_because(&root.path().join("missing")).is_some());
+    }
+}
@@ -1,8 +1,53 @@
-use console::Emoji;
-
-pub static ERROR: Emoji<'_, '_> = Emoji("⛔  ", "");
-pub static SPARKLE: Emoji<'_, '_> = Emoji("✨  ", "");
-pub static WARN: Emoji<'_, '_> = Emoji("⚠️  ", "");
-pub static WRENCH: Emoji<'_, '_> = Emoji("🔧  ", "");
-pub static SHRUG: Emoji<'_, '_> = Emoji("🤷  ", "");
-pub static DIAMOND: Emoji<'_, '_> = Emoji("🔸  ", "");
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Set to any non-empty value to get plain ASCII symbols instead of emoji, like `--no-emoji`.
+pub const NO_EMOJI_ENV: &str = "CARGO_GENERATE_NO_EMOJI";
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// A symbol prefixing messages, printed as emoji if both the user and the terminal want them,
+/// and as plain ASCII otherwise.
+pub struct Emoji(&'static str, &'static str);
+
+pub static ERROR: Emoji = Emoji("⛔  ", "error:");
+pub static SPARKLE: Emoji = Emoji("✨  ", "*");
+pub static WARN: Emoji = Emoji("⚠️  ", "warning:");
+pub static WRENCH: Emoji = Emoji("🔧  ", ">");
+pub static SHRUG: Emoji = Emoji("🤷  ", "?");
+pub static DIAMOND: Emoji = Emoji("🔸  ", "-");
+
+/// Turns emoji and other non-ASCII output off if `no_emoji` is set or [`NO_EMOJI_ENV`] is
+/// non-empty.
+pub fn configure(no_emoji: bool) {
+    let no_emoji = no_emoji || std::env::var_os(NO_EMOJI_ENV).is_some_and(|v| !v.is_empty());
+    ENABLED.store(!no_emoji, Ordering::Relaxed);
+}
+
+/// Whether non-ASCII symbols may be printed.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed) && console::Term::stdout().features().wants_emoji()
+}
+
+impl fmt::Display for Emoji {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if enabled() { self.0 } else { self.1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_ascii_when_disabled() {
+        configure(true);
+        let plain = [&ERROR, &SPARKLE, &WARN, &WRENCH, &SHRUG, &DIAMOND].map(ToString::to_string);
+        configure(false);
+
+        assert!(plain.iter().all(|symbol| symbol.is_ascii()), "{plain:?}");
+        assert_eq!(plain[0], "error:");
+    }
+}
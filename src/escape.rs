@@ -0,0 +1,110 @@
+//! Escaping of the template filters, for values embedded in generated config and source files.
+//!
+//! All filters but `shell_quote` escape the contents of a double-quoted string, the quotes are
+//! left to the template: `description = "{{ description | toml_escape }}"`.
+
+/// Escapes the contents of a TOML basic string, or of a quoted key.
+pub fn toml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\u{c}' => escaped.push_str("\\f"),
+            '\r' => escaped.push_str("\\r"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04X}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the contents of a JSON string.
+pub fn json_escape(s: &str) -> String {
+    let quoted = serde_json::Value::from(s).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Escapes the contents of a double-quoted YAML scalar.
+pub fn yaml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\0' => escaped.push_str("\\0"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\u{85}' => escaped.push_str("\\N"),
+            '\u{2028}' => escaped.push_str("\\L"),
+            '\u{2029}' => escaped.push_str("\\P"),
+            c if c.is_control() => escaped.push_str(&format!("\\x{:02X}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the contents of a Rust string literal.
+pub fn rust_string_escape(s: &str) -> String {
+    s.escape_debug().to_string()
+}
+
+/// Quotes a single word for POSIX shells, unless it needs no quotes.
+pub fn shell_quote(s: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c);
+    if !s.is_empty() && s.chars().all(plain) {
+        return s.to_string();
+    }
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRICKY: &str = "say \"hi\"\\\n\tit's\u{1}";
+
+    #[test]
+    fn toml_escaped_values_parse_back() {
+        let document = format!(
+            "key = \"{}\"\n\"{}\" = 1",
+            toml_escape(TRICKY),
+            toml_escape(TRICKY)
+        );
+        let table: toml::Table = toml::from_str(&document).unwrap();
+
+        assert_eq!(table["key"].as_str(), Some(TRICKY));
+        assert!(table.contains_key(TRICKY));
+    }
+
+    #[test]
+    fn json_escaped_values_parse_back() {
+        let value: String = serde_json::from_str(&format!("\"{}\"", json_escape(TRICKY))).unwrap();
+        assert_eq!(value, TRICKY);
+    }
+
+    #[test]
+    fn yaml_escapes_quotes_and_control_characters() {
+        assert_eq!(yaml_escape(TRICKY), r#"say \"hi\"\\\n\tit's\x01"#);
+        assert_eq!(yaml_escape("ünïcode\u{2028}"), r"ünïcode\L");
+    }
+
+    #[test]
+    fn rust_strings_escape_quotes_and_newlines() {
+        assert_eq!(rust_string_escape("say \"hi\"\n"), r#"say \"hi\"\n"#);
+        assert_eq!(rust_string_escape("ünïcode"), "ünïcode");
+    }
+
+    #[test]
+    fn shell_quote_quotes_only_what_needs_it() {
+        assert_eq!(shell_quote("my-project/src"), "my-project/src");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("my project"), "'my project'");
+        assert_eq!(shell_quote("it's $HOME"), r"'it'\''s $HOME'");
+    }
+}
@@ -0,0 +1,112 @@
+//! The exit codes of cargo-generate, telling scripts and CI what went wrong without parsing
+//! stderr: errors are marked with the [`Failure`] they come of where they happen, and the mark
+//! picks the code.
+
+use anyhow::Result;
+use std::{error::Error, fmt};
+
+use crate::interrupt::Interrupted;
+
+/// The exit codes, listed at the end of `--help`.
+pub const HELP: &str = "\
+Exit codes:
+  0    The project was generated
+  1    Generating failed for any other reason
+  2    The arguments are invalid
+  3    The template could not be fetched
+  4    A value was rejected by its placeholder, the project name is invalid, or the
+       destination lacks the space or permissions for the project
+  5    A hook or a postprocessing script failed
+  6    A file, a name or a computed variable could not be rendered
+  7    Aborted when asked whether to go on, by a hook, or by --prompt-timeout
+  130  Interrupted with Ctrl-C";
+
+/// What generating failed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    Fetch,
+    Validation,
+    Hook,
+    Render,
+    Abort,
+}
+
+impl Failure {
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            Self::Fetch => 3,
+            Self::Validation => 4,
+            Self::Hook => 5,
+            Self::Render => 6,
+            Self::Abort => 7,
+        }
+    }
+}
+
+/// An error marked with the [`Failure`] it comes of, showing as the error itself.
+#[derive(Debug)]
+pub struct Failed {
+    pub failure: Failure,
+    error: anyhow::Error,
+}
+
+impl Error for Failed {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl fmt::Display for Failed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+pub trait FailedAt<T> {
+    /// Marks an error with `failure`, unless it is marked already or an interrupt.
+    fn failed_at(self, failure: Failure) -> Result<T>;
+}
+
+impl<T> FailedAt<T> for Result<T> {
+    fn failed_at(self, failure: Failure) -> Self {
+        self.map_err(|error| {
+            if error.is::<Failed>() || error.is::<Interrupted>() {
+                error
+            } else {
+                Failed { failure, error }.into()
+            }
+        })
+    }
+}
+
+/// The code to exit with after generating failed with `error`.
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    if error.is::<Interrupted>() {
+        return Interrupted::EXIT_CODE;
+    }
+    error
+        .downcast_ref::<Failed>()
+        .map_or(1, |failed| failed.failure.exit_code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn errors_keep_the_first_failure_and_their_messages() {
+        let hook = Err::<(), _>(anyhow!("exit 1"))
+            .context("Failed executing script: post.rhai")
+            .failed_at(Failure::Hook);
+        let error = hook.failed_at(Failure::Render).unwrap_err();
+
+        assert_eq!(exit_code(&error), 5);
+        assert_eq!(
+            format!("{error:#}"),
+            "Failed executing script: post.rhai: exit 1"
+        );
+        assert_eq!(exit_code(&anyhow!("anything")), 1);
+        assert_eq!(exit_code(&Interrupted.into()), 130);
+    }
+}
@@ -0,0 +1,259 @@
+//! Where the values of variables came from, recorded with `--explain-var` to find out which of the
+//! built-ins, the values given, the answers, `[computed]` and the hooks set a variable last.
+
+use console::style;
+use log::info;
+use serde::Serialize;
+use std::{fmt, sync::Mutex};
+
+use crate::emoji;
+use crate::project_variables::same_variable;
+
+/// What set a variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    BuiltIn,
+    /// `--name`
+    Name,
+    /// The `values` of the config of cargo-generate
+    AppConfig,
+    /// `$CARGO_GENERATE_VALUE_*` and the values file of `$CARGO_GENERATE_TEMPLATE_VALUES_FILE`
+    Environment,
+    ValuesFile,
+    Define,
+    ValuesUrl,
+    /// The answers kept for `--resume`
+    Resumed,
+    Answer,
+    /// The default of the placeholder, taken with `--silent` or `--defaults`
+    Default,
+    Computed,
+    Hook(String),
+    /// A hook overwriting a protected built-in with `variable::force_set`
+    ForcedByHook(String),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BuiltIn => write!(f, "built in"),
+            Self::Name => write!(f, "--name"),
+            Self::AppConfig => write!(f, "the values of the config"),
+            Self::Environment => write!(f, "the environment"),
+            Self::ValuesFile => write!(f, "--values-file"),
+            Self::Define => write!(f, "--define"),
+            Self::ValuesUrl => write!(f, "--values-url"),
+            Self::Resumed => write!(f, "--resume"),
+            Self::Answer => write!(f, "the answer to its prompt"),
+            Self::Default => write!(f, "the default of its placeholder"),
+            Self::Computed => write!(f, "[computed]"),
+            Self::Hook(script) => write!(f, "the hook `{script}`"),
+            Self::ForcedByHook(script) => {
+                write!(f, "the hook `{script}`, forcing the built-in value")
+            }
+        }
+    }
+}
+
+/// The changes of the variables a generation explains, if any.
+#[derive(Debug, Default)]
+pub struct Audit(Mutex<Option<Log>>);
+
+#[derive(Debug, Default)]
+struct Log {
+    /// The variables explained
+    names: Vec<String>,
+    /// The values each of them was set to, in order
+    changes: Vec<(String, serde_json::Value, Source)>,
+    current_hook: Option<String>,
+}
+
+impl Log {
+    fn push(&mut self, name: &str, value: &impl Serialize, source: Source) {
+        let Some(explained) = self
+            .names
+            .iter()
+            .find(|explained| same_variable(explained, name))
+        else {
+            return;
+        };
+        let change = (
+            explained.clone(),
+            serde_json::to_value(value).unwrap_or_default(),
+            source,
+        );
+        // the spellings of a variable are set together
+        let repeated = self
+            .changes
+            .iter()
+            .rev()
+            .find(|(changed, _, _)| *changed == change.0)
+            .is_some_and(|last| *last == change);
+        if !repeated {
+            self.changes.push(change);
+        }
+    }
+}
+
+impl Audit {
+    /// Records the changes of the variables `names`, if any.
+    pub fn new(names: &[String]) -> Self {
+        let log = (!names.is_empty()).then(|| Log {
+            names: names.to_vec(),
+            ..Log::default()
+        });
+        Self(Mutex::new(log))
+    }
+
+    fn with_log(&self, f: impl FnOnce(&mut Log)) {
+        if let Some(log) = self.0.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            f(log);
+        }
+    }
+
+    /// Records that `source` set the variable `name` to `value`.
+    pub fn record(&self, name: &str, value: &impl Serialize, source: Source) {
+        self.with_log(|log| log.push(name, value, source));
+    }
+
+    /// Records that the hook running set the variable `name` to `value`.
+    pub fn record_by_hook(&self, name: &str, value: &impl Serialize) {
+        self.record_by_running_hook(name, value, Source::Hook);
+    }
+
+    /// Records that the hook running forced the built-in variable `name` to `value`.
+    pub fn record_forced_by_hook(&self, name: &str, value: &impl Serialize) {
+        self.record_by_running_hook(name, value, Source::ForcedByHook);
+    }
+
+    fn record_by_running_hook(
+        &self,
+        name: &str,
+        value: &impl Serialize,
+        source: fn(String) -> Source,
+    ) {
+        self.with_log(|log| {
+            if let Some(script) = log.current_hook.clone() {
+                log.push(name, value, source(script));
+            }
+        });
+    }
+
+    /// Attributes the variables set until the returned guard is dropped to the hook `script`.
+    pub fn hook_scope(&self, script: &str) -> HookScope<'_> {
+        self.with_log(|log| log.current_hook = Some(script.to_string()));
+        HookScope(self)
+    }
+
+    /// Prints the changes of each variable explained.
+    pub fn report(&self) {
+        if let Some(log) = self.0.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            for line in report_lines(log) {
+                info!("{line}");
+            }
+        }
+    }
+}
+
+pub struct HookScope<'a>(&'a Audit);
+
+impl Drop for HookScope<'_> {
+    fn drop(&mut self) {
+        self.0.with_log(|log| log.current_hook = None);
+    }
+}
+
+fn report_lines(log: &Log) -> Vec<String> {
+    let mut lines = vec![];
+    for name in &log.names {
+        let changes = log
+            .changes
+            .iter()
+            .filter(|(changed, _, _)| changed == name)
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            lines.push(format!(
+                "{} {} {}",
+                emoji::WRENCH,
+                style(format!("`{name}`")).bold(),
+                style("was never set").bold(),
+            ));
+            continue;
+        }
+        lines.push(format!(
+            "{} {} {}",
+            emoji::WRENCH,
+            style(format!("`{name}`")).bold(),
+            style("was set by:").bold(),
+        ));
+        let mut previous: Option<&serde_json::Value> = None;
+        for (_, value, source) in changes {
+            let overwritten = previous
+                .map(|previous| format!(", overwriting {previous}"))
+                .unwrap_or_default();
+            lines.push(format!("    {source}: {value}{overwritten}"));
+            previous = Some(value);
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_the_changes_in_order() {
+        let log = Log {
+            names: vec!["license".into(), "missing".into()],
+            changes: vec![
+                ("license".into(), "MIT".into(), Source::Define),
+                (
+                    "license".into(),
+                    "Apache-2.0".into(),
+                    Source::Hook("pre.rhai".into()),
+                ),
+            ],
+            current_hook: None,
+        };
+
+        let lines = report_lines(&log)
+            .iter()
+            .map(|line| console::strip_ansi_codes(line).trim().to_string())
+            .collect::<Vec<_>>();
+
+        assert!(lines[0].ends_with("`license` was set by:"));
+        assert_eq!(lines[1], r#"--define: "MIT""#);
+        assert_eq!(
+            lines[2],
+            r#"the hook `pre.rhai`: "Apache-2.0", overwriting "MIT""#
+        );
+        assert!(lines[3].ends_with("`missing` was never set"));
+    }
+
+    #[test]
+    fn changes_are_attributed_to_the_hook_running() {
+        let audit = Audit::new(&["license".to_string()]);
+        audit.record("license", &"MIT", Source::Define);
+        // no hook is running
+        audit.record_by_hook("license", &"ISC");
+        {
+            let _scope = audit.hook_scope("pre.rhai");
+            audit.record_by_hook("license", &"Apache-2.0");
+        }
+        audit.record_by_hook("license", &"ISC");
+
+        let log = audit.0.lock().unwrap().take().unwrap();
+        assert_eq!(
+            log.changes,
+            [
+                ("license".into(), "MIT".into(), Source::Define),
+                (
+                    "license".into(),
+                    "Apache-2.0".into(),
+                    Source::Hook("pre.rhai".into())
+                ),
+            ]
+        );
+    }
+}
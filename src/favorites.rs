@@ -2,14 +2,17 @@
 
 use crate::{
     app_config::{app_config_path, AppConfig, FavoriteConfig},
-    emoji, GenerateArgs,
+    emoji, theme, GenerateArgs,
 };
 use anyhow::Result;
 use console::style;
 use log::info;
 
 pub fn list_favorites(args: &GenerateArgs) -> Result<()> {
+    emoji::configure(args.no_emoji);
+    theme::set_color_choice(args.color);
     let app_config: AppConfig = app_config_path(&args.config)?.as_path().try_into()?;
+    theme::set_theme(app_config.theme.as_ref());
 
     let data = {
         let mut d = app_config
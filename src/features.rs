@@ -0,0 +1,309 @@
+//! Template features, declared in `[template.features]`: the user picks some of them, and each one
+//! picked brings its placeholders, its include/exclude/ignore globs and its part of `Cargo.toml`.
+//!
+//! The features picked are the array variable `features`, asked for like any placeholder.
+
+use anyhow::{bail, Result};
+use console::style;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::{fs, path::Path};
+use toml_edit::{DocumentMut, TableLike};
+
+use crate::config::{ConditionalConfig, Config, TemplateConfig, TemplateSlotsTable};
+use crate::emoji;
+use crate::hooks::PoisonError;
+use crate::merge;
+use crate::template::TemplateObjectResource;
+
+/// The placeholder holding the names of the features picked.
+pub const FEATURES_PLACEHOLDER: &str = "features";
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct FeatureConfig {
+    /// Whether the feature is picked unless the user says otherwise
+    pub default: Option<bool>,
+    /// Files and folders only generated when the feature is picked, ignored otherwise
+    pub files: Option<Vec<String>>,
+    /// The placeholders and globs the feature adds, like those of a conditional
+    #[serde(flatten)]
+    pub conditional: ConditionalConfig,
+    /// Merged into the generated `Cargo.toml`, e.g. its `dependencies` and `features`
+    pub cargo: Option<toml::Table>,
+}
+
+/// Adds the `features` placeholder, asked for before all others, offering the features of the
+/// template unless it defines the placeholder itself.
+pub fn add_features_placeholder(config: &mut Config) {
+    let Some(features) = config.template.as_ref().and_then(|t| t.features.as_ref()) else {
+        return;
+    };
+    if features.is_empty() {
+        return;
+    }
+
+    let names = |picked_by_default: bool| {
+        features
+            .iter()
+            .filter(|(_, feature)| !picked_by_default || feature.default.unwrap_or(false))
+            .map(|(name, _)| toml::Value::from(name.as_str()))
+            .collect::<Vec<_>>()
+    };
+    let mut table = toml::Table::new();
+    table.insert("type".into(), "array".into());
+    table.insert("prompt".into(), "Which features?".into());
+    table.insert("choices".into(), names(false).into());
+    table.insert("default".into(), names(true).into());
+
+    let placeholders = config
+        .placeholders
+        .get_or_insert_with(TemplateSlotsTable::default);
+    if !placeholders.0.contains_key(FEATURES_PLACEHOLDER) {
+        placeholders.0.shift_insert(
+            0,
+            FEATURES_PLACEHOLDER.to_string(),
+            toml::Value::Table(table),
+        );
+    }
+}
+
+/// The names of the features picked so far.
+pub fn picked(template_object: &TemplateObjectResource) -> Result<Vec<String>> {
+    let object = template_object.lock().map_err(|_| PoisonError)?;
+    let picked = object
+        .borrow()
+        .get(FEATURES_PLACEHOLDER)
+        .and_then(|value| value.as_array())
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(|name| name.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(picked)
+}
+
+/// Ignores the `files` of the features not `picked`.
+pub fn ignore_files_not_picked(
+    config: &mut Config,
+    features: &IndexMap<String, FeatureConfig>,
+    picked: &[String],
+) {
+    let not_picked = features
+        .iter()
+        .filter(|(name, _)| !picked.contains(name))
+        .filter_map(|(name, feature)| Some((name, feature.files.as_ref()?)))
+        .filter(|(_, files)| !files.is_empty())
+        .collect::<Vec<_>>();
+    if not_picked.is_empty() {
+        return;
+    }
+    let template = config.template.get_or_insert_with(TemplateConfig::default);
+    for (name, files) in not_picked {
+        let section = format!("[template.features.{name}]");
+        template.pattern_sources.add(files, &section);
+        template
+            .ignore
+            .get_or_insert_with(Vec::default)
+            .extend(files.iter().cloned());
+    }
+}
+
+/// Merges the `cargo` tables of the features picked into the `Cargo.toml` in `dir`.
+pub fn patch_cargo_toml(
+    dir: &Path,
+    features: Option<&IndexMap<String, FeatureConfig>>,
+    template_object: &TemplateObjectResource,
+) -> Result<()> {
+    let Some(features) = features else {
+        return Ok(());
+    };
+    let patches = picked(template_object)?
+        .iter()
+        .filter_map(|name| features.get(name)?.cargo.as_ref())
+        .cloned()
+        .collect::<Vec<_>>();
+    if patches.is_empty() {
+        return Ok(());
+    }
+
+    let path = dir.join("Cargo.toml");
+    if !path.exists() {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Features of the template patch a missing")
+                .bold()
+                .red(),
+            style("Cargo.toml").bold(),
+        );
+    }
+    let contents = fs::read_to_string(&path)?;
+    let manifest = patched(&contents, patches)?;
+    // a manifest the features leave as it is, is not touched
+    if manifest != contents {
+        fs::write(path, manifest)?;
+    }
+    Ok(())
+}
+
+/// The manifest `contents` with the `patches` merged in, its comments and formatting kept.
+fn patched(contents: &str, patches: Vec<toml::Table>) -> Result<String> {
+    let mut manifest = contents.parse::<DocumentMut>()?;
+    for patch in patches {
+        let patch = toml::to_string(&patch)?.parse::<DocumentMut>()?;
+        merge(manifest.as_table_mut(), patch.as_table());
+    }
+    Ok(manifest.to_string())
+}
+
+/// Merges `patch` into `table`: tables are merged key by key, arrays are extended by the items
+/// they lack and anything else is replaced.
+fn merge(table: &mut dyn TableLike, patch: &dyn TableLike) {
+    for (key, item) in patch.iter() {
+        let Some(existing) = table.get_mut(key) else {
+            table.insert(key, merge::detached(item));
+            continue;
+        };
+        if let (Some(existing), Some(patch)) = (existing.as_table_like_mut(), item.as_table_like())
+        {
+            merge(existing, patch);
+        } else if let (Some(existing), Some(patch)) = (existing.as_array_mut(), item.as_array()) {
+            for value in patch {
+                if !existing.iter().any(|known| merge::same_value(known, value)) {
+                    let mut value = value.clone();
+                    value.decor_mut().clear();
+                    existing.push(value);
+                }
+            }
+        } else if let (Some(existing), Some(value)) = (existing.as_value_mut(), item.as_value()) {
+            // the comments around the value replaced stay
+            let decor = existing.decor().clone();
+            *existing = value.clone();
+            *existing.decor_mut() = decor;
+        } else {
+            *existing = merge::detached(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patches_merge_tables_and_arrays() {
+        let manifest = r#"
+            [package]
+            name = "demo"
+            [dependencies]
+            anyhow = "1"
+            [features]
+            default = ["std"]
+            "#;
+        let patch: toml::Table = toml::from_str(
+            r#"
+            [dependencies]
+            serde = { version = "1", features = ["derive"] }
+            [features]
+            default = ["std", "serde"]
+            serde = ["dep:serde"]
+            "#,
+        )
+        .unwrap();
+
+        let manifest: toml::Table =
+            toml::from_str(&patched(manifest, vec![patch]).unwrap()).unwrap();
+
+        let expected: toml::Table = toml::from_str(
+            r#"
+            [package]
+            name = "demo"
+            [dependencies]
+            anyhow = "1"
+            serde = { version = "1", features = ["derive"] }
+            [features]
+            default = ["std", "serde"]
+            serde = ["dep:serde"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest, expected);
+    }
+
+    #[test]
+    fn patches_keep_the_comments_and_formatting_of_the_manifest() {
+        let manifest = indoc::indoc! {r#"
+            [package]
+            name = "demo" # the crate name
+            version = "0.1.0"   # bumped on release
+
+            # what the crate needs
+            [dependencies]
+            anyhow = "1"
+        "#};
+        let patch: toml::Table = toml::from_str(
+            r#"
+            package.version = "0.2.0"
+            dependencies.serde = "1"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            patched(manifest, vec![patch.clone()]).unwrap(),
+            indoc::indoc! {r#"
+                [package]
+                name = "demo" # the crate name
+                version = "0.2.0"   # bumped on release
+
+                # what the crate needs
+                [dependencies]
+                anyhow = "1"
+                serde = "1"
+            "#}
+        );
+        let patched_once = patched(manifest, vec![patch.clone()]).unwrap();
+        assert_eq!(
+            patched(&patched_once, vec![patch]).unwrap(),
+            patched_once,
+            "patching again changes nothing"
+        );
+    }
+
+    #[test]
+    fn the_placeholder_offers_all_features_and_picks_the_defaults() {
+        let mut config = Config::try_from(
+            r#"
+            [template.features.serde]
+            default = true
+            [template.features.cli]
+            files = ["src/cli.rs"]
+            [placeholders.license]
+            type = "string"
+            prompt = "License?"
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        add_features_placeholder(&mut config);
+
+        let placeholders = &config.placeholders.as_ref().unwrap().0;
+        assert_eq!(
+            placeholders.keys().collect::<Vec<_>>(),
+            [FEATURES_PLACEHOLDER, "license"]
+        );
+        let features = &placeholders[FEATURES_PLACEHOLDER];
+        assert_eq!(features["choices"], toml::Value::from(vec!["serde", "cli"]));
+        assert_eq!(features["default"], toml::Value::from(vec!["serde"]));
+
+        let features = config.template.as_ref().unwrap().features.clone().unwrap();
+        ignore_files_not_picked(&mut config, &features, &["serde".to_string()]);
+        assert_eq!(
+            config.template.unwrap().ignore,
+            Some(vec!["src/cli.rs".to_string()])
+        );
+    }
+}
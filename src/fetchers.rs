@@ -0,0 +1,166 @@
+//! Fetchers for templates behind URL schemes cargo-generate knows nothing about, like `s3://` or
+//! `oci://`.
+//!
+//! They are registered through [`register_fetcher`], or declared as external commands in the
+//! `[fetchers]` of the application config:
+//!
+//! ```toml
+//! [fetchers.s3]
+//! command = ["s3-template-fetch", "--profile", "templates"]
+//! ```
+//!
+//! An external command is run with the URL and the directory to put the template into appended
+//! to its arguments, and has to exit successfully once the template is there.
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::Path,
+    process::Command,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use crate::{emoji, oci};
+
+/// The fetchers by scheme, starting with the built-in ones.
+static FETCHERS: LazyLock<Mutex<HashMap<String, Arc<dyn TemplateFetcher>>>> = LazyLock::new(|| {
+    let oci: Arc<dyn TemplateFetcher> = Arc::new(oci::pull);
+    Mutex::new(HashMap::from([("oci".to_string(), oci)]))
+});
+
+/// Gets templates from a location cargo-generate can't get them from itself.
+pub trait TemplateFetcher: Send + Sync {
+    /// Puts the template at `url` into the existing, empty `destination`.
+    fn fetch(&self, url: &str, destination: &Path) -> Result<()>;
+}
+
+impl<F> TemplateFetcher for F
+where
+    F: Fn(&str, &Path) -> Result<()> + Send + Sync,
+{
+    fn fetch(&self, url: &str, destination: &Path) -> Result<()> {
+        self(url, destination)
+    }
+}
+
+/// An external command declared in the `[fetchers]` of the application config.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FetcherConfig {
+    /// The program and its first arguments
+    pub command: Vec<String>,
+}
+
+impl TemplateFetcher for FetcherConfig {
+    fn fetch(&self, url: &str, destination: &Path) -> Result<()> {
+        let Some((program, args)) = self.command.split_first() else {
+            bail!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("The fetcher has an empty command, it cannot fetch")
+                    .bold()
+                    .red(),
+                style(url).bold(),
+            );
+        };
+        let status = Command::new(program)
+            .args(args)
+            .arg(url)
+            .arg(destination)
+            .status()
+            .with_context(|| format!("Failed to run the fetcher `{program}`"))?;
+        if !status.success() {
+            bail!(
+                "{} {} `{}` ({status})",
+                emoji::ERROR,
+                style("Fetcher failed to fetch").bold().red(),
+                style(url).bold(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Makes templates at URLs with `scheme`, like `s3` for `s3://bucket/template`, get fetched by
+/// `fetcher`, replacing any fetcher registered for it before.
+pub fn register_fetcher(scheme: &str, fetcher: impl TemplateFetcher + 'static) {
+    FETCHERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(scheme.to_lowercase(), Arc::new(fetcher));
+}
+
+/// Registers the external commands declared in the application config.
+pub fn register_configured(fetchers: Option<&HashMap<String, FetcherConfig>>) {
+    for (scheme, fetcher) in fetchers.into_iter().flatten() {
+        register_fetcher(scheme, fetcher.clone());
+    }
+}
+
+/// The scheme of `url`, if it is one like `s3://...`.
+fn scheme(url: &str) -> Option<&str> {
+    let (scheme, _) = url.split_once("://")?;
+    let mut chars = scheme.chars();
+    (chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+    .then_some(scheme)
+}
+
+/// Whether a fetcher is registered for the scheme of `url`.
+pub fn has_fetcher(url: &str) -> bool {
+    fetcher(url).is_some()
+}
+
+fn fetcher(url: &str) -> Option<Arc<dyn TemplateFetcher>> {
+    let scheme = scheme(url)?.to_lowercase();
+    FETCHERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&scheme)
+        .cloned()
+}
+
+/// Fetches the template at `url` into `destination` with the fetcher registered for its scheme.
+pub fn fetch(url: &str, destination: &Path) -> Result<()> {
+    let Some(fetcher) = fetcher(url) else {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("No fetcher is registered for the scheme of")
+                .bold()
+                .red(),
+            style(url).bold(),
+        );
+    };
+    fetcher.fetch(url, destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetchers_are_found_by_the_scheme_of_the_url() {
+        register_fetcher("test-scheme", |_: &str, destination: &Path| {
+            std::fs::write(destination.join("fetched.txt"), "yes")?;
+            Ok(())
+        });
+
+        assert!(has_fetcher("TEST-SCHEME://bucket/template"));
+        assert!(!has_fetcher("other-scheme://bucket/template"));
+        assert!(!has_fetcher("test-scheme"));
+
+        let dir = tempfile::tempdir().unwrap();
+        fetch("test-scheme://bucket/template", dir.path()).unwrap();
+        assert!(dir.path().join("fetched.txt").exists());
+    }
+
+    #[test]
+    fn schemes_are_parsed_as_in_urls() {
+        assert_eq!(scheme("s3://bucket"), Some("s3"));
+        assert_eq!(scheme("git+ssh://host/repo"), Some("git+ssh"));
+        assert_eq!(scheme("/path/to://x"), None);
+        assert_eq!(scheme("C:\\path"), None);
+    }
+}
@@ -1,21 +1,174 @@
 use crate::{template::TemplateObjectResource, Result};
 
+use crate::emoji;
+use crate::hooks::PoisonError;
+use crate::interactive::LIST_SEP;
 use crate::template::render_string_gracefully;
+use anyhow::bail;
+use console::style;
 use minijinja::Environment;
+use regex::Regex;
+use std::cell::RefCell;
+use std::ffi::OsStr;
 use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use walkdir::WalkDir;
 
+/// Longest path the platform accepts: in UTF-16 units on Windows, with the `\\?\` prefix added by
+/// [`long_path`], and `PATH_MAX` bytes elsewhere.
+const MAX_PATH_LEN: usize = if cfg!(windows) { 32_767 } else { 4_096 };
+
+/// Matches `{{ each <var> }}` and `{{ each <name> in <var> }}` in a file or folder name.
+static EACH_MARKER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\{\{\s*each\s+(?:([A-Za-z_][A-Za-z0-9_]*)\s+in\s+)?([A-Za-z_][A-Za-z0-9_\-]*)\s*\}\}",
+    )
+    .unwrap()
+});
+
+/// A path to be instantiated once per element of an array variable, with the marker replaced by
+/// the element and a context in which the element is bound to `item` and `loop_value`, and to
+/// `<name>` if given, and its position, starting at 1, to `loop_index`.
+pub type Iteration = (PathBuf, TemplateObjectResource);
+
+/// Expands the `each` markers of `relative_path`, returning `None` if there are none.
+///
+/// Markers in several components of the path multiply, unless they are identical, so
+/// `{{ each service in services }}/{{ each service in services }}.rs` yields one file per service.
+pub fn expand_each_markers(
+    relative_path: &Path,
+    context: &TemplateObjectResource,
+) -> Result<Option<Vec<Iteration>>> {
+    let components = relative_path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+
+    let mut loops: Vec<(Option<String>, String)> = vec![];
+    for component in &components {
+        for cap in EACH_MARKER.captures_iter(component) {
+            let key = loop_key(&cap);
+            if !loops.contains(&key) {
+                loops.push(key);
+            }
+        }
+    }
+    if loops.is_empty() {
+        return Ok(None);
+    }
+
+    let base = context.lock().map_err(|_| PoisonError)?.borrow().clone();
+    let mut elements = Vec::with_capacity(loops.len());
+    for (_, var) in &loops {
+        elements.push(each_elements(base.get(var), var)?);
+    }
+
+    // the cartesian product of all loops, as indices into `elements`
+    let mut combinations: Vec<Vec<usize>> = vec![vec![]];
+    for values in &elements {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                (0..values.len()).map(move |i| {
+                    let mut combination = combination.clone();
+                    combination.push(i);
+                    combination
+                })
+            })
+            .collect();
+    }
+
+    let iterations = combinations
+        .into_iter()
+        .map(|combination| {
+            let mut iteration_context = base.clone();
+            for (((name, _), values), &i) in loops.iter().zip(&elements).zip(&combination) {
+                let value = serde_json::Value::from(values[i].clone());
+                if let Some(name) = name {
+                    iteration_context.insert(name.clone(), value.clone());
+                }
+                iteration_context.insert("item".to_string(), value.clone());
+                iteration_context.insert("loop_value".to_string(), value);
+                iteration_context.insert("loop_index".to_string(), (i + 1).into());
+            }
+
+            let path = components
+                .iter()
+                .map(|component| {
+                    EACH_MARKER
+                        .replace_all(component, |cap: &regex::Captures| {
+                            let key = loop_key(cap);
+                            let index = loops.iter().position(|l| *l == key).unwrap();
+                            sanitize_filename(&elements[index][combination[index]])
+                        })
+                        .to_string()
+                })
+                .collect::<PathBuf>();
+
+            (path, Arc::new(Mutex::new(RefCell::new(iteration_context))))
+        })
+        .collect();
+
+    Ok(Some(iterations))
+}
+
+/// Identifies a loop by the name its element is bound to, and the variable it loops over.
+fn loop_key(cap: &regex::Captures) -> (Option<String>, String) {
+    (
+        cap.get(1).map(|m| m.as_str().to_string()),
+        cap[2].to_string(),
+    )
+}
+
+/// The elements of the variable an `each` marker loops over: an array, or a comma separated list.
+fn each_elements(value: Option<&serde_json::Value>, var: &str) -> Result<Vec<String>> {
+    let elements = match value {
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .map(|item| match item {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect(),
+        Some(serde_json::Value::String(list)) => list
+            .split(LIST_SEP)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Files can only be expanded for each element of an array or list, not for")
+                .bold()
+                .red(),
+            style(var).bold().red(),
+        ),
+    };
+    Ok(elements)
+}
+
+/// Renders the names in `filepath` with the `variables` and the `engine` of the generation, see
+/// [`render_string_gracefully`].
 pub fn substitute_filename(
     filepath: &Path,
-    parser: &Environment,
-    context: &TemplateObjectResource,
-    preserve_whitespace: bool,
+    engine: &Environment,
+    variables: &minijinja::Value,
 ) -> Result<PathBuf> {
     let mut path = PathBuf::new();
     for elem in filepath.components() {
         match elem {
             Component::Normal(e) => {
-                let parsed = render_string_gracefully(context, parser, e.to_str().unwrap(), preserve_whitespace)?;
+                let parsed = render_string_gracefully(variables, engine, e.to_str().unwrap())?;
                 let parsed = sanitize_filename(parsed.as_str());
+                if parsed.is_empty() {
+                    bail!(
+                        "{} `{}` {}",
+                        style("The name").bold().red(),
+                        style(e.to_string_lossy()).bold(),
+                        style("renders to an empty one").bold().red(),
+                    );
+                }
                 path.push(parsed);
             }
             other => path.push(other),
@@ -24,6 +177,93 @@ pub fn substitute_filename(
     Ok(path)
 }
 
+/// Makes Windows accept `path` beyond the 260 characters of `MAX_PATH`, by turning it into an
+/// extended-length path: `C:\dir` becomes `\\?\C:\dir` and the UNC path `\\server\share\dir`
+/// becomes `\\?\UNC\server\share\dir`. Anything else, and any path on other platforms, is
+/// returned as is.
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+    path.to_str()
+        .and_then(extended_length_path)
+        .map_or_else(|| path.to_path_buf(), PathBuf::from)
+}
+
+/// Windows does not normalize extended-length paths, so separators are unified, and `.` and `..`
+/// resolved, here.
+fn extended_length_path(path: &str) -> Option<String> {
+    let path = path.replace('/', "\\");
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        // verbatim or device paths already
+        return None;
+    }
+    let is_drive_absolute = path
+        .as_bytes()
+        .get(..3)
+        .is_some_and(|start| start[0].is_ascii_alphabetic() && start[1..] == *b":\\");
+    let (prefix, rest, root_len) = match path.strip_prefix(r"\\") {
+        // the server and the share cannot be left
+        Some(unc) => (r"\\?\UNC\", unc, 2),
+        None if is_drive_absolute => (r"\\?\", path.as_str(), 1),
+        None => return None,
+    };
+
+    let mut components = Vec::new();
+    for component in rest.split('\\') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                if components.len() > root_len {
+                    components.pop();
+                }
+            }
+            component => components.push(component),
+        }
+    }
+    Some(format!("{prefix}{}", components.join("\\")))
+}
+
+/// Checks, before anything is moved to the destination, that no file of the expanded template
+/// exceeds the path length limit of the platform once it is below `project_dir`.
+pub fn check_path_lengths(template_dir: &Path, project_dir: &Path) -> Result<()> {
+    let project_dir = long_path(project_dir);
+    let entries = WalkDir::new(template_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .filter_map(Result::ok);
+    for entry in entries {
+        let relative_path = entry.path().strip_prefix(template_dir)?;
+        let destination = project_dir.join(relative_path);
+        if path_len(destination.as_os_str()) > MAX_PATH_LEN {
+            bail!(
+                "{} {} `{}` {}",
+                emoji::ERROR,
+                style("The path of").bold().red(),
+                style(relative_path.display()).bold(),
+                style(format!(
+                    "would be longer than {MAX_PATH_LEN}, the limit of this platform, below the destination"
+                ))
+                .bold()
+                .red(),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn path_len(path: &OsStr) -> usize {
+    use std::os::windows::ffi::OsStrExt;
+    path.encode_wide().count()
+}
+
+#[cfg(not(windows))]
+fn path_len(path: &OsStr) -> usize {
+    path.len()
+}
+
 fn sanitize_filename(filename: &str) -> String {
     use sanitize_filename::sanitize_with_options;
 
@@ -39,6 +279,7 @@ fn sanitize_filename(filename: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::template::TemplateContext;
     use serde_json::{Map, Value};
     use std::{
         cell::RefCell,
@@ -154,6 +395,128 @@ mod tests {
         );
     }
 
+    #[test]
+    fn names_must_not_render_empty() {
+        let err = substitute_filename("src/{{nothing}}/lib.rs", &prepare_context("sassman"))
+            .unwrap_err();
+        assert!(err.to_string().contains("{{nothing}}"), "{err}");
+    }
+
+    #[test]
+    fn a_context_made_once_sees_variables_change() {
+        let ctx = prepare_context("sassman");
+        let variables = TemplateContext::value(&ctx, &Arc::default());
+        let env = minijinja::Environment::new();
+        let render = || {
+            super::substitute_filename(Path::new("{{author}}.rs"), &env, &variables).unwrap()
+        };
+
+        assert_eq!(render(), Path::new("sassman.rs"));
+        crate::project_variables::insert_variable(
+            &mut ctx.lock().unwrap().borrow_mut(),
+            "author",
+            Value::from("someone"),
+        );
+        assert_eq!(render(), Path::new("someone.rs"));
+    }
+
+    #[test]
+    fn each_markers_expand_per_element() {
+        let mut ctx = Map::new();
+        ctx.insert("services".to_string(), Value::from(vec!["auth", "billing"]));
+        ctx.insert("flavors".to_string(), Value::from("sweet, sour"));
+        let ctx = Arc::new(Mutex::new(RefCell::new(ctx)));
+
+        assert!(expand_each_markers(Path::new("src/main.rs"), &ctx)
+            .unwrap()
+            .is_none());
+
+        let iterations =
+            expand_each_markers(Path::new("src/{{ each service in services }}.rs"), &ctx)
+                .unwrap()
+                .unwrap();
+        let paths = iterations
+            .iter()
+            .map(|(path, _)| path.to_str().unwrap().replace('\\', "/"))
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["src/auth.rs", "src/billing.rs"]);
+        let context = iterations[1].1.lock().unwrap();
+        assert_eq!(context.borrow()["service"], "billing");
+        assert_eq!(context.borrow()["item"], "billing");
+        assert_eq!(context.borrow()["loop_value"], "billing");
+        assert_eq!(context.borrow()["loop_index"], 2);
+    }
+
+    #[test]
+    fn each_markers_in_several_components_multiply() {
+        let mut ctx = Map::new();
+        ctx.insert("a".to_string(), Value::from(vec!["x", "y"]));
+        ctx.insert("b".to_string(), Value::from(vec!["1", "2"]));
+        let ctx = Arc::new(Mutex::new(RefCell::new(ctx)));
+
+        let paths = |path: &str| {
+            expand_each_markers(Path::new(path), &ctx)
+                .unwrap()
+                .unwrap()
+                .into_iter()
+                .map(|(path, _)| path.to_str().unwrap().replace('\\', "/"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            paths("{{ each a }}/{{ each b }}.rs"),
+            ["x/1.rs", "x/2.rs", "y/1.rs", "y/2.rs"]
+        );
+        assert_eq!(paths("{{ each a }}/{{each a}}.rs"), ["x/x.rs", "y/y.rs"]);
+    }
+
+    #[test]
+    fn each_markers_need_a_list() {
+        assert!(
+            expand_each_markers(Path::new("{{ each nope }}.rs"), &prepare_context("x")).is_err()
+        );
+    }
+
+    #[test]
+    fn long_paths_are_extended_length_paths() {
+        assert_eq!(
+            extended_length_path(r"C:\Users\me\project").as_deref(),
+            Some(r"\\?\C:\Users\me\project")
+        );
+        assert_eq!(
+            extended_length_path("C:/Users/me/./old/../project").as_deref(),
+            Some(r"\\?\C:\Users\me\project")
+        );
+        assert_eq!(
+            extended_length_path(r"\\server\share\projects\..\project").as_deref(),
+            Some(r"\\?\UNC\server\share\project")
+        );
+        assert_eq!(
+            extended_length_path(r"\\server\share\..\..\project").as_deref(),
+            Some(r"\\?\UNC\server\share\project")
+        );
+        assert_eq!(extended_length_path(r"\\?\C:\project"), None);
+        assert_eq!(extended_length_path(r"\\?\UNC\server\share"), None);
+        assert_eq!(extended_length_path(r"project\src"), None);
+        #[cfg(not(windows))]
+        assert_eq!(
+            long_path(Path::new("/tmp/project")),
+            Path::new("/tmp/project")
+        );
+    }
+
+    #[test]
+    fn paths_beyond_the_limit_fail_before_copying() {
+        let template = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(template.path().join("src")).unwrap();
+        std::fs::write(template.path().join("src/lib.rs"), "").unwrap();
+
+        assert!(check_path_lengths(template.path(), Path::new("/tmp/project")).is_ok());
+        // only `src` itself still fits below it
+        let deep = Path::new("/tmp").join("d/".repeat((MAX_PATH_LEN - 10) / 2));
+        let err = check_path_lengths(template.path(), &deep).unwrap_err();
+        assert!(err.to_string().contains("lib.rs"), "{err}");
+    }
+
     //region wrapper helpers
     fn prepare_context(value: &str) -> TemplateObjectResource {
         let mut ctx = Map::new();
@@ -167,7 +530,8 @@ mod tests {
         env.set_trim_blocks(true);
         env.set_lstrip_blocks(true);
 
-        super::substitute_filename(f.as_ref(), &env, ctx, false)
+        let variables = TemplateContext::value(ctx, &Arc::default());
+        super::substitute_filename(f.as_ref(), &env, &variables)
             .map(|p| p.to_str().unwrap().to_string())
     }
     //endregion
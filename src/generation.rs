@@ -0,0 +1,58 @@
+//! What a generation keeps besides its variables: the changes of the variables it explains, the
+//! aliases, functions and include roots of its template, the state its files share, the moment it
+//! happens and how it renders and prompts.
+//!
+//! It is passed down from [`crate::generate`] to whatever renders, prompts or runs hooks for the
+//! generation, so projects generated at once, or by the hooks of another, each keep their own.
+
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+use crate::explain_var::Audit;
+use crate::limits::Budget;
+use crate::prompt_timeout::PromptTimeout;
+use crate::render_state::RenderState;
+use crate::template_functions::TemplateFunction;
+use crate::template_includes::IncludeRoots;
+use crate::variable_aliases::Aliases;
+
+#[derive(Debug)]
+pub struct Generation {
+    /// The changes of the variables explained with `--explain-var`
+    pub audit: Audit,
+    /// The other names of variables of `[template.variable_aliases]`
+    pub aliases: Aliases,
+    /// The `state` variable, and the files generated so far
+    pub render_state: RenderState,
+    /// What the generation may still write, within the `[limits]` of the application config
+    pub budget: Budget,
+    /// Whether rendering is strict, see [`crate::strict`]
+    pub strict: bool,
+    /// Whether the scripts of the template are skipped, see [`crate::no_scripts`]
+    pub no_scripts: bool,
+    /// The functions of `[template.functions]`
+    pub functions: Vec<TemplateFunction>,
+    /// Where templates are included from, none until the template is known
+    pub include_roots: Option<Arc<IncludeRoots>>,
+    /// The moment the project is generated at, that of the `current_*` variables and `now()`
+    pub generated_at: OffsetDateTime,
+    /// How long prompts wait for an answer
+    pub prompt_timeout: PromptTimeout,
+}
+
+impl Default for Generation {
+    fn default() -> Self {
+        Self {
+            audit: Audit::default(),
+            aliases: Aliases::default(),
+            render_state: RenderState::default(),
+            budget: Budget::default(),
+            strict: false,
+            no_scripts: false,
+            functions: vec![],
+            include_roots: None,
+            generated_at: OffsetDateTime::now_utc(),
+            prompt_timeout: PromptTimeout::default(),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! CI pipeline generators for GitHub Actions, GitLab CI and Woodpecker.
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::{fmt, path::Path, str::FromStr};
+
+use super::{render_with, write_file};
+use crate::limits::Budget;
+use crate::template::TemplateObjectResource;
+
+const GITHUB: &str = include_str!("resources/ci/github.yml");
+const GITLAB: &str = include_str!("resources/ci/gitlab.yml");
+const WOODPECKER: &str = include_str!("resources/ci/woodpecker.yml");
+
+/// The CI services the `ci` generator can emit a pipeline for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum CiProvider {
+    #[serde(rename = "github")]
+    GitHub,
+    #[serde(rename = "gitlab")]
+    GitLab,
+    #[serde(rename = "woodpecker")]
+    Woodpecker,
+}
+
+impl CiProvider {
+    #[cfg(test)]
+    const ALL: [Self; 3] = [Self::GitHub, Self::GitLab, Self::Woodpecker];
+
+    const fn template(self) -> &'static str {
+        match self {
+            Self::GitHub => GITHUB,
+            Self::GitLab => GITLAB,
+            Self::Woodpecker => WOODPECKER,
+        }
+    }
+
+    /// The file the provider reads its pipeline from, relative to the project root.
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::GitHub => ".github/workflows/ci.yml",
+            Self::GitLab => ".gitlab-ci.yml",
+            Self::Woodpecker => ".woodpecker.yml",
+        }
+    }
+}
+
+impl FromStr for CiProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "github" => Ok(Self::GitHub),
+            "gitlab" => Ok(Self::GitLab),
+            "woodpecker" => Ok(Self::Woodpecker),
+            _ => bail!(
+                "Unsupported CI provider `{s}`, must be one of `github`, `gitlab` or `woodpecker`"
+            ),
+        }
+    }
+}
+
+/// The jobs a generated pipeline can consist of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiFeature {
+    /// `cargo fmt --check`
+    Fmt,
+    /// `cargo clippy -- -D warnings`
+    Clippy,
+    /// `cargo test` on a matrix of toolchains (and operating systems, where available)
+    Test,
+    /// On tags: publish a library to crates.io, or build release binaries of a binary crate
+    Release,
+}
+
+impl CiFeature {
+    pub const DEFAULT: [Self; 3] = [Self::Fmt, Self::Clippy, Self::Test];
+    const ALL: [Self; 4] = [Self::Fmt, Self::Clippy, Self::Test, Self::Release];
+}
+
+impl fmt::Display for CiFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fmt => write!(f, "fmt"),
+            Self::Clippy => write!(f, "clippy"),
+            Self::Test => write!(f, "test"),
+            Self::Release => write!(f, "release"),
+        }
+    }
+}
+
+impl FromStr for CiFeature {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|feature| feature.to_string() == s.to_lowercase())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported CI feature `{s}`, must be one of {}",
+                    Self::ALL
+                        .iter()
+                        .map(|f| format!("`{f}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+/// Emits the pipeline of `provider` with the jobs listed in `features`.
+///
+/// What the release job does depends on the `crate_type`: libraries get published, binaries get
+/// built.
+pub fn ci(
+    dir: &Path,
+    template_object: &TemplateObjectResource,
+    provider: CiProvider,
+    features: &[CiFeature],
+    budget: &Budget,
+) -> Result<()> {
+    let mut extra = serde_json::Map::new();
+    for feature in CiFeature::ALL {
+        extra.insert(feature.to_string(), features.contains(&feature).into());
+    }
+    let needs = features
+        .iter()
+        .filter(|feature| **feature != CiFeature::Release)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    extra.insert("needs".into(), needs.into());
+
+    let pipeline = render_with(provider.template(), template_object, extra)?;
+    write_file(
+        dir,
+        provider.file_name(),
+        &format!("{}\n", pipeline.trim_end()),
+        budget,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        cell::RefCell,
+        sync::{Arc, Mutex},
+    };
+    use tempfile::TempDir;
+
+    fn prepare_context(crate_type: &str) -> TemplateObjectResource {
+        let mut ctx = serde_json::Map::new();
+        ctx.insert("crate_name".into(), "my_project".into());
+        ctx.insert("crate_type".into(), crate_type.into());
+        Arc::new(Mutex::new(RefCell::new(ctx)))
+    }
+
+    fn generate(provider: CiProvider, crate_type: &str, features: &[CiFeature]) -> String {
+        let tmp = TempDir::new().unwrap();
+        ci(
+            tmp.path(),
+            &prepare_context(crate_type),
+            provider,
+            features,
+            &Budget::default(),
+        )
+        .unwrap();
+        std::fs::read_to_string(tmp.path().join(provider.file_name())).unwrap()
+    }
+
+    #[test]
+    fn parses_features() {
+        assert_eq!("Clippy".parse::<CiFeature>().unwrap(), CiFeature::Clippy);
+        assert!("deploy".parse::<CiFeature>().is_err());
+    }
+
+    #[test]
+    fn github_keeps_expressions_and_skips_unselected_jobs() {
+        let pipeline = generate(CiProvider::GitHub, "lib", &[CiFeature::Test]);
+
+        assert!(pipeline.contains("runs-on: ${{ matrix.os }}"));
+        assert!(!pipeline.contains("cargo fmt"));
+        assert!(!pipeline.contains("cargo publish"));
+    }
+
+    #[test]
+    fn release_depends_on_crate_type() {
+        for provider in CiProvider::ALL {
+            let lib = generate(provider, "lib", &[CiFeature::Release]);
+            assert!(lib.contains("cargo publish"), "{provider:?}");
+
+            let bin = generate(provider, "bin", &[CiFeature::Release]);
+            assert!(bin.contains("cargo build --release"), "{provider:?}");
+            assert!(!bin.contains("cargo publish"), "{provider:?}");
+        }
+    }
+
+    #[test]
+    fn pipelines_render_cleanly_for_all_combinations() {
+        for provider in CiProvider::ALL {
+            for crate_type in ["lib", "bin"] {
+                let pipeline = generate(provider, crate_type, &CiFeature::ALL);
+                assert!(
+                    pipeline.lines().all(|l| !l.contains('\t')),
+                    "{provider:?} pipeline must not contain tabs"
+                );
+                assert!(!pipeline.contains("\n\n\n"), "{provider:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Container image and devcontainer generator.
+
+use anyhow::Result;
+use std::path::Path;
+
+use super::{render, write_file};
+use crate::limits::Budget;
+use crate::template::TemplateObjectResource;
+
+pub const CONTAINER_PLACEHOLDER: &str = "container";
+
+const DOCKERFILE: &str = include_str!("resources/container/Dockerfile");
+const DOCKERIGNORE: &str = include_str!("resources/container/dockerignore");
+const DEVCONTAINER: &str = include_str!("resources/container/devcontainer.json");
+
+/// The built-in `container` placeholder, asking whether the container files should be emitted.
+pub fn container_placeholder() -> toml::Value {
+    let mut table = toml::Table::new();
+    table.insert("type".into(), "bool".into());
+    table.insert(
+        "prompt".into(),
+        "Add a Dockerfile and a devcontainer configuration?".into(),
+    );
+    table.insert("default".into(), false.into());
+    toml::Value::Table(table)
+}
+
+/// Emits a `Dockerfile`, a `.dockerignore` and a `.devcontainer/devcontainer.json`.
+///
+/// The image of a `bin` crate ships the release binary, the one of a `lib` crate runs the tests.
+/// Both use the Rust version from the `rust_version` variable, or the latest one.
+pub fn container(
+    dir: &Path,
+    template_object: &TemplateObjectResource,
+    budget: &Budget,
+) -> Result<()> {
+    let dockerfile = render(DOCKERFILE, template_object)?;
+    write_file(dir, "Dockerfile", &dockerfile, budget)?;
+    write_file(dir, ".dockerignore", DOCKERIGNORE, budget)?;
+    write_file(
+        dir,
+        ".devcontainer/devcontainer.json",
+        &render(DEVCONTAINER, template_object)?,
+        budget,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        cell::RefCell,
+        sync::{Arc, Mutex},
+    };
+    use tempfile::TempDir;
+
+    fn prepare_context(crate_type: &str, rust_version: Option<&str>) -> TemplateObjectResource {
+        let mut ctx = serde_json::Map::new();
+        ctx.insert("project_name".into(), "my-project".into());
+        ctx.insert("crate_type".into(), crate_type.into());
+        if let Some(rust_version) = rust_version {
+            ctx.insert("rust_version".into(), rust_version.into());
+        }
+        Arc::new(Mutex::new(RefCell::new(ctx)))
+    }
+
+    #[test]
+    fn bin_image_ships_the_binary() {
+        let tmp = TempDir::new().unwrap();
+
+        container(
+            tmp.path(),
+            &prepare_context("bin", Some("1.80")),
+            &Budget::default(),
+        )
+        .unwrap();
+
+        let dockerfile = std::fs::read_to_string(tmp.path().join("Dockerfile")).unwrap();
+        assert!(dockerfile.starts_with("FROM rust:1.80 AS build\n"));
+        assert!(dockerfile.contains("CMD [\"my-project\"]"));
+        assert!(tmp.path().join(".dockerignore").exists());
+
+        let devcontainer =
+            std::fs::read_to_string(tmp.path().join(".devcontainer/devcontainer.json")).unwrap();
+        let devcontainer: serde_json::Value = serde_json::from_str(&devcontainer).unwrap();
+        assert_eq!(devcontainer["name"], "my-project");
+        assert_eq!(
+            devcontainer["features"]["ghcr.io/devcontainers/features/rust:1"]["version"],
+            "1.80"
+        );
+    }
+
+    #[test]
+    fn lib_image_runs_the_tests_on_the_latest_rust() {
+        let tmp = TempDir::new().unwrap();
+
+        container(
+            tmp.path(),
+            &prepare_context("lib", None),
+            &Budget::default(),
+        )
+        .unwrap();
+
+        let dockerfile = std::fs::read_to_string(tmp.path().join("Dockerfile")).unwrap();
+        assert!(dockerfile.starts_with("FROM rust:latest AS build\n"));
+        assert!(dockerfile.contains("RUN cargo test"));
+        assert!(!dockerfile.contains("CMD"));
+    }
+}
@@ -0,0 +1,253 @@
+//! License, code of conduct and contribution guide generators.
+
+use anyhow::{bail, Result};
+use std::{fmt, path::Path, str::FromStr};
+
+use super::{render, write_file};
+use crate::hooks::PoisonError;
+use crate::limits::Budget;
+use crate::template::TemplateObjectResource;
+
+pub const LICENSE_PLACEHOLDER: &str = "license";
+
+const LICENSE_MIT: &str = include_str!("resources/LICENSE-MIT");
+const LICENSE_APACHE: &str = include_str!("resources/LICENSE-APACHE");
+const CODE_OF_CONDUCT: &str = include_str!("resources/CODE_OF_CONDUCT.md");
+const CONTRIBUTING: &str = include_str!("resources/CONTRIBUTING.md");
+
+/// The licenses the `license` generator knows how to emit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum License {
+    MitOrApache2,
+    Mit,
+    Apache2,
+    None,
+}
+
+impl License {
+    const ALL: [Self; 4] = [Self::MitOrApache2, Self::Mit, Self::Apache2, Self::None];
+
+    /// Reads the license chosen via the `license` placeholder.
+    ///
+    /// Falls back to the default license if the variable is not set at all.
+    pub fn from_template_object(template_object: &TemplateObjectResource) -> Result<Self> {
+        let value = template_object
+            .lock()
+            .map_err(|_| PoisonError)?
+            .borrow()
+            .get(LICENSE_PLACEHOLDER)
+            .and_then(|v| v.as_str().map(str::to_string));
+
+        value.map_or(Ok(Self::MitOrApache2), |v| v.parse())
+    }
+}
+
+impl fmt::Display for License {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MitOrApache2 => write!(f, "MIT OR Apache-2.0"),
+            Self::Mit => write!(f, "MIT"),
+            Self::Apache2 => write!(f, "Apache-2.0"),
+            Self::None => write!(f, "none"),
+        }
+    }
+}
+
+impl FromStr for License {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mit or apache-2.0" | "mit/apache-2.0" | "apache-2.0 or mit" => Ok(Self::MitOrApache2),
+            "mit" => Ok(Self::Mit),
+            "apache-2.0" | "apache" => Ok(Self::Apache2),
+            "none" | "" => Ok(Self::None),
+            _ => bail!(
+                "Unsupported license `{s}`, must be one of {}",
+                Self::ALL
+                    .iter()
+                    .map(|l| format!("`{l}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// The built-in `license` placeholder, added when a template enables the license generator
+/// without defining a `license` placeholder itself.
+pub fn license_placeholder() -> toml::Value {
+    let mut table = toml::Table::new();
+    table.insert("type".into(), "string".into());
+    table.insert(
+        "prompt".into(),
+        "Which license should the project use?".into(),
+    );
+    table.insert(
+        "choices".into(),
+        toml::Value::Array(
+            License::ALL
+                .iter()
+                .map(|l| toml::Value::from(l.to_string()))
+                .collect(),
+        ),
+    );
+    table.insert("default".into(), License::MitOrApache2.to_string().into());
+    toml::Value::Table(table)
+}
+
+/// Emits `LICENSE` for a single license, or `LICENSE-MIT` and `LICENSE-APACHE` for a dual license.
+pub fn license(
+    dir: &Path,
+    template_object: &TemplateObjectResource,
+    license: License,
+    budget: &Budget,
+) -> Result<()> {
+    match license {
+        License::MitOrApache2 => {
+            let mit = render(LICENSE_MIT, template_object)?;
+            write_file(dir, "LICENSE-MIT", &mit, budget)?;
+            write_file(dir, "LICENSE-APACHE", LICENSE_APACHE, budget)
+        }
+        License::Mit => write_file(
+            dir,
+            "LICENSE",
+            &render(LICENSE_MIT, template_object)?,
+            budget,
+        ),
+        License::Apache2 => write_file(dir, "LICENSE", LICENSE_APACHE, budget),
+        License::None => Ok(()),
+    }
+}
+
+pub fn code_of_conduct(
+    dir: &Path,
+    template_object: &TemplateObjectResource,
+    budget: &Budget,
+) -> Result<()> {
+    write_file(
+        dir,
+        "CODE_OF_CONDUCT.md",
+        &render(CODE_OF_CONDUCT, template_object)?,
+        budget,
+    )
+}
+
+pub fn contributing(
+    dir: &Path,
+    template_object: &TemplateObjectResource,
+    budget: &Budget,
+) -> Result<()> {
+    write_file(
+        dir,
+        "CONTRIBUTING.md",
+        &render(CONTRIBUTING, template_object)?,
+        budget,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        cell::RefCell,
+        sync::{Arc, Mutex},
+    };
+    use tempfile::TempDir;
+
+    fn prepare_context(license: Option<&str>) -> TemplateObjectResource {
+        let mut ctx = serde_json::Map::new();
+        ctx.insert("authors".into(), "Jane Doe <jane@example.com>".into());
+        ctx.insert("project_name".into(), "my-project".into());
+        if let Some(license) = license {
+            ctx.insert(LICENSE_PLACEHOLDER.into(), license.into());
+        }
+        Arc::new(Mutex::new(RefCell::new(ctx)))
+    }
+
+    #[test]
+    fn parses_license_names() {
+        assert_eq!("MIT".parse::<License>().unwrap(), License::Mit);
+        assert_eq!("apache-2.0".parse::<License>().unwrap(), License::Apache2);
+        assert_eq!(
+            "MIT OR Apache-2.0".parse::<License>().unwrap(),
+            License::MitOrApache2
+        );
+        assert_eq!("none".parse::<License>().unwrap(), License::None);
+        assert!("GPL-3.0".parse::<License>().is_err());
+    }
+
+    #[test]
+    fn license_placeholder_choices_round_trip() {
+        let placeholder = license_placeholder();
+        let choices = placeholder["choices"].as_array().unwrap();
+        for choice in choices {
+            assert!(choice.as_str().unwrap().parse::<License>().is_ok());
+        }
+    }
+
+    #[test]
+    fn dual_license_emits_both_files() {
+        let tmp = TempDir::new().unwrap();
+        let ctx = prepare_context(Some("MIT OR Apache-2.0"));
+
+        license(
+            tmp.path(),
+            &ctx,
+            License::from_template_object(&ctx).unwrap(),
+            &Budget::default(),
+        )
+        .unwrap();
+
+        let mit = std::fs::read_to_string(tmp.path().join("LICENSE-MIT")).unwrap();
+        assert!(mit.starts_with("Copyright (c) "));
+        assert!(mit.contains("Jane Doe <jane@example.com>"));
+        assert!(tmp.path().join("LICENSE-APACHE").exists());
+        assert!(!tmp.path().join("LICENSE").exists());
+    }
+
+    #[test]
+    fn existing_files_are_not_overwritten() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("LICENSE"), "custom").unwrap();
+
+        license(
+            tmp.path(),
+            &prepare_context(None),
+            License::Mit,
+            &Budget::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("LICENSE")).unwrap(),
+            "custom"
+        );
+    }
+
+    #[test]
+    fn code_of_conduct_uses_email_from_authors() {
+        let tmp = TempDir::new().unwrap();
+
+        code_of_conduct(tmp.path(), &prepare_context(None), &Budget::default()).unwrap();
+
+        let coc = std::fs::read_to_string(tmp.path().join("CODE_OF_CONDUCT.md")).unwrap();
+        assert!(coc.contains("contacting the project team at jane@example.com."));
+    }
+
+    #[test]
+    fn contributing_mentions_the_license() {
+        let tmp = TempDir::new().unwrap();
+
+        contributing(
+            tmp.path(),
+            &prepare_context(Some("MIT")),
+            &Budget::default(),
+        )
+        .unwrap();
+
+        let contributing = std::fs::read_to_string(tmp.path().join("CONTRIBUTING.md")).unwrap();
+        assert!(contributing.starts_with("# Contributing to my-project"));
+        assert!(contributing.contains("licensed as\n`MIT`"));
+    }
+}
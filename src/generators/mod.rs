@@ -0,0 +1,206 @@
+//! Built-in generators for files that most projects need, and that templates used to vendor
+//! themselves (licenses, code of conduct, contribution guide, CI pipelines, ...).
+//!
+//! Generators are opt-in, either via the `[generators]` section of `cargo-generate.toml` or from
+//! hook scripts via the `generators` rhai module. They run after the template has been expanded,
+//! and never overwrite a file the template already ships. What they write is taken from the
+//! [`Budget`] of the generation, like the files rendered.
+
+mod ci;
+mod container;
+mod hygiene;
+mod pre_commit;
+
+pub use ci::{ci, CiFeature, CiProvider};
+pub use container::container;
+pub use hygiene::{code_of_conduct, contributing, license, License};
+pub use pre_commit::{activate_git_hooks, PreCommit};
+
+use anyhow::{Context, Result};
+use console::style;
+use fs_err as fs;
+use log::info;
+use minijinja::Environment;
+use std::path::Path;
+use time::OffsetDateTime;
+
+use crate::config::{Config, GeneratorsConfig, TemplateSlotsTable};
+use crate::emoji;
+use crate::hooks::PoisonError;
+use crate::limits::Budget;
+use crate::template::TemplateObjectResource;
+
+/// Adds the placeholders the enabled generators depend on, unless the template defines them.
+pub fn add_builtin_placeholders(config: &mut Config) {
+    let Some(generators) = config.generators.as_ref() else {
+        return;
+    };
+
+    let mut builtin = vec![];
+    if generators.license.unwrap_or(false) {
+        builtin.push((hygiene::LICENSE_PLACEHOLDER, hygiene::license_placeholder()));
+    }
+    if generators.pre_commit.is_some() {
+        builtin.push((
+            pre_commit::PRE_COMMIT_PLACEHOLDER,
+            pre_commit::pre_commit_placeholder(),
+        ));
+    }
+    if generators.container.unwrap_or(false) {
+        builtin.push((
+            container::CONTAINER_PLACEHOLDER,
+            container::container_placeholder(),
+        ));
+    }
+
+    if builtin.is_empty() {
+        return;
+    }
+    let placeholders = config
+        .placeholders
+        .get_or_insert_with(TemplateSlotsTable::default);
+    for (name, placeholder) in builtin {
+        placeholders
+            .0
+            .entry(name.to_string())
+            .or_insert(placeholder);
+    }
+}
+
+/// Runs all generators enabled in `config`, writing their output into `dir`.
+pub fn execute_generators(
+    config: Option<&GeneratorsConfig>,
+    dir: &Path,
+    template_object: &TemplateObjectResource,
+    budget: &Budget,
+) -> Result<()> {
+    let Some(config) = config else {
+        return Ok(());
+    };
+
+    if config.license.unwrap_or(false) {
+        let license = License::from_template_object(template_object)?;
+        hygiene::license(dir, template_object, license, budget)?;
+    }
+    if config.code_of_conduct.unwrap_or(false) {
+        hygiene::code_of_conduct(dir, template_object, budget)?;
+    }
+    if config.contributing.unwrap_or(false) {
+        hygiene::contributing(dir, template_object, budget)?;
+    }
+    if let Some(ci_config) = config.ci.as_ref() {
+        let features = ci_config.features.as_deref().unwrap_or(&CiFeature::DEFAULT);
+        ci::ci(dir, template_object, ci_config.provider, features, budget)?;
+    }
+    if let Some(kind) = config.pre_commit {
+        if is_answered_yes(template_object, pre_commit::PRE_COMMIT_PLACEHOLDER)? {
+            pre_commit::pre_commit(dir, kind, budget)?;
+        }
+    }
+    if config.container.unwrap_or(false)
+        && is_answered_yes(template_object, container::CONTAINER_PLACEHOLDER)?
+    {
+        container::container(dir, template_object, budget)?;
+    }
+
+    Ok(())
+}
+
+/// Reads the answer to one of the bool placeholders of the generators, which might also be given
+/// as a string via `--define`.
+fn is_answered_yes(template_object: &TemplateObjectResource, placeholder: &str) -> Result<bool> {
+    let answer = template_object
+        .lock()
+        .map_err(|_| PoisonError)?
+        .borrow()
+        .get(placeholder)
+        .is_some_and(|v| v.as_bool().unwrap_or_else(|| v.as_str() == Some("true")));
+    Ok(answer)
+}
+
+/// Renders one of the bundled resources against the template object.
+///
+/// Besides all template variables, the resources have access to `year` and `email`, the latter
+/// being extracted from `authors` if it has the form `name <email>`.
+fn render(resource: &str, template_object: &TemplateObjectResource) -> Result<String> {
+    render_with(resource, template_object, serde_json::Map::new())
+}
+
+/// Like [`render`], with additional variables that take precedence over the template variables.
+fn render_with(
+    resource: &str,
+    template_object: &TemplateObjectResource,
+    extra: serde_json::Map<String, serde_json::Value>,
+) -> Result<String> {
+    let mut context = template_object
+        .lock()
+        .map_err(|_| PoisonError)?
+        .borrow()
+        .clone();
+
+    let email = context
+        .get("authors")
+        .and_then(|authors| authors.as_str())
+        .and_then(|authors| authors.rsplit_once('<'))
+        .and_then(|(_, email)| email.strip_suffix('>'))
+        .map(str::to_string);
+    let year = context
+        .get("current_year")
+        .cloned()
+        .unwrap_or_else(|| OffsetDateTime::now_utc().year().into());
+    context.entry("year").or_insert(year);
+    context.entry("email").or_insert_with(|| email.into());
+    context.extend(extra);
+
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.render_str(resource, serde_json::Value::Object(context))
+        .context("Failed to render a built-in generator resource")
+}
+
+/// Writes a generated file, unless the template already ships a file with that name, failing if
+/// it exceeds the `budget`.
+fn write_file(dir: &Path, name: &str, contents: &str, budget: &Budget) -> Result<()> {
+    let path = dir.join(name);
+    if path.exists() {
+        info!(
+            "{} {} `{}`",
+            emoji::WRENCH,
+            style("Template already provides").bold(),
+            style(name).bold().yellow(),
+        );
+        return Ok(());
+    }
+
+    budget.add_file(Path::new(name), contents.len() as u64)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        cell::RefCell,
+        sync::{Arc, Mutex},
+    };
+
+    #[test]
+    fn reads_answers_from_bool_or_string() {
+        for (value, expected) in [
+            (serde_json::Value::from(true), true),
+            ("true".into(), true),
+            ("false".into(), false),
+        ] {
+            let mut ctx = serde_json::Map::new();
+            ctx.insert("answer".into(), value);
+            let ctx = Arc::new(Mutex::new(RefCell::new(ctx)));
+            assert_eq!(is_answered_yes(&ctx, "answer").unwrap(), expected);
+        }
+        assert!(!is_answered_yes(&TemplateObjectResource::default(), "answer").unwrap());
+    }
+}
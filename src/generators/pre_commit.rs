@@ -0,0 +1,156 @@
+//! Pre-commit hooks running `cargo fmt` and `cargo clippy`.
+
+use anyhow::Result;
+use console::style;
+use log::warn;
+use serde::Deserialize;
+use std::path::Path;
+
+use super::write_file;
+use crate::git;
+use crate::limits::Budget;
+
+pub const PRE_COMMIT_PLACEHOLDER: &str = "pre_commit";
+
+/// Where the git hook script goes, relative to the project root.
+pub const GIT_HOOKS_DIR: &str = ".githooks";
+
+const GIT_HOOK: &str = include_str!("resources/pre_commit/pre-commit.sh");
+const PRE_COMMIT_CONFIG: &str = include_str!("resources/pre_commit/pre-commit-config.yaml");
+
+/// How the pre-commit checks get installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreCommit {
+    /// A plain `.githooks/pre-commit` script, activated via `core.hooksPath`
+    GitHook,
+    /// A `.pre-commit-config.yaml` for the [pre-commit](https://pre-commit.com) framework
+    PreCommit,
+}
+
+/// The built-in `pre_commit` placeholder, asking whether the hook should be installed at all.
+pub fn pre_commit_placeholder() -> toml::Value {
+    let mut table = toml::Table::new();
+    table.insert("type".into(), "bool".into());
+    table.insert(
+        "prompt".into(),
+        "Install a pre-commit hook running `cargo fmt` and `cargo clippy`?".into(),
+    );
+    table.insert("default".into(), true.into());
+    toml::Value::Table(table)
+}
+
+pub fn pre_commit(dir: &Path, kind: PreCommit, budget: &Budget) -> Result<()> {
+    match kind {
+        PreCommit::GitHook => {
+            let file_name = format!("{GIT_HOOKS_DIR}/pre-commit");
+            write_file(dir, &file_name, GIT_HOOK, budget)?;
+            make_executable(&dir.join(file_name))
+        }
+        PreCommit::PreCommit => {
+            write_file(dir, ".pre-commit-config.yaml", PRE_COMMIT_CONFIG, budget)
+        }
+    }
+}
+
+/// Points the repository the project in `project_dir` is within at the generated hooks, whether
+/// it was initialized for the project or existed already. A repository using other hooks already
+/// is left alone, like a project outside of any repository, with a warning telling how to activate
+/// them.
+pub fn activate_git_hooks(project_dir: &Path) -> Result<()> {
+    if !project_dir.join(GIT_HOOKS_DIR).join("pre-commit").exists() {
+        return Ok(());
+    }
+    let backend = git::backend();
+    let Some(work_tree) = backend.work_tree(project_dir) else {
+        warn_inactive("The project is within no git repository", GIT_HOOKS_DIR);
+        return Ok(());
+    };
+    // the hooks path is relative to the root of the work tree
+    let hooks_path = project_dir
+        .canonicalize()?
+        .strip_prefix(work_tree.canonicalize()?)?
+        .join(GIT_HOOKS_DIR)
+        .to_string_lossy()
+        .replace('\\', "/");
+    match backend.config_value(Some(&work_tree), "core.hooksPath") {
+        Some(configured) if configured != hooks_path => warn_inactive(
+            &format!("The git repository uses the hooks in `{configured}` already"),
+            &hooks_path,
+        ),
+        _ => backend.set_config_value(&work_tree, "core.hooksPath", &hooks_path)?,
+    }
+    Ok(())
+}
+
+/// Warns that the pre-commit hook is not active, as of `reason`, and how to activate it.
+fn warn_inactive(reason: &str, hooks_path: &str) {
+    warn!(
+        "{}, {} `{}`",
+        style(reason).bold(),
+        style("to activate the pre-commit hook run").bold(),
+        style(format!("git config core.hooksPath {hooks_path}"))
+            .bold()
+            .yellow(),
+    );
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs_err::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs_err::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn git_hook_is_activated_for_the_new_repository() {
+        let tmp = TempDir::new().unwrap();
+        pre_commit(tmp.path(), PreCommit::GitHook, &Budget::default()).unwrap();
+        git::init(tmp.path(), None, false).unwrap();
+
+        activate_git_hooks(tmp.path()).unwrap();
+
+        assert_eq!(
+            git::backend()
+                .config_value(Some(tmp.path()), "core.hooksPath")
+                .as_deref(),
+            Some(GIT_HOOKS_DIR)
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let hook = tmp.path().join(GIT_HOOKS_DIR).join("pre-commit");
+            assert_ne!(hook.metadata().unwrap().permissions().mode() & 0o111, 0);
+        }
+    }
+
+    #[test]
+    fn git_hook_is_activated_in_the_repository_the_project_is_within() {
+        let tmp = TempDir::new().unwrap();
+        git::init(tmp.path(), None, false).unwrap();
+        let project_dir = tmp.path().join("crates/member");
+        pre_commit(&project_dir, PreCommit::GitHook, &Budget::default()).unwrap();
+
+        activate_git_hooks(&project_dir).unwrap();
+
+        assert_eq!(
+            git::backend()
+                .config_value(Some(tmp.path()), "core.hooksPath")
+                .as_deref(),
+            Some("crates/member/.githooks")
+        );
+    }
+}
@@ -0,0 +1,7 @@
+#!/bin/sh
+# Installed by cargo-generate, runs the same checks as CI before every commit.
+# Skip it once with `git commit --no-verify`.
+set -e
+
+cargo fmt --all -- --check
+cargo clippy --all-targets --all-features -- -D warnings
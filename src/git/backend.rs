@@ -0,0 +1,103 @@
+//! The version control operations cargo-generate relies on, behind a trait so the backend doing
+//! them can be chosen when building and running the tool.
+
+use anyhow::Result;
+use log::debug;
+#[cfg(not(feature = "libgit2"))]
+use log::warn;
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use super::cli::GitCli;
+#[cfg(feature = "libgit2")]
+use super::libgit2::LibGit2;
+
+/// Overrides the backend the tool was built to prefer, `libgit2` or `git-cli`.
+pub const BACKEND_ENV: &str = "CARGO_GENERATE_VCS_BACKEND";
+
+/// How [`VcsBackend::clone_repository`] checks out a repository.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CloneOptions<'a> {
+    pub branch: Option<&'a str>,
+    /// Checked out after cloning, takes precedence over `revision`
+    pub tag: Option<&'a str>,
+    pub revision: Option<&'a str>,
+    /// The SSH key to authenticate with
+    pub identity: Option<&'a Path>,
+    /// The git config to use instead of the user's one
+    pub gitconfig: Option<&'a Path>,
+    pub skip_submodules: bool,
+}
+
+/// What cargo-generate needs from a version control system.
+pub trait VcsBackend: Sync {
+    /// The name the backend is selected by with [`BACKEND_ENV`].
+    fn name(&self) -> &'static str;
+
+    /// Clones the repository at `url` into the existing, empty `destination`.
+    fn clone_repository(&self, url: &str, destination: &Path, options: &CloneOptions)
+        -> Result<()>;
+
+    /// Initializes a fresh repository in `project_dir`, on `branch` if given.
+    ///
+    /// Nothing happens if `project_dir` is within a repository already, unless it is `force`d.
+    fn init(&self, project_dir: &Path, branch: Option<&str>, force: bool) -> Result<()>;
+
+    /// The branch checked out in the repository at `dir`, if it is the root of one.
+    fn branch(&self, dir: &Path) -> Option<String>;
+
+    /// The id of the commit checked out in the repository at `dir`, if it is the root of one.
+    fn commit(&self, dir: &Path) -> Option<String>;
+
+    /// The root of the work tree `dir` is within, if any.
+    fn work_tree(&self, dir: &Path) -> Option<PathBuf>;
+
+    /// The value of `key` in the config of the repository `dir` is within, falling back to the
+    /// user's config, which is also used without `dir`.
+    fn config_value(&self, dir: Option<&Path>, key: &str) -> Option<String>;
+
+    /// Sets `key` to `value` in the config of the repository at `dir`.
+    fn set_config_value(&self, dir: &Path, key: &str, value: &str) -> Result<()>;
+
+    /// The URL of the `origin` remote of the repository `dir` is within.
+    fn origin_url(&self, dir: &Path) -> Option<String>;
+}
+
+/// The backend selected with [`BACKEND_ENV`], or else libgit2 if the tool was built with the
+/// `libgit2` feature, or else the `git` command line tool.
+pub fn backend() -> &'static dyn VcsBackend {
+    static BACKEND: OnceLock<&'static dyn VcsBackend> = OnceLock::new();
+    *BACKEND.get_or_init(|| {
+        let backend = select(std::env::var(BACKEND_ENV).ok().as_deref());
+        debug!("Using the `{}` version control backend", backend.name());
+        backend
+    })
+}
+
+fn select(selected: Option<&str>) -> &'static dyn VcsBackend {
+    #[cfg(feature = "libgit2")]
+    if selected != Some(GitCli.name()) {
+        return &LibGit2;
+    }
+    #[cfg(not(feature = "libgit2"))]
+    if selected == Some("libgit2") {
+        warn!("This build has no libgit2 support, using the `git` command line tool instead");
+    }
+    &GitCli
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_git_cli_backend_can_always_be_selected() {
+        assert_eq!(select(Some("git-cli")).name(), "git-cli");
+        #[cfg(feature = "libgit2")]
+        assert_eq!(select(None).name(), "libgit2");
+        #[cfg(not(feature = "libgit2"))]
+        assert_eq!(select(Some("libgit2")).name(), "git-cli");
+    }
+}
@@ -0,0 +1,193 @@
+//! The [`VcsBackend`] running the `git` command line tool, for builds without libgit2.
+
+use anyhow::{anyhow, Context, Result};
+use console::style;
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use super::backend::{CloneOptions, VcsBackend};
+use super::utils;
+use crate::resume;
+
+pub struct GitCli;
+
+/// A `git` command, run in `dir` if given.
+fn git(dir: Option<&Path>) -> Command {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    command
+}
+
+/// Runs `command`, returning what it printed.
+fn run(command: &mut Command) -> Result<String> {
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to run `git`, is it installed?")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let error = anyhow!("`git` failed: {}", stderr.trim());
+        return Err(resume::transient_if(is_unreachable(&stderr), error));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether `git` failed to reach a server over HTTP, or got an answer it may not get another
+/// time, as it tells in its `unable to access '<url>': <reason>`.
+fn is_unreachable(stderr: &str) -> bool {
+    let Some((_, reason)) = stderr
+        .split_once("unable to access '")
+        .and_then(|(_, access)| access.split_once("': "))
+    else {
+        return false;
+    };
+    reason
+        .strip_prefix("The requested URL returned error: ")
+        .is_none_or(|status| {
+            status
+                .get(..3)
+                .and_then(|status| status.parse().ok())
+                .is_some_and(resume::is_transient_status)
+        })
+}
+
+/// Whether `dir` is the root of a repository, as opposed to somewhere within one.
+fn is_repository_root(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+impl VcsBackend for GitCli {
+    fn name(&self) -> &'static str {
+        "git-cli"
+    }
+
+    fn clone_repository(
+        &self,
+        url: &str,
+        destination: &Path,
+        options: &CloneOptions,
+    ) -> Result<()> {
+        let destination = utils::canonicalize_path(destination)?;
+        let tag_or_revision = options.tag.or(options.revision);
+
+        // git refuses local submodules by default, as a remote template could reach into the
+        // machine with them, a local template however has access anyway
+        let is_local_repo = !url.contains("://") && Path::new(url).exists();
+        let allow_local_submodules = if is_local_repo {
+            ["-c", "protocol.file.allow=always"].as_slice()
+        } else {
+            &[]
+        };
+
+        let mut command = git(None);
+        command
+            .args(allow_local_submodules)
+            .args(["clone", "--quiet"]);
+        if let Some(branch) = options.branch {
+            command.args(["--branch", branch]);
+        }
+        let is_http_repo = url.starts_with("http://") || url.starts_with("https://");
+        if is_http_repo && tag_or_revision.is_none() {
+            command.args(["--depth", "1"]);
+        }
+        if !options.skip_submodules && tag_or_revision.is_none() {
+            command.arg("--recurse-submodules");
+        }
+        command.arg("--").arg(url).arg(&destination);
+
+        if let Some(gitconfig) = options.gitconfig {
+            command.env("GIT_CONFIG_GLOBAL", utils::canonicalize_path(gitconfig)?);
+        }
+        if let Some(identity) = options.identity {
+            let identity = utils::canonicalize_path(identity)?;
+            log::info!(
+                "{} `{}` {}",
+                style("Using private key:").bold(),
+                style(format_args!("{}", identity.display()))
+                    .bold()
+                    .yellow(),
+                style("for git-ssh checkout").bold()
+            );
+            command.env(
+                "GIT_SSH_COMMAND",
+                format!("ssh -i '{}' -o IdentitiesOnly=yes", identity.display()),
+            );
+        }
+        run(&mut command).context("Please check if the Git user / repository exists.")?;
+
+        if let Some(tag_or_revision) = tag_or_revision {
+            run(git(Some(&destination)).args(["checkout", "--quiet", tag_or_revision]))?;
+            if !options.skip_submodules {
+                run(git(Some(&destination)).args(allow_local_submodules).args([
+                    "submodule",
+                    "update",
+                    "--quiet",
+                    "--init",
+                    "--recursive",
+                ]))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn init(&self, project_dir: &Path, branch: Option<&str>, force: bool) -> Result<()> {
+        let within_repository =
+            run(git(Some(project_dir)).args(["rev-parse", "--git-dir"])).is_ok();
+        if !within_repository || (force && !is_repository_root(project_dir)) {
+            let mut command = git(None);
+            command.args(["init", "--quiet"]);
+            if let Some(branch) = branch {
+                command.args(["--initial-branch", branch]);
+            }
+            run(command.arg(project_dir))?;
+        }
+        Ok(())
+    }
+
+    fn branch(&self, dir: &Path) -> Option<String> {
+        if !is_repository_root(dir) {
+            return None;
+        }
+        run(git(Some(dir)).args(["symbolic-ref", "--quiet", "--short", "HEAD"])).ok()
+    }
+
+    fn commit(&self, dir: &Path) -> Option<String> {
+        if !is_repository_root(dir) {
+            return None;
+        }
+        run(git(Some(dir)).args(["rev-parse", "--verify", "--quiet", "HEAD^{commit}"])).ok()
+    }
+
+    fn work_tree(&self, dir: &Path) -> Option<PathBuf> {
+        run(git(Some(dir)).args(["rev-parse", "--show-toplevel"]))
+            .ok()
+            .map(PathBuf::from)
+    }
+
+    fn config_value(&self, dir: Option<&Path>, key: &str) -> Option<String> {
+        let mut command = git(dir);
+        if dir.is_none() {
+            // keeps the repository of the current directory, if any, from being consulted
+            command.env(
+                "GIT_DIR",
+                std::env::temp_dir().join("cargo-generate-no-repository"),
+            );
+        }
+        run(command.args(["config", "--get", key]))
+            .ok()
+            .filter(|value| !value.is_empty())
+    }
+
+    fn set_config_value(&self, dir: &Path, key: &str, value: &str) -> Result<()> {
+        run(git(Some(dir)).args(["config", key, value]))?;
+        Ok(())
+    }
+
+    fn origin_url(&self, dir: &Path) -> Option<String> {
+        run(git(Some(dir)).args(["remote", "get-url", "origin"])).ok()
+    }
+}
@@ -0,0 +1,104 @@
+//! The [`VcsBackend`] using libgit2, via the `git2` crate.
+
+use anyhow::Result;
+use git2::{Config, Repository, RepositoryInitOptions};
+use std::path::{Path, PathBuf};
+
+use super::backend::{CloneOptions, VcsBackend};
+use super::clone_tool::RepoCloneBuilder;
+
+pub struct LibGit2;
+
+impl VcsBackend for LibGit2 {
+    fn name(&self) -> &'static str {
+        "libgit2"
+    }
+
+    fn clone_repository(
+        &self,
+        url: &str,
+        destination: &Path,
+        options: &CloneOptions,
+    ) -> Result<()> {
+        RepoCloneBuilder::new(url)
+            .with_branch(options.branch)
+            .with_ssh_identity(options.identity)?
+            .with_submodules(!options.skip_submodules)
+            .with_gitconfig(options.gitconfig)?
+            .with_destination(destination)?
+            .with_tag(options.tag)
+            .with_revision(options.revision)
+            .build()?
+            .do_clone()?;
+        Ok(())
+    }
+
+    fn init(&self, project_dir: &Path, branch: Option<&str>, force: bool) -> Result<()> {
+        if Repository::discover(project_dir).is_err()
+            || (force && Repository::open(project_dir).is_err())
+        {
+            let mut opts = RepositoryInitOptions::new();
+            opts.bare(false);
+            if let Some(branch) = branch {
+                opts.initial_head(branch);
+            }
+            Repository::init_opts(project_dir, &opts)?;
+        }
+        Ok(())
+    }
+
+    fn branch(&self, dir: &Path) -> Option<String> {
+        Repository::open(dir)
+            .ok()
+            .and_then(|repo| get_branch_name_repo(&repo).ok())
+    }
+
+    fn commit(&self, dir: &Path) -> Option<String> {
+        let repo = Repository::open(dir).ok()?;
+        let commit = repo.head().ok()?.peel_to_commit().ok()?;
+        Some(commit.id().to_string())
+    }
+
+    fn work_tree(&self, dir: &Path) -> Option<PathBuf> {
+        let repo = Repository::discover(dir).ok()?;
+        repo.workdir().map(Path::to_path_buf)
+    }
+
+    fn config_value(&self, dir: Option<&Path>, key: &str) -> Option<String> {
+        dir.map_or_else(Config::open_default, |dir| {
+            Repository::discover(dir)
+                .and_then(|repo| repo.config())
+                .or_else(|_| Config::open_default())
+        })
+        .and_then(|config| config.get_string(key))
+        .ok()
+    }
+
+    fn set_config_value(&self, dir: &Path, key: &str, value: &str) -> Result<()> {
+        Repository::open(dir)?.config()?.set_str(key, value)?;
+        Ok(())
+    }
+
+    fn origin_url(&self, dir: &Path) -> Option<String> {
+        let repo = Repository::discover(dir).ok()?;
+        let remote = repo.find_remote("origin").ok()?;
+        remote.url().map(str::to_string)
+    }
+}
+
+/// thanks to @extrawurst for pointing this out
+/// <https://github.com/extrawurst/gitui/blob/master/asyncgit/src/sync/branch/mod.rs#L38>
+fn get_branch_name_repo(repo: &Repository) -> Result<String> {
+    let iter = repo.branches(None)?;
+
+    for b in iter {
+        let b = b?;
+
+        if b.0.is_head() {
+            let name = b.0.name()?.unwrap_or("");
+            return Ok(name.into());
+        }
+    }
+
+    anyhow::bail!("A repo has no Head")
+}
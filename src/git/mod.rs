@@ -4,16 +4,22 @@ use std::path::Path;
 use std::{io, ops::Sub, thread::sleep, time::Duration};
 
 use anyhow::Result;
-use git2::{Repository, RepositoryInitOptions};
 use log::warn;
 use remove_dir_all::remove_dir_all;
 pub use utils::clone_git_template_into_temp;
 
+mod backend;
+mod cli;
+#[cfg(feature = "libgit2")]
 mod clone_tool;
+#[cfg(feature = "libgit2")]
 mod gitconfig;
+#[cfg(feature = "libgit2")]
+mod libgit2;
 mod utils;
 
-pub use utils::{tmp_dir, try_get_branch_from_path};
+pub use backend::backend;
+pub use utils::{tmp_dir, try_get_branch_from_path, try_get_commit_from_path};
 
 // cargo-generate (as application) want from git module:
 // 1. cloning remote
@@ -30,33 +36,17 @@ pub use utils::{tmp_dir, try_get_branch_from_path};
 // basically we want to call:
 // git clone --recurse-submodules --depth 1 --branch <branch> <url> <tmp_dir>
 // with --recurse-submodules being optional.
-
-type Git2Result<T> = Result<T, git2::Error>;
+//
+// All of that is done by a `VcsBackend`: libgit2 when built with the `libgit2` feature (the
+// default), or the `git` command line tool otherwise or when `$CARGO_GENERATE_VCS_BACKEND` is
+// `git-cli`.
 
 /// Init project_dir with fresh repository on branch
 ///
 /// Arguments:
 /// - `force` - enforce a fresh git init
-pub fn init(project_dir: &Path, branch: Option<&str>, force: bool) -> Git2Result<Repository> {
-    Repository::discover(project_dir).map_or_else(
-        |_| just_init(project_dir, branch),
-        |repo| {
-            if force {
-                Repository::open(project_dir).or_else(|_| just_init(project_dir, branch))
-            } else {
-                Ok(repo)
-            }
-        },
-    )
-}
-
-fn just_init(project_dir: &Path, branch: Option<&str>) -> Git2Result<Repository> {
-    let mut opts = RepositoryInitOptions::new();
-    opts.bare(false);
-    if let Some(branch) = branch {
-        opts.initial_head(branch);
-    }
-    Repository::init_opts(project_dir, &opts)
+pub fn init(project_dir: &Path, branch: Option<&str>, force: bool) -> Result<()> {
+    backend().init(project_dir, branch, force)
 }
 
 /// remove context of repository by removing `.git` from filesystem
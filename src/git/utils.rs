@@ -2,10 +2,9 @@ use anyhow::Context;
 use anyhow::Result;
 use std::path::{Path, PathBuf};
 
-use git2::Repository;
 use tempfile::TempDir;
 
-use super::clone_tool::RepoCloneBuilder;
+use super::backend::{backend, CloneOptions};
 
 pub fn tmp_dir() -> std::io::Result<tempfile::TempDir> {
     tempfile::Builder::new().prefix("cargo-generate").tempdir()
@@ -31,7 +30,7 @@ pub fn home() -> Result<PathBuf> {
     home::home_dir().context("$HOME was not set")
 }
 
-// clone git repository into temp using libgit2
+// clone git repository into temp using the selected backend
 pub fn clone_git_template_into_temp(
     git_url: &str,
     branch: Option<&str>,
@@ -43,43 +42,28 @@ pub fn clone_git_template_into_temp(
 ) -> anyhow::Result<(TempDir, Option<String>)> {
     let git_clone_dir = tmp_dir()?;
 
-    let repo = RepoCloneBuilder::new(git_url)
-        .with_branch(branch)
-        .with_ssh_identity(identity)?
-        .with_submodules(!skip_submodules)
-        .with_gitconfig(gitconfig)?
-        .with_destination(git_clone_dir.path())?
-        .with_tag(tag)
-        .with_revision(revision)
-        .build()?
-        .do_clone()?;
+    let options = CloneOptions {
+        branch,
+        tag,
+        revision,
+        identity,
+        gitconfig,
+        skip_submodules,
+    };
+    backend().clone_repository(git_url, git_clone_dir.path(), &options)?;
 
-    let branch = get_branch_name_repo(&repo).ok();
+    let branch = backend().branch(git_clone_dir.path());
 
     Ok((git_clone_dir, branch))
 }
 
 pub fn try_get_branch_from_path(git: impl AsRef<Path>) -> Option<String> {
-    Repository::open(git)
-        .ok()
-        .and_then(|repo| get_branch_name_repo(&repo).ok())
+    backend().branch(git.as_ref())
 }
 
-/// thanks to @extrawurst for pointing this out
-/// <https://github.com/extrawurst/gitui/blob/master/asyncgit/src/sync/branch/mod.rs#L38>
-fn get_branch_name_repo(repo: &Repository) -> anyhow::Result<String> {
-    let iter = repo.branches(None)?;
-
-    for b in iter {
-        let b = b?;
-
-        if b.0.is_head() {
-            let name = b.0.name()?.unwrap_or("");
-            return Ok(name.into());
-        }
-    }
-
-    anyhow::bail!("A repo has no Head")
+/// The id of the commit checked out in the repository at `git`, if it is one.
+pub fn try_get_commit_from_path(git: impl AsRef<Path>) -> Option<String> {
+    backend().commit(git.as_ref())
 }
 
 #[test]
@@ -0,0 +1,88 @@
+//! A bundled library of `.gitignore` fragments, combined by the `gitignore` template function,
+//! e.g. `{{ gitignore(["rust", "vscode", "macos"]) }}`.
+
+use minijinja::{Error, ErrorKind};
+use std::collections::HashSet;
+
+const FRAGMENTS: [(&str, &str); 10] = [
+    ("rust", include_str!("resources/gitignore/rust.gitignore")),
+    ("node", include_str!("resources/gitignore/node.gitignore")),
+    (
+        "python",
+        include_str!("resources/gitignore/python.gitignore"),
+    ),
+    (
+        "jetbrains",
+        include_str!("resources/gitignore/jetbrains.gitignore"),
+    ),
+    (
+        "vscode",
+        include_str!("resources/gitignore/vscode.gitignore"),
+    ),
+    ("macos", include_str!("resources/gitignore/macos.gitignore")),
+    (
+        "windows",
+        include_str!("resources/gitignore/windows.gitignore"),
+    ),
+    ("linux", include_str!("resources/gitignore/linux.gitignore")),
+    ("vim", include_str!("resources/gitignore/vim.gitignore")),
+    ("emacs", include_str!("resources/gitignore/emacs.gitignore")),
+];
+
+/// Combines the fragments `names` into one `.gitignore`, each under a heading, leaving out the
+/// patterns an earlier fragment has already.
+pub fn gitignore(names: Vec<String>) -> Result<String, Error> {
+    let mut picked = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut sections = vec![];
+    for name in &names {
+        let Some((name, fragment)) = FRAGMENTS
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        else {
+            let known = FRAGMENTS.map(|(known, _)| format!("`{known}`"));
+            return Err(Error::new(
+                ErrorKind::InvalidOperation,
+                format!(
+                    "unknown gitignore `{name}`, must be one of {}",
+                    known.join(", ")
+                ),
+            ));
+        };
+        if !picked.insert(*name) {
+            continue;
+        }
+        let lines = fragment
+            .lines()
+            .filter(|line| {
+                let pattern = line.trim();
+                pattern.is_empty() || pattern.starts_with('#') || seen.insert(pattern)
+            })
+            .collect::<Vec<_>>();
+        sections.push(format!("# {name}\n{}\n", lines.join("\n")));
+    }
+    Ok(sections.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragments_are_combined_without_repeating_patterns() {
+        let combined = gitignore(vec!["rust".into(), "Vim".into(), "linux".into()]).unwrap();
+
+        assert!(combined.starts_with("# rust\n"));
+        assert!(combined.contains("/target/"));
+        assert!(combined.contains("\n# vim\n"));
+        assert!(combined.contains("\n# linux\n"));
+        // both vim and linux ignore backup files
+        assert_eq!(combined.matches("\n*~\n").count(), 1);
+    }
+
+    #[test]
+    fn unknown_fragments_are_errors() {
+        let error = gitignore(vec!["rust".into(), "cobol".into()]).unwrap_err();
+        assert!(error.to_string().contains("cobol"), "{error}");
+    }
+}
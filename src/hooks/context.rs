@@ -1,7 +1,13 @@
+use indexmap::IndexMap;
+use indicatif::MultiProgress;
 use std::fmt;
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::config::HookSettings;
+use crate::generation::Generation;
 use crate::template::TemplateObjectResource;
+use crate::GenerateArgs;
 
 pub struct RhaiHooksContext {
     pub template_object: TemplateObjectResource,
@@ -9,6 +15,40 @@ pub struct RhaiHooksContext {
     pub silent: bool,
     pub working_directory: PathBuf,
     pub destination_directory: PathBuf,
+    /// Whether the project name is final, protected like the other built-in variables. Init
+    /// hooks may still set it
+    pub project_name_final: bool,
+    /// Whether `render` keeps the whitespace around block tags, like `preserve_whitespace` of the
+    /// template
+    pub preserve_whitespace: bool,
+    /// The settings of the hooks declared with any, by script
+    pub hook_settings: IndexMap<String, HookSettings>,
+    /// The arguments of the running generation, the base of nested generations
+    pub generate_args: GenerateArgs,
+    /// The progress of the generation, where the bars of hooks are shown
+    pub progress: MultiProgress,
+    /// The generation the hooks run for, its moment being the one of `system::date()`
+    pub generation: Arc<Generation>,
+}
+
+impl RhaiHooksContext {
+    /// A context of hooks run in `dir` for tests, silent and not allowed to run commands.
+    #[cfg(test)]
+    pub fn for_test(dir: &std::path::Path) -> Self {
+        Self {
+            template_object: TemplateObjectResource::default(),
+            allow_commands: false,
+            silent: true,
+            working_directory: dir.to_path_buf(),
+            destination_directory: dir.join("destination"),
+            project_name_final: true,
+            preserve_whitespace: false,
+            hook_settings: IndexMap::new(),
+            generate_args: GenerateArgs::default(),
+            progress: MultiProgress::default(),
+            generation: Arc::default(),
+        }
+    }
 }
 
 impl fmt::Debug for RhaiHooksContext {
@@ -19,6 +59,12 @@ impl fmt::Debug for RhaiHooksContext {
             .field("silent", &self.silent)
             .field("working_directory", &self.working_directory)
             .field("destination_directory", &self.destination_directory)
+            .field("project_name_final", &self.project_name_final)
+            .field("preserve_whitespace", &self.preserve_whitespace)
+            .field("hook_settings", &self.hook_settings)
+            .field("generate_args", &self.generate_args)
+            .field("progress", &"<MultiProgress>")
+            .field("generation", &self.generation)
             .finish()
     }
 }
@@ -0,0 +1,120 @@
+use rhai::{Dynamic, Map, Module};
+
+use crate::GenerateArgs;
+
+/// Creates the context module, telling hooks what was given on the command line, e.g. to tell a
+/// value the user chose with `--define` from a default.
+pub fn create_module(args: &GenerateArgs) -> Module {
+    let mut module = Module::new();
+
+    let defines = defines(&args.define);
+    module.set_native_fn("defines", move || Ok(defines.clone()));
+
+    let args = self::args(args);
+    module.set_native_fn("args", move || Ok(args.clone()));
+
+    module
+}
+
+/// The `--define key=value` pairs as they were given, without the values from anywhere else.
+fn defines(definitions: &[String]) -> Map {
+    definitions
+        .iter()
+        .filter_map(|definition| definition.split_once('='))
+        .map(|(key, value)| (key.trim().into(), value.trim().to_string().into()))
+        .collect()
+}
+
+/// The flags and options of the command line a hook may care about, `()` for those not given.
+fn args(args: &GenerateArgs) -> Map {
+    let optional = |value: Option<&String>| value.cloned().map_or(Dynamic::UNIT, Dynamic::from);
+    let flags = [
+        ("silent", args.silent),
+        ("force", args.force),
+        ("init", args.init),
+        ("overwrite", args.overwrite),
+        ("allow_commands", args.allow_commands),
+        ("lib", args.lib),
+        ("bin", args.bin),
+        ("lockfile", args.lockfile),
+        ("test", args.template_path.test),
+    ];
+
+    let mut map = flags
+        .into_iter()
+        .map(|(name, value)| (name.into(), value.into()))
+        .collect::<Map>();
+    map.insert("name".into(), optional(args.name.as_ref()));
+    map.insert(
+        "destination".into(),
+        optional(
+            args.destination
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .as_ref(),
+        ),
+    );
+    map.insert(
+        "vcs".into(),
+        optional(
+            args.vcs
+                .map(|vcs| format!("{vcs:?}").to_lowercase())
+                .as_ref(),
+        ),
+    );
+    map.insert(
+        "values_file".into(),
+        optional(args.template_values_file.as_ref()),
+    );
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hooks::{create_rhai_engine, RhaiHooksContext},
+        GenerateArgs, Vcs,
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_context_module() {
+        let tmp_dir = TempDir::new().unwrap();
+        let context = RhaiHooksContext {
+            generate_args: GenerateArgs {
+                name: Some("foobar".into()),
+                silent: true,
+                vcs: Some(Vcs::None),
+                define: vec!["license=MIT".into(), "empty=".into()],
+                ..GenerateArgs::default()
+            },
+            ..RhaiHooksContext::for_test(tmp_dir.path())
+        };
+        let engine = create_rhai_engine(&context);
+
+        assert_eq!(
+            engine.eval::<String>("context::defines().license").unwrap(),
+            "MIT"
+        );
+        assert_eq!(
+            engine.eval::<String>("context::defines().empty").unwrap(),
+            ""
+        );
+        assert!(engine
+            .eval::<bool>(r#""author" !in context::defines()"#)
+            .unwrap());
+        assert!(engine.eval::<bool>("context::args().silent").unwrap());
+        assert!(!engine.eval::<bool>("context::args().force").unwrap());
+        assert_eq!(
+            engine.eval::<String>("context::args().name").unwrap(),
+            "foobar"
+        );
+        assert_eq!(
+            engine.eval::<String>("context::args().vcs").unwrap(),
+            "none"
+        );
+        assert!(engine
+            .eval::<bool>("context::args().destination == ()")
+            .unwrap());
+    }
+}
@@ -28,21 +28,15 @@ pub fn create_module(env: Environment) -> Module {
 
 #[cfg(test)]
 mod tests {
-    use crate::{
-        hooks::{create_rhai_engine, RhaiHooksContext},
-        template::TemplateObjectResource,
-    };
+    use crate::hooks::{create_rhai_engine, RhaiHooksContext};
     use tempfile::TempDir;
 
     #[test]
     fn test_env_module() {
         let tmp_dir = TempDir::new().unwrap();
         let context = RhaiHooksContext {
-            working_directory: tmp_dir.path().to_path_buf(),
-            destination_directory: tmp_dir.path().join("destination").to_path_buf(),
-            template_object: TemplateObjectResource::default(),
             allow_commands: true,
-            silent: true,
+            ..RhaiHooksContext::for_test(tmp_dir.path())
         };
         let engine = create_rhai_engine(&context);
 
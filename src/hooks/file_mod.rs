@@ -86,17 +86,18 @@ pub fn create_module(dir: &Path) -> Module {
 }
 
 fn listdir(base_dir: &Path, path: &str) -> HookResult<Array> {
-    let entries = std::fs::read_dir(to_sandboxed_absolute_path(base_dir, path)?)
+    let mut entries = std::fs::read_dir(to_sandboxed_absolute_path(base_dir, path)?)
         .map_err(|e| e.to_string())?
         .filter_map(|e| e.ok())
         .filter_map(|entry| entry.path().to_str().map(|s| s.to_string()))
-        .map(Dynamic::from)
-        .collect::<Array>();
+        .collect::<Vec<_>>();
+    // the order of `read_dir` depends on the file system, scripts must not
+    entries.sort();
 
-    Ok(entries)
+    Ok(entries.into_iter().map(Dynamic::from).collect())
 }
 
-fn to_sandboxed_absolute_path(sandbox_dir: &Path, any_path: &str) -> HookResult<PathBuf> {
+pub(super) fn to_sandboxed_absolute_path(sandbox_dir: &Path, any_path: &str) -> HookResult<PathBuf> {
     Ok(PathBuf::from(any_path)
         .as_sandboxed_absolute(sandbox_dir)
         .map_err(|_| invalid_path(any_path))?)
@@ -114,10 +115,7 @@ fn invalid_path(path: &str) -> String {
 mod tests {
     use std::io::Write;
 
-    use crate::{
-        hooks::{create_rhai_engine, RhaiHooksContext},
-        template::TemplateObjectResource,
-    };
+    use crate::hooks::{create_rhai_engine, RhaiHooksContext};
     use rhai::Array;
     use tempfile::TempDir;
 
@@ -222,11 +220,8 @@ mod tests {
 
     fn prepare_context(tmp_dir: &TempDir) -> RhaiHooksContext {
         RhaiHooksContext {
-            working_directory: tmp_dir.path().to_path_buf(),
-            destination_directory: tmp_dir.path().join("destination").to_path_buf(),
-            template_object: TemplateObjectResource::default(),
             allow_commands: true,
-            silent: true,
+            ..RhaiHooksContext::for_test(tmp_dir.path())
         }
     }
 }
@@ -0,0 +1,115 @@
+use rhai::{Map, Module};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{GenerateArgs, GenerateOptions, TemplatePath, Vcs};
+
+use super::{CleanupJob, HookResult};
+
+/// How deep templates may generate projects from templates, which in turn generate projects.
+pub const MAX_NESTING_DEPTH: usize = 4;
+
+static DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Creates the generate module, letting hooks generate sub-projects from other templates.
+/// Sub-projects are generated into the template folder, so they are moved to the destination
+/// with the rest; `args` are the arguments of the generation running the hook.
+pub fn create_module(dir: &Path, args: &GenerateArgs) -> Module {
+    let mut module = Module::new();
+
+    module.set_native_fn("run", {
+        let dir = dir.to_owned();
+        let args = args.clone();
+        move |template: &str, name: &str| -> HookResult<String> {
+            run(&dir, &args, template, name, Map::new())
+        }
+    });
+
+    module.set_native_fn("run", {
+        let dir = dir.to_owned();
+        let args = args.clone();
+        move |template: &str, name: &str, defines: Map| -> HookResult<String> {
+            run(&dir, &args, template, name, defines)
+        }
+    });
+
+    module
+}
+
+/// Generates the project `name` from `template` into `dir` and returns its path relative to
+/// `dir`.
+fn run(
+    dir: &Path,
+    args: &GenerateArgs,
+    template: &str,
+    name: &str,
+    defines: Map,
+) -> HookResult<String> {
+    if DEPTH.fetch_add(1, Ordering::SeqCst) >= MAX_NESTING_DEPTH {
+        DEPTH.fetch_sub(1, Ordering::SeqCst);
+        return Err(format!(
+            "Not generating `{name}`, templates are nested more than {MAX_NESTING_DEPTH} levels deep"
+        )
+        .into());
+    }
+    let _depth = CleanupJob::new(|| {
+        DEPTH.fetch_sub(1, Ordering::SeqCst);
+    });
+
+    // relative paths point into the template, anything else is left to the template selection,
+    // which goes online only if the generation running the hook may
+    let local_template = dir.join(template);
+    let template_path = if local_template.is_dir() {
+        TemplatePath {
+            path: Some(local_template.display().to_string()),
+            ..TemplatePath::default()
+        }
+    } else if !args.allow_network {
+        return Err(format!(
+            "Generating `{name}` from `{template}`, which is no local folder, needs `--allow-network`"
+        )
+        .into());
+    } else {
+        TemplatePath {
+            auto_path: Some(template.to_string()),
+            ..TemplatePath::default()
+        }
+    };
+    let define = defines
+        .into_iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+
+    let nested_args = GenerateArgs {
+        template_path,
+        name: Some(name.to_string()),
+        destination: Some(dir.to_owned()),
+        define,
+        template_values_file: None,
+        values_url: None,
+        vcs: Some(Vcs::None),
+        init: false,
+        force: false,
+        force_git_init: false,
+        overwrite: false,
+        regenerate_script: false,
+        other_args: None,
+        command: None,
+        // timings of nested projects are part of the report of the outermost one, their variables
+        // are their own
+        profile_render: None,
+        explain_var: vec![],
+        ..args.clone()
+    };
+
+    let options = GenerateOptions {
+        nested: true,
+        ..GenerateOptions::default()
+    };
+    let project_dir = crate::generate_with(nested_args, options).map_err(|e| format!("{e:#}"))?;
+    Ok(project_dir
+        .strip_prefix(dir)
+        .unwrap_or(&project_dir)
+        .display()
+        .to_string())
+}
@@ -0,0 +1,149 @@
+use rhai::{Array, Module};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::generation::Generation;
+use crate::generators::{self, CiFeature, CiProvider, License};
+use crate::template::TemplateObjectResource;
+
+use super::HookResult;
+
+/// Creates the generators module, giving hooks access to the built-in generators.
+/// All output goes into the template folder, so it is moved to the destination with the rest,
+/// and is taken from the budget of the `generation`.
+pub fn create_module(
+    dir: &Path,
+    template_object: &TemplateObjectResource,
+    generation: &Arc<Generation>,
+) -> Module {
+    let dir = dir.to_owned();
+    let mut module = Module::new();
+
+    module.set_native_fn("license", {
+        let dir = dir.clone();
+        let template_object = template_object.clone();
+        let generation = generation.clone();
+        move |license: &str| -> HookResult<()> {
+            let license = license.parse::<License>().map_err(|e| e.to_string())?;
+            generators::license(&dir, &template_object, license, &generation.budget)
+                .map_err(|e| e.to_string().into())
+        }
+    });
+
+    module.set_native_fn("license", {
+        let dir = dir.clone();
+        let template_object = template_object.clone();
+        let generation = generation.clone();
+        move || -> HookResult<()> {
+            let license =
+                License::from_template_object(&template_object).map_err(|e| e.to_string())?;
+            generators::license(&dir, &template_object, license, &generation.budget)
+                .map_err(|e| e.to_string().into())
+        }
+    });
+
+    module.set_native_fn("code_of_conduct", {
+        let dir = dir.clone();
+        let template_object = template_object.clone();
+        let generation = generation.clone();
+        move || -> HookResult<()> {
+            generators::code_of_conduct(&dir, &template_object, &generation.budget)
+                .map_err(|e| e.to_string().into())
+        }
+    });
+
+    module.set_native_fn("contributing", {
+        let dir = dir.clone();
+        let template_object = template_object.clone();
+        let generation = generation.clone();
+        move || -> HookResult<()> {
+            generators::contributing(&dir, &template_object, &generation.budget)
+                .map_err(|e| e.to_string().into())
+        }
+    });
+
+    module.set_native_fn("container", {
+        let dir = dir.clone();
+        let template_object = template_object.clone();
+        let generation = generation.clone();
+        move || -> HookResult<()> {
+            generators::container(&dir, &template_object, &generation.budget)
+                .map_err(|e| e.to_string().into())
+        }
+    });
+
+    module.set_native_fn("ci", {
+        let dir = dir.clone();
+        let template_object = template_object.clone();
+        let generation = generation.clone();
+        move |provider: &str| -> HookResult<()> {
+            let provider = provider.parse::<CiProvider>().map_err(|e| e.to_string())?;
+            generators::ci(
+                &dir,
+                &template_object,
+                provider,
+                &CiFeature::DEFAULT,
+                &generation.budget,
+            )
+            .map_err(|e| e.to_string().into())
+        }
+    });
+
+    module.set_native_fn("ci", {
+        let dir = dir.clone();
+        let template_object = template_object.clone();
+        let generation = generation.clone();
+        move |provider: &str, features: Array| -> HookResult<()> {
+            let provider = provider.parse::<CiProvider>().map_err(|e| e.to_string())?;
+            let features = features
+                .into_iter()
+                .map(|feature| {
+                    feature
+                        .into_immutable_string()
+                        .map_err(|t| format!("Expected a CI feature name, found `{t}`"))
+                        .and_then(|feature| feature.parse::<CiFeature>().map_err(|e| e.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            generators::ci(
+                &dir,
+                &template_object,
+                provider,
+                &features,
+                &generation.budget,
+            )
+            .map_err(|e| e.to_string().into())
+        }
+    });
+
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hooks::{create_rhai_engine, RhaiHooksContext};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_generators_module() {
+        let tmp_dir = TempDir::new().unwrap();
+        let context = RhaiHooksContext::for_test(tmp_dir.path());
+        let engine = create_rhai_engine(&context);
+
+        engine
+            .eval::<()>(r#"generators::license("Apache-2.0")"#)
+            .unwrap();
+        assert!(tmp_dir.path().join("LICENSE").exists());
+
+        let err = engine
+            .eval::<()>(r#"generators::license("WTFPL")"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("Unsupported license `WTFPL`"));
+
+        engine
+            .eval::<()>(r#"generators::ci("gitlab", ["fmt", "clippy"])"#)
+            .unwrap();
+        let pipeline = std::fs::read_to_string(tmp_dir.path().join(".gitlab-ci.yml")).unwrap();
+        assert!(pipeline.contains("cargo clippy"));
+        assert!(!pipeline.contains("cargo test"));
+    }
+}
@@ -0,0 +1,228 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget};
+use rhai::Module;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use crate::progressbar;
+use crate::sha256::Sha256;
+
+use super::file_mod::to_sandboxed_absolute_path;
+use super::HookResult;
+
+/// How often the bytes downloaded so far are shown.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What a download is checked against, and how it is shown.
+struct Download<'a> {
+    url: &'a str,
+    destination: PathBuf,
+    sha256: String,
+    progress: &'a MultiProgress,
+    quiet: bool,
+}
+
+/// Creates the http module, letting hooks download what is too large to be part of a template,
+/// e.g. models or SDKs, checking each download against its SHA-256.
+///
+/// Like all going online, downloading needs `--allow-network`. Downloads are made with `curl`.
+pub fn create_module(
+    dir: &Path,
+    progress: &MultiProgress,
+    allow_network: bool,
+    quiet: bool,
+) -> Module {
+    let dir = dir.to_owned();
+    let progress = progress.clone();
+    let mut module = Module::new();
+
+    module.set_native_fn(
+        "download",
+        move |url: &str, destination: &str, sha256: &str| -> HookResult<()> {
+            if !allow_network {
+                return Err(format!("Downloading `{url}` needs `--allow-network`").into());
+            }
+            Download {
+                url,
+                destination: to_sandboxed_absolute_path(&dir, destination)?,
+                sha256: checksum(sha256)?,
+                progress: &progress,
+                quiet,
+            }
+            .run()
+            .map_err(|e| format!("Failed downloading `{url}`: {e}").into())
+        },
+    );
+
+    module
+}
+
+/// The SHA-256 `sha256` in lowercase, if it is one.
+fn checksum(sha256: &str) -> HookResult<String> {
+    if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("`{sha256}` is no SHA-256, 64 hexadecimal digits").into());
+    }
+    Ok(sha256.to_ascii_lowercase())
+}
+
+impl Download<'_> {
+    /// Downloads next to the destination first, where nothing is left of a failed download, and
+    /// only a download of the right checksum is moved to the destination.
+    fn run(&self) -> Result<(), String> {
+        let folder = self
+            .destination
+            .parent()
+            .ok_or("the destination is no file")?;
+        fs::create_dir_all(folder).map_err(|e| e.to_string())?;
+        let partial = tempfile::NamedTempFile::new_in(folder).map_err(|e| e.to_string())?;
+
+        let name = self
+            .destination
+            .strip_prefix(folder)
+            .unwrap_or(&self.destination);
+        let bar = self.progress.add(
+            ProgressBar::new_spinner()
+                .with_style(progressbar::download())
+                .with_message(format!("Downloading {}", name.display())),
+        );
+        if self.quiet {
+            bar.set_draw_target(ProgressDrawTarget::hidden());
+        }
+        let downloaded = self.fetch(partial.path(), &bar);
+        bar.finish_and_clear();
+        downloaded?;
+
+        let actual = sha256_of(partial.path()).map_err(|e| e.to_string())?;
+        if actual != self.sha256 {
+            return Err(format!(
+                "its SHA-256 is {actual}, while {} was expected",
+                self.sha256
+            ));
+        }
+        partial
+            .persist(&self.destination)
+            .map_err(|e| e.error.to_string())?;
+        Ok(())
+    }
+
+    /// Downloads to `file`, showing the bytes it has so far on `bar`.
+    fn fetch(&self, file: &Path, bar: &ProgressBar) -> Result<(), String> {
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--location", "--fail"])
+            .args([
+                "--user-agent",
+                concat!("cargo-generate/", env!("CARGO_PKG_VERSION")),
+            ])
+            .arg("--output")
+            .arg(file)
+            .args(["--url", self.url])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to run `curl`, is it installed? {e}"))?;
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+                break status;
+            }
+            bar.set_position(fs::metadata(file).map_or(0, |metadata| metadata.len()));
+            bar.tick();
+            thread::sleep(POLL_INTERVAL);
+        };
+        if !status.success() {
+            let mut error = String::new();
+            child
+                .stderr
+                .take()
+                .expect("stderr of curl is piped")
+                .read_to_string(&mut error)
+                .ok();
+            return Err(error.trim().to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The SHA-256 of the file at `path`, read piece by piece, as downloads may be large.
+fn sha256_of(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        match file.read(&mut buffer)? {
+            0 => return Ok(hasher.hex()),
+            read => hasher.update(&buffer[..read]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256::sha256_hex;
+    use rhai::Engine;
+    use tempfile::TempDir;
+
+    fn engine(dir: &Path, allow_network: bool) -> Engine {
+        let progress = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let mut engine = Engine::new();
+        let module = create_module(dir, &progress, allow_network, true);
+        engine.register_static_module("http", module.into());
+        engine
+    }
+
+    #[test]
+    fn test_http_module() {
+        let source = TempDir::new().unwrap();
+        let asset = source.path().join("model.bin");
+        fs::write(&asset, "weights").unwrap();
+        let url = format!("file://{}", asset.display());
+        let sha256 = sha256_hex(b"weights");
+        let template = TempDir::new().unwrap();
+        let download = |engine: &Engine, destination: &str, sha256: &str| {
+            engine.run(&format!(
+                r#"http::download("{url}", "{destination}", "{sha256}")"#
+            ))
+        };
+
+        let offline = engine(template.path(), false);
+        let engine = engine(template.path(), true);
+        download(&engine, "assets/model.bin", &sha256.to_uppercase()).unwrap();
+        assert_eq!(
+            fs::read_to_string(template.path().join("assets/model.bin")).unwrap(),
+            "weights"
+        );
+
+        let error = download(&engine, "other.bin", &sha256_hex(b"other"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains(&format!("its SHA-256 is {sha256}")),
+            "{error}"
+        );
+        assert!(!template.path().join("other.bin").exists());
+        assert_eq!(fs::read_dir(template.path()).unwrap().count(), 1);
+
+        assert!(download(&engine, "../outside.bin", &sha256).is_err());
+        assert!(download(&engine, "short.bin", "abc").is_err());
+
+        let error = download(&offline, "offline.bin", &sha256)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("needs `--allow-network`"), "{error}");
+    }
+
+    #[test]
+    fn files_are_hashed_piece_by_piece() {
+        let dir = TempDir::new().unwrap();
+        let data = vec![7; 200 * 1024];
+        fs::write(dir.path().join("large"), &data).unwrap();
+
+        assert_eq!(
+            sha256_of(&dir.path().join("large")).unwrap(),
+            sha256_hex(&data)
+        );
+    }
+}
@@ -1,21 +1,34 @@
 use anyhow::{Context, Result};
 use console::style;
 use env_mod::Environment;
-use heck::{
-    ToKebabCase, ToLowerCamelCase, ToPascalCase, ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase,
-    ToTitleCase, ToUpperCamelCase,
-};
 use log::debug;
 use rhai::EvalAltResult;
-use std::{env, path::Path};
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
 
-use crate::emoji;
-use crate::template::TemplateObjectResource;
+use crate::exit_code::{FailedAt, Failure};
+use crate::{case, emoji, interrupt, profile};
+use crate::template::{self, TemplateObjectResource};
+use crate::template_variables::SOURCE_DATE_EPOCH;
 
 mod context;
+mod context_mod;
 mod env_mod;
 mod file_mod;
+mod generate_mod;
+mod generators_mod;
+mod http_mod;
+mod outcome;
+mod progress_mod;
 mod system_mod;
+mod toml_mod;
 mod variable_mod;
 
 type HookResult<T> = std::result::Result<T, Box<EvalAltResult>>;
@@ -23,6 +36,7 @@ type HookResult<T> = std::result::Result<T, Box<EvalAltResult>>;
 struct CleanupJob<F: FnOnce()>(Option<F>);
 
 pub use context::RhaiHooksContext;
+pub use outcome::HookOutcome;
 
 impl<F: FnOnce()> CleanupJob<F> {
     pub const fn new(f: F) -> Self {
@@ -36,36 +50,106 @@ impl<F: FnOnce()> Drop for CleanupJob<F> {
     }
 }
 
-pub fn execute_hooks(context: &RhaiHooksContext, scripts: &[String]) -> Result<()> {
+/// How often a hook running is checked on for Ctrl-C.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Runs the hook `scripts` on a thread of their own, stopping them when Ctrl-C is pressed, and
+/// returns what they asked for.
+pub fn execute_hooks(context: &RhaiHooksContext, scripts: &[String]) -> Result<HookOutcome> {
+    if context.generation.no_scripts {
+        return Ok(HookOutcome::default());
+    }
     debug!("executing rhai with context: {context:?}");
 
-    let engine = create_rhai_engine(context);
-    evaluate_scripts(&context.working_directory, scripts, engine)?;
-    Ok(())
+    let interrupt = interrupt::catch();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let finished = thread::scope(|scope| {
+        let worker = thread::Builder::new()
+            .name("hooks".to_string())
+            .spawn_scoped(scope, || evaluate_scripts(context, scripts, &cancelled))?;
+        while !worker.is_finished() {
+            if interrupt.interrupted() {
+                cancelled.store(true, Ordering::SeqCst);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        std::io::Result::Ok(worker.join())
+    })?;
+    interrupt.check()?;
+    let finished = finished.unwrap_or_else(|_| {
+        // the variables stay usable for whatever reports the failure
+        context.template_object.clear_poison();
+        anyhow::bail!(
+            "{} {}",
+            emoji::ERROR,
+            style("A hook crashed").bold().red()
+        )
+    });
+    finished.failed_at(Failure::Hook)
 }
 
-fn evaluate_scripts(template_dir: &Path, scripts: &[String], engine: rhai::Engine) -> Result<()> {
+fn evaluate_scripts(
+    context: &RhaiHooksContext,
+    scripts: &[String],
+    cancelled: &Arc<AtomicBool>,
+) -> Result<HookOutcome> {
     let cwd = env::current_dir()?;
     let _ = CleanupJob::new(move || {
         env::set_current_dir(cwd).ok();
     });
-    env::set_current_dir(template_dir)?;
+    env::set_current_dir(&context.working_directory)?;
 
+    let mut outcome = HookOutcome::default();
     for script in scripts {
-        engine
-            .eval_file::<()>(script.into())
-            .map_err(|e| anyhow::anyhow!(e.to_string()))
-            .with_context(|| {
-                format!(
-                    "{} {} {}",
-                    emoji::ERROR,
-                    style("Failed executing script:").bold().red(),
-                    style(script.to_owned()).yellow(),
-                )
-            })?;
+        let mut engine = create_hook_engine(context, script)?;
+        let cancelled = Arc::clone(cancelled);
+        interrupt::stop_when(&mut engine, move || cancelled.load(Ordering::SeqCst));
+        let _scope = context.generation.audit.hook_scope(script);
+        let result =
+            profile::time_hook(script, || engine.eval_file::<rhai::Dynamic>(script.into()))
+                .map_err(|e| anyhow::anyhow!(e.to_string()))
+                .with_context(|| {
+                    format!(
+                        "{} {} {}",
+                        emoji::ERROR,
+                        style("Failed executing script:").bold().red(),
+                        style(script.to_owned()).yellow(),
+                    )
+                })?;
+        outcome.merge(HookOutcome::from_result(script, result)?);
+        if outcome.abort.is_some() {
+            // the scripts after it would run in vain
+            break;
+        }
     }
 
-    Ok(())
+    Ok(outcome)
+}
+
+/// Runs a postprocessing `script` on the `content` of the file at `path`, returning its new
+/// content.
+pub fn execute_postprocess_script(
+    context: &RhaiHooksContext,
+    script: &str,
+    path: &str,
+    content: String,
+) -> Result<String> {
+    let engine = create_rhai_engine(context);
+    let mut scope = rhai::Scope::new();
+    scope.push("path", path.to_string());
+    scope.push("content", content);
+
+    engine
+        .eval_file_with_scope::<String>(&mut scope, context.working_directory.join(script))
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+        .with_context(|| {
+            format!(
+                "{} {} {} `{path}`",
+                emoji::ERROR,
+                style("Failed postprocessing with script:").bold().red(),
+                style(script.to_owned()).yellow(),
+            )
+        })
 }
 
 pub fn evaluate_script<T: Clone + 'static>(
@@ -87,6 +171,7 @@ pub fn evaluate_script<T: Clone + 'static>(
                     Ok(value.as_bool()
                         .map(rhai::Dynamic::from)
                         .or_else(|| value.as_str().map(|s| rhai::Dynamic::from(s.to_string())))
+                        .or_else(|| value.as_object().map(|o| object_to_map(o).into()))
                         .map(Some)
                         .unwrap_or_else(|| Some(rhai::Dynamic::from(value.to_string()))))
                 })
@@ -96,18 +181,108 @@ pub fn evaluate_script<T: Clone + 'static>(
     conditional_evaluation_engine.eval_expression::<T>(script)
 }
 
+/// Converts an object variable like `platform` into a rhai map, so its fields can be accessed
+/// with `platform.os`.
+fn object_to_map(object: &serde_json::Map<String, serde_json::Value>) -> rhai::Map {
+    object
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::Bool(b) => rhai::Dynamic::from(*b),
+                serde_json::Value::String(s) => rhai::Dynamic::from(s.clone()),
+                serde_json::Value::Object(o) => rhai::Dynamic::from(object_to_map(o)),
+                other => rhai::Dynamic::from(other.to_string()),
+            };
+            (key.into(), value)
+        })
+        .collect()
+}
+
+/// The engine running the hook `script`, its commands running where its settings say, with the
+/// variables they add to the environment.
+fn create_hook_engine(context: &RhaiHooksContext, script: &str) -> Result<rhai::Engine> {
+    let Some(settings) = context.hook_settings.get(script) else {
+        return Ok(create_rhai_engine(context));
+    };
+    let render = |text: &str| {
+        template::render_string(
+            &context.working_directory,
+            &context.template_object,
+            context.allow_commands,
+            context.silent,
+            context.preserve_whitespace,
+            text,
+            &context.generation,
+        )
+        .with_context(|| {
+            format!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("Failed rendering the settings of the hook").bold().red(),
+                style(script).yellow(),
+            )
+        })
+    };
+    let working_directory = match settings.cwd.as_deref() {
+        None | Some("template") => context.working_directory.clone(),
+        Some("dest") => context.destination_directory.clone(),
+        Some(path) => context.working_directory.join(render(path)?),
+    };
+    let mut commands = system_mod::Commands::new(working_directory);
+    for (name, value) in settings.env.iter().flatten() {
+        commands.env.push((name.clone(), render(value)?));
+    }
+    Ok(build_rhai_engine(context, commands))
+}
+
 pub fn create_rhai_engine(context: &RhaiHooksContext) -> rhai::Engine {
+    build_rhai_engine(
+        context,
+        system_mod::Commands::new(context.working_directory.clone()),
+    )
+}
+
+fn build_rhai_engine(
+    context: &RhaiHooksContext,
+    mut commands: system_mod::Commands,
+) -> rhai::Engine {
     let mut engine = rhai::Engine::new();
+    if context.generate_args.reproducible {
+        // the commands get to see the pinned time as well
+        commands.env.push((
+            SOURCE_DATE_EPOCH.to_string(),
+            context.generation.generated_at.unix_timestamp().to_string(),
+        ));
+    }
 
     // register modules
-    let module = variable_mod::create_module(&context.template_object);
+    let module = variable_mod::create_module(
+        &context.template_object,
+        &context.generation,
+        context.silent,
+        context.project_name_final,
+    );
     engine.register_static_module("variable", module.into());
 
     let module = file_mod::create_module(&context.working_directory);
     engine.register_static_module("file", module.into());
 
+    let module = toml_mod::create_module(&context.working_directory);
+    engine.register_static_module("toml", module.into());
+
+    let module = generators_mod::create_module(
+        &context.working_directory,
+        &context.template_object,
+        &context.generation,
+    );
+    engine.register_static_module("generators", module.into());
+
+    let module = generate_mod::create_module(&context.working_directory, &context.generate_args);
+    engine.register_static_module("generate", module.into());
+
     let module = system_mod::create_module(
-        context.working_directory.clone(),
+        commands,
+        &context.generation,
         context.allow_commands,
         context.silent,
     );
@@ -119,24 +294,54 @@ pub fn create_rhai_engine(context: &RhaiHooksContext) -> rhai::Engine {
     });
     engine.register_static_module("env", module.into());
 
+    let module = context_mod::create_module(&context.generate_args);
+    engine.register_static_module("context", module.into());
+
+    let module = progress_mod::create_module(&context.progress, context.generate_args.quiet);
+    engine.register_static_module("progress", module.into());
+
+    let module = http_mod::create_module(
+        &context.working_directory,
+        &context.progress,
+        context.generate_args.allow_network,
+        context.generate_args.quiet,
+    );
+    engine.register_static_module("http", module.into());
+
     // register functions for changing case
-    engine.register_fn("to_kebab_case", |str: &str| str.to_kebab_case());
-    engine.register_fn("to_lower_camel_case", |str: &str| str.to_lower_camel_case());
-    engine.register_fn("to_pascal_case", |str: &str| str.to_pascal_case());
-    engine.register_fn("to_shouty_kebab_case", |str: &str| {
-        str.to_shouty_kebab_case()
-    });
-    engine.register_fn("to_shouty_snake_case", |str: &str| {
-        str.to_shouty_snake_case()
-    });
-    engine.register_fn("to_snake_case", |str: &str| str.to_snake_case());
-    engine.register_fn("to_title_case", |str: &str| str.to_title_case());
-    engine.register_fn("to_upper_camel_case", |str: &str| str.to_upper_camel_case());
+    engine.register_fn("to_kebab_case", case::kebab_case);
+    engine.register_fn("to_lower_camel_case", case::lower_camel_case);
+    engine.register_fn("to_pascal_case", case::pascal_case);
+    engine.register_fn("to_shouty_kebab_case", case::shouty_kebab_case);
+    engine.register_fn("to_shouty_snake_case", case::shouty_snake_case);
+    engine.register_fn("to_snake_case", case::snake_case);
+    engine.register_fn("to_title_case", case::title_case);
+    engine.register_fn("to_upper_camel_case", case::upper_camel_case);
+    engine.register_fn("to_ascii_slug", |str: &str| case::ascii_slug(str, "-"));
 
     // other free-standing functions
     engine.register_fn("abort", |error: &str| -> HookResult<String> {
         Err(error.into())
     });
+    engine.register_fn("render", {
+        let template_object = context.template_object.clone();
+        let working_directory = context.working_directory.clone();
+        let (allow_commands, silent) = (context.allow_commands, context.silent);
+        let preserve_whitespace = context.preserve_whitespace;
+        let generation = Arc::clone(&context.generation);
+        move |content: &str| -> HookResult<String> {
+            template::render_string(
+                &working_directory,
+                &template_object,
+                allow_commands,
+                silent,
+                preserve_whitespace,
+                content,
+                &generation,
+            )
+            .map_err(|e| format!("Failed rendering `{content}`: {e:#}").into())
+        }
+    });
 
     engine
 }
@@ -0,0 +1,172 @@
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use log::info;
+use rhai::Dynamic;
+use std::path::{Component, Path};
+
+use crate::emoji;
+use crate::exit_code::{FailedAt, Failure};
+
+/// What the hook scripts asked generation to do, besides setting variables. A script returns it
+/// by ending with a map, e.g. `#{ ignore: ["docs"], message: "Docs skipped" }`; scripts ending with
+/// anything else ask for nothing.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct HookOutcome {
+    /// Files and folders of the template not to generate, like the `ignore` of the template
+    pub ignore: Vec<String>,
+    /// Shown once the hooks ran
+    pub messages: Vec<String>,
+    /// Why generation has to stop, if it has to
+    pub abort: Option<String>,
+}
+
+impl HookOutcome {
+    /// The outcome `script` ended with.
+    pub fn from_result(script: &str, result: Dynamic) -> Result<Self> {
+        let Some(map) = result.try_cast::<rhai::Map>() else {
+            return Ok(Self::default());
+        };
+        let invalid = |key: &str, expected: &str| {
+            anyhow!(
+                "{} {} `{}` {} `{}`, {}",
+                emoji::ERROR,
+                style("The hook").bold().red(),
+                style(script).yellow(),
+                style("returned an invalid").bold().red(),
+                style(key).bold(),
+                style(expected).bold().red(),
+            )
+        };
+
+        let mut outcome = Self::default();
+        for (key, value) in map {
+            match key.as_str() {
+                "ignore" => {
+                    outcome.ignore = strings(value)
+                        .ok_or_else(|| invalid(&key, "expected a string or an array of them"))?;
+                }
+                "message" => {
+                    outcome.messages = vec![value
+                        .into_string()
+                        .map_err(|_| invalid(&key, "expected a string"))?];
+                }
+                "abort" => {
+                    outcome.abort = Some(
+                        value
+                            .into_string()
+                            .map_err(|_| invalid(&key, "expected a string"))?,
+                    );
+                }
+                _ => return Err(invalid(&key, "expected `ignore`, `message` or `abort`")),
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Adds the outcome of a later script.
+    pub fn merge(&mut self, later: Self) {
+        self.ignore.extend(later.ignore);
+        self.messages.extend(later.messages);
+        self.abort = self.abort.take().or(later.abort);
+    }
+
+    /// Shows the messages, and fails if a script aborted, returning what is to be ignored.
+    pub fn into_ignored(self) -> Result<Vec<String>> {
+        for message in &self.messages {
+            info!("{} {}", emoji::DIAMOND, style(message).bold());
+        }
+        if let Some(reason) = self.abort {
+            return Err(anyhow!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("A hook stopped generating:").bold().red(),
+                style(reason).bold(),
+            ))
+            .failed_at(Failure::Abort);
+        }
+        check_ignored(&self.ignore)?;
+        Ok(self.ignore)
+    }
+}
+
+fn strings(value: Dynamic) -> Option<Vec<String>> {
+    if value.is_string() {
+        return value.into_string().ok().map(|s| vec![s]);
+    }
+    value
+        .try_cast::<rhai::Array>()?
+        .into_iter()
+        .map(|item| item.into_string().ok())
+        .collect()
+}
+
+/// Fails unless every ignored path stays within the template.
+fn check_ignored(ignored: &[String]) -> Result<()> {
+    for path in ignored {
+        let escapes = Path::new(path)
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            bail!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("Hooks can only ignore files of the template, not")
+                    .bold()
+                    .red(),
+                style(path).bold().yellow(),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(script: &str) -> Result<HookOutcome> {
+        let result = rhai::Engine::new().eval::<Dynamic>(script).unwrap();
+        HookOutcome::from_result("hook.rhai", result)
+    }
+
+    #[test]
+    fn maps_are_outcomes() {
+        assert_eq!(
+            outcome(r#"#{ ignore: ["docs", "ci"], message: "hi", abort: "no" }"#).unwrap(),
+            HookOutcome {
+                ignore: vec!["docs".into(), "ci".into()],
+                messages: vec!["hi".into()],
+                abort: Some("no".into()),
+            }
+        );
+        assert_eq!(outcome(r#"#{ ignore: "docs" }"#).unwrap().ignore, ["docs"]);
+    }
+
+    #[test]
+    fn anything_else_asks_for_nothing() {
+        assert_eq!(outcome("let x = 1;").unwrap(), HookOutcome::default());
+        assert_eq!(outcome(r#""text""#).unwrap(), HookOutcome::default());
+    }
+
+    #[test]
+    fn invalid_outcomes_fail() {
+        assert!(outcome("#{ ignore: 1 }").is_err());
+        assert!(outcome("#{ ignored: [] }").is_err());
+    }
+
+    #[test]
+    fn later_scripts_add_to_the_outcome() {
+        let mut first = outcome(r#"#{ ignore: "a", abort: "first" }"#).unwrap();
+        first.merge(outcome(r#"#{ ignore: "b", abort: "second" }"#).unwrap());
+
+        assert_eq!(first.ignore, ["a", "b"]);
+        assert_eq!(first.abort.as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn only_files_of_the_template_can_be_ignored() {
+        assert!(check_ignored(&["docs".into(), "./ci/gitlab.yml".into()]).is_ok());
+        assert!(check_ignored(&["../outside".into()]).is_err());
+        assert!(check_ignored(&["/etc".into()]).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget};
+use rhai::Module;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::progressbar;
+
+use super::HookResult;
+
+/// How often a spinner turns, also while the hook waits on a command.
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The bar of a hook, one at a time, shared by the functions of its module.
+type Bar = Arc<Mutex<Option<ProgressBar>>>;
+
+/// Creates the progress module, letting a hook working long, e.g. downloading assets or running
+/// a build, show it does with a spinner, or a bar when it knows how many steps there are.
+///
+/// The bars are added to the progress of the generation, and hidden with `--quiet` like it.
+pub fn create_module(progress: &MultiProgress, quiet: bool) -> Module {
+    let bar: Bar = Arc::default();
+    let mut module = Module::new();
+
+    module.set_native_fn("start", {
+        let (bar, progress) = (bar.clone(), progress.clone());
+        move |message: &str| -> HookResult<()> {
+            start(&bar, &progress, quiet, message, None);
+            Ok(())
+        }
+    });
+
+    module.set_native_fn("start", {
+        let (bar, progress) = (bar.clone(), progress.clone());
+        move |message: &str, total: i64| -> HookResult<()> {
+            start(&bar, &progress, quiet, message, Some(steps(total)?));
+            Ok(())
+        }
+    });
+
+    module.set_native_fn("inc", {
+        let bar = bar.clone();
+        move || -> HookResult<()> {
+            with_bar(&bar, |bar| bar.inc(1));
+            Ok(())
+        }
+    });
+
+    module.set_native_fn("inc", {
+        let bar = bar.clone();
+        move |steps: i64| -> HookResult<()> {
+            let steps = self::steps(steps)?;
+            with_bar(&bar, |bar| bar.inc(steps));
+            Ok(())
+        }
+    });
+
+    module.set_native_fn("message", {
+        let bar = bar.clone();
+        move |message: &str| -> HookResult<()> {
+            with_bar(&bar, |bar| bar.set_message(message.to_string()));
+            Ok(())
+        }
+    });
+
+    module.set_native_fn("finish", {
+        let bar = bar.clone();
+        move || -> HookResult<()> {
+            finish(&bar, None);
+            Ok(())
+        }
+    });
+
+    module.set_native_fn("finish", move |message: &str| -> HookResult<()> {
+        finish(&bar, Some(message));
+        Ok(())
+    });
+
+    module
+}
+
+/// Shows a new bar with `message`, of `total` steps if given, a spinner otherwise, finishing the
+/// one shown before.
+fn start(bar: &Bar, progress: &MultiProgress, quiet: bool, message: &str, total: Option<u64>) {
+    finish(bar, None);
+    let new = total.map_or_else(
+        || ProgressBar::new_spinner().with_style(progressbar::spinner()),
+        |total| ProgressBar::new(total).with_style(progressbar::bar()),
+    );
+    let new = progress.add(new.with_message(message.to_string()));
+    if quiet {
+        new.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    new.enable_steady_tick(TICK_INTERVAL);
+    *bar.lock().unwrap_or_else(|e| e.into_inner()) = Some(new);
+}
+
+/// Finishes the bar shown, if any, with its last message or `message`.
+fn finish(bar: &Bar, message: Option<&str>) {
+    let Some(bar) = bar.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return;
+    };
+    match message {
+        Some(message) => bar.finish_with_message(message.to_string()),
+        None => bar.finish(),
+    }
+}
+
+/// Does `f` with the bar shown, nothing without one.
+fn with_bar(bar: &Bar, f: impl FnOnce(&ProgressBar)) {
+    if let Some(bar) = bar.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        f(bar);
+    }
+}
+
+fn steps(steps: i64) -> HookResult<u64> {
+    u64::try_from(steps).map_err(|_| format!("A number of steps can't be negative: {steps}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rhai::Engine;
+
+    #[test]
+    fn test_progress_module() {
+        let progress = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let mut engine = Engine::new();
+        engine.register_static_module("progress", create_module(&progress, false).into());
+
+        engine
+            .run(
+                r#"
+                progress::inc();
+                progress::finish();
+                progress::start("Downloading", 10);
+                progress::inc();
+                progress::inc(4);
+                progress::message("Downloading assets");
+                progress::finish("Downloaded");
+                "#,
+            )
+            .unwrap();
+        assert!(engine.run(r#"progress::start("Building", -1)"#).is_err());
+    }
+
+    #[test]
+    fn a_new_bar_finishes_the_one_before() {
+        let progress = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let bar = Bar::default();
+
+        start(&bar, &progress, false, "Downloading", Some(10));
+        with_bar(&bar, |bar| bar.inc(5));
+        let first = bar.lock().unwrap().clone().unwrap();
+        assert_eq!(first.position(), 5);
+        assert_eq!(first.length(), Some(10));
+
+        start(&bar, &progress, true, "Building", None);
+        assert!(first.is_finished());
+        assert!(!bar.lock().unwrap().as_ref().unwrap().is_finished());
+
+        finish(&bar, Some("Built"));
+        assert!(bar.lock().unwrap().is_none());
+    }
+}
@@ -2,44 +2,80 @@ use log::debug;
 use rhai::{Dynamic, Module};
 use std::{
     collections::BTreeMap,
-    path::{Path, PathBuf},
+    path::PathBuf,
     process::Command,
 };
-use time::OffsetDateTime;
+use std::sync::Arc;
+use time::{OffsetDateTime, UtcOffset};
 
 use crate::{
+    generation::Generation,
     interactive::prompt_and_check_variable,
     project_variables::{StringEntry, StringKind, TemplateSlots, VarInfo},
+    prompt_timeout::PromptTimeout,
 };
 
 use super::HookResult;
 
+/// Where the commands of a hook run, and the variables added to their environment.
+#[derive(Debug, Clone)]
+pub struct Commands {
+    pub working_directory: PathBuf,
+    pub env: Vec<(String, String)>,
+}
+
+impl Commands {
+    pub const fn new(working_directory: PathBuf) -> Self {
+        Self {
+            working_directory,
+            env: Vec::new(),
+        }
+    }
+}
+
 /// Creates the system module, containing the `command` function,
-/// which allows you to run system command.
-pub fn create_module(working_directory: PathBuf, allow_commands: bool, silent: bool) -> Module {
+/// which allows you to run system command, and the `date` function, telling the date the
+/// `generation` happens at.
+pub fn create_module(
+    commands: Commands,
+    generation: &Arc<Generation>,
+    allow_commands: bool,
+    silent: bool,
+) -> Module {
     let mut module = Module::new();
 
-    let cwd = working_directory.clone();
+    let (cmds, asking) = (commands.clone(), Arc::clone(generation));
     module.set_native_fn("command", move |name: &str, commands_args: rhai::Array| {
-        run_command(&cwd, name, commands_args, allow_commands, silent)
+        let timeout = &asking.prompt_timeout;
+        run_command(&cmds, name, commands_args, allow_commands, silent, timeout)
     });
 
-    let cwd = working_directory.clone();
+    let asking = Arc::clone(generation);
     module.set_native_fn("command", move |name: &str| {
-        run_command(&cwd, name, rhai::Array::new(), allow_commands, silent)
+        let timeout = &asking.prompt_timeout;
+        run_command(
+            &commands,
+            name,
+            rhai::Array::new(),
+            allow_commands,
+            silent,
+            timeout,
+        )
     });
 
-    module.set_native_fn("date", get_utc_date);
+    let now = generation.generated_at;
+    module.set_native_fn("date", move || Ok(get_utc_date(now)));
 
     module
 }
 
 fn run_command(
-    working_directory: &Path,
+    commands: &Commands,
     name: &str,
     args: rhai::Array,
     allow_commands: bool,
     silent: bool,
+    timeout: &PromptTimeout,
 ) -> HookResult<Dynamic> {
     let args: Vec<String> = args.into_iter().map(|arg| arg.to_string()).collect();
 
@@ -69,10 +105,16 @@ fn run_command(
                             default: Some("no".into()),
                             kind: StringKind::Choices(vec!["yes".into(), "no".into()]),
                             regex: None,
+                            regex_description: None,
+                            transform: Vec::new(),
+                            shape: None,
                         }),
                     },
+                    remember: false,
+                    default_from_command: None,
                 },
                 None,
+                timeout,
             );
 
             // Only accept clearly positive affirmations.
@@ -88,20 +130,22 @@ fn run_command(
 
     debug!(
         "the command is executed within the working directory: {}",
-        working_directory.display()
+        commands.working_directory.display()
     );
 
     let output = if cfg!(target_os = "windows") {
         Command::new("cmd")
             .arg("/C")
             .arg(&full_command)
-            .current_dir(working_directory)
+            .current_dir(&commands.working_directory)
+            .envs(commands.env.iter().cloned())
             .output()
     } else {
         Command::new("sh")
             .arg("-c")
             .arg(&full_command)
-            .current_dir(working_directory)
+            .current_dir(&commands.working_directory)
+            .envs(commands.env.iter().cloned())
             .output()
     };
 
@@ -127,8 +171,9 @@ fn run_command(
     }
 }
 
-fn get_utc_date() -> HookResult<Dynamic> {
-    Ok(construct_date_map(OffsetDateTime::now_utc()))
+/// The date of `now` in UTC.
+fn get_utc_date(now: OffsetDateTime) -> Dynamic {
+    construct_date_map(now.to_offset(UtcOffset::UTC))
 }
 
 fn construct_date_map(dt: OffsetDateTime) -> Dynamic {
@@ -151,10 +196,11 @@ fn construct_date_map(dt: OffsetDateTime) -> Dynamic {
 #[cfg(test)]
 mod tests {
     use std::io::Write;
+    use std::sync::Arc;
 
     use crate::{
+        generation::Generation,
         hooks::{create_rhai_engine, RhaiHooksContext},
-        template::TemplateObjectResource,
     };
     use rhai::Engine;
     use tempfile::TempDir;
@@ -165,11 +211,8 @@ mod tests {
         let mut file1 = std::fs::File::create(tmp_dir.path().join("file1")).unwrap();
         file1.write_all(b"test1").unwrap();
         let context = RhaiHooksContext {
-            working_directory: tmp_dir.path().to_path_buf(),
-            destination_directory: tmp_dir.path().join("destination").to_path_buf(),
-            template_object: TemplateObjectResource::default(),
             allow_commands: true,
-            silent: true,
+            ..RhaiHooksContext::for_test(tmp_dir.path())
         };
         std::env::set_current_dir(&context.working_directory).unwrap();
         let engine = create_rhai_engine(&context);
@@ -215,11 +258,8 @@ mod tests {
     fn test_run_command_failure() {
         let tmp_dir = TempDir::new().unwrap();
         let context = RhaiHooksContext {
-            working_directory: tmp_dir.path().to_path_buf(),
-            destination_directory: tmp_dir.path().join("destination").to_path_buf(),
-            template_object: TemplateObjectResource::default(),
             allow_commands: true,
-            silent: true,
+            ..RhaiHooksContext::for_test(tmp_dir.path())
         };
         let engine = create_rhai_engine(&context);
         std::env::set_current_dir(tmp_dir.path()).unwrap();
@@ -235,13 +275,7 @@ mod tests {
     )]
     fn test_run_command_silent_mode_denied() {
         let tmp_dir = TempDir::new().unwrap();
-        let context = RhaiHooksContext {
-            working_directory: tmp_dir.path().to_path_buf(),
-            destination_directory: tmp_dir.path().join("destination").to_path_buf(),
-            template_object: TemplateObjectResource::default(),
-            allow_commands: false,
-            silent: true,
-        };
+        let context = RhaiHooksContext::for_test(tmp_dir.path());
         let engine = create_rhai_engine(&context);
         std::env::set_current_dir(tmp_dir.path()).unwrap();
 
@@ -254,17 +288,25 @@ mod tests {
     fn test_get_utc_date() {
         let tmp_dir = TempDir::new().unwrap();
         let mut engine = Engine::new();
-        let module = super::create_module(tmp_dir.path().to_path_buf(), true, true);
+        // New Year's Day at +02:00, still 2023 in UTC
+        let now = time::OffsetDateTime::from_unix_timestamp(1_704_067_199)
+            .unwrap()
+            .to_offset(time::UtcOffset::from_hms(2, 0, 0).unwrap());
+        let generation = Generation {
+            generated_at: now,
+            ..Generation::default()
+        };
+        let module = super::create_module(
+            super::Commands::new(tmp_dir.path().to_path_buf()),
+            &Arc::new(generation),
+            true,
+            true,
+        );
         engine.register_static_module("system", module.into());
 
         let result = engine.eval::<rhai::Map>(r#"system::date()"#).unwrap();
-        let now = time::OffsetDateTime::now_utc();
-        assert!(result.contains_key("year"));
-        assert!(result.contains_key("month"));
-        assert!(result.contains_key("day"));
-
-        assert_eq!(result["year"].as_int().unwrap(), i64::from(now.year()));
-        assert_eq!(result["month"].as_int().unwrap(), now.month() as i64);
-        assert_eq!(result["day"].as_int().unwrap(), i64::from(now.day()));
+        assert_eq!(result["year"].as_int().unwrap(), 2023);
+        assert_eq!(result["month"].as_int().unwrap(), 12);
+        assert_eq!(result["day"].as_int().unwrap(), 31);
     }
 }
@@ -0,0 +1,264 @@
+use rhai::{Dynamic, Module};
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+use super::file_mod::to_sandboxed_absolute_path;
+use super::HookResult;
+
+/// Creates the toml module, for patching TOML files like the generated `Cargo.toml`.
+///
+/// Keys are addressed by their dotted path, e.g. `dependencies.serde.version`. The rest of the
+/// file is kept as it is, comments and formatting included.
+pub fn create_module(dir: &Path) -> Module {
+    let dir = dir.to_owned();
+    let mut module = Module::new();
+
+    module.set_native_fn("set", {
+        let dir = dir.clone();
+        move |file: &str, path: &str, value: Dynamic| -> HookResult<()> {
+            let (parents, key) = split_path(path)?;
+            let value = to_toml(value)?;
+            edit(&dir, file, |document| {
+                table_at(document, &parents, true)?.insert(key, Item::Value(value));
+                Ok(())
+            })
+        }
+    });
+
+    module.set_native_fn("push_array", {
+        let dir = dir.clone();
+        move |file: &str, path: &str, value: Dynamic| -> HookResult<()> {
+            let (parents, key) = split_path(path)?;
+            let value = to_toml(value)?;
+            edit(&dir, file, |document| {
+                let array = table_at(document, &parents, true)?
+                    .entry(key)
+                    .or_insert(Item::Value(Value::Array(Array::new())))
+                    .as_array_mut()
+                    .ok_or_else(|| format!("`{path}` is not an array"))?;
+                array.push(value);
+                Ok(())
+            })
+        }
+    });
+
+    module.set_native_fn("remove", {
+        let dir = dir.clone();
+        move |file: &str, path: &str| -> HookResult<()> {
+            let (parents, key) = split_path(path)?;
+            edit(&dir, file, |document| {
+                if let Ok(table) = table_at(document, &parents, false) {
+                    table.remove(key);
+                }
+                Ok(())
+            })
+        }
+    });
+
+    module
+}
+
+fn split_path(path: &str) -> HookResult<(Vec<&str>, &str)> {
+    let mut keys = path.split('.').collect::<Vec<_>>();
+    if keys.iter().any(|k| k.is_empty()) {
+        return Err(format!("Invalid key path `{path}`").into());
+    }
+    let key = keys.pop().unwrap_or_default();
+    Ok((keys, key))
+}
+
+/// Reads the TOML `file`, applies `f` and writes it back, leaving alone what `f` did not touch.
+fn edit(
+    dir: &Path,
+    file: &str,
+    f: impl FnOnce(&mut DocumentMut) -> Result<(), String>,
+) -> HookResult<()> {
+    let path: PathBuf = to_sandboxed_absolute_path(dir, file)?;
+    let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let mut document = contents
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Invalid TOML in {file}: {e}"))?;
+    f(&mut document)?;
+    std::fs::write(path, document.to_string()).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Walks down the tables named by `keys`, creating missing ones if `create` is set. Tables are
+/// created without a header of their own, so only those getting keys show up.
+fn table_at<'a>(
+    document: &'a mut DocumentMut,
+    keys: &[&str],
+    create: bool,
+) -> Result<&'a mut dyn TableLike, String> {
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    for key in keys {
+        if create && !table.contains_key(key) {
+            let mut inner = Table::new();
+            inner.set_implicit(true);
+            table.insert(key, Item::Table(inner));
+        }
+        table = match table.get_mut(key) {
+            Some(item) => item
+                .as_table_like_mut()
+                .ok_or_else(|| format!("`{key}` is not a table"))?,
+            None => return Err(format!("`{key}` does not exist")),
+        };
+    }
+    Ok(table)
+}
+
+fn to_toml(value: Dynamic) -> Result<Value, String> {
+    let type_name = value.type_name();
+    if value.is_string() {
+        Ok(Value::from(value.into_string()?))
+    } else if let Some(v) = value.clone().try_cast::<bool>() {
+        Ok(Value::from(v))
+    } else if let Some(v) = value.clone().try_cast::<rhai::INT>() {
+        Ok(Value::from(v))
+    } else if let Some(v) = value.clone().try_cast::<rhai::FLOAT>() {
+        Ok(Value::from(v))
+    } else if let Some(array) = value.clone().try_cast::<rhai::Array>() {
+        array
+            .into_iter()
+            .map(to_toml)
+            .collect::<Result<Array, _>>()
+            .map(Value::Array)
+    } else if let Some(map) = value.try_cast::<rhai::Map>() {
+        map.into_iter()
+            .map(|(k, v)| Ok((k.to_string(), to_toml(v)?)))
+            .collect::<Result<InlineTable, String>>()
+            .map(Value::InlineTable)
+    } else {
+        Err(format!(
+            "Values of type {type_name} cannot be written to TOML"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hooks::{create_rhai_engine, RhaiHooksContext};
+    use indoc::indoc;
+    use tempfile::TempDir;
+
+    fn patch(manifest: &str, script: &str) -> Result<toml::Table, String> {
+        let tmp_dir = TempDir::new().unwrap();
+        patch_in(&tmp_dir, manifest, script).map(|contents| contents.parse().unwrap())
+    }
+
+    /// Patches `manifest` in `tmp_dir` with `script`, returning it as written.
+    fn patch_in(tmp_dir: &TempDir, manifest: &str, script: &str) -> Result<String, String> {
+        std::fs::write(tmp_dir.path().join("Cargo.toml"), manifest).unwrap();
+        let context = RhaiHooksContext::for_test(tmp_dir.path());
+        let engine = create_rhai_engine(&context);
+        engine.run(script).map_err(|e| e.to_string())?;
+
+        Ok(std::fs::read_to_string(tmp_dir.path().join("Cargo.toml")).unwrap())
+    }
+
+    #[test]
+    fn sets_nested_keys() {
+        let manifest = patch(
+            "[package]\nname = \"foo\"\n\n[dependencies]\nserde = \"1\"\n",
+            r#"
+                toml::set("Cargo.toml", "dependencies.serde", #{ version: "1", features: ["derive"] });
+                toml::set("Cargo.toml", "package.publish", false);
+                toml::set("Cargo.toml", "profile.release.lto", true);
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest["dependencies"]["serde"]["version"].as_str(),
+            Some("1")
+        );
+        assert_eq!(
+            manifest["dependencies"]["serde"]["features"][0].as_str(),
+            Some("derive")
+        );
+        assert_eq!(manifest["package"]["publish"].as_bool(), Some(false));
+        assert_eq!(manifest["profile"]["release"]["lto"].as_bool(), Some(true));
+        assert_eq!(
+            manifest.keys().collect::<Vec<_>>(),
+            ["package", "dependencies", "profile"],
+            "the order of the keys is preserved"
+        );
+    }
+
+    #[test]
+    fn pushes_to_and_removes_from_arrays() {
+        let manifest = patch(
+            "[workspace]\nmembers = [\"a\"]\nexclude = [\"old\"]\n",
+            r#"
+                toml::push_array("Cargo.toml", "workspace.members", "b");
+                toml::push_array("Cargo.toml", "package.keywords", "cli");
+                toml::remove("Cargo.toml", "workspace.exclude");
+                toml::remove("Cargo.toml", "does.not.exist");
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest["workspace"]["members"],
+            toml::Value::Array(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(manifest["package"]["keywords"][0].as_str(), Some("cli"));
+        assert!(manifest["workspace"].get("exclude").is_none());
+    }
+
+    #[test]
+    fn keeps_the_rest_of_the_file_as_it_is() {
+        let tmp_dir = TempDir::new().unwrap();
+        let original = indoc! {r#"
+            # the package of the project
+            [package]
+            name    = "foo" # aligned by hand
+            edition = "2021"
+
+            [dependencies]
+            serde = { version = "1", features = ["derive"] }
+        "#};
+        let manifest = patch_in(
+            &tmp_dir,
+            original,
+            r#"
+                toml::set("Cargo.toml", "package.publish", false);
+                toml::set("Cargo.toml", "dependencies.serde.version", "1.0.200");
+                toml::push_array("Cargo.toml", "workspace.members", "cli");
+                toml::set("Cargo.toml", "profile.release.lto", true);
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest,
+            indoc! {r#"
+                # the package of the project
+                [package]
+                name    = "foo" # aligned by hand
+                edition = "2021"
+                publish = false
+
+                [dependencies]
+                serde = { version = "1.0.200", features = ["derive"] }
+
+                [workspace]
+                members = ["cli"]
+
+                [profile.release]
+                lto = true
+            "#}
+        );
+    }
+
+    #[test]
+    fn reports_keys_of_the_wrong_type() {
+        let error = patch(
+            "[package]\nname = \"foo\"\n",
+            r#"toml::push_array("Cargo.toml", "package.name", "bar");"#,
+        )
+        .unwrap_err();
+
+        assert!(error.contains("`package.name` is not an array"), "{error}");
+    }
+}
@@ -1,19 +1,36 @@
+use console::style;
+use log::warn;
 use regex::Regex;
-use rhai::{Array, Dynamic, Module};
+use rhai::{Array, Dynamic, Map, Module, INT};
+use std::sync::Arc;
 
+use crate::emoji;
+use crate::explain_var::Audit;
+use crate::generation::Generation;
 use crate::interactive::prompt_and_check_variable;
-use crate::project_variables::{StringEntry, StringKind, TemplateSlots, VarInfo};
+use crate::project_variables::{
+    insert_variable, is_project_name, is_protected, nested_value, StringEntry, StringKind,
+    TemplateSlots, VarInfo,
+};
+use crate::prompt_timeout::PromptTimeout;
 use crate::template::TemplateObjectResource;
+use crate::variable_aliases::Aliases;
 
-use super::{HookResult, PoisonError};
+use super::{object_to_map, HookResult, PoisonError};
 
-pub fn create_module(template_object: &TemplateObjectResource) -> Module {
+pub fn create_module(
+    template_object: &TemplateObjectResource,
+    generation: &Arc<Generation>,
+    silent: bool,
+    project_name_final: bool,
+) -> Module {
     let mut module = Module::new();
 
     module.set_native_fn("is_set", {
         let template_object = template_object.clone();
+        let generation = Arc::clone(generation);
         move |name: &str| -> HookResult<bool> {
-            match template_object.get_value(name)? {
+            match template_object.get_value(name, &generation.aliases)? {
                 NamedValue::NonExistent => Ok(false),
                 _ => Ok(true),
             }
@@ -22,8 +39,9 @@ pub fn create_module(template_object: &TemplateObjectResource) -> Module {
 
     module.set_native_fn("get", {
         let template_object = template_object.clone();
+        let generation = Arc::clone(generation);
         move |name: &str| -> HookResult<Dynamic> {
-            match template_object.get_value(name)? {
+            match template_object.get_value(name, &generation.aliases)? {
                 NamedValue::NonExistent => Ok(Dynamic::from(String::from(""))),
                 NamedValue::Bool(v) => Ok(Dynamic::from(v)),
                 NamedValue::String(v) => Ok(Dynamic::from(v)),
@@ -34,23 +52,28 @@ pub fn create_module(template_object: &TemplateObjectResource) -> Module {
                         .collect();
                     Ok(Dynamic::from(rhai_array))
                 }
+                NamedValue::Object(object) => Ok(Dynamic::from(object_to_map(&object))),
             }
         }
     });
 
     module.set_native_fn("set", {
         let template_object = template_object.clone();
+        let generation = Arc::clone(generation);
         move |name: &str, value: &str| -> HookResult<()> {
-            match template_object.get_value(name)? {
+            let aliases = &generation.aliases;
+            ensure_unprotected(&template_object, aliases, name, project_name_final)?;
+            match template_object.get_value(name, aliases)? {
                 NamedValue::NonExistent | NamedValue::String(_) => {
-                    template_object
-                        .lock()
-                        .map_err(|_| PoisonError::new_eval_alt_result())?
-                        .borrow_mut()
-                        .insert(
-                            name.to_string(),
-                            serde_json::Value::from(value.to_string()),
-                        );
+                    generation.audit.record_by_hook(name, &value);
+                    insert_variable(
+                        &mut template_object
+                            .lock()
+                            .map_err(|_| PoisonError::new_eval_alt_result())?
+                            .borrow_mut(),
+                        name,
+                        serde_json::Value::from(value.to_string()),
+                    );
                     Ok(())
                 }
                 _ => Err(format!("Variable {name} not a String").into()),
@@ -60,14 +83,21 @@ pub fn create_module(template_object: &TemplateObjectResource) -> Module {
 
     module.set_native_fn("set", {
         let template_object = template_object.clone();
+        let generation = Arc::clone(generation);
         move |name: &str, value: bool| -> HookResult<()> {
-            match template_object.get_value(name)? {
+            let aliases = &generation.aliases;
+            ensure_unprotected(&template_object, aliases, name, project_name_final)?;
+            match template_object.get_value(name, aliases)? {
                 NamedValue::NonExistent | NamedValue::Bool(_) => {
-                    template_object
-                        .lock()
-                        .map_err(|_| PoisonError::new_eval_alt_result())?
-                        .borrow_mut()
-                        .insert(name.to_string(), serde_json::Value::from(value));
+                    generation.audit.record_by_hook(name, &value);
+                    insert_variable(
+                        &mut template_object
+                            .lock()
+                            .map_err(|_| PoisonError::new_eval_alt_result())?
+                            .borrow_mut(),
+                        name,
+                        serde_json::Value::from(value),
+                    );
                     Ok(())
                 }
                 _ => Err(format!("Variable {name} not a bool").into()),
@@ -77,15 +107,22 @@ pub fn create_module(template_object: &TemplateObjectResource) -> Module {
 
     module.set_native_fn("set", {
         let template_object = template_object.clone();
+        let generation = Arc::clone(generation);
         move |name: &str, value: Array| -> HookResult<()> {
-            match template_object.get_value(name)? {
+            let aliases = &generation.aliases;
+            ensure_unprotected(&template_object, aliases, name, project_name_final)?;
+            match template_object.get_value(name, aliases)? {
                 NamedValue::NonExistent | NamedValue::Array(_) => {
                     let val = rhai_to_liquid_value(Dynamic::from(value))?;
-                    template_object
-                        .lock()
-                        .map_err(|_| PoisonError::new_eval_alt_result())?
-                        .borrow_mut()
-                        .insert(name.to_string(), val);
+                    generation.audit.record_by_hook(name, &val);
+                    insert_variable(
+                        &mut template_object
+                            .lock()
+                            .map_err(|_| PoisonError::new_eval_alt_result())?
+                            .borrow_mut(),
+                        name,
+                        val,
+                    );
                     Ok(())
                 }
                 _ => Err(format!("Variable {name} not an array").into()),
@@ -93,152 +130,307 @@ pub fn create_module(template_object: &TemplateObjectResource) -> Module {
         }
     });
 
-    module.set_native_fn("prompt", {
-        move |prompt: &str, default_value: bool| -> HookResult<bool> {
-            let value = prompt_and_check_variable(
-                &TemplateSlots {
-                    prompt: prompt.into(),
-                    var_name: "".into(),
-                    var_info: VarInfo::Bool {
-                        default: Some(default_value),
-                    },
-                },
-                None,
-            );
+    module.set_native_fn("force_set", {
+        let template_object = template_object.clone();
+        let generation = Arc::clone(generation);
+        move |name: &str, value: &str| -> HookResult<()> {
+            let value = serde_json::Value::from(value);
+            force_set(&template_object, &generation.audit, name, value)
+        }
+    });
 
-            match value {
-                Ok(v) => Ok(v.parse::<bool>().map_err(|_| "Unable to parse into bool")?),
-                Err(e) => Err(e.to_string().into()),
-            }
+    module.set_native_fn("force_set", {
+        let template_object = template_object.clone();
+        let generation = Arc::clone(generation);
+        move |name: &str, value: bool| -> HookResult<()> {
+            let value = serde_json::Value::from(value);
+            force_set(&template_object, &generation.audit, name, value)
+        }
+    });
+
+    module.set_native_fn("force_set", {
+        let template_object = template_object.clone();
+        let generation = Arc::clone(generation);
+        move |name: &str, value: Array| -> HookResult<()> {
+            let value = rhai_to_liquid_value(Dynamic::from(value))?;
+            force_set(&template_object, &generation.audit, name, value)
         }
     });
 
     module.set_native_fn("prompt", {
-        move |prompt: &str| -> HookResult<String> {
-            let value = prompt_and_check_variable(
-                &TemplateSlots {
-                    prompt: prompt.into(),
-                    var_name: "".into(),
-                    var_info: VarInfo::String {
-                        entry: Box::new(StringEntry {
-                            default: None,
-                            kind: StringKind::String,
-                            regex: None,
-                        }),
-                    },
-                },
-                None,
-            );
+        let generation = Arc::clone(generation);
+        move |prompt: &str, default_value: bool| {
+            ensure_interactive(silent, prompt)?;
+            prompt_for_bool(&generation.prompt_timeout, prompt, default_value)
+        }
+    });
 
-            match value {
-                Ok(v) => Ok(v),
-                Err(e) => Err(e.to_string().into()),
-            }
+    module.set_native_fn("prompt", {
+        let generation = Arc::clone(generation);
+        move |prompt: &str| {
+            ensure_interactive(silent, prompt)?;
+            prompt_for_string(&generation.prompt_timeout, prompt, None, None)
         }
     });
 
     module.set_native_fn("prompt", {
-        move |prompt: &str, default_value: &str| -> HookResult<String> {
-            let value = prompt_and_check_variable(
-                &TemplateSlots {
-                    prompt: prompt.into(),
-                    var_name: "".into(),
-                    var_info: VarInfo::String {
-                        entry: Box::new(StringEntry {
-                            default: Some(default_value.into()),
-                            kind: StringKind::String,
-                            regex: None,
-                        }),
-                    },
-                },
+        let generation = Arc::clone(generation);
+        move |prompt: &str, default_value: &str| {
+            ensure_interactive(silent, prompt)?;
+            prompt_for_string(
+                &generation.prompt_timeout,
+                prompt,
+                Some(default_value),
                 None,
-            );
-
-            match value {
-                Ok(v) => Ok(v),
-                Err(e) => Err(e.to_string().into()),
-            }
+            )
         }
     });
 
     module.set_native_fn("prompt", {
-        move |prompt: &str, default_value: &str, regex: &str| -> HookResult<String> {
-            let value = prompt_and_check_variable(
-                &TemplateSlots {
-                    prompt: prompt.into(),
-                    var_name: "".into(),
-                    var_info: VarInfo::String {
-                        entry: Box::new(StringEntry {
-                            default: Some(default_value.into()),
-                            kind: StringKind::String,
-                            regex: Some(Regex::new(regex).map_err(|_| "Invalid regex")?),
-                        }),
-                    },
-                },
-                None,
-            );
+        let generation = Arc::clone(generation);
+        move |prompt: &str, default_value: &str, regex: &str| {
+            ensure_interactive(silent, prompt)?;
+            let regex = Regex::new(regex).map_err(|_| "Invalid regex")?;
+            let timeout = &generation.prompt_timeout;
+            prompt_for_string(timeout, prompt, Some(default_value), Some(regex))
+        }
+    });
 
-            match value {
-                Ok(v) => Ok(v),
-                Err(e) => Err(e.to_string().into()),
+    module.set_native_fn("prompt_or", {
+        let generation = Arc::clone(generation);
+        move |prompt: &str, default_value: bool| {
+            if silent {
+                return Ok(default_value);
             }
+            prompt_for_bool(&generation.prompt_timeout, prompt, default_value)
+        }
+    });
+
+    module.set_native_fn("prompt_or", {
+        let generation = Arc::clone(generation);
+        move |prompt: &str, default_value: &str| {
+            if silent {
+                return Ok(default_value.to_string());
+            }
+            prompt_for_string(
+                &generation.prompt_timeout,
+                prompt,
+                Some(default_value),
+                None,
+            )
         }
     });
 
     module.set_native_fn("prompt", {
+        let generation = Arc::clone(generation);
         move |prompt: &str, default_value: &str, choices: rhai::Array| -> HookResult<String> {
-            let value = prompt_and_check_variable(
-                &TemplateSlots {
-                    prompt: prompt.into(),
-                    var_name: "".into(),
-                    var_info: VarInfo::String {
-                        entry: Box::new(StringEntry {
-                            default: Some(default_value.into()),
-                            kind: StringKind::Choices(
-                                choices
-                                    .iter()
-                                    .map(|d| d.to_owned().into_string().unwrap())
-                                    .collect(),
-                            ),
-                            regex: None,
-                        }),
-                    },
-                },
-                None,
-            );
+            ensure_interactive(silent, prompt)?;
+            prompt_for_choice(&generation.prompt_timeout, prompt, default_value, &choices)
+                .map(|(value, _)| value)
+        }
+    });
 
-            match value {
-                Ok(v) => Ok(v),
-                Err(e) => Err(e.to_string().into()),
-            }
+    module.set_native_fn("prompt_choice", {
+        let generation = Arc::clone(generation);
+        move |prompt: &str, default_value: &str, choices: rhai::Array| -> HookResult<Map> {
+            ensure_interactive(silent, prompt)?;
+            let timeout = &generation.prompt_timeout;
+            let (value, index) = prompt_for_choice(timeout, prompt, default_value, &choices)?;
+            let mut choice = Map::new();
+            choice.insert("value".into(), value.into());
+            choice.insert("index".into(), index.into());
+            Ok(choice)
         }
     });
 
     module
 }
 
+/// Fails if `name` is a built-in variable only `variable::force_set` may overwrite. Until the
+/// project name is final, it may be set like any other variable.
+fn ensure_unprotected(
+    template_object: &TemplateObjectResource,
+    aliases: &Aliases,
+    name: &str,
+    project_name_final: bool,
+) -> HookResult<()> {
+    let protected = is_protected(name) && (project_name_final || !is_project_name(name));
+    if protected
+        && !matches!(
+            template_object.get_value(name, aliases)?,
+            NamedValue::NonExistent
+        )
+    {
+        return Err(format!(
+            "Variable {name} is built in. Use `variable::force_set` to overwrite it anyway."
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Sets the variable `name` to `value`, whatever it was, warning when overwriting a built-in.
+fn force_set(
+    template_object: &TemplateObjectResource,
+    audit: &Audit,
+    name: &str,
+    value: serde_json::Value,
+) -> HookResult<()> {
+    if is_protected(name) {
+        warn!(
+            "{} {} `{}`",
+            emoji::WARN,
+            style("A hook overwrites the built-in variable").bold(),
+            style(name).bold().yellow(),
+        );
+    }
+    audit.record_forced_by_hook(name, &value);
+    insert_variable(
+        &mut template_object
+            .lock()
+            .map_err(|_| PoisonError::new_eval_alt_result())?
+            .borrow_mut(),
+        name,
+        value,
+    );
+    Ok(())
+}
+
+/// Fails in silent mode, where there is nobody to answer `prompt`.
+fn ensure_interactive(silent: bool, prompt: &str) -> HookResult<()> {
+    if silent {
+        return Err(format!(
+            "Cannot prompt for `{prompt}` in silent mode. Use `variable::prompt_or` to fall back to a default instead."
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn prompt_for_bool(timeout: &PromptTimeout, prompt: &str, default_value: bool) -> HookResult<bool> {
+    let value = prompt_and_check_variable(
+        &TemplateSlots {
+            prompt: prompt.into(),
+            var_name: "".into(),
+            var_info: VarInfo::Bool {
+                default: Some(default_value),
+            },
+            remember: false,
+            default_from_command: None,
+        },
+        None,
+        timeout,
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(value
+        .parse::<bool>()
+        .map_err(|_| "Unable to parse into bool")?)
+}
+
+fn prompt_for_string(
+    timeout: &PromptTimeout,
+    prompt: &str,
+    default_value: Option<&str>,
+    regex: Option<Regex>,
+) -> HookResult<String> {
+    let value = prompt_and_check_variable(
+        &TemplateSlots {
+            prompt: prompt.into(),
+            var_name: "".into(),
+            var_info: VarInfo::String {
+                entry: Box::new(StringEntry {
+                    default: default_value.map(String::from),
+                    kind: StringKind::String,
+                    regex,
+                    regex_description: None,
+                    transform: Vec::new(),
+                    shape: None,
+                }),
+            },
+            remember: false,
+            default_from_command: None,
+        },
+        None,
+        timeout,
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(value)
+}
+
+/// Prompts for one of `choices`, returning it along with its index in `choices`.
+fn prompt_for_choice(
+    timeout: &PromptTimeout,
+    prompt: &str,
+    default_value: &str,
+    choices: &[Dynamic],
+) -> HookResult<(String, INT)> {
+    let choices = choices
+        .iter()
+        .map(|choice| choice.clone().into_string())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|type_name| format!("Choices must be strings, found a '{type_name}'"))?;
+
+    let value = prompt_and_check_variable(
+        &TemplateSlots {
+            prompt: prompt.into(),
+            var_name: "".into(),
+            var_info: VarInfo::String {
+                entry: Box::new(StringEntry {
+                    default: Some(default_value.into()),
+                    kind: StringKind::Choices(choices.clone()),
+                    regex: None,
+                    regex_description: None,
+                    transform: Vec::new(),
+                    shape: None,
+                }),
+            },
+            remember: false,
+            default_from_command: None,
+        },
+        None,
+        timeout,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let index = choices
+        .iter()
+        .position(|choice| *choice == value)
+        .ok_or_else(|| format!("{value} is not one of the choices"))?;
+    Ok((value, index as INT))
+}
+
 enum NamedValue {
     NonExistent,
     Bool(bool),
     String(String),
     Array(Vec<serde_json::Value>),
+    Object(serde_json::Map<String, serde_json::Value>),
 }
 
 trait GetNamedValue {
-    fn get_value(&self, name: &str) -> HookResult<NamedValue>;
+    fn get_value(&self, name: &str, aliases: &Aliases) -> HookResult<NamedValue>;
 }
 
 impl GetNamedValue for TemplateObjectResource {
-    fn get_value(&self, name: &str) -> HookResult<NamedValue> {
+    fn get_value(&self, name: &str, aliases: &Aliases) -> HookResult<NamedValue> {
         let lock = self.lock()
             .map_err(|_| PoisonError::new_eval_alt_result())?;
         let obj = lock.borrow();
         
-        Ok(obj.get(name).map_or_else(|| NamedValue::NonExistent, |value| {
+        let value = nested_value(&obj, name).or_else(|| {
+            aliases.aliased(name).and_then(|aliased| nested_value(&obj, aliased))
+        });
+        Ok(value.map_or_else(|| NamedValue::NonExistent, |value| {
             // Try to interpret as array first
             if let Some(arr) = value.as_array() {
                 return NamedValue::Array(arr.clone());
             }
+            // Then try object
+            if let Some(object) = value.as_object() {
+                return NamedValue::Object(object.clone());
+            }
             // Then try bool
             if let Some(b) = value.as_bool() {
                 return NamedValue::Bool(b);
@@ -291,7 +483,7 @@ mod tests {
         let mut engine = rhai::Engine::new();
         let template_object = Arc::new(Mutex::new(RefCell::new(serde_json::Map::new())));
 
-        let module = create_module(&template_object);
+        let module = create_module(&template_object, &Arc::default(), false, true);
         engine.register_static_module("variable", module.into());
 
         engine
@@ -321,4 +513,81 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_rhai_set_protects_built_ins() {
+        let mut object = serde_json::Map::new();
+        object.insert("crate_name".into(), "foobar".into());
+        object.insert("project-name".into(), "foobar".into());
+        let template_object = Arc::new(Mutex::new(RefCell::new(object)));
+
+        let mut engine = rhai::Engine::new();
+        engine.register_static_module(
+            "variable",
+            create_module(&template_object, &Arc::default(), false, false).into(),
+        );
+        let err = engine
+            .eval::<()>(r#"variable::set("crate_name", "other")"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("variable::force_set"), "{err}");
+        // until the project name is final
+        engine
+            .eval::<()>(r#"variable::set("project-name", "renamed")"#)
+            .unwrap();
+
+        let mut engine = rhai::Engine::new();
+        engine.register_static_module(
+            "variable",
+            create_module(&template_object, &Arc::default(), false, true).into(),
+        );
+        assert!(engine
+            .eval::<()>(r#"variable::set("project-name", "again")"#)
+            .is_err());
+        engine
+            .eval::<()>(r#"variable::force_set("crate_name", "other")"#)
+            .unwrap();
+
+        let template_object = template_object.lock().unwrap();
+        let template_object = template_object.borrow();
+        assert_eq!(template_object["crate_name"], "other");
+        assert_eq!(template_object["project-name"], "renamed");
+    }
+
+    #[test]
+    fn test_rhai_prompt_choice_rejects_non_string_choices() {
+        let mut engine = rhai::Engine::new();
+        let template_object = Arc::new(Mutex::new(RefCell::new(serde_json::Map::new())));
+
+        let module = create_module(&template_object, &Arc::default(), false, true);
+        engine.register_static_module("variable", module.into());
+
+        let err = engine
+            .eval::<Map>(r#"variable::prompt_choice("Pick one", "a", ["a", 2])"#)
+            .unwrap_err();
+
+        assert!(err.to_string().contains("Choices must be strings"), "{err}");
+    }
+
+    #[test]
+    fn test_rhai_prompt_in_silent_mode() {
+        let mut engine = rhai::Engine::new();
+        let template_object = Arc::new(Mutex::new(RefCell::new(serde_json::Map::new())));
+
+        let module = create_module(&template_object, &Arc::default(), true, true);
+        engine.register_static_module("variable", module.into());
+
+        let err = engine
+            .eval::<String>(r#"variable::prompt("Name?", "anonymous")"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("in silent mode"), "{err}");
+
+        let name = engine
+            .eval::<String>(r#"variable::prompt_or("Name?", "anonymous")"#)
+            .unwrap();
+        assert_eq!(name, "anonymous");
+        let confirmed = engine
+            .eval::<bool>(r#"variable::prompt_or("Sure?", true)"#)
+            .unwrap();
+        assert!(confirmed);
+    }
 }
@@ -1,3 +1,4 @@
+use ignore::gitignore::Gitignore;
 use ignore::WalkBuilder;
 use log::{info, warn};
 use remove_dir_all::*;
@@ -7,7 +8,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::config::CONFIG_FILE_NAME;
+use crate::config::{TemplateConfig, CONFIG_FILE_NAME};
 pub const IGNORE_FILE_NAME: &str = ".genignore";
 
 // We ignore the `.cargo-ok` file if one is present. This file is a somewhat
@@ -21,23 +22,28 @@ pub const IGNORE_FILE_NAME: &str = ".genignore";
 const CARGO_OK_FILE_NAME: &str = ".cargo-ok";
 
 /// Takes the directory path and removes the files/directories specified in the
-/// `.genignore` file
+/// `.genignore` file and the `ignore` list of the `template_config`, telling why each one is
+/// removed if `verbose`
 /// It handles all errors internally
 pub fn remove_unneeded_files(
     dir: &Path,
-    ignored_files: &Option<Vec<String>>,
+    template_config: &TemplateConfig,
     verbose: bool,
 ) -> anyhow::Result<()> {
     let mut items = get_ignored(dir);
-    if let Some(ignored_files) = ignored_files {
+    if let Some(ignored_files) = &template_config.ignore {
         for f in ignored_files {
             let mut p = PathBuf::new();
             p.push(dir);
             p.push(f);
-            items.push(p);
+            let section = template_config.pattern_sources.of(f);
+            items.push((p, format!("listed in `ignore` of `{section}`")));
         }
     }
-    remove_dir_files(&items, verbose);
+    for (item, reason) in items {
+        let message = format!("Ignoring: {}, {reason}", item.display());
+        remove_dir_file(item, verbose.then_some(message));
+    }
     Ok(())
 }
 
@@ -48,13 +54,15 @@ fn check_if_genignore_exists(location: &Path) -> bool {
     ignore_path.exists()
 }
 
-fn get_ignored(location: &Path) -> Vec<PathBuf> {
+/// The files `.genignore` ignores, along with those always ignored, with the reason for each.
+fn get_ignored(location: &Path) -> Vec<(PathBuf, String)> {
     let default_ignored = [IGNORE_FILE_NAME, CONFIG_FILE_NAME, CARGO_OK_FILE_NAME]
         .iter()
-        .map(|&file_name| location.join(file_name));
+        .map(|&file_name| (location.join(file_name), "always ignored".to_string()));
     if !check_if_genignore_exists(location) {
         return default_ignored.collect();
     }
+    let (genignore, _) = Gitignore::new(location.join(IGNORE_FILE_NAME));
     let all = WalkBuilder::new(location)
         .standard_filters(false)
         .build()
@@ -67,8 +75,21 @@ fn get_ignored(location: &Path) -> Vec<PathBuf> {
         .map(unwrap_path)
         .collect();
 
+    let reason = |path: &Path| {
+        // the patterns of `.genignore` files in folders below aren't known
+        genignore
+            .matched_path_or_any_parents(path, path.is_dir())
+            .inner()
+            .map_or_else(
+                || format!("ignored by a `{IGNORE_FILE_NAME}`"),
+                |glob| format!("matching `{}` of `{IGNORE_FILE_NAME}`", glob.original()),
+            )
+    };
     default_ignored
-        .chain(all.filter(|it| !whitelisted.contains(it)))
+        .chain(
+            all.filter(|it| !whitelisted.contains(it))
+                .map(|it| (it.clone(), reason(&it))),
+        )
         .collect()
 }
 
@@ -77,27 +98,29 @@ fn unwrap_path(it: Result<ignore::DirEntry, ignore::Error>) -> PathBuf {
 }
 
 pub fn remove_dir_files(files: impl IntoIterator<Item = impl Into<PathBuf>>, verbose: bool) {
-    for item in files
-        .into_iter()
-        .map(|i| i.into() as PathBuf)
-        .filter(|file| file.exists())
-    {
+    for item in files.into_iter().map(|i| i.into() as PathBuf) {
         let ignore_message = format!("Ignoring: {}", &item.display());
-        if item.is_dir() {
-            remove_dir_all(&item).unwrap();
-            if verbose {
-                info!("{ignore_message}");
-            }
-        } else if item.is_file() {
-            remove_file(&item).unwrap();
-            if verbose {
-                info!("{ignore_message}");
-            }
-        } else {
-            warn!(
-                "The given paths are neither files nor directories! {}",
-                &item.display()
-            );
-        }
+        remove_dir_file(item, verbose.then_some(ignore_message));
+    }
+}
+
+/// Removes the file or folder `item`, if it exists, logging `message` if given.
+fn remove_dir_file(item: PathBuf, message: Option<String>) {
+    if !item.exists() {
+        return;
+    }
+    if item.is_dir() {
+        remove_dir_all(&item).unwrap();
+    } else if item.is_file() {
+        remove_file(&item).unwrap();
+    } else {
+        warn!(
+            "The given paths are neither files nor directories! {}",
+            &item.display()
+        );
+        return;
+    }
+    if let Some(message) = message {
+        info!("{message}");
     }
 }
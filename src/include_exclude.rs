@@ -1,11 +1,30 @@
+//! Which files of the template are rendered, by the gitignore-style patterns of the `include` or
+//! `exclude` list.
+//!
+//! Brace groups expand to one pattern per alternative before matching, `src/{bin,lib}/**` to
+//! `src/bin/**` and `src/lib/**`, nested groups and empty alternatives included, as in
+//! `*.rs{,.bak}`. As in `.gitignore`, a leading `!` negates a pattern, and the last pattern
+//! matching a file, or one of its folders, decides.
+
 use crate::config::{TemplateConfig, CONFIG_FILE_NAME};
-use anyhow::Result;
+use crate::emoji;
+use anyhow::{anyhow, Result};
+use console::style;
 use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
 use log::warn;
+use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
 
 #[derive(Default)]
-pub struct Matcher(Option<MatcherKind>, Vec<String>);
+pub struct Matcher {
+    kind: Option<MatcherKind>,
+    permanent_excluded: Vec<String>,
+    sources: PatternSources,
+    /// The patterns as written, by the patterns their brace groups expand to
+    written: HashMap<String, String>,
+}
 
 pub enum ShouldInclude {
     Include,
@@ -18,6 +37,64 @@ enum MatcherKind {
     Exclude(Gitignore),
 }
 
+/// The sections of the config the patterns of `include`, `exclude` and `ignore` were merged from,
+/// those not listed coming from `[template]`.
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct PatternSources(Vec<(String, String)>);
+
+impl PatternSources {
+    /// Records `patterns` as coming from `section`.
+    pub fn add<'a>(&mut self, patterns: impl IntoIterator<Item = &'a String>, section: &str) {
+        self.0.extend(
+            patterns
+                .into_iter()
+                .map(|pattern| (pattern.clone(), section.to_string())),
+        );
+    }
+
+    /// The section `pattern` comes from, the one added last if several have it.
+    pub fn of(&self, pattern: &str) -> &str {
+        self.0
+            .iter()
+            .rev()
+            .find(|(added, _)| added == pattern)
+            .map_or("[template]", |(_, section)| section)
+    }
+}
+
+/// What decided whether a file is rendered, see [`Matcher::decide`].
+#[derive(Debug, PartialEq)]
+pub enum Rule {
+    /// The file is a hook script, which is never rendered
+    Hook,
+    /// There is neither an `include` nor an `exclude` list
+    NoPatterns,
+    /// The pattern of `list`, from `section`, matching the file or one of its folders, the last
+    /// one if several do
+    Pattern {
+        list: &'static str,
+        pattern: String,
+        section: String,
+    },
+    /// None of the patterns of `list` matches the file
+    Unmatched { list: &'static str },
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hook => write!(f, "a hook script"),
+            Self::NoPatterns => write!(f, "neither `include` nor `exclude` is given"),
+            Self::Pattern {
+                list,
+                pattern,
+                section,
+            } => write!(f, "matching `{pattern}` of `{list}` in `{section}`"),
+            Self::Unmatched { list } => write!(f, "matching no pattern of `{list}`"),
+        }
+    }
+}
+
 impl Matcher {
     pub(crate) fn new(
         template_config: &mut TemplateConfig,
@@ -33,52 +110,245 @@ impl Matcher {
             )
         }
 
+        let mut written = HashMap::new();
         let kind = match (&template_config.exclude, &template_config.include) {
             (None, None) => None,
-            (None, Some(it)) => Some(MatcherKind::Include(Self::create_matcher(project_dir, it)?)),
-            (Some(it), None) => Some(MatcherKind::Exclude(Self::create_matcher(project_dir, it)?)),
+            (None, Some(it)) => Some(MatcherKind::Include(Self::create_matcher(
+                project_dir,
+                "include",
+                it,
+                &mut written,
+            )?)),
+            (Some(it), None) => Some(MatcherKind::Exclude(Self::create_matcher(
+                project_dir,
+                "exclude",
+                it,
+                &mut written,
+            )?)),
             (Some(_), Some(_)) => unreachable!(
                 "BUG: template config has both include and exclude specified: {:?}",
                 template_config
             ),
         };
-        Ok(Self(kind, permanent_excluded.into()))
+        Ok(Self {
+            kind,
+            permanent_excluded: permanent_excluded.into(),
+            sources: template_config.pattern_sources.clone(),
+            written,
+        })
     }
 
-    fn create_matcher(project_dir: &Path, patterns: &[String]) -> Result<Gitignore> {
+    fn create_matcher(
+        project_dir: &Path,
+        list: &str,
+        patterns: &[String],
+        written: &mut HashMap<String, String>,
+    ) -> Result<Gitignore> {
         let mut builder = GitignoreBuilder::new(project_dir);
         for rule in patterns {
-            builder.add_line(None, rule)?;
+            let invalid = |cause: &dyn fmt::Display| {
+                anyhow!(
+                    "{} {} `{}` {} {cause}",
+                    emoji::ERROR,
+                    style("The pattern").bold().red(),
+                    style(rule).bold().yellow(),
+                    style(format!("of `{list}` is invalid:")).bold().red(),
+                )
+            };
+            for expanded in expand_braces(rule).map_err(|e| invalid(&e))? {
+                builder.add_line(None, &expanded).map_err(|e| invalid(&e))?;
+                written.insert(expanded, rule.clone());
+            }
         }
         Ok(builder.build()?)
     }
 
     pub fn should_include(&self, relative_path: &Path) -> ShouldInclude {
+        self.decide(relative_path).0
+    }
+
+    /// Whether the file at `relative_path` is included, with the rule that decided it.
+    pub fn decide(&self, relative_path: &Path) -> (ShouldInclude, Rule) {
         if self
-            .1
+            .permanent_excluded
             .iter()
             .any(|e| relative_path.to_str().map(|p| p == e).unwrap_or_default())
         {
-            return ShouldInclude::Ignore;
+            return (ShouldInclude::Ignore, Rule::Hook);
         }
 
         // "Include" and "exclude" options are mutually exclusive.
         // if no include is made, we will default to ignore_exclude
         // which if there is no options, matches everything
-        if match &self.0 {
-            Some(MatcherKind::Exclude(it)) => {
-                !it.matched_path_or_any_parents(relative_path, /* is_dir */ false)
-                    .is_ignore()
-            }
-            Some(MatcherKind::Include(it)) => {
-                it.matched_path_or_any_parents(relative_path, /* is_dir */ false)
-                    .is_ignore()
-            }
-            None => true,
-        } {
+        let (list, matcher) = match &self.kind {
+            Some(MatcherKind::Exclude(it)) => ("exclude", it),
+            Some(MatcherKind::Include(it)) => ("include", it),
+            None => return (ShouldInclude::Include, Rule::NoPatterns),
+        };
+        let matched = matcher.matched_path_or_any_parents(relative_path, /* is_dir */ false);
+        let should_include = if matched.is_ignore() == (list == "include") {
             ShouldInclude::Include
         } else {
             ShouldInclude::Exclude
+        };
+        let rule = match matched {
+            Match::None => Rule::Unmatched { list },
+            Match::Ignore(glob) | Match::Whitelist(glob) => {
+                let pattern = self
+                    .written
+                    .get(glob.original())
+                    .map_or(glob.original(), String::as_str);
+                Rule::Pattern {
+                    list,
+                    pattern: pattern.to_string(),
+                    section: self.sources.of(pattern).to_string(),
+                }
+            }
+        };
+        (should_include, rule)
+    }
+}
+
+/// The patterns `pattern` expands to, one for each alternative of its first brace group, each
+/// expanded in turn.
+fn expand_braces(pattern: &str) -> Result<Vec<String>, String> {
+    let mut depth = 0;
+    let mut open = 0;
+    let mut commas = vec![];
+    for (index, c) in special_chars(pattern) {
+        match c {
+            '{' => {
+                if depth == 0 {
+                    open = index;
+                }
+                depth += 1;
+            }
+            ',' if depth == 1 => commas.push(index),
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    let bounds = [vec![open], commas, vec![index]].concat();
+                    let (prefix, suffix) = (&pattern[..open], &pattern[index + 1..]);
+                    let mut expanded = vec![];
+                    for alternative in bounds.windows(2) {
+                        let alternative = &pattern[alternative[0] + 1..alternative[1]];
+                        expanded.extend(expand_braces(&format!("{prefix}{alternative}{suffix}"))?);
+                    }
+                    return Ok(expanded);
+                }
+            }
+            _ => {}
         }
     }
+    if depth > 0 {
+        return Err(format!("the `{{` at {} is never closed", open + 1));
+    }
+    Ok(vec![pattern.to_string()])
+}
+
+/// The braces and commas of `pattern`, by index, but those escaped with a backslash or within a
+/// character class.
+fn special_chars(pattern: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut escaped = false;
+    let mut in_class = false;
+    pattern.char_indices().filter(move |&(_, c)| {
+        let special = !escaped && !in_class && matches!(c, '{' | '}' | ',');
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            _ => {}
+        }
+        special
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_rule_deciding_is_given_with_its_section() {
+        let mut config = TemplateConfig {
+            exclude: Some(vec![
+                "docs/**".into(),
+                "!docs/index.md".into(),
+                "*.png".into(),
+            ]),
+            ..Default::default()
+        };
+        config
+            .pattern_sources
+            .add(&["*.png".to_string()], "[conditional.'images']");
+        let matcher = Matcher::new(&mut config, Path::new("/t"), &["pre.rhai".into()]).unwrap();
+        let rule = |path: &str| matcher.decide(Path::new(path)).1.to_string();
+
+        assert_eq!(rule("pre.rhai"), "a hook script");
+        assert_eq!(
+            rule("docs/guide.md"),
+            "matching `docs/**` of `exclude` in `[template]`"
+        );
+        assert_eq!(
+            rule("docs/index.md"),
+            "matching `!docs/index.md` of `exclude` in `[template]`"
+        );
+        assert!(matches!(
+            matcher.should_include(Path::new("docs/index.md")),
+            ShouldInclude::Include
+        ));
+        assert_eq!(
+            rule("logo.png"),
+            "matching `*.png` of `exclude` in `[conditional.'images']`"
+        );
+        assert_eq!(rule("src/main.rs"), "matching no pattern of `exclude`");
+    }
+
+    #[test]
+    fn braces_expand_to_their_alternatives() {
+        let expand = |pattern| expand_braces(pattern).unwrap();
+
+        assert_eq!(expand("src/{bin,lib}/**"), ["src/bin/**", "src/lib/**"]);
+        assert_eq!(expand("!{a,b{c,d}}.md"), ["!a.md", "!bc.md", "!bd.md"]);
+        assert_eq!(expand("*.rs{,.bak}"), ["*.rs", "*.rs.bak"]);
+        assert_eq!(expand("{a,b}/{c,d}"), ["a/c", "a/d", "b/c", "b/d"]);
+        assert_eq!(expand(r"\{a,b}/[{]x,y"), [r"\{a,b}/[{]x,y"]);
+        assert_eq!(expand("a}b"), ["a}b"]);
+        assert!(expand_braces("src/{bin,lib").is_err());
+    }
+
+    #[test]
+    fn the_last_pattern_matching_decides() {
+        let mut config = TemplateConfig {
+            include: Some(vec![
+                "src/{bin,lib}/**".into(),
+                "!src/lib/generated/**".into(),
+                "src/lib/generated/keep.rs".into(),
+            ]),
+            ..Default::default()
+        };
+        let matcher = Matcher::new(&mut config, Path::new("/t"), &[]).unwrap();
+        let included = |path: &str| {
+            matches!(
+                matcher.should_include(Path::new(path)),
+                ShouldInclude::Include
+            )
+        };
+
+        assert!(included("src/bin/main.rs"));
+        assert!(included("src/lib/mod.rs"));
+        assert!(!included("src/other/mod.rs"));
+        assert!(!included("src/lib/generated/schema.rs"));
+        assert!(included("src/lib/generated/keep.rs"));
+        assert_eq!(
+            matcher.decide(Path::new("src/bin/main.rs")).1.to_string(),
+            "matching `src/{bin,lib}/**` of `include` in `[template]`"
+        );
+
+        let mut config = TemplateConfig {
+            include: Some(vec!["src/{a,b".into()]),
+            ..Default::default()
+        };
+        assert!(Matcher::new(&mut config, Path::new("/t"), &[]).is_err());
+    }
 }
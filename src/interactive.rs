@@ -1,48 +1,70 @@
 use crate::{
-    emoji,
+    accessible, emoji,
     project_variables::{ArrayEntry, Prompt, StringEntry, StringKind, TemplateSlots, VarInfo},
+    prompt_timeout::{self, PromptTimeout},
+    select, theme,
 };
 use anyhow::{anyhow, bail, Result};
 use console::style;
-use dialoguer::{theme::ColorfulTheme, MultiSelect, Select};
+use dialoguer::console::Term;
+use dialoguer::MultiSelect;
 use dialoguer::{Editor, Input};
 use log::warn;
-use std::{
-    borrow::Cow,
-    io::{stdin, Read},
-    ops::Index,
-    str::FromStr,
-};
+use std::{borrow::Cow, ops::Index, str::FromStr};
 
 pub const LIST_SEP: &str = ",";
 
-pub fn name() -> Result<String> {
+pub fn name(timeout: &PromptTimeout) -> Result<String> {
+    ask_name("Project Name", None, timeout)
+}
+
+/// Asks for another project name than `taken`, which is offered as the default to keep it.
+pub fn another_name(taken: &str, timeout: &PromptTimeout) -> Result<String> {
+    ask_name("Another Project Name", Some(taken.to_string()), timeout)
+}
+
+fn ask_name(prompt: &str, default: Option<String>, timeout: &PromptTimeout) -> Result<String> {
     let valid_ident = regex::Regex::new(r"^([a-zA-Z][a-zA-Z0-9_-]+)$")?;
     let project_var = TemplateSlots {
         var_name: "crate_name".into(),
-        prompt: "Project Name".into(),
+        prompt: Prompt::new(prompt, default.clone()),
         var_info: VarInfo::String {
             entry: Box::new(StringEntry {
-                default: None,
+                default,
                 kind: StringKind::String,
                 regex: Some(valid_ident),
+                regex_description: Some(
+                    "it must be a letter followed by letters, digits, `_` or `-`".into(),
+                ),
+                transform: Vec::new(),
+                shape: None,
             }),
         },
+        remember: false,
+        default_from_command: None,
     };
-    prompt_and_check_variable(&project_var, None)
+    prompt_and_check_variable(&project_var, None, timeout)
 }
 
+/// Asks the user for a string, offering `initial_text` for editing, if any.
 pub fn user_question(
     prompt: &Prompt,
     default: &Option<String>,
     kind: &StringKind,
+    initial_text: Option<&str>,
 ) -> Result<String> {
     match kind {
+        StringKind::String if accessible::line_prompts() => {
+            accessible::input(&prompt.with_default, default.as_deref())
+        }
         StringKind::String => {
             let mut i = Input::<String>::new().with_prompt(&prompt.styled_with_default);
             if let Some(s) = default {
                 i = i.default(s.to_owned());
             }
+            if let Some(text) = initial_text {
+                i = i.with_initial_text(text);
+            }
             i.interact().map_err(Into::<anyhow::Error>::into)
         }
         StringKind::Editor => {
@@ -58,7 +80,7 @@ pub fn user_question(
                 prompt.styled_with_default
             );
             let mut buffer = String::new();
-            stdin().read_to_string(&mut buffer)?;
+            while prompt_timeout::read_line(&mut buffer)? > 0 {}
             Ok(buffer)
         }
         StringKind::Choices(_) => {
@@ -67,12 +89,37 @@ pub fn user_question(
     }
 }
 
+/// The answer for `variable`, `provided_value` unless none, which asks for it, within `timeout`.
 pub fn prompt_and_check_variable(
     variable: &TemplateSlots,
     provided_value: Option<String>,
+    timeout: &PromptTimeout,
 ) -> Result<String> {
+    if provided_value.is_some() || !timeout.active() {
+        return check_variable(variable, provided_value);
+    }
+    timeout.ask(&variable.var_name, default_answer(variable), || {
+        check_variable(variable, None)
+    })
+}
+
+/// The answer taking the default of `variable`, if it has one.
+fn default_answer(variable: &TemplateSlots) -> Option<String> {
+    match &variable.var_info {
+        VarInfo::Bool { default } => default.map(|default| default.to_string()),
+        VarInfo::String { entry } => entry.default.clone(),
+        VarInfo::Array { entry } => entry.default.as_ref().map(|default| default.join(LIST_SEP)),
+    }
+}
+
+fn check_variable(variable: &TemplateSlots, provided_value: Option<String>) -> Result<String> {
     match &variable.var_info {
-        VarInfo::Bool { default } => handle_bool_input(provided_value, &variable.prompt, default),
+        VarInfo::Bool { default } => handle_bool_input(
+            provided_value,
+            &variable.var_name,
+            &variable.prompt,
+            default,
+        ),
         VarInfo::String { entry } => match &entry.kind {
             StringKind::Choices(choices) => handle_choice_input(
                 provided_value,
@@ -91,8 +138,13 @@ pub fn prompt_and_check_variable(
     }
 }
 
-pub fn variable(variable: &TemplateSlots, provided_value: Option<&impl ToString>) -> Result<serde_json::Value> {
-    let user_entry = prompt_and_check_variable(variable, provided_value.map(|v| v.to_string()))?;
+pub fn variable(
+    variable: &TemplateSlots,
+    provided_value: Option<&impl ToString>,
+    timeout: &PromptTimeout,
+) -> Result<serde_json::Value> {
+    let user_entry =
+        prompt_and_check_variable(variable, provided_value.map(|v| v.to_string()), timeout)?;
     match &variable.var_info {
         VarInfo::Bool { .. } => {
             let as_bool = user_entry.parse::<bool>()?;
@@ -114,6 +166,52 @@ pub fn variable(variable: &TemplateSlots, provided_value: Option<&impl ToString>
     }
 }
 
+/// Like [`variable`], but when the placeholder rejects `provided_value`, the user is told why and
+/// asked for a value instead, unless `silent` or there is no terminal to ask on.
+pub fn provided_variable(
+    variable: &TemplateSlots,
+    provided_value: Option<&impl ToString>,
+    silent: bool,
+    timeout: &PromptTimeout,
+) -> Result<serde_json::Value> {
+    self::variable(variable, provided_value, timeout).or_else(|error| {
+        if provided_value.is_none() || silent || !Term::stderr().is_term() {
+            return Err(error);
+        }
+        warn!("{error}");
+        self::variable(variable, None::<&String>, timeout)
+    })
+}
+
+/// The rule of `entry` a rejected value broke, in the words of the template if it has them.
+fn regex_rule(entry: &StringEntry) -> String {
+    match (&entry.regex_description, &entry.regex) {
+        (Some(description), _) => description.clone(),
+        (None, Some(regex)) => format!("it must match `{regex}`"),
+        (None, None) => "it is rejected".to_string(),
+    }
+}
+
+/// Why `entry` rejects `value`, if it does: it must match the regex and be of the shape of the
+/// placeholder.
+fn rejection(entry: &StringEntry, value: &str) -> Option<String> {
+    if entry.regex.as_ref().is_some_and(|regex| !regex.is_match(value)) {
+        return Some(regex_rule(entry));
+    }
+    entry.shape.and_then(|shape| shape.rejects(value))
+}
+
+fn invalid_value_message(value: &str, var_name: &str, rule: &str) -> String {
+    format!(
+        "{} \"{}\" {}",
+        style("Sorry,").bold().red(),
+        style(value).bold().yellow(),
+        style(format!("is not a valid value for {var_name}: {rule}"))
+            .bold()
+            .red()
+    )
+}
+
 fn handle_string_input(
     provided_value: Option<String>,
     var_name: &str,
@@ -121,57 +219,47 @@ fn handle_string_input(
     prompt: &Prompt,
 ) -> Result<String> {
     if let Some(value) = provided_value {
-        if entry
-            .regex
-            .as_ref()
-            .map(|ex| ex.is_match(&value))
-            .unwrap_or(true)
-        {
+        let value = entry.transformed(value);
+        let Some(rule) = rejection(entry, &value) else {
             return Ok(value);
-        }
+        };
         bail!(
-            "{} {} \"{}\" {}",
+            "{} {}",
             emoji::WARN,
-            style("Sorry,").bold().red(),
-            style(&value).bold().yellow(),
-            style(format!("is not a valid value for {var_name}"))
-                .bold()
-                .red()
+            invalid_value_message(&value, var_name, &rule)
         )
     };
     let mut prompt: Cow<'_, Prompt> = Cow::Borrowed(prompt);
-    match &entry.regex {
-        Some(regex) => loop {
-            let user_entry = user_question(&prompt, &entry.default, &entry.kind)?;
-            if regex.is_match(&user_entry) {
-                break Ok(user_entry);
+    if entry.regex.is_none() && entry.shape.is_none() {
+        return Ok(entry.transformed(user_question(&prompt, &entry.default, &entry.kind, None)?));
+    }
+    let mut typed: Option<String> = None;
+    loop {
+        let raw_entry = user_question(&prompt, &entry.default, &entry.kind, typed.as_deref())?;
+        let user_entry = entry.transformed(raw_entry.clone());
+        let Some(rule) = rejection(entry, &user_entry) else {
+            break Ok(user_entry);
+        };
+        // the user won't see the error in stdout if in a editor
+        match entry.kind {
+            StringKind::Editor => {
+                // Editor use with_default
+                prompt.to_mut().with_default = format!(
+                    "{}: \"{user_entry}\" is not a valid value for `{var_name}`: {}",
+                    prompt
+                        .with_default
+                        .split_once(':')
+                        .map(|t| t.0)
+                        .unwrap_or(&prompt.with_default),
+                    rule,
+                );
             }
-            // the user won't see the error in stdout if in a editor
-            match entry.kind {
-                StringKind::Editor => {
-                    // Editor use with_default
-                    prompt.to_mut().with_default = format!(
-                        "{}: \"{user_entry}\" is not a valid value for `{var_name}`",
-                        prompt
-                            .with_default
-                            .split_once(':')
-                            .map(|t| t.0)
-                            .unwrap_or(&prompt.with_default)
-                    );
-                }
-                _ => {
-                    warn!(
-                        "{} \"{}\" {}",
-                        style("Sorry,").bold().red(),
-                        style(&user_entry).bold().yellow(),
-                        style(format!("is not a valid value for {var_name}"))
-                            .bold()
-                            .red()
-                    );
-                }
-            };
-        },
-        None => Ok(user_question(&prompt, &entry.default, &entry.kind)?),
+            _ => {
+                warn!("{}", invalid_value_message(&user_entry, var_name, &rule));
+            }
+        };
+        // what was typed is offered for editing, instead of retyping it all
+        typed = Some(raw_entry);
     }
 }
 
@@ -184,36 +272,39 @@ fn handle_choice_input(
 ) -> Result<String> {
     match provided_value {
         Some(value) => {
+            let value = entry.transformed(value);
             if choices.contains(&value) {
                 Ok(value)
             } else {
                 bail!(
-                    "{} {} \"{}\" {}",
+                    "{} {}",
                     emoji::WARN,
-                    style("Sorry,").bold().red(),
-                    style(&value).bold().yellow(),
-                    style(format!("is not a valid value for {var_name}"))
-                        .bold()
-                        .red(),
+                    invalid_value_message(&value, var_name, &choices_rule(choices)),
                 )
             }
         }
         None => {
-            let default = entry
-                .default
-                .as_ref()
-                .map_or(0, |default| choices.binary_search(default).unwrap_or(0));
-            let chosen = Select::with_theme(&ColorfulTheme::default())
-                .items(choices)
-                .with_prompt(&prompt.styled)
-                .default(default)
-                .interact()?;
+            let default = entry.default.as_ref().map_or(0, |default| {
+                choices
+                    .iter()
+                    .position(|choice| choice == default)
+                    .unwrap_or(0)
+            });
+            let chosen = select::select(&prompt.styled, choices, default)?;
 
             Ok(choices.index(chosen).to_string())
         }
     }
 }
 
+fn choices_rule(choices: &[String]) -> String {
+    let choices = choices
+        .iter()
+        .map(|choice| format!("`{choice}`"))
+        .collect::<Vec<_>>();
+    format!("it must be one of {}", choices.join(", "))
+}
+
 // simple function so we can easily get more complicated later if we need to
 fn parse_list(provided_value: &str) -> Vec<String> {
     provided_value
@@ -264,11 +355,15 @@ fn handle_multi_select_input(
                 }
             };
 
-            let choice_indices = MultiSelect::with_theme(&ColorfulTheme::default())
-                .items(&entry.choices)
-                .with_prompt(&prompt.styled)
-                .defaults(&selected_by_default)
-                .interact()?;
+            let choice_indices = if accessible::line_prompts() {
+                accessible::multi_select(&prompt.styled, &entry.choices, &selected_by_default)?
+            } else {
+                MultiSelect::with_theme(&theme::prompt_theme())
+                    .items(&entry.choices)
+                    .with_prompt(&prompt.styled)
+                    .defaults(&selected_by_default)
+                    .interact()?
+            };
 
             choice_indices
                 .iter()
@@ -282,10 +377,11 @@ fn handle_multi_select_input(
     match check_provided_selections(&val, &entry.choices) {
         Ok(s) => Ok(s.join(LIST_SEP)),
         Err(s) => {
+            let rule = choices_rule(&entry.choices);
             let err_string = if s.len() > 1 {
-                format!("are not valid values for {var_name}")
+                format!("are not valid values for {var_name}: {rule}")
             } else {
-                format!("is not a valid value for {var_name}")
+                format!("is not a valid value for {var_name}: {rule}")
             };
 
             bail!(
@@ -301,21 +397,26 @@ fn handle_multi_select_input(
 
 fn handle_bool_input(
     provided_value: Option<String>,
+    var_name: &str,
     prompt: &Prompt,
     default: &Option<bool>,
 ) -> Result<String> {
     match provided_value {
-        Some(value) => {
-            let value = bool::from_str(&value.to_lowercase())?;
-            Ok(value.to_string())
-        }
+        Some(value) => match bool::from_str(&value.to_lowercase()) {
+            Ok(value) => Ok(value.to_string()),
+            Err(_) => bail!(
+                "{} {}",
+                emoji::WARN,
+                invalid_value_message(&value, var_name, "it must be `true` or `false`"),
+            ),
+        },
         None => {
             let choices = [false.to_string(), true.to_string()];
-            let chosen = Select::with_theme(&ColorfulTheme::default())
-                .items(&choices)
-                .with_prompt(&prompt.styled)
-                .default(usize::from(default.unwrap_or(false)))
-                .interact()?;
+            let chosen = select::select(
+                &prompt.styled,
+                &choices,
+                usize::from(default.unwrap_or(false)),
+            )?;
 
             Ok(choices.index(chosen).to_string())
         }
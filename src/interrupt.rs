@@ -0,0 +1,212 @@
+//! Ctrl-C while generating: it does not end the process right away, with locks held and half a
+//! project written, but stops the scripts of the template, hooks or `rhai` filters, and the walk
+//! over its files, restores the terminal, and fails generation with nothing left behind.
+//!
+//! The handler of Ctrl-C is the process's, so generating only sets one while it runs once the
+//! application asked for it with [`catch_ctrl_c`], as the binary does. Applications handling
+//! Ctrl-C themselves tell generating about it with [`forward_ctrl_c`] instead.
+
+use anyhow::Result;
+use console::{style, Term};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use crate::emoji;
+
+/// How often Ctrl-C was pressed while caught.
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+
+/// How many [`Catch`]es are alive, and the interrupts before the first of them.
+static CATCHING: AtomicUsize = AtomicUsize::new(0);
+static SINCE: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether generating sets a handler of Ctrl-C while it runs.
+static HANDLING: AtomicBool = AtomicBool::new(false);
+
+/// The handler of Ctrl-C before it was caught, if generating set one.
+static PREVIOUS: Mutex<Option<libc::sighandler_t>> = Mutex::new(None);
+
+extern "C" fn on_interrupt(_signal: libc::c_int) {
+    INTERRUPTS.fetch_add(1, Ordering::SeqCst);
+    // the handler is reset to the default before it is called
+    #[cfg(windows)]
+    unsafe {
+        libc::signal(libc::SIGINT, handler());
+    }
+}
+
+fn handler() -> libc::sighandler_t {
+    on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t
+}
+
+/// Makes generating catch Ctrl-C from now on, setting a handler of the signal for as long as it
+/// runs and restoring the one before afterwards.
+pub fn catch_ctrl_c() {
+    HANDLING.store(true, Ordering::SeqCst);
+}
+
+/// Tells generating Ctrl-C was pressed, for applications handling the signal themselves. It does
+/// nothing but count, so it may be called from a signal handler.
+pub fn forward_ctrl_c() {
+    INTERRUPTS.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Ctrl-C being caught instead of ending the process, until dropped.
+pub struct Catch {
+    since: usize,
+}
+
+/// Catches Ctrl-C, telling the returned [`Catch`] about it.
+pub fn catch() -> Catch {
+    let since = INTERRUPTS.load(Ordering::SeqCst);
+    let mut previous = PREVIOUS.lock().unwrap_or_else(|e| e.into_inner());
+    if CATCHING.fetch_add(1, Ordering::SeqCst) == 0 {
+        SINCE.store(since, Ordering::SeqCst);
+        if HANDLING.load(Ordering::SeqCst) {
+            // SAFETY: the handler does nothing but count, which is safe in a signal handler
+            *previous = Some(unsafe { libc::signal(libc::SIGINT, handler()) });
+        }
+    }
+    Catch { since }
+}
+
+impl Catch {
+    /// Whether Ctrl-C was pressed since it is caught.
+    pub fn interrupted(&self) -> bool {
+        INTERRUPTS.load(Ordering::SeqCst) > self.since
+    }
+
+    /// Fails with [`Interrupted`] after Ctrl-C was pressed.
+    pub fn check(&self) -> Result<()> {
+        if self.interrupted() {
+            return Err(Interrupted.into());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Catch {
+    fn drop(&mut self) {
+        let mut previous = PREVIOUS.lock().unwrap_or_else(|e| e.into_inner());
+        if CATCHING.fetch_sub(1, Ordering::SeqCst) == 1 {
+            if let Some(previous) = previous.take() {
+                // SAFETY: the handler is the one Ctrl-C had before
+                unsafe {
+                    libc::signal(libc::SIGINT, previous);
+                }
+            }
+        }
+    }
+}
+
+/// Whether Ctrl-C was pressed while it is caught, for scripts to stop with.
+pub fn caught() -> bool {
+    CATCHING.load(Ordering::SeqCst) > 0
+        && INTERRUPTS.load(Ordering::SeqCst) > SINCE.load(Ordering::SeqCst)
+}
+
+/// Fails with [`Interrupted`] after Ctrl-C was pressed while it is caught, to stop in between
+/// steps that take long.
+pub fn stop_if_caught() -> Result<()> {
+    if caught() {
+        return Err(Interrupted.into());
+    }
+    Ok(())
+}
+
+/// Stops the scripts `engine` runs as soon as `cancelled` tells so.
+pub fn stop_when(engine: &mut rhai::Engine, cancelled: impl Fn() -> bool + 'static) {
+    engine.on_progress(move |_| cancelled().then(|| "interrupted".into()));
+}
+
+/// The folders made for projects being generated, removed when generating them is interrupted.
+static MADE: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+fn made() -> MutexGuard<'static, Vec<PathBuf>> {
+    MADE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Records that `dir` was made for the project being generated, to remove it if interrupted.
+pub fn made_for_project(dir: &Path) {
+    made().push(dir.to_path_buf());
+}
+
+/// Generating stopped as Ctrl-C was pressed.
+#[derive(Debug)]
+pub struct Interrupted;
+
+impl Interrupted {
+    /// What cargo-generate exits with when interrupted, as shells do for Ctrl-C.
+    pub const EXIT_CODE: i32 = 130;
+}
+
+impl std::error::Error for Interrupted {}
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", emoji::ERROR, style("Interrupted").bold().red())
+    }
+}
+
+/// Runs `generate` with Ctrl-C caught, and when it fails for Ctrl-C being pressed, be it caught
+/// or read by a prompt, removes the folders made for projects since, unless `keep_partial`.
+pub fn generating<T>(keep_partial: bool, generate: impl FnOnce() -> Result<T>) -> Result<T> {
+    let interrupt = catch();
+    let made_before = made().len();
+    let result = generate();
+    let dirs = {
+        let mut made = made();
+        let since = made_before.min(made.len());
+        made.split_off(since)
+    };
+    let Err(error) = result else {
+        return result;
+    };
+    if !interrupt.interrupted() && !read_by_prompt(&error) && !error.is::<Interrupted>() {
+        return Err(error);
+    }
+    let _ = Term::stderr().show_cursor();
+    if !keep_partial {
+        for dir in dirs {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+    Err(Interrupted.into())
+}
+
+/// Whether `error` is of a prompt reading Ctrl-C, as they do in raw mode instead of the process
+/// being signalled.
+fn read_by_prompt(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|error| error.kind() == std::io::ErrorKind::Interrupted)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::Cell, rc::Rc};
+
+    #[test]
+    fn scripts_stop_once_cancelled() {
+        let cancelled = Rc::new(Cell::new(false));
+        let mut engine = rhai::Engine::new();
+        stop_when(&mut engine, {
+            let cancelled = Rc::clone(&cancelled);
+            move || cancelled.get()
+        });
+        assert_eq!(engine.eval::<i64>("let n = 0; for i in 0..100 { n += i } n").unwrap(), 4950);
+
+        cancelled.set(true);
+        let error = engine.eval::<()>("loop {}").unwrap_err();
+        assert!(matches!(*error, rhai::EvalAltResult::ErrorTerminated(..)), "{error}");
+    }
+}
@@ -0,0 +1,76 @@
+//! Keeping the template files of some globs along with what they render to, for templates meant to
+//! be rendered again within the generated project:
+//!
+//! ```toml
+//! [template.keep_source]
+//! "config/*.toml" = true
+//! ```
+//!
+//! The files are kept as they are in the template, under their unrendered path, in the folder
+//! [`SOURCE_DIR`] of the project.
+
+use anyhow::Result;
+use fs_err as fs;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use indexmap::IndexMap;
+use std::path::Path;
+
+/// The folder of the project the template files are kept in.
+pub const SOURCE_DIR: &str = ".template";
+
+/// The `keep_source` globs of a template, matched against the path of a file relative to the
+/// template root. The first matching glob wins.
+#[derive(Default)]
+pub struct KeepSource {
+    rules: Vec<(Gitignore, bool)>,
+}
+
+impl KeepSource {
+    pub fn new(root: &Path, keep_source: Option<&IndexMap<String, bool>>) -> Result<Self> {
+        let mut rules = vec![];
+        for (glob, keep) in keep_source.into_iter().flatten() {
+            let mut builder = GitignoreBuilder::new(root);
+            builder.add_line(None, glob)?;
+            rules.push((builder.build()?, *keep));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Whether the template file at `relative_path` is kept.
+    pub fn keeps(&self, relative_path: &Path) -> bool {
+        self.rules
+            .iter()
+            .find(|(matcher, _)| {
+                matcher
+                    .matched_path_or_any_parents(relative_path, /* is_dir */ false)
+                    .is_ignore()
+            })
+            .is_some_and(|(_, keep)| *keep)
+    }
+
+    /// Copies the template file at `relative_path` of `project_dir`, before it is rendered, to
+    /// [`SOURCE_DIR`].
+    pub fn keep(&self, project_dir: &Path, relative_path: &Path) -> Result<()> {
+        let kept = project_dir.join(SOURCE_DIR).join(relative_path);
+        fs::create_dir_all(kept.parent().unwrap())?;
+        fs::copy(project_dir.join(relative_path), kept)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_first_matching_glob_wins() {
+        let keep_source: IndexMap<String, bool> =
+            toml::from_str("\"config/local.toml\" = false\n\"config/*.toml\" = true").unwrap();
+        let keep_source = KeepSource::new(Path::new("/template"), Some(&keep_source)).unwrap();
+
+        assert!(keep_source.keeps(Path::new("config/app.toml")));
+        assert!(!keep_source.keeps(Path::new("config/local.toml")));
+        assert!(!keep_source.keeps(Path::new("Cargo.toml")));
+        assert!(!KeepSource::default().keeps(Path::new("config/app.toml")));
+    }
+}
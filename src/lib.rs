@@ -23,45 +23,114 @@
 )]
 
 mod absolute_path;
+mod accessible;
 mod app_config;
+mod archive;
 mod args;
+mod case;
 mod config;
 mod copy;
+mod crates_io;
+mod dates;
+mod destination;
 mod emoji;
+mod escape;
+mod exit_code;
+mod explain_var;
 mod favorites;
+mod features;
+mod fetchers;
 mod filenames;
+mod generation;
+mod generators;
 mod git;
+mod gitignore;
 mod hooks;
 mod ignore_me;
 mod include_exclude;
 mod interactive;
+mod interrupt;
+mod keep_source;
+mod limits;
+mod locale;
+mod lockfile;
+mod merge;
+mod no_scripts;
+mod oci;
+mod package;
+mod postprocess;
+mod preflight;
+mod preview;
+mod profile;
+mod prompt_timeout;
 mod progressbar;
 mod project_variables;
+mod migrate_config;
+mod regenerate;
+mod render;
+mod render_error;
+mod render_state;
+mod resume;
+mod select;
+mod sha256;
+mod signature;
+mod strict;
+mod targets;
 mod template;
 mod template_filters;
+mod template_functions;
+mod template_includes;
 mod template_variables;
+mod theme;
+mod undo;
 mod user_parsed_input;
+mod variable_aliases;
 mod workspace_member;
+mod workspace_template;
 
 pub use crate::app_config::{app_config_path, AppConfig};
+use crate::app_config::RememberedAnswers;
 pub use crate::favorites::list_favorites;
+pub use crate::exit_code::exit_code;
+pub use crate::fetchers::{register_fetcher, FetcherConfig, TemplateFetcher};
+pub use crate::interrupt::{catch_ctrl_c, forward_ctrl_c, Interrupted};
+pub use crate::limits::LimitsConfig;
+pub use crate::lockfile::{info, GenerationLock, LockedTemplate, LOCK_FILE_NAME};
+pub use crate::migrate_config::migrate_config;
+pub use crate::oci::publish;
+pub use crate::package::package;
+use crate::render::RenderOnly;
+pub use crate::render::{eval, render};
 use crate::template::create_minijinja_engine;
+pub use crate::undo::undo;
 pub use args::*;
 
 use anyhow::{anyhow, bail, Context, Result};
-use config::{locate_template_configs, Config, CONFIG_FILE_NAME};
+use config::{
+    locate_template_configs, ConditionalConfig, Config, ProjectType, TemplateKind,
+    CONFIG_FILE_NAME,
+};
 use console::style;
-use copy::{copy_files_recursively, TEMPLATE_SUFFIX};
+use copy::{copy_files_recursively, Snapshot, TEMPLATE_SUFFIX};
 use env_logger::fmt::Formatter;
+use exit_code::{FailedAt, Failure};
 use fs_err as fs;
+use generation::Generation;
+use generators::PreCommit;
 use hooks::{execute_hooks, RhaiHooksContext};
 use ignore_me::remove_dir_files;
+use indexmap::IndexMap;
 use interactive::{prompt_and_check_variable, LIST_SEP};
+use limits::{Budget, LimitExceeded};
 use log::Record;
 use log::{info, warn};
+use merge::{MergeRules, MergeStrategy, OVERLAY_MERGE};
 use project_variables::{StringEntry, StringKind, TemplateSlots, VarInfo};
+use prompt_timeout::PromptTimeout;
+use render_error::{InvalidTree, RenderErrors};
 use std::{
-    collections::HashMap,
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap, HashSet},
     env,
     io::Write,
     path::{Path, PathBuf},
@@ -73,14 +142,18 @@ use workspace_member::WorkspaceMemberStatus;
 
 use crate::git::tmp_dir;
 use crate::template_variables::{
-    load_env_and_args_template_values, CrateName, ProjectDir, ProjectNameInput,
+    get_generation_time, load_env_and_args_template_values, load_remote_template_values,
+    usable_project_name, CrateName, ProjectDir, ProjectNameInput,
 };
 use crate::{project_variables::ConversionError, template_variables::ProjectName};
 
 use self::config::TemplateConfig;
-use self::git::try_get_branch_from_path;
+use self::git::{try_get_branch_from_path, try_get_commit_from_path};
 use self::hooks::evaluate_script;
-use self::template::{create_template_object, set_project_name_variables, TemplateObjectResource};
+use self::template::{
+    create_template_object, set_computed_variables, set_date_variables,
+    set_project_name_variables, set_repository_variable, TemplateObjectResource,
+};
 
 /// Logging formatter function
 pub fn log_formatter(
@@ -93,28 +166,138 @@ pub fn log_formatter(
         _ => "".to_string(),
     };
 
-    writeln!(buf, "{}{}", prefix, record.args())
+    let line = theme::style_log_line(record.level(), format!("{}{}", prefix, record.args()));
+    writeln!(buf, "{line}")
+}
+
+/// What a generation is asked for by the crate itself, rather than by the [`GenerateArgs`] of
+/// the user.
+#[derive(Debug, Default)]
+struct GenerateOptions {
+    /// What to print rendered instead of generating the project, see [`render()`] and [`eval()`]
+    render_only: Option<RenderOnly>,
+    /// Answers offered as the defaults of their placeholders, those given before changing them
+    /// in a `--preview`
+    previous_answers: Option<toml::Table>,
+    /// Generated by a hook of another generation into its template, which is no workspace to
+    /// join, but a template still to be rendered
+    nested: bool,
 }
 
 /// # Panics
 pub fn generate(args: GenerateArgs) -> Result<PathBuf> {
+    generate_with(args, GenerateOptions::default())
+}
+
+fn generate_with(args: GenerateArgs, options: GenerateOptions) -> Result<PathBuf> {
+    // resolved once, as a relative `--config` would resolve elsewhere once the working directory
+    // changed
+    let app_config_path = app_config_path(&args.config)?;
+    let template_path = args.template_path.clone();
+    interrupt::generating(args.keep_partial, || {
+        generate_project(args, options, &app_config_path)
+    })
+    .inspect_err(|_| resume::hint(&app_config_path, &template_path))
+}
+
+fn generate_project(
+    args: GenerateArgs,
+    options: GenerateOptions,
+    app_config_path: &Path,
+) -> Result<PathBuf> {
     let _working_dir_scope = ScopedWorkingDirectory::default();
+    accessible::configure(args.accessible);
+    // screen readers read emoji out by their names
+    emoji::configure(args.no_emoji || accessible::enabled());
+    theme::set_color_choice(args.color);
+    if args.profile_render.is_some() {
+        profile::enable();
+    }
+    locale::configure(args.lang.as_deref());
+    // completed as the template becomes known, and shared once it is rendered
+    let mut generation = Generation {
+        audit: explain_var::Audit::new(&args.explain_var),
+        generated_at: get_generation_time(
+            args.timestamp,
+            args.utc_offset.as_deref(),
+            args.reproducible,
+        )?,
+        prompt_timeout: PromptTimeout::new(args.prompt_timeout),
+        ..Generation::default()
+    };
+
+    let app_config = AppConfig::try_from(app_config_path)?;
+    theme::set_theme(app_config.theme.as_ref());
 
-    let app_config = AppConfig::try_from(app_config_path(&args.config)?.as_path())?;
+    let defaults = app_config.defaults.as_ref();
+    let values_url = args
+        .values_url
+        .clone()
+        .or_else(|| defaults.and_then(|defaults| defaults.values_url.clone()));
+    let require_signed = args.require_signed
+        || defaults
+            .and_then(|defaults| defaults.require_signed)
+            .unwrap_or(false);
+    let trusted_keys = defaults
+        .and_then(|defaults| defaults.trusted_keys.clone())
+        .unwrap_or_default();
+
+    fetchers::register_configured(app_config.fetchers.as_ref());
+    generation.budget = Budget::new(app_config.limits);
+
+    // the answers kept by a generation of the template that failed, unless given otherwise
+    let (args, resumed_answers) = resume::resumed(app_config_path, args);
 
     // mash AppConfig and CLI arguments together into UserParsedInput
     let mut user_parsed_input = UserParsedInput::try_from_args_and_config(app_config, &args);
+    for (key, value) in user_parsed_input.template_values() {
+        generation
+            .audit
+            .record(key, value, explain_var::Source::AppConfig);
+    }
+    // let values published centrally provide defaults for all the others
+    if let Some(url) = values_url {
+        offer_template_values(
+            user_parsed_input.template_values_mut(),
+            load_remote_template_values(&url).failed_at(Failure::Fetch)?,
+            explain_var::Source::ValuesUrl,
+            &generation.audit,
+        );
+    }
     // let ENV vars provide values we don't have yet
     user_parsed_input
         .template_values_mut()
-        .extend(load_env_and_args_template_values(&args)?);
+        .extend(load_env_and_args_template_values(&args, &generation.audit)?);
+    offer_template_values(
+        user_parsed_input.template_values_mut(),
+        resumed_answers,
+        explain_var::Source::Resumed,
+        &generation.audit,
+    );
 
-    let (template_base_dir, template_dir, branch) = prepare_local_template(&user_parsed_input)?;
+    let (template_base_dir, template_dir, branch, commit) =
+        prepare_local_template(&user_parsed_input, &generation.prompt_timeout)
+            .failed_at(Failure::Fetch)?;
+    interrupt::stop_if_caught()?;
 
     // read configuration in the template
-    let mut config = Config::from_path(
-        &locate_template_file(CONFIG_FILE_NAME, &template_base_dir, &template_dir).ok(),
-    )?;
+    let config_file =
+        locate_template_file(CONFIG_FILE_NAME, &template_base_dir, &template_dir).ok();
+    let mut config = Config::from_path(&config_file)?;
+    // before any of the hooks had a chance to run
+    if require_signed {
+        signature::verify_template(
+            config_file.as_deref(),
+            &template_dir,
+            &config.get_hook_files(),
+            &trusted_keys,
+        )?;
+    }
+    signature::remove_signatures(
+        config_file.as_deref(),
+        &template_dir,
+        &config.get_hook_files(),
+    );
 
     // the `--init` parameter may also be set by the template itself
     if config
@@ -131,29 +314,171 @@ pub fn generate(args: GenerateArgs) -> Result<PathBuf> {
 
         user_parsed_input.init = true;
     };
+    user_parsed_input.generic =
+        config.template.as_ref().and_then(|t| t.project_type) == Some(ProjectType::Generic);
+    if user_parsed_input.generic() && user_parsed_input.test() {
+        bail!(
+            "{} {}",
+            emoji::ERROR,
+            style("Generic projects are no crates, `--test` is not allowed")
+                .bold()
+                .red(),
+        );
+    }
 
-    check_cargo_generate_version(&config)?;
+    let overlay = config.template.as_ref().and_then(|t| t.kind) == Some(TemplateKind::Overlay);
+    if overlay {
+        check_overlay(&user_parsed_input)?;
+        user_parsed_input.init = true;
+    }
+    destination::ask_unless_pinned(
+        &args,
+        &options,
+        &mut user_parsed_input,
+        &generation.prompt_timeout,
+    )?;
 
-    let project_dir = expand_template(&template_dir, &mut config, &user_parsed_input, &args)?;
-    let (mut should_initialize_git, with_force) = {
-        let vcs = &config
+    check_cargo_generate_version(&config)?;
+    generation.no_scripts = args.no_scripts;
+    no_scripts::warn_skipped(generation.no_scripts, &template_dir, &config);
+    generation.strict = strict::asked(args.strict, &config);
+    generation.functions = template_functions::load(
+        &template_dir,
+        config
             .template
             .as_ref()
-            .and_then(|t| t.vcs)
-            .unwrap_or_else(|| user_parsed_input.vcs());
-
-        (
-            !vcs.is_none() && (!user_parsed_input.init || user_parsed_input.force_git_init()),
-            user_parsed_input.force_git_init(),
-        )
-    };
+            .and_then(|t| t.functions.as_ref())
+            .filter(|_| !generation.no_scripts),
+    )?;
+    generation.include_roots = Some(Arc::new(template_includes::roots(
+        template_base_dir.path(),
+        &template_dir,
+        config.template.as_ref().and_then(|t| t.include_roots.as_ref()),
+        &args.include_root,
+    )?));
+    generation.aliases = variable_aliases::Aliases::new(
+        config
+            .template
+            .as_ref()
+            .and_then(|t| t.variable_aliases.as_ref()),
+    );
+    let generation = Arc::new(generation);
+    generators::add_builtin_placeholders(&mut config);
+    features::add_features_placeholder(&mut config);
+
+    let locked_template = args
+        .lockfile
+        .then(|| LockedTemplate::new(&user_parsed_input, commit));
+    let (project_dir, template_object) = expand_template(
+        &template_dir,
+        &mut config,
+        &user_parsed_input,
+        &args,
+        &options,
+        locked_template,
+        app_config_path,
+        &generation,
+    )?;
+    if options.render_only.is_some() {
+        // what was rendered was printed, the project is not needed
+        return Ok(project_dir);
+    }
+    if args.regenerate_script {
+        let name = template_object
+            .lock()
+            .unwrap()
+            .borrow()
+            .get("project-name")
+            .and_then(|name| name.as_str().map(str::to_string))
+            .unwrap_or_default();
+        regenerate::write(
+            &template_dir,
+            &user_parsed_input,
+            &name,
+            &preview::answers(&config, &template_object),
+        )?;
+    }
+    if args.preview {
+        let decision = preview::review(&template_dir)?;
+        if !matches!(decision, preview::Decision::Write) && !user_parsed_input.init() {
+            // the destination was created for the project, and is still empty
+            fs::remove_dir(&project_dir)?;
+        }
+        match decision {
+            preview::Decision::Write => {}
+            preview::Decision::ChangeAnswers => {
+                let name = template_object
+                    .lock()
+                    .unwrap()
+                    .borrow()
+                    .get("project-name")
+                    .and_then(|name| name.as_str().map(str::to_string));
+                return generate_project(
+                    GenerateArgs {
+                        name: name.or(args.name.clone()),
+                        resume: false,
+                        ..args
+                    },
+                    GenerateOptions {
+                        previous_answers: Some(preview::answers(&config, &template_object)),
+                        ..options
+                    },
+                    app_config_path,
+                );
+            }
+            preview::Decision::Abort => {
+                resume::clear(app_config_path, &args.template_path);
+                return Err(anyhow!(
+                    "{} {}",
+                    emoji::ERROR,
+                    style("Aborted, nothing was written").bold().red(),
+                ))
+                .failed_at(Failure::Abort);
+            }
+        }
+    }
+    let vcs = config
+        .template
+        .as_ref()
+        .and_then(|t| t.vcs)
+        .unwrap_or_else(|| user_parsed_input.vcs());
+    let (mut should_initialize_git, with_force) = (
+        !overlay
+            && !vcs.is_none()
+            && (!user_parsed_input.init || user_parsed_input.force_git_init()),
+        user_parsed_input.force_git_init(),
+    );
 
-    let target_path = if user_parsed_input.test() {
+    // projects generated into existing directories are never removed again
+    let test = user_parsed_input.test();
+    let undoable = !test && !user_parsed_input.init;
+    let generic = user_parsed_input.generic();
+    let mut workspace_manifest = None;
+    let target_path = if test {
         test_expanded_template(&template_dir, args.other_args)?
     } else {
-        let project_path = copy_expanded_template(template_dir, project_dir, user_parsed_input)?;
-
-        match workspace_member::add_to_workspace(&project_path)? {
+        let mut merge = config
+            .template
+            .as_ref()
+            .and_then(|t| t.merge.clone())
+            .unwrap_or_default();
+        if overlay {
+            skip_overlay_sources(&template_dir)?;
+            for (glob, strategy) in OVERLAY_MERGE {
+                merge.entry(glob.to_string()).or_insert(strategy);
+            }
+        }
+        let project_path =
+            copy_expanded_template(template_dir, project_dir, user_parsed_input, Some(&merge))?;
+
+        let workspace_status = if overlay || generic || options.nested {
+            // the project already is whatever it is, or it is no crate, or what is around it is
+            // no project yet
+            WorkspaceMemberStatus::NoWorkspaceFound
+        } else {
+            workspace_member::add_to_workspace(&project_path)?
+        };
+        match workspace_status {
             WorkspaceMemberStatus::Added(workspace_cargo_toml) => {
                 should_initialize_git = with_force;
                 info!(
@@ -162,6 +487,7 @@ pub fn generate(args: GenerateArgs) -> Result<PathBuf> {
                     style("Project added as member to workspace").bold(),
                     style(workspace_cargo_toml.display()).bold().yellow(),
                 );
+                workspace_manifest = Some(workspace_cargo_toml);
             }
             WorkspaceMemberStatus::NoWorkspaceFound => {
                 // not an issue, just a notification
@@ -180,7 +506,25 @@ pub fn generate(args: GenerateArgs) -> Result<PathBuf> {
 
         git::init(&target_path, branch.as_deref(), with_force)?;
     }
+    // in the repository initialized, or the one the project was generated into
+    let git_hook =
+        config.generators.as_ref().and_then(|g| g.pre_commit) == Some(PreCommit::GitHook);
+    if git_hook && !vcs.is_none() && !test {
+        generators::activate_git_hooks(&target_path)?;
+    }
+
+    if undoable {
+        let recorded = undo::record(app_config_path, &target_path, workspace_manifest.as_deref());
+        if let Err(error) = recorded {
+            warn!(
+                "{} {}",
+                style("The project can't be undone, recording it failed:").bold(),
+                error
+            );
+        }
+    }
 
+    resume::clear(app_config_path, &args.template_path);
     info!(
         "{} {} {} {}",
         emoji::SPARKLE,
@@ -188,14 +532,97 @@ pub fn generate(args: GenerateArgs) -> Result<PathBuf> {
         style("New project created").bold(),
         style(&target_path.display()).underlined()
     );
+    if let Some(limit) = args.profile_render {
+        profile::report(limit);
+    }
+    generation.audit.report();
 
     Ok(target_path)
 }
 
+/// Adds the `values` of `source` for the names not given a value otherwise.
+fn offer_template_values(
+    template_values: &mut HashMap<String, toml::Value>,
+    values: impl IntoIterator<Item = (String, toml::Value)>,
+    source: explain_var::Source,
+    audit: &explain_var::Audit,
+) {
+    for (key, value) in values {
+        if let Entry::Vacant(entry) = template_values.entry(key) {
+            audit.record(entry.key(), &value, source.clone());
+            entry.insert(value);
+        }
+    }
+}
+
+/// Refuses to apply an overlay template anywhere but onto an existing project, or in any way that
+/// could replace its files.
+fn check_overlay(user_parsed_input: &UserParsedInput) -> Result<()> {
+    if user_parsed_input.overwrite() || user_parsed_input.force_git_init() {
+        bail!(
+            "{} {}",
+            emoji::ERROR,
+            style("Overlay templates never replace files, `--overwrite` and `--force-git-init` are not allowed")
+                .bold()
+                .red(),
+        );
+    }
+    let manifest = if user_parsed_input.generic() {
+        user_parsed_input.destination().to_owned()
+    } else {
+        user_parsed_input.destination().join("Cargo.toml")
+    };
+    if !manifest.exists() {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Overlay templates are added to existing projects, found no")
+                .bold()
+                .red(),
+            style(manifest.display()).bold(),
+        );
+    }
+    Ok(())
+}
+
+/// Drops the `src` folder of an overlay template, as overlays must not touch the sources of the
+/// project.
+fn skip_overlay_sources(template_dir: &Path) -> Result<()> {
+    let sources = template_dir.join("src");
+    if sources.exists() {
+        warn!(
+            "{}",
+            style("Skipping `src`, overlay templates never touch the sources of the project")
+                .bold(),
+        );
+        fs::remove_dir_all(sources)?;
+    }
+    Ok(())
+}
+
+/// Drops the files that failed to render from the expanded template, so that with `--init` the
+/// files of the existing project they would replace are kept as they are.
+fn keep_originals(template_dir: &Path, errors: &RenderErrors) -> Result<()> {
+    for error in &errors.0 {
+        let path = template_dir.join(&error.file);
+        if path.is_file() {
+            fs::remove_file(&path)?;
+            warn!(
+                "{} `{}` {}",
+                style("Not writing").bold(),
+                style(error.file.display()).bold().yellow(),
+                style("as it failed to render").bold(),
+            );
+        }
+    }
+    Ok(())
+}
+
 fn copy_expanded_template(
     template_dir: PathBuf,
     project_dir: PathBuf,
     user_parsed_input: UserParsedInput,
+    merge: Option<&IndexMap<String, MergeStrategy>>,
 ) -> Result<PathBuf> {
     info!(
         "{} {} `{}`{}",
@@ -204,12 +631,37 @@ fn copy_expanded_template(
         style(project_dir.display()).bold().yellow(),
         style("...").bold()
     );
-    copy_files_recursively(template_dir, &project_dir, user_parsed_input.overwrite())?;
+    filenames::check_path_lengths(&template_dir, &project_dir)?;
+    let destination = filenames::long_path(&project_dir);
+    let merge_rules = MergeRules::new(&destination, merge)?;
+    let template_dir = filenames::long_path(&template_dir);
+    // an existing project must not be left half overwritten
+    let snapshot = user_parsed_input
+        .init()
+        .then(|| Snapshot::take(&template_dir, &destination))
+        .transpose()?;
+    if let Err(error) = copy_files_recursively(
+        &template_dir,
+        &destination,
+        user_parsed_input.overwrite(),
+        &merge_rules,
+    ) {
+        if let Some(snapshot) = snapshot {
+            snapshot.restore();
+            warn!(
+                "{} `{}` {}",
+                style("Restored the files of").bold(),
+                style(project_dir.display()).bold().yellow(),
+                style("as they were").bold(),
+            );
+        }
+        return Err(error);
+    }
 
     Ok(project_dir)
 }
 
-fn test_expanded_template(template_dir: &PathBuf, args: Option<Vec<String>>) -> Result<PathBuf> {
+fn test_expanded_template(template_dir: &Path, args: Option<Vec<String>>) -> Result<PathBuf> {
     info!(
         "{} {}{}{}",
         emoji::WRENCH,
@@ -230,7 +682,7 @@ fn test_expanded_template(template_dir: &PathBuf, args: Option<Vec<String>>) ->
     );
     std::process::Command::new(cmd)
         .args(cmd_args)
-        .args(args.unwrap_or_default().into_iter())
+        .args(args.unwrap_or_default())
         .spawn()?
         .wait()?
         .success()
@@ -238,40 +690,66 @@ fn test_expanded_template(template_dir: &PathBuf, args: Option<Vec<String>>) ->
         .ok_or_else(|| anyhow!("{} Testing failed", emoji::ERROR))
 }
 
+/// Copies the template into a temporary folder, returning it with the folder of the template
+/// within, the branch and the commit it was taken from.
 fn prepare_local_template(
     source_template: &UserParsedInput,
-) -> Result<(TempDir, PathBuf, Option<String>), anyhow::Error> {
-    let (temp_dir, branch) = get_source_template_into_temp(source_template.location())?;
-    let template_folder = resolve_template_dir(&temp_dir, source_template.subfolder())?;
+    timeout: &PromptTimeout,
+) -> Result<(TempDir, PathBuf, Option<String>, Option<String>), anyhow::Error> {
+    let (temp_dir, branch, commit) = get_source_template_into_temp(source_template.location())?;
+    let template_folder = resolve_template_dir(
+        &temp_dir,
+        source_template.subfolder(),
+        source_template.silent(),
+        timeout,
+    )?;
 
-    Ok((temp_dir, template_folder, branch))
+    Ok((temp_dir, template_folder, branch, commit))
 }
 
 fn get_source_template_into_temp(
     template_location: &TemplateLocation,
-) -> Result<(TempDir, Option<String>)> {
+) -> Result<(TempDir, Option<String>, Option<String>)> {
     match template_location {
         TemplateLocation::Git(git) => {
-            let result = git::clone_git_template_into_temp(
-                git.url(),
-                git.branch(),
-                git.tag(),
-                git.revision(),
-                git.identity(),
-                git.gitconfig(),
-                git.skip_submodules,
-            );
-            if let Ok((ref temp_dir, _)) = result {
-                git::remove_history(temp_dir.path())?;
-                strip_template_suffixes(temp_dir.path())?;
-            };
-            result
+            let (temp_dir, branch) = resume::with_retries("the template", || {
+                git::clone_git_template_into_temp(
+                    git.url(),
+                    git.branch(),
+                    git.tag(),
+                    git.revision(),
+                    git.identity(),
+                    git.gitconfig(),
+                    git.skip_submodules,
+                )
+            })?;
+            let commit = try_get_commit_from_path(temp_dir.path());
+            git::remove_history(temp_dir.path())?;
+            strip_template_suffixes(temp_dir.path())?;
+            Ok((temp_dir, branch, commit))
+        }
+        TemplateLocation::Fetched(url) => {
+            let temp_dir = tmp_dir()?;
+            resume::with_retries("the template", || fetchers::fetch(url, temp_dir.path()))?;
+            let commit = try_get_commit_from_path(temp_dir.path());
+            git::remove_history(temp_dir.path())?;
+            strip_template_suffixes(temp_dir.path())?;
+            Ok((temp_dir, None, commit))
+        }
+        TemplateLocation::Path(path) if package::is_archive(path) => {
+            let temp_dir = tmp_dir()?;
+            package::unpack(path, temp_dir.path())?;
+            Ok((temp_dir, None, None))
         }
         TemplateLocation::Path(path) => {
             let temp_dir = tmp_dir()?;
-            copy_files_recursively(path, temp_dir.path(), false)?;
+            copy_files_recursively(path, temp_dir.path(), false, &MergeRules::default())?;
             git::remove_history(temp_dir.path())?;
-            Ok((temp_dir, try_get_branch_from_path(path)))
+            Ok((
+                temp_dir,
+                try_get_branch_from_path(path),
+                try_get_commit_from_path(path),
+            ))
         }
     }
 }
@@ -294,14 +772,45 @@ fn strip_template_suffixes(dir: impl AsRef<Path>) -> Result<()> {
     Ok(())
 }
 
-/// resolve the template location for the actual template to expand
-fn resolve_template_dir(template_base_dir: &TempDir, subfolder: Option<&str>) -> Result<PathBuf> {
+/// resolve the template location for the actual template to expand, failing when there are
+/// several to choose from but no one to ask, with `silent` or without a terminal
+fn resolve_template_dir(
+    template_base_dir: &TempDir,
+    subfolder: Option<&str>,
+    silent: bool,
+    timeout: &PromptTimeout,
+) -> Result<PathBuf> {
     let template_dir = resolve_template_dir_subfolder(template_base_dir.path(), subfolder)?;
     auto_locate_template_dir(template_dir, &mut |slots| {
-        prompt_and_check_variable(slots, None)
+        if silent || !(console::Term::stderr().is_term() || accessible::enabled()) {
+            bail!(several_templates(slots));
+        }
+        prompt_and_check_variable(slots, None, timeout)
     })
 }
 
+/// The error of having to choose between the templates `slots` offers without anyone to ask.
+fn several_templates(slots: &TemplateSlots) -> String {
+    let choices = match &slots.var_info {
+        VarInfo::String { entry } => match &entry.kind {
+            StringKind::Choices(choices) => choices.as_slice(),
+            _ => &[],
+        },
+        _ => &[],
+    };
+    let mut error = format!(
+        "{} {}",
+        emoji::ERROR,
+        style("Several templates were found, give the subfolder of the one to expand:")
+            .bold()
+            .red(),
+    );
+    for choice in choices {
+        error.push_str(&format!("\n    {choice}"));
+    }
+    error
+}
+
 /// join the base-dir and the subfolder, ensuring that we stay within the template directory
 fn resolve_template_dir_subfolder(
     template_base_dir: &Path,
@@ -364,23 +873,16 @@ fn auto_locate_template_dir(
         _ => {
             // Multiple configurations found, each in different "roots"
             // let user select between them
-            let prompt_args = TemplateSlots {
-                prompt: "Which template should be expanded?".into(),
-                var_name: "Template".into(),
-                var_info: VarInfo::String {
-                    entry: Box::new(StringEntry {
-                        default: Some(config_paths[0].display().to_string()),
-                        kind: StringKind::Choices(
-                            config_paths
-                                .into_iter()
-                                .map(|p| p.display().to_string())
-                                .collect(),
-                        ),
-                        regex: None,
-                    }),
-                },
-            };
-            let path = prompt(&prompt_args)?;
+            let folders = config_paths
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>();
+            let path = choose_template(
+                "Which template should be expanded?",
+                &template_base_dir,
+                &folders,
+                prompt,
+            )?;
 
             // recursively retry to resolve the template,
             // until we hit a single or no config, identifying the final template folder
@@ -401,18 +903,12 @@ fn resolve_configured_sub_templates(
             || Ok(PathBuf::from(config_path)),
             |sub_templates| {
                 // we have a config that defines sub-templates, let the user select
-                let prompt_args = TemplateSlots {
-                    prompt: "Which sub-template should be expanded?".into(),
-                    var_name: "Template".into(),
-                    var_info: VarInfo::String {
-                        entry: Box::new(StringEntry {
-                            default: Some(sub_templates[0].clone()),
-                            kind: StringKind::Choices(sub_templates.clone()),
-                            regex: None,
-                        }),
-                    },
-                };
-                let path = prompt(&prompt_args)?;
+                let path = choose_template(
+                    "Which sub-template should be expanded?",
+                    config_path,
+                    &sub_templates,
+                    prompt,
+                )?;
 
                 // recursively retry to resolve the template,
                 // until we hit a single or no config, identifying the final template folder
@@ -424,6 +920,51 @@ fn resolve_configured_sub_templates(
         )
 }
 
+/// Asks which of the template `folders` of `base_dir` to expand, showing each with the
+/// `description` of its config, if any, the first one being the default.
+fn choose_template(
+    question: &str,
+    base_dir: &Path,
+    folders: &[String],
+    prompt: &mut impl FnMut(&TemplateSlots) -> Result<String>,
+) -> Result<String> {
+    let choices = folders
+        .iter()
+        .map(|folder| {
+            let description =
+                Config::from_path(&Some(base_dir.join(folder).join(CONFIG_FILE_NAME)))
+                    .ok()
+                    .and_then(|config| config.template)
+                    .and_then(|config| config.description);
+            description.map_or_else(
+                || folder.clone(),
+                |description| format!("{folder}: {description}"),
+            )
+        })
+        .collect::<Vec<_>>();
+    let prompt_args = TemplateSlots {
+        prompt: question.into(),
+        var_name: "Template".into(),
+        var_info: VarInfo::String {
+            entry: Box::new(StringEntry {
+                default: choices.first().cloned(),
+                kind: StringKind::Choices(choices.clone()),
+                regex: None,
+                regex_description: None,
+                transform: Vec::new(),
+                shape: None,
+            }),
+        },
+        remember: false,
+        default_from_command: None,
+    };
+    let chosen = prompt(&prompt_args)?;
+    Ok(choices
+        .iter()
+        .position(|choice| *choice == chosen)
+        .map_or(chosen, |index| folders[index].clone()))
+}
+
 fn locate_template_file(
     name: &str,
     template_base_folder: impl AsRef<Path>,
@@ -446,19 +987,52 @@ fn locate_template_file(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn expand_template(
     template_dir: &Path,
     config: &mut Config,
     user_parsed_input: &UserParsedInput,
     args: &GenerateArgs,
-) -> Result<PathBuf> {
+    options: &GenerateOptions,
+    locked_template: Option<LockedTemplate>,
+    app_config_path: &Path,
+    generation: &Arc<Generation>,
+) -> Result<(PathBuf, TemplateObjectResource)> {
     let template_object = create_template_object(user_parsed_input)?;
+    if config.defines_placeholder("platform") {
+        // templates that came up with the name first keep their own `platform`
+        project_variables::remove_variable(
+            &mut template_object.lock().unwrap().borrow_mut(),
+            "platform",
+        );
+    }
+    for (name, value) in template_object.lock().unwrap().borrow().iter() {
+        let source = if project_variables::is_project_name(name) {
+            explain_var::Source::Name
+        } else {
+            explain_var::Source::BuiltIn
+        };
+        generation.audit.record(name, value, source);
+    }
+    set_date_variables(&template_object, generation)?;
+    // the bars of hooks are shown along with the files being rendered
+    let mut pbar = progressbar::new();
     let context = RhaiHooksContext {
         template_object: template_object.clone(),
         allow_commands: user_parsed_input.allow_commands(),
         silent: user_parsed_input.silent(),
         working_directory: template_dir.to_owned(),
         destination_directory: user_parsed_input.destination().to_owned(),
+        project_name_final: false,
+        preserve_whitespace: config
+            .template
+            .as_ref()
+            .and_then(|t| t.preserve_whitespace)
+            .unwrap_or(false),
+        hook_settings: config.get_hook_settings(),
+        generate_args: args.clone(),
+        progress: pbar.clone(),
+        generation: Arc::clone(generation),
     };
 
     // run init hooks - these won't have access to `crate_name`/`within_cargo_project`
@@ -466,17 +1040,75 @@ fn expand_template(
     // user input!
     // The init hooks are free to set `project-name` (but it will be validated before further
     // use).
-    execute_hooks(&context, &config.get_init_hooks())?;
+    let ignored = execute_hooks(&context, &config.get_init_hooks())?.into_ignored()?;
+    ignore_from_hooks(config, ignored);
 
-    let project_name_input = ProjectNameInput::try_from((&template_object, user_parsed_input))?;
+    let given_name_input = ProjectNameInput::try_from((
+        &template_object,
+        user_parsed_input,
+        &generation.prompt_timeout,
+    ))?;
+    let generic = user_parsed_input.generic();
+    let project_name_input = if generic {
+        // there is no crate name to compile
+        given_name_input.clone()
+    } else {
+        usable_project_name(
+            given_name_input.clone(),
+            user_parsed_input.silent(),
+            &generation.prompt_timeout,
+        )
+        .failed_at(Failure::Validation)?
+    };
+    let project_name_input = if args.allow_network && !generic {
+        ProjectNameInput(crates_io::check_name_available(
+            project_name_input.0,
+            user_parsed_input.silent(),
+            &generation.prompt_timeout,
+        )?)
+    } else {
+        project_name_input
+    };
+    // the project joins the workspace it is generated in, alongside packages of other names
+    let overlay = config.template.as_ref().and_then(|t| t.kind) == Some(TemplateKind::Overlay);
+    let workspace_dir = if user_parsed_input.init() {
+        user_parsed_input.destination().parent()
+    } else {
+        Some(user_parsed_input.destination())
+    };
+    let joins_workspace = !overlay && !generic && !user_parsed_input.test() && !options.nested;
+    let project_name_input = match workspace_dir.filter(|_| joins_workspace) {
+        Some(workspace_dir) => ProjectNameInput(
+            workspace_member::check_name_unique(
+                project_name_input.0,
+                workspace_dir,
+                user_parsed_input.silent(),
+                &generation.prompt_timeout,
+            )
+            .failed_at(Failure::Validation)?,
+        ),
+        None => project_name_input,
+    };
     let project_name = ProjectName::from((&project_name_input, user_parsed_input));
-    let crate_name = CrateName::from(&project_name_input);
-    let destination = ProjectDir::try_from((&project_name_input, user_parsed_input))?;
+    let crate_name = (!generic).then(|| CrateName::from(&project_name_input));
+    let destination = if project_name_input == given_name_input {
+        ProjectDir::try_from((&project_name_input, user_parsed_input))?
+    } else {
+        ProjectDir::renamed(&project_name_input, user_parsed_input)?
+    };
+    preflight::check(template_dir, destination.as_ref()).failed_at(Failure::Validation)?;
     if !user_parsed_input.init() {
         destination.create()?;
+        interrupt::made_for_project(destination.as_ref());
     }
 
-    set_project_name_variables(&template_object, &destination, &project_name, &crate_name)?;
+    set_project_name_variables(
+        &template_object,
+        &destination,
+        &project_name,
+        crate_name.as_ref(),
+        generation,
+    )?;
 
     info!(
         "{} {} {}",
@@ -505,79 +1137,188 @@ fn expand_template(
         &template_object,
         user_parsed_input.template_values(),
         args,
-    )?;
-    add_missing_provided_values(&template_object, user_parsed_input.template_values())?;
+        options,
+        generation,
+    )
+    .failed_at(Failure::Validation)?;
+    add_missing_provided_values(&template_object, user_parsed_input.template_values())
+        .failed_at(Failure::Validation)?;
+    set_repository_variable(&template_object, &destination, &project_name, generation)?;
+    if let Some(computed) = &config.computed {
+        set_computed_variables(
+            template_dir,
+            &template_object,
+            computed,
+            user_parsed_input,
+            generation,
+        )
+        .failed_at(Failure::Render)?;
+    }
+    if options.render_only.is_none() {
+        // until the project is generated, for `--resume` after a failure
+        resume::save(
+            app_config_path,
+            &args.template_path,
+            &project_name_input.0,
+            (!user_parsed_input.init()).then(|| destination.as_ref().to_owned()),
+            preview::answers(config, &template_object),
+        );
+    }
 
     let context = RhaiHooksContext {
         template_object: Arc::clone(&template_object),
         destination_directory: destination.as_ref().to_owned(),
+        project_name_final: true,
         ..context
     };
 
     // run pre-hooks
-    execute_hooks(&context, &config.get_pre_hooks())?;
+    let ignored = execute_hooks(&context, &config.get_pre_hooks())?.into_ignored()?;
+    ignore_from_hooks(config, ignored);
 
     // walk/evaluate the template
     let all_hook_files = config.get_hook_files();
     let mut template_config = config.template.take().unwrap_or_default();
     let preserve_whitespace = template_config.preserve_whitespace.unwrap_or(false);
 
-    ignore_me::remove_unneeded_files(template_dir, &template_config.ignore, args.verbose)?;
-    let mut pbar = progressbar::new();
-
-    let rhai_filter_files = Arc::new(Mutex::new(vec![]));
-    let rhai_engine = create_minijinja_engine(
+    // the scripts of `rhai` filters are no files of the project, wherever they are used, the
+    // config included, which is gone once the unneeded files are
+    let rhai_filter_files = template_filters::rhai_filter_files(
+        template_dir,
+        template_config.rhai_filters.as_ref(),
+    )
+    .failed_at(Failure::Validation)?;
+    let rhai_filter_files = Arc::new(Mutex::new(rhai_filter_files));
+    // renders all files of the project
+    let engine = create_minijinja_engine(
         template_dir.to_owned(),
         template_object.clone(),
         user_parsed_input.allow_commands(),
         user_parsed_input.silent(),
         rhai_filter_files.clone(),
         preserve_whitespace,
+        generation,
     );
+
+    match &options.render_only {
+        Some(RenderOnly::File(file)) => {
+            let contents =
+                template::render_file(template_dir, file, &template_object, &engine, generation)
+                    .failed_at(Failure::Render)?;
+            print!("{contents}");
+            return Ok((destination.as_ref().to_owned(), template_object));
+        }
+        Some(RenderOnly::Expression(expression)) => {
+            let value = template::eval_expression(&template_object, expression, generation)
+                .failed_at(Failure::Render)?;
+            println!("{value}");
+            return Ok((destination.as_ref().to_owned(), template_object));
+        }
+        None => {}
+    }
+
+    ignore_me::remove_unneeded_files(template_dir, &template_config, args.verbose)?;
+
+    // members of a workspace template are expanded on their own, each with its own context
+    let workspace_members = config
+        .workspace_template
+        .as_ref()
+        .map(|workspace| {
+            workspace_template::expand_members(
+                workspace,
+                template_dir,
+                &template_object,
+                user_parsed_input,
+                args,
+                options,
+                &mut pbar,
+                generation,
+            )
+        })
+        .transpose()?;
+
     let result = template::walk_dir(
         &mut template_config,
         template_dir,
         &all_hook_files,
         &template_object,
-        rhai_engine,
+        &engine,
         &rhai_filter_files,
         &mut pbar,
         args.quiet,
+        args.verbose,
+        generation,
     );
 
     match result {
         Ok(()) => (),
+        // nothing was rendered, there is nothing to continue with
+        Err(e) if e.is::<InvalidTree>() || e.is::<Interrupted>() || e.is::<LimitExceeded>() => {
+            return Err(e).failed_at(Failure::Render)
+        }
         Err(e) => {
             // Don't print the error twice
             if !args.quiet && args.continue_on_error {
                 warn!("{e}");
             }
             if !args.continue_on_error {
-                return Err(e);
+                return Err(e).failed_at(Failure::Render);
+            }
+            if let Some(errors) = e.downcast_ref::<RenderErrors>().filter(|_| user_parsed_input.init()) {
+                keep_originals(template_dir, errors)?;
             }
         }
     };
 
+    if let Some(workspace_members) = workspace_members {
+        workspace_members.finish(template_dir)?;
+    }
+
+    features::patch_cargo_toml(
+        template_dir,
+        template_config.features.as_ref(),
+        &template_object,
+    )?;
+
+    // emit the files of all built-in generators the template opted in to
+    generators::execute_generators(
+        config.generators.as_ref(),
+        template_dir,
+        &template_object,
+        &generation.budget,
+    )
+    .failed_at(Failure::Render)?;
+
+    if let Some(postprocess) = template_config.postprocess.as_ref() {
+        postprocess::postprocess(template_dir, postprocess, &all_hook_files, &context)
+            .failed_at(Failure::Hook)?;
+    }
+
     // run post-hooks
-    execute_hooks(&context, &config.get_post_hooks())?;
+    let ignored = execute_hooks(&context, &config.get_post_hooks())?.into_ignored()?;
+    remove_dir_files(ignored.iter().map(|file| template_dir.join(file)), args.verbose);
 
     // remove all hook and filter files as they are never part of the template output
     let rhai_filter_files = rhai_filter_files
         .lock()
         .unwrap()
         .iter()
-        .cloned()
+        .map(|file| template_dir.join(file))
         .collect::<Vec<_>>();
     remove_dir_files(
         all_hook_files
             .into_iter()
-            .map(PathBuf::from)
+            .map(|file| template_dir.join(file))
             .chain(rhai_filter_files),
         false,
     );
 
+    if let Some(locked_template) = locked_template {
+        lockfile::write(template_dir, locked_template, &template_object, config)?;
+    }
+
     config.template.replace(template_config);
-    Ok(destination.as_ref().to_owned())
+    Ok((destination.as_ref().to_owned(), template_object))
 }
 
 /// Try to add all provided `template_values` to the `template_object`.
@@ -591,7 +1332,18 @@ pub(crate) fn add_missing_provided_values(
     template_values.iter().try_for_each(|(k, v)| {
         let map = template_object.lock().unwrap();
         let borrowed = map.borrow();
-        if borrowed.contains_key(k.as_str()) {
+        if let Some(current) = project_variables::nested_value(&borrowed, k) {
+            if project_variables::is_protected(k)
+                && serde_json::to_value(v).ok().as_ref() != Some(current)
+            {
+                warn!(
+                    "{} {} `{}` {}",
+                    emoji::WARN,
+                    style("The value given for the built-in variable").bold(),
+                    style(k).bold().yellow(),
+                    style("is ignored").bold(),
+                );
+            }
             return Ok(());
         }
         drop(borrowed);
@@ -610,11 +1362,11 @@ pub(crate) fn add_missing_provided_values(
                     .red(),
             )),
         };
-        template_object
-            .lock()
-            .unwrap()
-            .borrow_mut()
-            .insert(k.clone(), value);
+        project_variables::set_nested_value(
+            &mut template_object.lock().unwrap().borrow_mut(),
+            k,
+            value,
+        );
         Ok(())
     })?;
     Ok(())
@@ -634,14 +1386,20 @@ fn read_default_variable_value_from_template(slot: &TemplateSlots) -> Result<Str
             } => default.clone(),
             _ => return Err(()),
         },
+        VarInfo::Array { entry } => match &entry.default {
+            Some(default) => default.join(LIST_SEP),
+            None => return Err(()),
+        },
         _ => return Err(()),
     };
     let (key, value) = (&slot.var_name, &default_value);
-    info!(
-        "{} {} (default value from template)",
-        emoji::WRENCH,
-        style(format!("{key}: {value:?}")).bold(),
-    );
+    if !project_variables::is_internal(key) {
+        info!(
+            "{} {} (default value from template)",
+            emoji::WRENCH,
+            style(format!("{key}: {value:?}")).bold(),
+        );
+    }
     Ok(default_value)
 }
 
@@ -666,35 +1424,102 @@ fn extract_toml_string(value: &toml::Value) -> Option<String> {
     }
 }
 
+/// The value given for the placeholder `name`, for a nested name also found in the tables of its
+/// groups, e.g. `host` of `[values.database]` for `database.host`.
+fn provided_template_value<'a>(
+    template_values: &'a HashMap<String, toml::Value>,
+    name: &str,
+) -> Option<&'a toml::Value> {
+    template_values.get(name).or_else(|| {
+        let (group, field) = name.rsplit_once(project_variables::NESTING_SEP)?;
+        provided_template_value(template_values, group)?
+            .as_table()?
+            .get(field)
+    })
+}
+
 // Evaluate the configuration, adding defined placeholder variables to the template object.
 fn fill_placeholders_and_merge_conditionals(
     config: &mut Config,
     template_object: &TemplateObjectResource,
     template_values: &HashMap<String, toml::Value>,
     args: &GenerateArgs,
+    options: &GenerateOptions,
+    generation: &Arc<Generation>,
 ) -> Result<()> {
+    // the platform is known from the start, unlike the values conditionals may depend on
+    for (section, mut target) in targets::matching(config.target.take())? {
+        merge_conditional(config, &mut target, &section);
+    }
     let mut conditionals = config.conditional.take().unwrap_or_default();
+    let features = config
+        .template
+        .as_ref()
+        .and_then(|t| t.features.clone())
+        .unwrap_or_default();
+    let mut applied = HashSet::new();
+    let mut remembered = RememberedAnswers::load(&app_config_path(&args.config)?)?;
+    if let Some(previous_answers) = &options.previous_answers {
+        remembered.offer(previous_answers.clone());
+    }
+    // the group of the placeholder asked for last, headed by its name
+    let asked_group = RefCell::new(None);
 
     loop {
         // keep evaluating for placeholder variables as long new ones are added.
-        project_variables::fill_project_variables(template_object, config, |slot| {
-            let provided_value = template_values
-                .get(&slot.var_name)
-                .and_then(extract_toml_string);
-            if provided_value.is_none() && args.silent {
-                let default_value = match read_default_variable_value_from_template(slot) {
-                    Ok(string) => string,
-                    Err(()) => {
-                        anyhow::bail!(ConversionError::MissingDefaultValueForPlaceholderVariable {
-                            var_name: slot.var_name.clone()
-                        })
-                    }
+        project_variables::fill_project_variables(
+            template_object,
+            config,
+            &mut remembered,
+            generation,
+            |slot| {
+                let provided_value = provided_template_value(template_values, &slot.var_name)
+                    .and_then(extract_toml_string);
+                if provided_value.is_none() {
+                    slot.resolve_default_from_command(args.allow_commands && !args.no_scripts);
+                }
+                // `--defaults` takes the default of any placeholder having one, only asking for the others
+                let default_value = if provided_value.is_none() && (args.silent || args.defaults) {
+                    read_default_variable_value_from_template(slot).ok()
+                } else {
+                    None
                 };
-                interactive::variable(slot, Some(&default_value))
-            } else {
-                interactive::variable(slot, provided_value.as_ref())
-            }
-        })?;
+                if provided_value.is_none() && default_value.is_none() && args.silent {
+                    anyhow::bail!(ConversionError::MissingDefaultValueForPlaceholderVariable {
+                        var_name: slot.var_name.clone()
+                    })
+                }
+                let asked = provided_value.is_none() && default_value.is_none();
+                if let Some(group) = project_variables::group_of(&slot.var_name).filter(|_| asked) {
+                    if asked_group.borrow().as_deref() != Some(group) {
+                        info!("{} {}", emoji::DIAMOND, style(group).bold());
+                        asked_group.replace(Some(group.to_string()));
+                    }
+                }
+                let timeout = &generation.prompt_timeout;
+                let value = default_value.as_ref().map_or_else(
+                    || {
+                        interactive::provided_variable(
+                            slot,
+                            provided_value.as_ref(),
+                            args.silent,
+                            timeout,
+                        )
+                    },
+                    |default_value| interactive::variable(slot, Some(default_value), timeout),
+                )?;
+                // values given were recorded where they came from
+                if provided_value.is_none() {
+                    let source = if default_value.is_some() {
+                        explain_var::Source::Default
+                    } else {
+                        explain_var::Source::Answer
+                    };
+                    generation.audit.record(&slot.var_name, &value, source);
+                }
+                Ok(value)
+            },
+        )?;
 
         let placeholders_changed = conditionals
             .iter_mut()
@@ -703,54 +1528,97 @@ fn fill_placeholders_and_merge_conditionals(
                 evaluate_script::<bool>(template_object, key)
                     .ok()
                     .filter(|&r| r)
-                    .map(|_| cfg)
+                    .map(|_| (key, cfg))
             })
-            .map(|conditional_template_cfg| {
-                // append the conditional blocks configuration, returning true if any placeholders were added
-                let template_cfg = config.template.get_or_insert_with(TemplateConfig::default);
-                if let Some(mut extras) = conditional_template_cfg.include.take() {
-                    template_cfg
-                        .include
-                        .get_or_insert_with(Vec::default)
-                        .append(&mut extras);
-                }
-                if let Some(mut extras) = conditional_template_cfg.exclude.take() {
-                    template_cfg
-                        .exclude
-                        .get_or_insert_with(Vec::default)
-                        .append(&mut extras);
-                }
-                if let Some(mut extras) = conditional_template_cfg.ignore.take() {
-                    template_cfg
-                        .ignore
-                        .get_or_insert_with(Vec::default)
-                        .append(&mut extras);
-                }
-                if let Some(extra_placeholders) = conditional_template_cfg.placeholders.take() {
-                    match config.placeholders.as_mut() {
-                        Some(placeholders) => {
-                            for (k, v) in extra_placeholders.0 {
-                                placeholders.0.insert(k, v);
-                            }
-                        }
-                        None => {
-                            config.placeholders = Some(extra_placeholders);
-                        }
-                    };
-                    return true;
-                }
-                false
+            .map(|(key, conditional_template_cfg)| {
+                let section = format!("[conditional.'{key}']");
+                merge_conditional(config, conditional_template_cfg, &section)
             })
             .fold(false, |acc, placeholders_changed| {
                 acc | placeholders_changed
             });
 
-        if !placeholders_changed {
+        // the features picked bring their configuration like conditionals do, once each
+        let mut features_changed = false;
+        for name in features::picked(template_object)? {
+            let section = format!("[template.features.{name}]");
+            if let Some(feature) = features.get(&name).filter(|_| applied.insert(name)) {
+                features_changed |=
+                    merge_conditional(config, &mut feature.conditional.clone(), &section);
+            }
+        }
+
+        if !placeholders_changed && !features_changed {
             break;
         }
     }
+    features::ignore_files_not_picked(config, &features, &features::picked(template_object)?);
 
-    Ok(())
+    remembered.save()
+}
+
+/// Appends the configuration of a conditional block that applies, from `section` of the config,
+/// returning true if any placeholders were added.
+fn merge_conditional(
+    config: &mut Config,
+    conditional_template_cfg: &mut ConditionalConfig,
+    section: &str,
+) -> bool {
+    let template_cfg = config.template.get_or_insert_with(TemplateConfig::default);
+    for patterns in [
+        &conditional_template_cfg.include,
+        &conditional_template_cfg.exclude,
+        &conditional_template_cfg.ignore,
+    ] {
+        template_cfg
+            .pattern_sources
+            .add(patterns.iter().flatten(), section);
+    }
+    if let Some(mut extras) = conditional_template_cfg.include.take() {
+        template_cfg
+            .include
+            .get_or_insert_with(Vec::default)
+            .append(&mut extras);
+    }
+    if let Some(mut extras) = conditional_template_cfg.exclude.take() {
+        template_cfg
+            .exclude
+            .get_or_insert_with(Vec::default)
+            .append(&mut extras);
+    }
+    if let Some(mut extras) = conditional_template_cfg.ignore.take() {
+        template_cfg
+            .ignore
+            .get_or_insert_with(Vec::default)
+            .append(&mut extras);
+    }
+    if let Some(extra_placeholders) = conditional_template_cfg.placeholders.take() {
+        match config.placeholders.as_mut() {
+            Some(placeholders) => {
+                for (k, v) in extra_placeholders.0 {
+                    placeholders.0.insert(k, v);
+                }
+            }
+            None => {
+                config.placeholders = Some(extra_placeholders);
+            }
+        };
+        return true;
+    }
+    false
+}
+
+/// Adds the files the hooks asked not to generate to those the template ignores.
+fn ignore_from_hooks(config: &mut Config, mut ignored: Vec<String>) {
+    if ignored.is_empty() {
+        return;
+    }
+    let template_cfg = config.template.get_or_insert_with(TemplateConfig::default);
+    template_cfg.pattern_sources.add(&ignored, "the hooks");
+    template_cfg
+        .ignore
+        .get_or_insert_with(Vec::default)
+        .append(&mut ignored);
 }
 
 fn check_cargo_generate_version(template_config: &Config) -> Result<(), anyhow::Error> {
@@ -976,6 +1844,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn auto_locate_template_shows_the_descriptions_of_the_templates() -> anyhow::Result<()> {
+        let tmp = tmp_dir().unwrap();
+        create_file(
+            &tmp,
+            "cli/cargo-generate.toml",
+            indoc::indoc! {r#"
+                [template]
+                description = "A command line tool"
+            "#},
+        )?;
+        create_file(&tmp, "web/cargo-generate.toml", "")?;
+
+        let actual = auto_locate_template_dir(tmp.path().to_path_buf(), &mut |slots| match &slots
+            .var_info
+        {
+            VarInfo::Bool { .. } | VarInfo::Array { .. } => anyhow::bail!("Wrong prompt type"),
+            VarInfo::String { entry } => {
+                if let StringKind::Choices(choices) = entry.kind.clone() {
+                    assert_eq!(choices, ["cli: A command line tool", "web"]);
+                    assert_eq!(entry.default.as_deref(), Some("cli: A command line tool"));
+                    Ok(choices[0].clone())
+                } else {
+                    anyhow::bail!("Missing choices")
+                }
+            }
+        })?
+        .canonicalize()?;
+        let expected = tmp.path().join("cli").canonicalize()?;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     pub trait PathString {
         fn to_string(&self) -> String;
     }
@@ -0,0 +1,206 @@
+//! Limits to what rendering a template may write, so a broken or malicious template, e.g. an
+//! `each` marker over a list far longer than meant, fails rather than filling the disk.
+//!
+//! The limits are those of the `[limits]` of the application config, generous by default:
+//!
+//! ```toml
+//! [limits]
+//! max_files = 100000          # files of the project
+//! max_bytes = 10737418240     # bytes of all of them, 10 GiB
+//! max_file_bytes = 1073741824 # bytes of any one of them, 1 GiB
+//! ```
+//!
+//! The number of files is checked before anything is rendered, the bytes before each file is
+//! written. The files of the built-in generators are taken from the same budget.
+
+use console::style;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::emoji;
+use crate::preflight::size;
+
+const MAX_FILES: u64 = 100_000;
+const MAX_BYTES: u64 = 10 << 30;
+const MAX_FILE_BYTES: u64 = 1 << 30;
+
+/// The `[limits]` of the application config, each left out taking its default.
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct LimitsConfig {
+    pub max_files: Option<u64>,
+    pub max_bytes: Option<u64>,
+    pub max_file_bytes: Option<u64>,
+}
+
+/// What a generation may still write, within the configured limits.
+#[derive(Debug)]
+pub struct Budget {
+    max_files: u64,
+    max_bytes: u64,
+    max_file_bytes: u64,
+    files: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl Budget {
+    /// The budget of the `limits` of the application config, the defaults if none.
+    pub fn new(limits: Option<LimitsConfig>) -> Self {
+        let limits = limits.unwrap_or_default();
+        Self {
+            max_files: limits.max_files.unwrap_or(MAX_FILES),
+            max_bytes: limits.max_bytes.unwrap_or(MAX_BYTES),
+            max_file_bytes: limits.max_file_bytes.unwrap_or(MAX_FILE_BYTES),
+            files: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// Takes the `files` about to be rendered from the budget, failing if the project would have
+    /// too many.
+    pub fn add_files(&self, files: u64) -> Result<(), LimitExceeded> {
+        let total = self.files.load(Ordering::Relaxed).saturating_add(files);
+        if total > self.max_files {
+            return Err(LimitExceeded::Files {
+                files: total,
+                limit: self.max_files,
+            });
+        }
+        self.files.store(total, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Takes a file written besides those rendered, e.g. by a generator, from the budget, failing
+    /// if there are too many files or it takes too many bytes.
+    pub fn add_file(&self, file: &Path, bytes: u64) -> Result<(), LimitExceeded> {
+        self.add_files(1)?;
+        self.write(file, bytes)
+    }
+
+    /// Takes the `bytes` of the `file` about to be written from the budget, failing if it is too
+    /// large, or leaves too little for it.
+    pub fn write(&self, file: &Path, bytes: u64) -> Result<(), LimitExceeded> {
+        if bytes > self.max_file_bytes {
+            return Err(LimitExceeded::FileBytes {
+                file: file.to_path_buf(),
+                bytes,
+                limit: self.max_file_bytes,
+            });
+        }
+        let total = self.bytes.load(Ordering::Relaxed).saturating_add(bytes);
+        if total > self.max_bytes {
+            return Err(LimitExceeded::Bytes {
+                file: file.to_path_buf(),
+                limit: self.max_bytes,
+            });
+        }
+        self.bytes.store(total, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// A limit rendering would exceed, which stops it at once, whatever `--continue-on-error` says.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LimitExceeded {
+    Files {
+        files: u64,
+        limit: u64,
+    },
+    Bytes {
+        file: PathBuf,
+        limit: u64,
+    },
+    FileBytes {
+        file: PathBuf,
+        bytes: u64,
+        limit: u64,
+    },
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (problem, key) = match self {
+            Self::Files { files, limit } => (
+                format!("The project would have {files} files, more than the limit of {limit}"),
+                "max_files",
+            ),
+            Self::Bytes { file, limit } => (
+                format!(
+                    "Writing `{}` would take the project beyond the limit of {}",
+                    file.display(),
+                    size(*limit)
+                ),
+                "max_bytes",
+            ),
+            Self::FileBytes { file, bytes, limit } => (
+                format!(
+                    "`{}` would take {}, more than the limit of {} for a file",
+                    file.display(),
+                    size(*bytes),
+                    size(*limit)
+                ),
+                "max_file_bytes",
+            ),
+        };
+        write!(
+            f,
+            "{} {}, raise `{}` in the `[limits]` of the config file if it is expected",
+            emoji::ERROR,
+            style(problem).bold().red(),
+            style(key).bold(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_are_taken_from_the_budget() {
+        let budget = Budget {
+            max_files: 2,
+            max_bytes: 100,
+            max_file_bytes: 60,
+            files: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+        };
+
+        assert!(budget.add_files(1).is_ok());
+        assert_eq!(
+            budget.add_files(2),
+            Err(LimitExceeded::Files { files: 3, limit: 2 })
+        );
+        assert!(budget.write(Path::new("a"), 60).is_ok());
+        assert_eq!(
+            budget.write(Path::new("b"), 61),
+            Err(LimitExceeded::FileBytes {
+                file: "b".into(),
+                bytes: 61,
+                limit: 60
+            })
+        );
+        assert!(budget.add_file(Path::new("c"), 40).is_ok());
+        assert_eq!(
+            budget.add_file(Path::new("e"), 0),
+            Err(LimitExceeded::Files { files: 3, limit: 2 })
+        );
+        assert_eq!(
+            budget.write(Path::new("d"), 1),
+            Err(LimitExceeded::Bytes {
+                file: "d".into(),
+                limit: 100
+            })
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! The language of the prompts of placeholders: the one given with `--lang`, else the one of the
+//! locale, from `$LC_ALL`, `$LC_MESSAGES` or `$LANG`.
+//!
+//! Templates serving several languages give the `prompt` and `regex_description` of a placeholder
+//! as a table of texts by language tag:
+//!
+//! ```toml
+//! [placeholders.license]
+//! type = "string"
+//! prompt.en = "License of the project?"
+//! prompt.de = "Lizenz des Projekts?"
+//! ```
+
+use std::{env, sync::Mutex};
+
+static LANG: Mutex<Option<String>> = Mutex::new(None);
+
+/// Asks in the language `lang`, or in the one of the locale if not given.
+pub fn configure(lang: Option<&str>) {
+    let lang = lang.and_then(tag).or_else(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| tag(&locale))
+    });
+    *LANG.lock().unwrap_or_else(|e| e.into_inner()) = lang;
+}
+
+/// The text of `texts` in the language asked in.
+pub fn localized(texts: &toml::Table) -> Option<&toml::Value> {
+    let lang = LANG.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    pick(texts, lang.as_deref())
+}
+
+/// The language tag of a locale, lowercase and without encoding, like `de-at` for `de_AT.UTF-8`,
+/// none for the `C` and `POSIX` locales.
+fn tag(locale: &str) -> Option<String> {
+    let tag = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-")
+        .to_lowercase();
+    (!tag.is_empty() && tag != "c" && tag != "posix").then_some(tag)
+}
+
+/// The text of `texts` for the language `lang`: the one of its tag, else the one of its language
+/// without the region, else the English one, else the first one.
+fn pick<'a>(texts: &'a toml::Table, lang: Option<&str>) -> Option<&'a toml::Value> {
+    let find = |wanted: &str| {
+        texts
+            .iter()
+            .find(|(key, _)| tag(key).as_deref() == Some(wanted))
+            .map(|(_, text)| text)
+    };
+    let language = lang.and_then(|lang| lang.split('-').next());
+    lang.and_then(find)
+        .or_else(|| language.and_then(find))
+        .or_else(|| find("en"))
+        .or_else(|| texts.values().next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locales_are_language_tags() {
+        assert_eq!(tag("de_AT.UTF-8").as_deref(), Some("de-at"));
+        assert_eq!(tag("sr_RS@latin").as_deref(), Some("sr-rs"));
+        assert_eq!(tag("fr").as_deref(), Some("fr"));
+        assert_eq!(tag("C.UTF-8"), None);
+        assert_eq!(tag("POSIX"), None);
+    }
+
+    #[test]
+    fn texts_fall_back_to_the_language_then_english() {
+        let texts: toml::Table = toml::from_str(indoc::indoc! {r#"
+            fr = "Licence ?"
+            en = "License?"
+            de = "Lizenz?"
+            de_AT = "Lizenz, bitte?"
+        "#})
+        .unwrap();
+        let pick = |lang| pick(&texts, lang).and_then(toml::Value::as_str);
+
+        assert_eq!(pick(Some("de-at")), Some("Lizenz, bitte?"));
+        assert_eq!(pick(Some("de-ch")), Some("Lizenz?"));
+        assert_eq!(pick(Some("ja")), Some("License?"));
+        assert_eq!(pick(None), Some("License?"));
+
+        let texts: toml::Table = toml::from_str(r#"fr = "Licence ?""#).unwrap();
+        assert_eq!(
+            super::pick(&texts, Some("de")).and_then(toml::Value::as_str),
+            Some("Licence ?")
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! The `.cargo-generate.lock` written into generated projects with `--lockfile`, recording where
+//! a project came from so it can be regenerated from the same template later on, and read back
+//! by `info`.
+//!
+//! Nothing in it leaves the machine, and it holds no answers, only a hash over them.
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{
+    config::Config,
+    emoji,
+    hooks::PoisonError,
+    project_variables::nested_value,
+    template::TemplateObjectResource,
+    user_parsed_input::{TemplateLocation, UserParsedInput},
+    InfoArgs,
+};
+
+pub const LOCK_FILE_NAME: &str = ".cargo-generate.lock";
+
+/// The provenance of a generated project.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GenerationLock {
+    /// The version of cargo-generate the project was generated with
+    pub tool_version: String,
+    /// When the project was generated, like `2024-05-06T07:08:09Z`
+    pub generated_at: String,
+    /// A hash over the names and values of all placeholders, telling whether a project was
+    /// generated with the same answers without recording them
+    pub placeholders_hash: String,
+    pub template: LockedTemplate,
+}
+
+/// Where the template of a generated project was taken from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct LockedTemplate {
+    /// The git URL or the path of the template
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subfolder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// The commit the template was checked out at, if it came from a git repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+impl LockedTemplate {
+    pub fn new(user_parsed_input: &UserParsedInput, commit: Option<String>) -> Self {
+        let (source, branch, tag) = match user_parsed_input.location() {
+            TemplateLocation::Git(git) => (
+                git.url().to_string(),
+                git.branch().map(str::to_string),
+                git.tag().map(str::to_string),
+            ),
+            TemplateLocation::Path(path) => (path.display().to_string(), None, None),
+            TemplateLocation::Fetched(url) => (url.clone(), None, None),
+        };
+        Self {
+            source,
+            subfolder: user_parsed_input.subfolder().map(str::to_string),
+            branch,
+            tag,
+            commit,
+        }
+    }
+}
+
+impl GenerationLock {
+    /// Reads the lock file of the project in `project_dir`.
+    pub fn read(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(LOCK_FILE_NAME);
+        if !path.exists() {
+            bail!(
+                "{} {} `{}`, {}",
+                emoji::ERROR,
+                style("There is no").bold().red(),
+                style(path.display()).bold(),
+                style("the project was generated without `--lockfile`")
+                    .bold()
+                    .red(),
+            );
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed reading `{}`", path.display()))?;
+        Ok(toml::from_str(&contents)?)
+    }
+}
+
+/// Tells where the project in `args.path` came from, as its lock file records it.
+pub fn info(args: &InfoArgs) -> Result<()> {
+    let lock = GenerationLock::read(&args.path)?;
+    let template = &lock.template;
+    let facts = [
+        ("Template", Some(&template.source)),
+        ("Subfolder", template.subfolder.as_ref()),
+        ("Branch", template.branch.as_ref()),
+        ("Tag", template.tag.as_ref()),
+        ("Commit", template.commit.as_ref()),
+        (
+            "Generated at",
+            Some(&lock.generated_at).filter(|at| !at.is_empty()),
+        ),
+        ("Generated with", Some(&lock.tool_version)),
+        ("Placeholders hash", Some(&lock.placeholders_hash)),
+    ];
+    for (fact, value) in facts {
+        if let Some(value) = value {
+            info!("{} {value}", style(format!("{fact}:")).bold());
+        }
+    }
+    if lock.tool_version != env!("CARGO_PKG_VERSION") {
+        warn!(
+            "{} {}, {} {}",
+            style("The project was generated with cargo-generate").bold(),
+            style(&lock.tool_version).bold().yellow(),
+            style("this is").bold(),
+            style(env!("CARGO_PKG_VERSION")).bold().yellow(),
+        );
+    }
+    Ok(())
+}
+
+/// Writes the lock file of the project expanded in `template_dir`, taking the placeholders of
+/// `config` and their values in `template_object`.
+pub fn write(
+    template_dir: &Path,
+    template: LockedTemplate,
+    template_object: &TemplateObjectResource,
+    config: &Config,
+) -> Result<()> {
+    let (generated_at, placeholders) = {
+        let ref_cell = template_object.lock().map_err(|_| PoisonError)?;
+        let object = ref_cell.borrow();
+        let generated_at = object
+            .get("current_datetime")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let placeholders = config
+            .placeholders
+            .iter()
+            .flat_map(|placeholders| placeholders.0.keys())
+            .map(|name| (name.as_str(), nested_value(&object, name).cloned()))
+            .collect::<BTreeMap<_, _>>();
+        (generated_at, placeholders)
+    };
+
+    let lock = GenerationLock {
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        generated_at,
+        placeholders_hash: placeholders_hash(&placeholders)?,
+        template,
+    };
+    fs::write(template_dir.join(LOCK_FILE_NAME), toml::to_string(&lock)?)?;
+    Ok(())
+}
+
+/// The git object id of the placeholders as JSON, which is stable across runs and versions.
+fn placeholders_hash(placeholders: &BTreeMap<&str, Option<serde_json::Value>>) -> Result<String> {
+    let json = serde_json::to_vec(placeholders)?;
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", json.len()));
+    hasher.update(&json);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_placeholders_hash_is_stable_and_depends_on_the_values() {
+        let hash = |value: &str| {
+            placeholders_hash(&BTreeMap::from([
+                ("a", Some(serde_json::Value::from(value))),
+                ("b", None),
+            ]))
+            .unwrap()
+        };
+
+        assert_eq!(hash("x"), hash("x"));
+        assert_ne!(hash("x"), hash("y"));
+        assert_eq!(hash("x").len(), 40);
+        // the same as `printf '{}' | git hash-object --stdin`
+        assert_eq!(
+            placeholders_hash(&BTreeMap::new()).unwrap(),
+            "9e26dfeeb6e641a33dae4961196235bdb965b21b"
+        );
+    }
+}
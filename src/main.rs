@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use cargo_generate::{generate, list_favorites, Cli};
+use cargo_generate::{
+    catch_ctrl_c, eval, exit_code, generate, info, list_favorites, migrate_config, package,
+    publish, render, undo, Cli, Command,
+};
 use clap::Parser;
 
 fn main() -> Result<()> {
@@ -17,11 +20,33 @@ fn main() -> Result<()> {
         .init();
 
     let args = resolve_args();
+    catch_ctrl_c();
 
-    if args.list_favorites {
+    if let Some(command) = &args.command {
+        match command {
+            Command::Publish(publish_args) => publish(publish_args)?,
+            Command::Package(package_args) => {
+                package(package_args)?;
+            }
+            Command::Undo(undo_args) => undo(undo_args)?,
+            Command::Info(info_args) => info(info_args)?,
+            Command::Render(render_args) => {
+                // nothing but the file goes to stdout
+                log::set_max_level(log::LevelFilter::Error);
+                render(render_args)?;
+            }
+            Command::Eval(eval_args) => {
+                log::set_max_level(log::LevelFilter::Error);
+                eval(eval_args)?;
+            }
+            Command::MigrateConfig(migrate_args) => migrate_config(migrate_args)?,
+        }
+    } else if args.list_favorites {
         list_favorites(&args)?;
-    } else {
-        generate(args)?;
+    } else if let Err(error) = generate(args) {
+        // as returning the error would, but telling what went wrong by the exit code too
+        eprintln!("Error: {error:?}");
+        std::process::exit(exit_code(&error));
     }
 
     Ok(())
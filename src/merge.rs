@@ -0,0 +1,380 @@
+//! Merging generated files into files that already exist at the destination.
+//!
+//! Templates meant to be used with `--init` can declare, per glob, how a generated file is
+//! combined with an existing one instead of conflicting with it:
+//!
+//! ```toml
+//! [template.merge]
+//! "Cargo.toml" = "merge-toml"
+//! ".gitignore" = "append"
+//! ```
+
+use anyhow::Result;
+use console::style;
+use fs_err as fs;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use indexmap::IndexMap;
+use log::debug;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+use crate::emoji;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// Add the generated content at the end of the existing file
+    Append,
+    /// Add the generated content at the beginning of the existing file
+    Prepend,
+    /// Add tables, keys and array items missing from the existing TOML document, keeping its
+    /// comments and formatting
+    MergeToml,
+    /// Add keys missing from the existing JSON document
+    MergeJson,
+    /// Keep the existing file as it is
+    SkipIfExists,
+}
+
+/// How the files of an [overlay](crate::config::TemplateKind::Overlay) template are combined with
+/// those of the existing project, unless the template has a merge strategy of its own: known file
+/// types are merged, other existing files are kept as they are.
+pub const OVERLAY_MERGE: [(&str, MergeStrategy); 4] = [
+    ("*.toml", MergeStrategy::MergeToml),
+    ("*.json", MergeStrategy::MergeJson),
+    (".gitignore", MergeStrategy::Append),
+    ("*", MergeStrategy::SkipIfExists),
+];
+
+/// The merge strategies of a template, matched against the path of a file relative to the
+/// project root. The first matching glob wins.
+#[derive(Default)]
+pub struct MergeRules {
+    root: PathBuf,
+    rules: Vec<(Gitignore, MergeStrategy)>,
+}
+
+impl MergeRules {
+    pub fn new(root: &Path, merge: Option<&IndexMap<String, MergeStrategy>>) -> Result<Self> {
+        let mut rules = vec![];
+        for (glob, strategy) in merge.into_iter().flatten() {
+            let mut builder = GitignoreBuilder::new(root);
+            builder.add_line(None, glob)?;
+            rules.push((builder.build()?, *strategy));
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            rules,
+        })
+    }
+
+    pub fn strategy_for(&self, dst_path: &Path) -> Option<MergeStrategy> {
+        let relative_path = dst_path.strip_prefix(&self.root).ok()?;
+        self.rules
+            .iter()
+            .find(|(matcher, _)| {
+                matcher
+                    .matched_path_or_any_parents(relative_path, /* is_dir */ false)
+                    .is_ignore()
+            })
+            .map(|(_, strategy)| *strategy)
+    }
+}
+
+/// Combines the generated file at `src_path` with the existing file at `dst_path`.
+pub fn merge_file(strategy: MergeStrategy, src_path: &Path, dst_path: &Path) -> Result<()> {
+    debug!(
+        "Merging {} into {} ({strategy:?})",
+        src_path.display(),
+        dst_path.display()
+    );
+    if strategy == MergeStrategy::SkipIfExists {
+        return Ok(());
+    }
+
+    let generated = fs::read_to_string(src_path)?;
+    let existing = fs::read_to_string(dst_path)?;
+    let merged = match strategy {
+        // generating into the same folder twice must not duplicate anything
+        MergeStrategy::Append | MergeStrategy::Prepend if existing.contains(generated.trim()) => {
+            existing
+        }
+        MergeStrategy::Append => concat(&existing, &generated),
+        MergeStrategy::Prepend => concat(&generated, &existing),
+        MergeStrategy::MergeToml => merge_toml(&existing, &generated, dst_path)?,
+        MergeStrategy::MergeJson => merge_json(&existing, &generated, dst_path)?,
+        MergeStrategy::SkipIfExists => unreachable!(),
+    };
+
+    fs::write(dst_path, merged)?;
+    Ok(())
+}
+
+/// Joins two texts on a line boundary.
+fn concat(first: &str, second: &str) -> String {
+    let mut joined = first.to_string();
+    if !joined.is_empty() && !joined.ends_with('\n') {
+        joined.push('\n');
+    }
+    joined.push_str(second);
+    joined
+}
+
+/// Adds the tables, keys and array items of `generated` missing from `existing`, leaving the rest
+/// of the existing document as it is, comments and formatting included.
+fn merge_toml(existing: &str, generated: &str, dst_path: &Path) -> Result<String> {
+    fn merge(existing: &mut dyn TableLike, generated: &dyn TableLike) {
+        for (key, item) in generated.iter() {
+            let Some(existing) = existing.get_mut(key) else {
+                existing.insert(key, detached(item));
+                continue;
+            };
+            if let (Some(existing), Some(generated)) =
+                (existing.as_table_like_mut(), item.as_table_like())
+            {
+                merge(existing, generated);
+            } else if let (Some(existing), Some(generated)) =
+                (existing.as_array_mut(), item.as_array())
+            {
+                for value in generated {
+                    if !existing.iter().any(|known| same_value(known, value)) {
+                        let mut value = value.clone();
+                        value.decor_mut().clear();
+                        existing.push(value);
+                    }
+                }
+            }
+            // values the project already has are left alone
+        }
+    }
+
+    let parse = |contents: &str| {
+        contents
+            .parse::<DocumentMut>()
+            .map_err(|e| unparsable(dst_path, "TOML", e))
+    };
+    let mut merged = parse(existing)?;
+    merge(merged.as_table_mut(), parse(generated)?.as_table());
+    Ok(merged.to_string())
+}
+
+/// `item` of another document, its tables placed after those of the document it is added to.
+pub fn detached(item: &Item) -> Item {
+    let mut item = item.clone();
+    if let Some(table) = item.as_table_mut() {
+        detach(table);
+    }
+    item
+}
+
+fn detach(table: &mut Table) {
+    table.set_position(isize::MAX);
+    for (_, item) in table.iter_mut() {
+        if let Some(table) = item.as_table_mut() {
+            detach(table);
+        }
+    }
+}
+
+/// Whether two TOML values are the same, however they are written.
+pub fn same_value(a: &Value, b: &Value) -> bool {
+    let parse = |value: &Value| {
+        toml::from_str::<toml::Table>(&format!("value = {}", value.clone().decorated("", ""))).ok()
+    };
+    parse(a) == parse(b)
+}
+
+fn merge_json(existing: &str, generated: &str, dst_path: &Path) -> Result<String> {
+    use serde_json::Value;
+
+    fn merge(existing: &mut Value, generated: Value) {
+        match (existing, generated) {
+            (Value::Object(existing), Value::Object(generated)) => {
+                for (key, value) in generated {
+                    match existing.get_mut(&key) {
+                        Some(existing) => merge(existing, value),
+                        None => {
+                            existing.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (Value::Array(existing), Value::Array(generated)) => {
+                for item in generated {
+                    if !existing.contains(&item) {
+                        existing.push(item);
+                    }
+                }
+            }
+            // values the project already has are left alone
+            _ => {}
+        }
+    }
+
+    let parse = |contents: &str| {
+        serde_json::from_str::<Value>(contents).map_err(|e| unparsable(dst_path, "JSON", e))
+    };
+    let mut merged = parse(existing)?;
+    merge(&mut merged, parse(generated)?);
+    Ok(format!("{}\n", serde_json::to_string_pretty(&merged)?))
+}
+
+fn unparsable(path: &Path, format: &str, e: impl std::fmt::Display) -> anyhow::Error {
+    anyhow::anyhow!(
+        "{} {} `{}`: {e}",
+        emoji::ERROR,
+        style(format!("Cannot merge, not valid {format}:"))
+            .bold()
+            .red(),
+        style(path.display()).bold(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn merge_files(strategy: MergeStrategy, existing: &str, generated: &str) -> String {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("src");
+        let dst = tmp.path().join("dst");
+        std::fs::write(&src, generated).unwrap();
+        std::fs::write(&dst, existing).unwrap();
+
+        merge_file(strategy, &src, &dst).unwrap();
+        std::fs::read_to_string(dst).unwrap()
+    }
+
+    #[test]
+    fn first_matching_glob_wins() {
+        let mut merge = IndexMap::new();
+        merge.insert("src/*.rs".to_string(), MergeStrategy::SkipIfExists);
+        merge.insert("*.rs".to_string(), MergeStrategy::Append);
+        let rules = MergeRules::new(Path::new("/project"), Some(&merge)).unwrap();
+
+        assert_eq!(
+            rules.strategy_for(Path::new("/project/src/main.rs")),
+            Some(MergeStrategy::SkipIfExists)
+        );
+        assert_eq!(
+            rules.strategy_for(Path::new("/project/build.rs")),
+            Some(MergeStrategy::Append)
+        );
+        assert_eq!(rules.strategy_for(Path::new("/project/Cargo.toml")), None);
+    }
+
+    #[test]
+    fn append_and_prepend_join_on_a_line_boundary() {
+        assert_eq!(
+            merge_files(MergeStrategy::Append, "target", "*.swp\n"),
+            "target\n*.swp\n"
+        );
+        assert_eq!(
+            merge_files(MergeStrategy::Prepend, "fn main() {}\n", "// header\n"),
+            "// header\nfn main() {}\n"
+        );
+        assert_eq!(
+            merge_files(MergeStrategy::Append, "target\n*.swp\n", "*.swp\n"),
+            "target\n*.swp\n",
+            "content that is already there is not added twice"
+        );
+    }
+
+    #[test]
+    fn merge_toml_keeps_existing_values() {
+        let merged = merge_files(
+            MergeStrategy::MergeToml,
+            "[package]\nname = \"mine\"\n\n[dependencies]\nanyhow = \"1\"\n",
+            "[package]\nname = \"template\"\nedition = \"2021\"\n\n[dependencies]\nserde = \"1\"\n",
+        );
+        let merged: toml::Table = toml::from_str(&merged).unwrap();
+
+        assert_eq!(merged["package"]["name"].as_str(), Some("mine"));
+        assert_eq!(merged["package"]["edition"].as_str(), Some("2021"));
+        assert_eq!(merged["dependencies"]["anyhow"].as_str(), Some("1"));
+        assert_eq!(merged["dependencies"]["serde"].as_str(), Some("1"));
+    }
+
+    #[test]
+    fn merge_toml_keeps_comments_and_formatting() {
+        let existing = indoc::indoc! {r#"
+            # my project
+            [package]
+            name = "mine"   # the name I picked
+
+            [dependencies]
+            anyhow = "1" # errors
+            features = ["a"]
+        "#};
+        let merged = merge_files(
+            MergeStrategy::MergeToml,
+            existing,
+            indoc::indoc! {r#"
+                [package]
+                name = "template"
+                edition = "2021"
+
+                [dependencies]
+                features = ["a", "b"]
+                serde = "1"
+
+                [dev-dependencies]
+                tempfile = "3"
+
+                [dev-dependencies.indoc]
+                version = "2"
+            "#},
+        );
+
+        assert_eq!(
+            merged,
+            indoc::indoc! {r#"
+                # my project
+                [package]
+                name = "mine"   # the name I picked
+                edition = "2021"
+
+                [dependencies]
+                anyhow = "1" # errors
+                features = ["a", "b"]
+                serde = "1"
+
+                [dev-dependencies]
+                tempfile = "3"
+
+                [dev-dependencies.indoc]
+                version = "2"
+            "#}
+        );
+    }
+
+    #[test]
+    fn merge_json_adds_missing_keys_and_array_items() {
+        let merged = merge_files(
+            MergeStrategy::MergeJson,
+            r#"{"name": "mine", "extensions": ["a"]}"#,
+            r#"{"name": "template", "extensions": ["a", "b"], "image": "rust"}"#,
+        );
+        let merged: serde_json::Value = serde_json::from_str(&merged).unwrap();
+
+        assert_eq!(
+            merged,
+            serde_json::json!({"name": "mine", "extensions": ["a", "b"], "image": "rust"})
+        );
+    }
+
+    #[test]
+    fn invalid_documents_are_reported() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("src");
+        let dst = tmp.path().join("dst");
+        std::fs::write(&src, "[package]").unwrap();
+        std::fs::write(&dst, "not toml").unwrap();
+
+        assert!(merge_file(MergeStrategy::MergeToml, &src, &dst).is_err());
+        assert_eq!(std::fs::read_to_string(dst).unwrap(), "not toml");
+    }
+}
@@ -0,0 +1,273 @@
+//! `migrate-config`: rewriting a `cargo-generate.toml` written for an older `config_version` to the
+//! current one.
+//!
+//! Configs without a `config_version` are of version 1, written when templates were rendered with
+//! Liquid. They still work, as they are upgraded when read, which the upgrade makes permanent:
+//! the Liquid syntax within the tags of their strings is rewritten to minijinja, `project-name`
+//! becoming `project_name`, `upcase` becoming `upper` and `| truncate: 10` becoming
+//! `| truncate(10)`. All else is kept as it is, comments included.
+
+use anyhow::{bail, Result};
+use console::style;
+use fs_err as fs;
+use log::{info, warn};
+use regex::{Captures, Regex};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use crate::config::CONFIG_FILE_NAME;
+use crate::{emoji, MigrateConfigArgs};
+
+/// The version of the configs written for this version of cargo-generate.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// The configs warned about being upgraded, as they are read more than once.
+static UPGRADED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Matches a tag, with its delimiters, the content being the second group.
+static TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)(\{\{-?|\{%-?)(.*?)(-?\}\}|-?%\})").unwrap());
+
+/// Matches a string literal within a tag, its quotes escaped if the TOML string is quoted alike.
+static LITERAL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\\"(?:[^"\\]|\\[^"])*\\"|"[^"]*"|'[^']*'"#).unwrap());
+
+/// Matches a name with hyphens, which minijinja reads as a subtraction.
+static HYPHENATED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^\w.])([A-Za-z_]\w*(?:-[A-Za-z_]\w*)+)").unwrap());
+
+/// Matches a filter given its arguments after a colon, up to the next filter.
+static COLON_ARGUMENTS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\|\s*(\w+)\s*:\s*([^|]*[^|\s])").unwrap());
+
+static FILTER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\|\s*(\w+)").unwrap());
+
+static LIQUID_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*)(assign|unless|endunless)\b(.*)$").unwrap());
+
+static VERSION_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^config_version\s*=\s*\d+[ \t]*$").unwrap());
+
+static FIRST_TABLE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^[ \t]*\[").unwrap());
+
+/// Liquid filters and what minijinja calls them.
+const RENAMED_FILTERS: [(&str, &str); 4] = [
+    ("upcase", "upper"),
+    ("downcase", "lower"),
+    ("strip", "trim"),
+    ("size", "length"),
+];
+
+/// The version of the config `contents`, 1 if it has none.
+pub fn version(contents: &toml::Table) -> Result<u32> {
+    let Some(version) = contents.get("config_version") else {
+        return Ok(1);
+    };
+    match version.as_integer().and_then(|v| u32::try_from(v).ok()) {
+        Some(version @ 1..=CONFIG_VERSION) => Ok(version),
+        Some(version) if version > CONFIG_VERSION => bail!(
+            "{} {} {}{}",
+            emoji::ERROR,
+            style(format!(
+                "The template config is of version {version}, while this"
+            ))
+            .bold()
+            .red(),
+            style(format!(
+                "cargo-generate reads versions up to {CONFIG_VERSION}"
+            ))
+            .bold()
+            .red(),
+            style(", update cargo-generate to generate it").bold().red(),
+        ),
+        _ => bail!(
+            "{} {} `{version}`",
+            emoji::ERROR,
+            style("Invalid `config_version` of the template config:")
+                .bold()
+                .red(),
+        ),
+    }
+}
+
+/// The config `contents` of version 1 with its Liquid syntax rewritten to minijinja.
+pub fn upgrade(contents: &str) -> String {
+    TAG.replace_all(contents, |tag: &Captures| {
+        let statement = tag[1].starts_with("{%");
+        format!("{}{}{}", &tag[1], upgrade_tag(&tag[2], statement), &tag[3])
+    })
+    .into_owned()
+}
+
+/// Warns, once, that the config at `path` was upgraded when read.
+pub fn warn_upgraded(path: &Path) {
+    let mut upgraded = UPGRADED.lock().unwrap_or_else(|e| e.into_inner());
+    if upgraded.iter().any(|upgraded| upgraded == path) {
+        return;
+    }
+    upgraded.push(path.to_path_buf());
+    warn!(
+        "{} {} `{}`{}",
+        emoji::WARN,
+        style("Read the Liquid syntax of").bold(),
+        style(path.display()).bold().yellow(),
+        style(", run `cargo generate migrate-config` on the template to rewrite it").bold(),
+    );
+}
+
+/// The content of a tag, of a `statement` or an expression, rewritten, leaving its string literals
+/// alone.
+fn upgrade_tag(content: &str, statement: bool) -> String {
+    let mut literals = vec![];
+    // the literals are replaced by markers no rewrite touches, and put back after
+    let code = LITERAL.replace_all(content, |literal: &Captures| {
+        literals.push(literal[0].to_string());
+        format!("\u{0}{}\u{0}", literals.len() - 1)
+    });
+
+    let code = HYPHENATED.replace_all(&code, |name: &Captures| {
+        format!("{}{}", &name[1], name[2].replace('-', "_"))
+    });
+    let code = COLON_ARGUMENTS.replace_all(&code, |filter: &Captures| match &filter[1] {
+        "append" => format!("~ {}", &filter[2]),
+        "remove" => format!("| replace({}, \"\")", &filter[2]),
+        name => format!("| {name}({})", &filter[2]),
+    });
+    let code = FILTER.replace_all(&code, |filter: &Captures| {
+        RENAMED_FILTERS
+            .iter()
+            .find(|(liquid, _)| *liquid == &filter[1])
+            .map_or_else(|| filter[0].to_string(), |(_, name)| format!("| {name}"))
+    });
+    let mut code = code.into_owned();
+    if statement {
+        code = LIQUID_TAG
+            .replace(&code, |tag: &Captures| match &tag[2] {
+                "assign" => format!("{}set{}", &tag[1], &tag[3]),
+                "unless" => format!("{}if not ({}) ", &tag[1], tag[3].trim()),
+                _ => format!("{}endif{}", &tag[1], &tag[3]),
+            })
+            .into_owned();
+    }
+    for (index, literal) in literals.iter().enumerate() {
+        code = code.replace(&format!("\u{0}{index}\u{0}"), literal);
+    }
+    code
+}
+
+/// The config `contents` upgraded to [`CONFIG_VERSION`], declaring it.
+fn migrate(contents: &str) -> String {
+    let upgraded = upgrade(contents);
+    let version = format!("config_version = {CONFIG_VERSION}");
+    if VERSION_LINE.is_match(&upgraded) {
+        return VERSION_LINE.replace(&upgraded, version).into_owned();
+    }
+    // keys of the root table come before all tables
+    FIRST_TABLE.find(&upgraded).map_or_else(
+        || format!("{upgraded}{version}\n"),
+        |table| {
+            let (root, tables) = upgraded.split_at(table.start());
+            format!("{root}{version}\n\n{tables}")
+        },
+    )
+}
+
+/// Rewrites the config of the template at `args.path` to the current version.
+pub fn migrate_config(args: &MigrateConfigArgs) -> Result<()> {
+    let path = if args.path.is_dir() {
+        args.path.join(CONFIG_FILE_NAME)
+    } else {
+        args.path.clone()
+    };
+    let contents = fs::read_to_string(&path)?;
+    let version = version(&toml::from_str(&contents)?)?;
+    if version == CONFIG_VERSION {
+        info!(
+            "{} {} {}",
+            emoji::WRENCH,
+            style(path.display()).bold(),
+            style(format!(
+                "is of the current version {CONFIG_VERSION} already"
+            ))
+            .bold(),
+        );
+        return Ok(());
+    }
+
+    let migrated = migrate(&contents);
+    // nothing is written that doesn't read
+    crate::config::Config::try_from(migrated.clone())?;
+    fs::write(&path, migrated)?;
+    info!(
+        "{} {} {}",
+        emoji::SPARKLE,
+        style(format!(
+            "Migrated {} from version {version} to",
+            path.display()
+        ))
+        .bold(),
+        style(CONFIG_VERSION).bold(),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn liquid_syntax_is_rewritten_within_tags() {
+        assert_eq!(upgrade("{{project-name}}"), "{{project_name}}");
+        assert_eq!(
+            upgrade("{{ crate-name | upcase }}"),
+            "{{ crate_name | upper }}"
+        );
+        assert_eq!(
+            upgrade(r#"{{ name | truncate: 10 | append: "-rs" }}"#),
+            r#"{{ name | truncate(10) ~ "-rs" }}"#
+        );
+        assert_eq!(
+            upgrade(r#"{{ name | replace: "a-b", "c" }}"#),
+            r#"{{ name | replace("a-b", "c") }}"#
+        );
+        assert_eq!(
+            upgrade(r#""{{ \"my-name\" | downcase }}""#),
+            r#""{{ \"my-name\" | lower }}""#
+        );
+        assert_eq!(
+            upgrade("{%- unless is-lib %}bin{% endunless -%}"),
+            "{%- if not (is_lib) %}bin{% endif -%}"
+        );
+        assert_eq!(upgrade("{% assign x = y-z %}"), "{% set x = y_z %}");
+        assert_eq!(
+            upgrade("{{ count - 1 }} keep-me"),
+            "{{ count - 1 }} keep-me"
+        );
+        assert_eq!(upgrade("{{ assign }}"), "{{ assign }}");
+    }
+
+    #[test]
+    fn the_version_is_declared_before_all_tables() {
+        let contents = "# a template\n\n[placeholders]\nname = { type = \"string\", prompt = \"{{project-name}}?\" }\n";
+
+        assert_eq!(
+            migrate(contents),
+            "# a template\n\nconfig_version = 2\n\n[placeholders]\nname = { type = \"string\", prompt = \"{{project_name}}?\" }\n"
+        );
+        assert_eq!(
+            migrate("config_version = 1\n[template]\n"),
+            "config_version = 2\n[template]\n"
+        );
+        assert_eq!(migrate(""), "config_version = 2\n");
+    }
+
+    #[test]
+    fn versions_are_checked() {
+        let table = |contents: &str| toml::from_str::<toml::Table>(contents).unwrap();
+
+        assert_eq!(version(&table("")).unwrap(), 1);
+        assert_eq!(version(&table("config_version = 2")).unwrap(), 2);
+        assert!(version(&table("config_version = 3")).is_err());
+        assert!(version(&table("config_version = \"2\"")).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! `--no-scripts`: nothing the template brings is run, neither its hooks, its postprocessing
+//! scripts and template functions, its `rhai` filters, nor the commands of its defaults, so that
+//! templates nobody reviewed can be generated without running any of their code.
+//!
+//! The Rhai expressions of conditionals are still evaluated, they compute a value and can't do
+//! anything else.
+//!
+//! Whether the scripts are skipped is kept by the generation, see
+//! [`Generation::no_scripts`](crate::generation::Generation::no_scripts).
+
+use console::style;
+use log::warn;
+use regex::Regex;
+use std::{fs, path::Path, sync::LazyLock};
+
+use crate::config::Config;
+use crate::emoji;
+use crate::project_variables;
+
+/// Matches the names of the variables a hook sets, `variable::set("name", value)`.
+static VARIABLE_SET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"variable::set\(\s*"([^"]+)""#).unwrap());
+
+/// Warns which scripts of the template in `template_dir` are skipped if `no_scripts`, and which
+/// variables their hooks set that may be missing now.
+pub fn warn_skipped(no_scripts: bool, template_dir: &Path, config: &Config) {
+    if !no_scripts {
+        return;
+    }
+
+    let scripts = config.get_hook_files();
+    if !scripts.is_empty() {
+        warn!(
+            "{} {} {}",
+            emoji::WARN,
+            style("Not running the scripts of the template (--no-scripts):").bold(),
+            style(scripts.join(", ")).bold().yellow(),
+        );
+    }
+    let missing = missing_variables(template_dir, config);
+    if !missing.is_empty() {
+        warn!(
+            "{} {} {}",
+            emoji::WARN,
+            style("Variables the hooks would set may be missing:").bold(),
+            style(missing.join(", ")).bold().yellow(),
+        );
+    }
+}
+
+/// The variables the hooks set, other than the placeholders asked for anyway.
+fn missing_variables(template_dir: &Path, config: &Config) -> Vec<String> {
+    let hooks = [
+        config.get_init_hooks(),
+        config.get_pre_hooks(),
+        config.get_post_hooks(),
+    ];
+    let mut missing: Vec<String> = vec![];
+    for hook in hooks.iter().flatten() {
+        let Ok(source) = fs::read_to_string(template_dir.join(hook)) else {
+            continue;
+        };
+        for name in variables_set(&source) {
+            let asked_for = config.defines_placeholder(&name)
+                || project_variables::is_project_name(&name);
+            if !asked_for && !missing.contains(&name) {
+                missing.push(name);
+            }
+        }
+    }
+    missing
+}
+
+fn variables_set(source: &str) -> Vec<String> {
+    VARIABLE_SET
+        .captures_iter(source)
+        .map(|cap| cap[1].to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variables_set_by_hooks_are_found() {
+        let source = indoc::indoc! {r#"
+            let name = variable::get("project-name");
+            variable::set("service_port", 8080);
+            variable::set( "license", "MIT");
+            if variable::is_set("db") { print("db"); }
+        "#};
+
+        assert_eq!(variables_set(source), ["service_port", "license"]);
+    }
+}
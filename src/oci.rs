@@ -0,0 +1,550 @@
+//! Templates distributed as OCI artifacts: `publish --oci <reference>` pushes a template to a
+//! registry, and `oci://<reference>` generates from it again.
+//!
+//! An artifact is a manifest with an empty config and a single layer, the template as gzipped
+//! tar archive. Requests are made with `curl`, authenticating with the credentials in
+//! `$CARGO_GENERATE_OCI_USERNAME` and `$CARGO_GENERATE_OCI_PASSWORD` when a registry asks for it.
+
+use anyhow::{anyhow, bail, Context, Result};
+use console::style;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    archive, config::Config, emoji, git::tmp_dir, resume, sha256::sha256_hex, PublishArgs,
+};
+
+pub const ARTIFACT_TYPE: &str = "application/vnd.cargo-generate.template.v1";
+const LAYER_MEDIA_TYPE: &str = "application/vnd.cargo-generate.template.v1.tar+gzip";
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+const EMPTY_CONFIG_MEDIA_TYPE: &str = "application/vnd.oci.empty.v1+json";
+const EMPTY_CONFIG: &[u8] = b"{}";
+
+pub const USERNAME_ENV: &str = "CARGO_GENERATE_OCI_USERNAME";
+pub const PASSWORD_ENV: &str = "CARGO_GENERATE_OCI_PASSWORD";
+
+/// Names an artifact, like `registry.example.com/templates/api:1.2.0` or
+/// `registry.example.com/templates/api@sha256:...`, optionally prefixed with `oci://`.
+#[derive(Debug, PartialEq, Eq)]
+struct Reference {
+    registry: String,
+    repository: String,
+    /// The tag, or the digest of the manifest
+    tag: String,
+}
+
+impl Reference {
+    fn parse(reference: &str) -> Result<Self> {
+        let reference = reference.strip_prefix("oci://").unwrap_or(reference);
+        let Some((registry, rest)) = reference.split_once('/') else {
+            bail!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("An OCI reference needs a registry and a repository, like `registry.example.com/templates/api:1.0`, but got").bold().red(),
+                style(reference).bold(),
+            );
+        };
+        let (repository, tag) = match rest.split_once('@') {
+            Some((repository, digest)) => (repository, digest),
+            None => match rest.rsplit_once(':') {
+                Some((repository, tag)) if !tag.contains('/') => (repository, tag),
+                _ => (rest, "latest"),
+            },
+        };
+        Ok(Self {
+            registry: registry.to_string(),
+            repository: repository.to_string(),
+            tag: tag.to_string(),
+        })
+    }
+
+    /// The base URL of the API for the repository, plain HTTP for registries on this machine.
+    fn repository_url(&self) -> String {
+        let host = self
+            .registry
+            .rsplit_once(':')
+            .map_or(&*self.registry, |(host, _)| host);
+        let scheme = if matches!(host, "localhost" | "127.0.0.1" | "[::1]") {
+            "http"
+        } else {
+            "https"
+        };
+        format!("{scheme}://{}/v2/{}", self.registry, self.repository)
+    }
+}
+
+impl std::fmt::Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let separator = if self.tag.contains(':') { '@' } else { ':' };
+        write!(
+            f,
+            "{}/{}{separator}{}",
+            self.registry, self.repository, self.tag
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    media_type: String,
+    digest: String,
+    size: usize,
+}
+
+impl Descriptor {
+    fn of(media_type: &str, data: &[u8]) -> Self {
+        Self {
+            media_type: media_type.to_string(),
+            digest: format!("sha256:{}", sha256_hex(data)),
+            size: data.len(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    schema_version: u32,
+    #[serde(default)]
+    media_type: Option<String>,
+    #[serde(default)]
+    artifact_type: Option<String>,
+    config: Descriptor,
+    layers: Vec<Descriptor>,
+}
+
+/// A response from `curl`.
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    const fn is_success(&self) -> bool {
+        self.status >= 200 && self.status < 300
+    }
+
+    /// Fails unless the request succeeded, telling what was tried.
+    fn expect_success(self, what: &str, reference: &Reference) -> Result<Self> {
+        if !self.is_success() {
+            let error = anyhow!(
+                "{} {} `{}`: HTTP {} {}",
+                emoji::ERROR,
+                style(format!("Failed to {what}")).bold().red(),
+                style(reference).bold(),
+                self.status,
+                String::from_utf8_lossy(&self.body).trim(),
+            );
+            return Err(resume::transient_if(
+                resume::is_transient_status(self.status),
+                error,
+            ));
+        }
+        Ok(self)
+    }
+}
+
+/// The exit codes of `curl` failing to reach the server: resolving it or a proxy, connecting,
+/// timing out, or the connection breaking off.
+const CURL_UNREACHABLE: [i32; 7] = [5, 6, 7, 28, 52, 55, 56];
+
+/// A request made with `curl`, which gets all its options through its stdin, keeping any
+/// credentials out of the process list.
+struct Request {
+    options: Vec<(&'static str, Option<String>)>,
+}
+
+impl Request {
+    fn new(method: &str, url: &str) -> Self {
+        Self {
+            options: vec![
+                ("request", Some(method.to_string())),
+                ("url", Some(url.to_string())),
+            ],
+        }
+    }
+
+    fn option(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.options.push((name, Some(value.into())));
+        self
+    }
+
+    fn flag(mut self, name: &'static str) -> Self {
+        self.options.push((name, None));
+        self
+    }
+
+    fn header(self, header: impl Into<String>) -> Self {
+        self.option("header", header)
+    }
+
+    fn send(mut self) -> Result<Response> {
+        let dir = tmp_dir()?;
+        let headers = dir.path().join("headers");
+        let body = dir.path().join("body");
+        for (name, value) in [
+            ("silent", None),
+            ("show-error", None),
+            ("location", None),
+            ("dump-header", Some(headers.display().to_string())),
+            ("output", Some(body.display().to_string())),
+            ("write-out", Some("%{http_code}".to_string())),
+        ] {
+            self.options.push((name, value));
+        }
+        let config = self
+            .options
+            .iter()
+            .map(|(name, value)| {
+                value.as_ref().map_or_else(
+                    || format!("{name}\n"),
+                    |value| {
+                        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+                        format!("{name} = \"{value}\"\n")
+                    },
+                )
+            })
+            .collect::<String>();
+
+        let mut child = Command::new("curl")
+            .args(["--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run `curl`, is it installed?")?;
+        child
+            .stdin
+            .take()
+            .expect("stdin of curl is piped")
+            .write_all(config.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let error = anyhow!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("OCI registry error:").bold().red(),
+                String::from_utf8_lossy(&output.stderr).trim(),
+            );
+            let unreachable = output
+                .status
+                .code()
+                .is_some_and(|code| CURL_UNREACHABLE.contains(&code));
+            return Err(resume::transient_if(unreachable, error));
+        }
+
+        let status = String::from_utf8_lossy(&output.stdout).trim().parse()?;
+        let headers = std::fs::read_to_string(&headers).unwrap_or_default();
+        // after redirects, the headers of the last response count
+        let headers = headers
+            .rsplit("HTTP/")
+            .next()
+            .unwrap_or_default()
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Ok(Response {
+            status,
+            headers,
+            body: std::fs::read(&body).unwrap_or_default(),
+        })
+    }
+}
+
+enum Authorization {
+    Basic,
+    Bearer(String),
+}
+
+/// The parameters of a `WWW-Authenticate` challenge, like `Bearer realm="...",scope="..."`.
+fn parse_challenge(challenge: &str) -> (String, Vec<(String, String)>) {
+    let (scheme, params) = challenge.trim().split_once(' ').unwrap_or((challenge, ""));
+    let mut parsed = vec![];
+    let mut rest = params.trim();
+    while let Some((key, value)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_string();
+        let (value, remainder) = value.strip_prefix('"').map_or_else(
+            || value.split_once(',').unwrap_or((value, "")),
+            |quoted| quoted.split_once('"').unwrap_or((quoted, "")),
+        );
+        parsed.push((key, value.to_string()));
+        rest = remainder.trim_start_matches(',').trim();
+    }
+    (scheme.to_string(), parsed)
+}
+
+/// A repository of a registry, remembering how it wants requests to be authorized.
+struct Registry {
+    repository_url: String,
+    credentials: Option<String>,
+    authorization: Option<Authorization>,
+}
+
+impl Registry {
+    fn new(reference: &Reference) -> Self {
+        let credentials = env::var(USERNAME_ENV)
+            .ok()
+            .map(|user| format!("{user}:{}", env::var(PASSWORD_ENV).unwrap_or_default()));
+        Self {
+            repository_url: reference.repository_url(),
+            credentials,
+            authorization: None,
+        }
+    }
+
+    /// The URL of the API `path` of the repository, or of the `Location` the registry sent.
+    fn url(&self, path: &str) -> String {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            path.to_string()
+        } else if path.starts_with('/') {
+            let origin_end = self.repository_url.find("/v2/").unwrap_or_default();
+            format!("{}{path}", &self.repository_url[..origin_end])
+        } else {
+            format!("{}/{path}", self.repository_url)
+        }
+    }
+
+    fn authorize(&self, request: Request) -> Request {
+        match (&self.authorization, &self.credentials) {
+            (Some(Authorization::Bearer(token)), _) => {
+                request.header(format!("Authorization: Bearer {token}"))
+            }
+            (Some(Authorization::Basic), Some(credentials)) => {
+                request.option("user", credentials.clone())
+            }
+            _ => request,
+        }
+    }
+
+    /// Sends the request made by `request`, authenticating and sending it again if the registry
+    /// asks for it.
+    fn send(&mut self, request: impl Fn() -> Request) -> Result<Response> {
+        let response = self.authorize(request()).send()?;
+        if response.status != 401 || self.authorization.is_some() {
+            return Ok(response);
+        }
+        let Some(challenge) = response.header("WWW-Authenticate") else {
+            return Ok(response);
+        };
+
+        let (scheme, params) = parse_challenge(challenge);
+        if scheme.eq_ignore_ascii_case("basic") {
+            self.authorization = Some(Authorization::Basic);
+        } else {
+            let realm = params
+                .iter()
+                .find(|(key, _)| key == "realm")
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default();
+            let mut token_request = Request::new("GET", &realm).flag("get");
+            for (key, value) in params.iter().filter(|(key, _)| key != "realm") {
+                token_request = token_request.option("data-urlencode", format!("{key}={value}"));
+            }
+            if let Some(credentials) = &self.credentials {
+                token_request = token_request.option("user", credentials.clone());
+            }
+            let token_response = token_request.send()?;
+            #[derive(Deserialize)]
+            struct Token {
+                token: Option<String>,
+                access_token: Option<String>,
+            }
+            let token: Token = serde_json::from_slice(&token_response.body)
+                .context("The OCI registry answered with no token")?;
+            let Some(token) = token.token.or(token.access_token) else {
+                bail!("The OCI registry answered with no token");
+            };
+            self.authorization = Some(Authorization::Bearer(token));
+        }
+        self.authorize(request()).send()
+    }
+
+    fn upload_blob(&mut self, reference: &Reference, file: &Path, digest: &str) -> Result<()> {
+        let upload_url = self.url("blobs/uploads/");
+        let response = self
+            .send(|| Request::new("POST", &upload_url).option("data-binary", ""))?
+            .expect_success("start an upload to", reference)?;
+        let Some(location) = response.header("Location") else {
+            bail!("The OCI registry answered with no upload location");
+        };
+
+        let location = self.url(location);
+        let separator = if location.contains('?') { '&' } else { '?' };
+        let url = format!("{location}{separator}digest={digest}");
+        self.send(|| {
+            Request::new("PUT", &url)
+                .header("Content-Type: application/octet-stream")
+                .option("data-binary", format!("@{}", file.display()))
+        })?
+        .expect_success("upload to", reference)?;
+        Ok(())
+    }
+}
+
+/// Pushes the template in `args.path` to the OCI registry as `args.oci`.
+pub fn publish(args: &PublishArgs) -> Result<()> {
+    let reference = Reference::parse(&args.oci)?;
+    if !args.path.is_dir() {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Template directory not found").bold().red(),
+            style(args.path.display()).bold(),
+        );
+    }
+    // the same checks as when generating, before anything goes out
+    Config::from_path(&Some(args.path.join(crate::config::CONFIG_FILE_NAME)))?;
+
+    let dir = tmp_dir()?;
+    let layer_file = dir.path().join("template.tar.gz");
+    archive::pack(&args.path, &layer_file)?;
+    let layer = Descriptor::of(LAYER_MEDIA_TYPE, &std::fs::read(&layer_file)?);
+    let config_file = dir.path().join("config.json");
+    std::fs::write(&config_file, EMPTY_CONFIG)?;
+    let config = Descriptor::of(EMPTY_CONFIG_MEDIA_TYPE, EMPTY_CONFIG);
+
+    let mut registry = Registry::new(&reference);
+    registry.upload_blob(&reference, &config_file, &config.digest)?;
+    registry.upload_blob(&reference, &layer_file, &layer.digest)?;
+
+    let manifest = Manifest {
+        schema_version: 2,
+        media_type: Some(MANIFEST_MEDIA_TYPE.to_string()),
+        artifact_type: Some(ARTIFACT_TYPE.to_string()),
+        config,
+        layers: vec![layer],
+    };
+    let manifest_file = dir.path().join("manifest.json");
+    std::fs::write(&manifest_file, serde_json::to_vec(&manifest)?)?;
+    let manifest_url = registry.url(&format!("manifests/{}", reference.tag));
+    registry
+        .send(|| {
+            Request::new("PUT", &manifest_url)
+                .header(format!("Content-Type: {MANIFEST_MEDIA_TYPE}"))
+                .option("data-binary", format!("@{}", manifest_file.display()))
+        })?
+        .expect_success("publish the manifest of", &reference)?;
+
+    info!(
+        "{} {} {}",
+        emoji::SPARKLE,
+        style("Published template as").bold().green(),
+        style(format!("oci://{reference}")).bold(),
+    );
+    Ok(())
+}
+
+/// Pulls the template published as `reference` into `destination`.
+pub fn pull(reference: &str, destination: &Path) -> Result<()> {
+    let reference = Reference::parse(reference)?;
+    let mut registry = Registry::new(&reference);
+
+    let manifest_url = registry.url(&format!("manifests/{}", reference.tag));
+    let response = registry
+        .send(|| {
+            Request::new("GET", &manifest_url).header(format!("Accept: {MANIFEST_MEDIA_TYPE}"))
+        })?
+        .expect_success("fetch the manifest of", &reference)?;
+    let manifest: Manifest = serde_json::from_slice(&response.body)
+        .with_context(|| format!("Invalid manifest of `{reference}`"))?;
+    let Some(layer) = manifest
+        .layers
+        .iter()
+        .find(|layer| layer.media_type == LAYER_MEDIA_TYPE)
+    else {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Not a cargo-generate template:").bold().red(),
+            style(&reference).bold(),
+        );
+    };
+
+    let blob_url = registry.url(&format!("blobs/{}", layer.digest));
+    let response = registry
+        .send(|| Request::new("GET", &blob_url))?
+        .expect_success("fetch the template of", &reference)?;
+    if format!("sha256:{}", sha256_hex(&response.body)) != layer.digest {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("The template does not match its digest:")
+                .bold()
+                .red(),
+            style(&reference).bold(),
+        );
+    }
+
+    let dir = tmp_dir()?;
+    let layer_file = dir.path().join("template.tar.gz");
+    std::fs::write(&layer_file, &response.body)?;
+    archive::unpack(&layer_file, destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_references() {
+        let reference = Reference::parse("oci://registry.example.com/templates/api:1.2.0").unwrap();
+        assert_eq!(
+            reference,
+            Reference {
+                registry: "registry.example.com".into(),
+                repository: "templates/api".into(),
+                tag: "1.2.0".into(),
+            }
+        );
+        assert_eq!(
+            reference.repository_url(),
+            "https://registry.example.com/v2/templates/api"
+        );
+
+        let reference = Reference::parse("localhost:5000/api").unwrap();
+        assert_eq!(reference.tag, "latest");
+        assert_eq!(reference.repository_url(), "http://localhost:5000/v2/api");
+        assert_eq!(reference.to_string(), "localhost:5000/api:latest");
+
+        let reference = Reference::parse("ghcr.io/org/api@sha256:abc").unwrap();
+        assert_eq!(reference.tag, "sha256:abc");
+        assert_eq!(reference.to_string(), "ghcr.io/org/api@sha256:abc");
+
+        assert!(Reference::parse("api:1.0").is_err());
+    }
+
+    #[test]
+    fn parses_authentication_challenges() {
+        let (scheme, params) = parse_challenge(
+            r#"Bearer realm="https://auth.example.com/token",service="registry.example.com",scope="repository:templates/api:pull,push""#,
+        );
+        assert_eq!(scheme, "Bearer");
+        assert_eq!(
+            params,
+            [
+                ("realm".into(), "https://auth.example.com/token".into()),
+                ("service".into(), "registry.example.com".into()),
+                ("scope".into(), "repository:templates/api:pull,push".into()),
+            ]
+        );
+
+        assert_eq!(parse_challenge(r#"Basic realm="Registry""#).0, "Basic");
+    }
+}
@@ -0,0 +1,223 @@
+//! Packaged templates: a template directory in a single gzipped tar archive, made with
+//! `package`, carrying a manifest of the checksums of all its files.
+//!
+//! Archives are generated from like template directories, with `--path` or as the template
+//! argument, and their files are checked against the manifest before anything else happens.
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use log::info;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+use crate::{
+    archive, config::Config, config::CONFIG_FILE_NAME, emoji, git::tmp_dir, sha256::sha256_hex,
+    PackageArgs,
+};
+
+/// The manifest of the checksums, in the format of `sha256sum`.
+pub const CHECKSUMS_FILE_NAME: &str = "cargo-generate.sha256";
+
+/// Whether `path` is a packaged template rather than a template directory.
+pub fn is_archive(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    path.is_file() && (name.ends_with(".tar.gz") || name.ends_with(".tgz"))
+}
+
+/// Packages the template in `args.path`, returning the path of the archive.
+pub fn package(args: &PackageArgs) -> Result<PathBuf> {
+    let output = args.output.clone().unwrap_or_else(|| {
+        let name = args
+            .path
+            .canonicalize()
+            .ok()
+            .and_then(|path| path.file_name().map(|name| name.to_owned()))
+            .unwrap_or_else(|| "template".into());
+        PathBuf::from(format!("{}.tar.gz", name.to_string_lossy()))
+    });
+    pack(&args.path, &output)?;
+
+    info!(
+        "{} {} `{}`",
+        emoji::SPARKLE,
+        style("Packaged template into").bold().green(),
+        style(output.display()).bold(),
+    );
+    Ok(output)
+}
+
+/// Packs the template in `template_dir` into `archive`, after checking its config and that its
+/// hooks are there.
+pub fn pack(template_dir: &Path, archive: &Path) -> Result<()> {
+    if !template_dir.is_dir() {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Template directory not found").bold().red(),
+            style(template_dir.display()).bold(),
+        );
+    }
+    let config = Config::from_path(&Some(template_dir.join(CONFIG_FILE_NAME)))
+        .with_context(|| format!("Invalid `{CONFIG_FILE_NAME}`"))?;
+    for hook in config.get_hook_files() {
+        if !template_dir.join(&hook).is_file() {
+            bail!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("The template lacks the script of a hook:")
+                    .bold()
+                    .red(),
+                style(hook).bold(),
+            );
+        }
+    }
+
+    let staging = tmp_dir()?;
+    let checksums = checksums(template_dir)?;
+    for relative in checksums.keys() {
+        let destination = staging.path().join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(template_dir.join(relative), destination)?;
+    }
+    fs::write(
+        staging.path().join(CHECKSUMS_FILE_NAME),
+        checksums
+            .iter()
+            .map(|(path, checksum)| format!("{checksum}  {path}\n"))
+            .collect::<String>(),
+    )?;
+
+    archive::pack(staging.path(), archive)
+}
+
+/// Unpacks the packaged template `archive` into `destination`, failing unless all its files match
+/// the manifest of their checksums.
+pub fn unpack(archive: &Path, destination: &Path) -> Result<()> {
+    archive::unpack(archive, destination)?;
+
+    let manifest = destination.join(CHECKSUMS_FILE_NAME);
+    let Ok(checksums) = fs::read_to_string(&manifest) else {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Not a packaged template, it has no checksums:")
+                .bold()
+                .red(),
+            style(archive.display()).bold(),
+        );
+    };
+    fs::remove_file(&manifest)?;
+    let expected = checksums
+        .lines()
+        .filter_map(|line| line.split_once("  "))
+        .map(|(checksum, path)| (path.to_string(), checksum.to_string()))
+        .collect::<BTreeMap<_, _>>();
+
+    if let Some(file) = first_difference(&expected, &self::checksums(destination)?) {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("The packaged template does not match its checksums at")
+                .bold()
+                .red(),
+            style(file).bold(),
+        );
+    }
+    Ok(())
+}
+
+/// The SHA-256 of every file in `dir` by its path relative to `dir`, leaving out the `.git` and
+/// `target` directories.
+pub fn checksums(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let files = WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() != 1
+                || !archive::EXCLUDED.contains(&&*entry.file_name().to_string_lossy())
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file());
+    let mut checksums = BTreeMap::new();
+    for file in files {
+        let relative = to_slashes(file.path().strip_prefix(dir)?);
+        checksums.insert(relative, sha256_hex(&fs::read(file.path())?));
+    }
+    Ok(checksums)
+}
+
+/// The first file changed, added or missing in `actual` compared to `expected`.
+pub fn first_difference<'a>(
+    expected: &'a BTreeMap<String, String>,
+    actual: &'a BTreeMap<String, String>,
+) -> Option<&'a String> {
+    expected
+        .iter()
+        .find(|(file, checksum)| actual.get(*file) != Some(checksum))
+        .map(|(file, _)| file)
+        .or_else(|| actual.keys().find(|file| !expected.contains_key(*file)))
+}
+
+fn to_slashes(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packages_unpack_again_unless_changed() {
+        let template = tempfile::tempdir().unwrap();
+        fs::create_dir_all(template.path().join("src")).unwrap();
+        fs::write(template.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::create_dir_all(template.path().join(".git")).unwrap();
+        fs::write(template.path().join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let archive = out.path().join("template.tar.gz");
+        pack(template.path(), &archive).unwrap();
+        assert!(is_archive(&archive));
+
+        let unpacked = tempfile::tempdir().unwrap();
+        unpack(&archive, unpacked.path()).unwrap();
+        assert!(unpacked.path().join("src/main.rs").exists());
+        assert!(!unpacked.path().join(".git").exists());
+        assert!(!unpacked.path().join(CHECKSUMS_FILE_NAME).exists());
+
+        // an archive with a file changed after packaging
+        let tampered = tempfile::tempdir().unwrap();
+        archive::unpack(&archive, tampered.path()).unwrap();
+        fs::write(tampered.path().join("src/main.rs"), "fn main() { evil() }").unwrap();
+        let tampered_archive = out.path().join("tampered.tar.gz");
+        archive::pack(tampered.path(), &tampered_archive).unwrap();
+        let unpacked = tempfile::tempdir().unwrap();
+        let error = unpack(&tampered_archive, unpacked.path()).unwrap_err();
+        assert!(error.to_string().contains("src/main.rs"), "{error}");
+    }
+
+    #[test]
+    fn packaging_needs_the_hooks() {
+        let template = tempfile::tempdir().unwrap();
+        fs::write(
+            template.path().join(CONFIG_FILE_NAME),
+            "[hooks]\npre = [\"pre.rhai\"]\n",
+        )
+        .unwrap();
+        let out = tempfile::tempdir().unwrap();
+
+        let error = pack(template.path(), &out.path().join("t.tar.gz")).unwrap_err();
+        assert!(error.to_string().contains("pre.rhai"), "{error}");
+    }
+}
@@ -0,0 +1,371 @@
+//! Post-processing of the expanded template, configured per glob:
+//!
+//! ```toml
+//! [template.postprocess]
+//! "*.rs" = ["rustfmt"]
+//! "*.json" = ["json-pretty", "ensure-final-newline"]
+//! "*.md" = ["strip-trailing-whitespace", "fix-links.rhai"]
+//! ```
+//!
+//! All processors of all matching globs run, in the order they are listed.
+
+use anyhow::{bail, Result};
+use console::style;
+use fs_err as fs;
+use ignore::gitignore::GitignoreBuilder;
+use indexmap::IndexMap;
+use log::{debug, warn};
+use serde::Deserialize;
+use std::{
+    fmt,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+use walkdir::WalkDir;
+
+use crate::emoji;
+use crate::hooks::{execute_postprocess_script, RhaiHooksContext};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Postprocessor {
+    /// Format Rust code with `rustfmt`, using the edition of the generated `Cargo.toml`
+    Rustfmt,
+    /// Pretty-print a JSON document
+    JsonPretty,
+    /// Re-serialize a TOML document, dropping comments and normalizing its formatting
+    TomlNormalize,
+    /// Remove whitespace at the end of every line
+    StripTrailingWhitespace,
+    /// Make sure a non-empty file ends with a newline
+    EnsureFinalNewline,
+    /// A rhai script of the template, getting the file as `content` and returning the new content
+    Script(String),
+}
+
+impl Postprocessor {
+    const BUILTIN: [Self; 5] = [
+        Self::Rustfmt,
+        Self::JsonPretty,
+        Self::TomlNormalize,
+        Self::StripTrailingWhitespace,
+        Self::EnsureFinalNewline,
+    ];
+
+    pub fn script(&self) -> Option<&str> {
+        match self {
+            Self::Script(script) => Some(script),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Postprocessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rustfmt => write!(f, "rustfmt"),
+            Self::JsonPretty => write!(f, "json-pretty"),
+            Self::TomlNormalize => write!(f, "toml-normalize"),
+            Self::StripTrailingWhitespace => write!(f, "strip-trailing-whitespace"),
+            Self::EnsureFinalNewline => write!(f, "ensure-final-newline"),
+            Self::Script(script) => write!(f, "{script}"),
+        }
+    }
+}
+
+impl TryFrom<String> for Postprocessor {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        if let Some(builtin) = Self::BUILTIN.into_iter().find(|p| p.to_string() == name) {
+            return Ok(builtin);
+        }
+        if name.ends_with(".rhai") {
+            return Ok(Self::Script(name));
+        }
+        Err(format!(
+            "unknown postprocessor `{name}`, must be a `.rhai` script or one of {}",
+            Self::BUILTIN
+                .iter()
+                .map(|p| format!("`{p}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+}
+
+/// Runs the postprocessors matching the files in `dir`, leaving the `skipped` files (the
+/// scripts of the template) alone.
+pub fn postprocess(
+    dir: &Path,
+    postprocess: &IndexMap<String, Vec<Postprocessor>>,
+    skipped: &[String],
+    context: &RhaiHooksContext,
+) -> Result<()> {
+    let mut rules = Vec::with_capacity(postprocess.len());
+    for (glob, processors) in postprocess {
+        let mut builder = GitignoreBuilder::new(dir);
+        builder.add_line(None, glob)?;
+        rules.push((builder.build()?, processors));
+    }
+    let edition = rust_edition(dir);
+
+    let files = WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file());
+    for entry in files {
+        let relative_path = entry.path().strip_prefix(dir)?;
+        if relative_path.starts_with(".git")
+            || skipped.iter().any(|s| relative_path == Path::new(s))
+        {
+            continue;
+        }
+        let processors = rules
+            .iter()
+            .filter(|(matcher, _)| {
+                matcher
+                    .matched_path_or_any_parents(relative_path, /* is_dir */ false)
+                    .is_ignore()
+            })
+            .flat_map(|(_, processors)| processors.iter())
+            .collect::<Vec<_>>();
+        if processors.is_empty() {
+            continue;
+        }
+
+        let Ok(original) = fs::read_to_string(entry.path()) else {
+            debug!("Not postprocessing binary file {}", relative_path.display());
+            continue;
+        };
+        let mut content = original.clone();
+        for processor in processors {
+            let path = relative_path.to_string_lossy().replace('\\', "/");
+            content = match processor {
+                Postprocessor::Rustfmt => rustfmt(&content, &edition, &path),
+                Postprocessor::JsonPretty => json_pretty(&content, &path)?,
+                Postprocessor::TomlNormalize => toml_normalize(&content, &path)?,
+                Postprocessor::StripTrailingWhitespace => strip_trailing_whitespace(&content),
+                Postprocessor::EnsureFinalNewline => ensure_final_newline(content),
+                Postprocessor::Script(_) if context.generation.no_scripts => content,
+                Postprocessor::Script(script) => {
+                    execute_postprocess_script(context, script, &path, content)?
+                }
+            };
+        }
+        if content != original {
+            fs::write(entry.path(), content)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The edition of the generated crate, so that `rustfmt` parses it the way cargo will.
+fn rust_edition(dir: &Path) -> String {
+    fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| manifest.parse::<toml::Table>().ok())
+        .and_then(|manifest| {
+            manifest
+                .get("package")?
+                .get("edition")?
+                .as_str()
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "2021".to_string())
+}
+
+/// Formats `content` with `rustfmt`, keeping it as it is if that fails, since a missing
+/// toolchain component must not fail the generation.
+fn rustfmt(content: &str, edition: &str, path: &str) -> String {
+    let formatted = Command::new("rustfmt")
+        .args(["--edition", edition, "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(content.as_bytes())?;
+            }
+            child.wait_with_output()
+        });
+
+    match formatted {
+        Ok(output) if output.status.success() => {
+            String::from_utf8(output.stdout).unwrap_or_else(|_| content.to_string())
+        }
+        Ok(output) => {
+            warn!(
+                "{} `{path}`: {}",
+                style("[Skipping] rustfmt failed to format").bold().yellow(),
+                String::from_utf8_lossy(&output.stderr).trim(),
+            );
+            content.to_string()
+        }
+        Err(e) => {
+            warn!(
+                "{} `{path}`: {e}",
+                style("[Skipping] rustfmt could not be run on")
+                    .bold()
+                    .yellow(),
+            );
+            content.to_string()
+        }
+    }
+}
+
+fn json_pretty(content: &str, path: &str) -> Result<String> {
+    match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(value) => Ok(format!("{}\n", serde_json::to_string_pretty(&value)?)),
+        Err(e) => unparsable(path, "JSON", e),
+    }
+}
+
+fn toml_normalize(content: &str, path: &str) -> Result<String> {
+    match content.parse::<toml::Table>() {
+        Ok(table) => Ok(toml::to_string(&table)?),
+        Err(e) => unparsable(path, "TOML", e),
+    }
+}
+
+fn unparsable(path: &str, format: &str, e: impl fmt::Display) -> Result<String> {
+    bail!(
+        "{} {} `{path}`: {e}",
+        emoji::ERROR,
+        style(format!("Cannot postprocess, not valid {format}:"))
+            .bold()
+            .red(),
+    )
+}
+
+fn strip_trailing_whitespace(content: &str) -> String {
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let text = line.trim_end_matches(['\r', '\n']);
+            format!("{}{}", text.trim_end(), &line[text.len()..])
+        })
+        .collect()
+}
+
+fn ensure_final_newline(mut content: String) -> String {
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::Generation;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn run(files: &[(&str, &str)], postprocess: &str) -> TempDir {
+        run_with(files, postprocess, Generation::default())
+    }
+
+    fn run_with(files: &[(&str, &str)], postprocess: &str, generation: Generation) -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        for (name, content) in files {
+            std::fs::write(tmp.path().join(name), content).unwrap();
+        }
+        let postprocess: IndexMap<String, Vec<Postprocessor>> =
+            toml::from_str(postprocess).unwrap();
+        let context = RhaiHooksContext {
+            destination_directory: tmp.path().to_path_buf(),
+            generation: Arc::new(generation),
+            ..RhaiHooksContext::for_test(tmp.path())
+        };
+
+        super::postprocess(tmp.path(), &postprocess, &["upper.rhai".into()], &context).unwrap();
+        tmp
+    }
+
+    fn read(dir: &TempDir, name: &str) -> String {
+        std::fs::read_to_string(dir.path().join(name)).unwrap()
+    }
+
+    #[test]
+    fn parses_builtin_and_script_names() {
+        assert_eq!(
+            Postprocessor::try_from("json-pretty".to_string()),
+            Ok(Postprocessor::JsonPretty)
+        );
+        assert_eq!(
+            Postprocessor::try_from("fix.rhai".to_string()),
+            Ok(Postprocessor::Script("fix.rhai".into()))
+        );
+        assert!(Postprocessor::try_from("prettier".to_string()).is_err());
+    }
+
+    #[test]
+    fn runs_processors_of_all_matching_globs_in_order() {
+        let dir = run(
+            &[
+                ("notes.md", "title  \r\nbody\t"),
+                ("data.json", r#"{"a":[1,2]}"#),
+                ("other.txt", "keep  "),
+            ],
+            r#"
+                "*.md" = ["strip-trailing-whitespace"]
+                "*.json" = ["json-pretty"]
+                "notes.*" = ["ensure-final-newline"]
+            "#,
+        );
+
+        assert_eq!(read(&dir, "notes.md"), "title\r\nbody\n");
+        assert_eq!(
+            read(&dir, "data.json"),
+            "{\n  \"a\": [\n    1,\n    2\n  ]\n}\n"
+        );
+        assert_eq!(read(&dir, "other.txt"), "keep  ");
+    }
+
+    #[test]
+    fn scripts_transform_the_content() {
+        let dir = run(
+            &[
+                ("README.md", "hello"),
+                ("upper.rhai", "`${path}: ${content.to_upper()}`"),
+            ],
+            r#""*" = ["upper.rhai"]"#,
+        );
+
+        assert_eq!(read(&dir, "README.md"), "README.md: HELLO");
+        assert_eq!(
+            read(&dir, "upper.rhai"),
+            "`${path}: ${content.to_upper()}`",
+            "scripts of the template are not processed themselves"
+        );
+    }
+
+    #[test]
+    fn scripts_are_skipped_without_scripts() {
+        let generation = Generation {
+            no_scripts: true,
+            ..Generation::default()
+        };
+        let dir = run_with(
+            &[
+                ("README.md", "hello"),
+                ("upper.rhai", "`${path}: ${content.to_upper()}`"),
+            ],
+            r#""*" = ["upper.rhai", "ensure-final-newline"]"#,
+            generation,
+        );
+
+        assert_eq!(read(&dir, "README.md"), "hello\n");
+    }
+
+    #[test]
+    fn invalid_documents_are_reported() {
+        assert!(toml_normalize("not = [toml", "Cargo.toml").is_err());
+        assert!(json_pretty("{", "package.json").is_err());
+    }
+}
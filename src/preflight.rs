@@ -0,0 +1,130 @@
+//! Checks made before anything is rendered, so a generation that can't succeed fails right away
+//! rather than midway, leaving a project half written: the destination must be writable, and the
+//! file system it is on must have room for at least as much as the template takes.
+//!
+//! The free space is only known on Unix, elsewhere only the permissions are checked.
+
+use anyhow::{bail, Result};
+use console::style;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::emoji;
+
+/// Fails unless the project generated from `template_dir` can be written within `destination`,
+/// the folder it is created in, or generated into with `--init`.
+pub fn check(template_dir: &Path, destination: &Path) -> Result<()> {
+    // a destination yet to be created is created within the first folder that exists
+    let Some(existing) = destination.ancestors().find(|folder| folder.is_dir()) else {
+        return Ok(());
+    };
+    if let Err(error) = tempfile::tempfile_in(existing) {
+        bail!(
+            "{} {} `{}`{} {error}",
+            emoji::ERROR,
+            style("The project can't be written to").bold().red(),
+            style(existing.display()).bold().yellow(),
+            style(":").bold().red(),
+        );
+    }
+
+    let Some(available) = available_space(existing) else {
+        return Ok(());
+    };
+    let needed = template_size(template_dir);
+    if needed > available {
+        bail!(
+            "{} {} {} {} `{}`",
+            emoji::ERROR,
+            style("Not enough space for the project, the template takes")
+                .bold()
+                .red(),
+            style(size(needed)).bold().yellow(),
+            style(format!("while {} are free in", size(available)))
+                .bold()
+                .red(),
+            style(existing.display()).bold().yellow(),
+        );
+    }
+    Ok(())
+}
+
+/// The bytes the files of the template take, but those of git.
+fn template_size(template_dir: &Path) -> u64 {
+    WalkDir::new(template_dir)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// The bytes free for the user on the file system of `folder`.
+#[cfg(unix)]
+fn available_space(folder: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(folder.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is a valid C string and `stat` has room for what `statvfs` fills in
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: `statvfs` succeeded, so it filled `stat`
+    let stat = unsafe { stat.assume_init() };
+    #[allow(clippy::useless_conversion)]
+    Some(u64::from(stat.f_bavail).saturating_mul(u64::from(stat.f_frsize)))
+}
+
+#[cfg(not(unix))]
+fn available_space(_folder: &Path) -> Option<u64> {
+    None
+}
+
+/// `bytes` in the largest binary unit they make at least one of.
+pub fn size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_are_given_in_binary_units() {
+        assert_eq!(size(512), "512 B");
+        assert_eq!(size(1536), "1.5 KiB");
+        assert_eq!(size(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(size(3 << 40), "3.0 TiB");
+    }
+
+    #[test]
+    fn the_template_size_leaves_out_git() {
+        let template = tempfile::tempdir().unwrap();
+        std::fs::write(template.path().join("Cargo.toml"), [0; 100]).unwrap();
+        std::fs::create_dir_all(template.path().join(".git/objects")).unwrap();
+        std::fs::write(template.path().join(".git/objects/pack"), [0; 1000]).unwrap();
+
+        assert_eq!(template_size(template.path()), 100);
+        assert!(check(template.path(), &template.path().join("missing/project")).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_free_space_is_known_on_unix() {
+        assert!(available_space(&std::env::temp_dir()).is_some_and(|free| free > 0));
+    }
+}
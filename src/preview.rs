@@ -0,0 +1,136 @@
+//! `--preview`: before any generated file is written, the tree they make and the start of some of
+//! them are shown, and the user decides to write them, to change the answers first, or to stop.
+
+use anyhow::Result;
+use std::{fs, path::Path};
+use walkdir::WalkDir;
+
+use crate::config::{Config, TemplateSlotsTable};
+use crate::project_variables::{is_internal, nested_value};
+use crate::select;
+use crate::template::TemplateObjectResource;
+
+/// How many files the start is shown of, and how many lines of each.
+const SAMPLE_FILES: usize = 3;
+const SAMPLE_LINES: usize = 8;
+
+pub enum Decision {
+    Write,
+    ChangeAnswers,
+    Abort,
+}
+
+/// Shows the files expanded into `template_dir` and asks what to do with them.
+pub fn review(template_dir: &Path) -> Result<Decision> {
+    for line in summary(template_dir) {
+        println!("{line}");
+    }
+    let choices = [
+        "Write the project".to_string(),
+        "Change answers".to_string(),
+        "Abort".to_string(),
+    ];
+    Ok(match select::select("What now?", &choices, 0)? {
+        0 => Decision::Write,
+        1 => Decision::ChangeAnswers,
+        _ => Decision::Abort,
+    })
+}
+
+/// The answers given to the placeholders of `config`, to offer as defaults when asking again.
+pub fn answers(config: &Config, template_object: &TemplateObjectResource) -> toml::Table {
+    let Some(TemplateSlotsTable(placeholders)) = config.placeholders.as_ref() else {
+        return toml::Table::new();
+    };
+    let Ok(template_object) = template_object.lock() else {
+        return toml::Table::new();
+    };
+    let template_object = template_object.borrow();
+    placeholders
+        .keys()
+        .filter(|name| !is_internal(name))
+        .filter_map(|name| {
+            let value = toml::Value::try_from(nested_value(&template_object, name)?).ok()?;
+            Some((name.clone(), value))
+        })
+        .collect()
+}
+
+/// The tree of the files in `dir`, followed by the first lines of the first text files.
+fn summary(dir: &Path) -> Vec<String> {
+    let entries = WalkDir::new(dir)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![];
+    for entry in &entries {
+        let indent = "  ".repeat(entry.depth() - 1);
+        let slash = if entry.file_type().is_dir() { "/" } else { "" };
+        lines.push(format!(
+            "{indent}{}{slash}",
+            entry.file_name().to_string_lossy()
+        ));
+    }
+
+    let samples = entries
+        .iter()
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| Some((e.path(), fs::read_to_string(e.path()).ok()?)))
+        .filter(|(_, contents)| !contents.trim().is_empty())
+        .take(SAMPLE_FILES);
+    for (path, contents) in samples {
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        lines.push(String::new());
+        lines.push(format!("--- {}", relative.display()));
+        lines.extend(contents.lines().take(SAMPLE_LINES).map(str::to_string));
+        if contents.lines().count() > SAMPLE_LINES {
+            lines.push("...".to_string());
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_summary_shows_the_tree_and_the_start_of_text_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/bin")).unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("src/bin/empty.rs"), "").unwrap();
+        let main = (1..=10).map(|n| format!("// {n}\n")).collect::<String>();
+        fs::write(dir.path().join("src/main.rs"), main).unwrap();
+
+        let summary = summary(dir.path());
+
+        let mut expected = vec![
+            "Cargo.toml",
+            "src/",
+            "  bin/",
+            "    empty.rs",
+            "  main.rs",
+            "",
+            "--- Cargo.toml",
+            "[package]",
+            "name = \"demo\"",
+            "",
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+        expected.push(format!("--- {}", Path::new("src").join("main.rs").display()));
+        expected.extend((1..=8).map(|n| format!("// {n}")));
+        expected.push("...".to_string());
+        assert_eq!(summary, expected);
+    }
+}
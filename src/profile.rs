@@ -0,0 +1,193 @@
+//! Timings of generating a project, recorded with `--profile-render` to find the files, hooks and
+//! Rhai filters that make a template slow.
+
+use console::style;
+use indexmap::IndexMap;
+use log::info;
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::emoji;
+
+/// The steps of rendering a file that are timed.
+#[derive(Debug, Clone, Copy)]
+pub enum Stage {
+    Parse,
+    Render,
+    Write,
+}
+
+#[derive(Debug, Default)]
+struct Profile {
+    /// The durations of the [`Stage`]s of each file, in the order of the stages
+    files: IndexMap<PathBuf, [Duration; 3]>,
+    hooks: IndexMap<String, Duration>,
+    filters: IndexMap<String, Duration>,
+    current_file: Option<PathBuf>,
+}
+
+static PROFILE: Mutex<Option<Profile>> = Mutex::new(None);
+
+/// Starts recording timings, unless they already are.
+pub fn enable() {
+    if let Ok(mut profile) = PROFILE.lock() {
+        profile.get_or_insert_with(Profile::default);
+    }
+}
+
+fn with_profile(f: impl FnOnce(&mut Profile)) {
+    if let Ok(mut profile) = PROFILE.lock() {
+        if let Some(profile) = profile.as_mut() {
+            f(profile);
+        }
+    }
+}
+
+fn is_enabled() -> bool {
+    PROFILE.lock().is_ok_and(|profile| profile.is_some())
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Attributes the [`Stage`]s timed until the returned guard is dropped to `file`.
+pub fn file_scope(file: &Path) -> FileScope {
+    with_profile(|profile| profile.current_file = Some(file.to_path_buf()));
+    FileScope
+}
+
+pub struct FileScope;
+
+impl Drop for FileScope {
+    fn drop(&mut self) {
+        with_profile(|profile| profile.current_file = None);
+    }
+}
+
+/// Times `stage` of the file of the current [`file_scope`].
+pub fn time_stage<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    let (result, duration) = timed(f);
+    with_profile(|profile| {
+        if let Some(file) = profile.current_file.clone() {
+            profile.files.entry(file).or_default()[stage as usize] += duration;
+        }
+    });
+    result
+}
+
+/// Times running the hook `script`.
+pub fn time_hook<T>(script: &str, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    let (result, duration) = timed(f);
+    with_profile(|profile| *profile.hooks.entry(script.to_string()).or_default() += duration);
+    result
+}
+
+/// Times a call of the filter `name`.
+pub fn time_filter<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    let (result, duration) = timed(f);
+    with_profile(|profile| *profile.filters.entry(name.to_string()).or_default() += duration);
+    result
+}
+
+/// Stops recording and prints the `limit` slowest files, hooks and filters.
+pub fn report(limit: usize) {
+    let Some(profile) = PROFILE.lock().ok().and_then(|mut profile| profile.take()) else {
+        return;
+    };
+    for line in report_lines(&profile, limit) {
+        info!("{line}");
+    }
+}
+
+fn report_lines(profile: &Profile, limit: usize) -> Vec<String> {
+    let mut lines = vec![];
+
+    let mut files = profile
+        .files
+        .iter()
+        .map(|(file, stages)| (file, stages.iter().sum::<Duration>(), stages))
+        .collect::<Vec<_>>();
+    files.sort_by(|(_, a, _), (_, b, _)| b.cmp(a));
+    lines.push(format!(
+        "{} {}",
+        emoji::WRENCH,
+        style("Slowest files (parse / render / write):").bold()
+    ));
+    lines.extend(
+        files
+            .into_iter()
+            .take(limit)
+            .map(|(file, total, [parse, render, write])| {
+                format!(
+                    "    {total:>10.2?}  {} ({parse:.2?} / {render:.2?} / {write:.2?})",
+                    file.display()
+                )
+            }),
+    );
+
+    for (heading, durations) in [
+        ("Slowest hooks:", &profile.hooks),
+        ("Slowest filters:", &profile.filters),
+    ] {
+        if durations.is_empty() {
+            continue;
+        }
+        let mut durations = durations.iter().collect::<Vec<_>>();
+        durations.sort_by(|(_, a), (_, b)| b.cmp(a));
+        lines.push(format!("{} {}", emoji::WRENCH, style(heading).bold()));
+        lines.extend(
+            durations
+                .into_iter()
+                .take(limit)
+                .map(|(name, duration)| format!("    {duration:>10.2?}  {name}")),
+        );
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_the_slowest_first() {
+        let ms = Duration::from_millis;
+        let profile = Profile {
+            files: IndexMap::from([
+                ("fast.rs".into(), [ms(1), ms(1), ms(1)]),
+                ("slow.rs".into(), [ms(1), ms(20), ms(1)]),
+                ("medium.rs".into(), [ms(5), ms(5), ms(0)]),
+            ]),
+            hooks: IndexMap::from([("pre.rhai".to_string(), ms(3))]),
+            ..Profile::default()
+        };
+
+        let lines = report_lines(&profile, 2)
+            .iter()
+            .map(|line| console::strip_ansi_codes(line).trim().to_string())
+            .collect::<Vec<_>>();
+
+        assert!(lines[0].ends_with("Slowest files (parse / render / write):"));
+        assert_eq!(lines[1], "22.00ms  slow.rs (1.00ms / 20.00ms / 1.00ms)");
+        assert_eq!(lines[2], "10.00ms  medium.rs (5.00ms / 5.00ms / 0.00ns)");
+        assert!(lines[3].ends_with("Slowest hooks:"));
+        assert_eq!(lines[4], "3.00ms  pre.rhai");
+        assert_eq!(lines.len(), 5, "no filters were timed");
+    }
+}
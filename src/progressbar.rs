@@ -1,12 +1,43 @@
-use indicatif::{MultiProgress, ProgressStyle};
+use indicatif::{MultiProgress, ProgressDrawTarget, ProgressStyle};
 
+use crate::{accessible, emoji};
+
+/// The progress of rendering, not shown at all to screen readers, which can't follow spinners.
 pub fn new() -> MultiProgress {
-    MultiProgress::new()
+    if accessible::enabled() {
+        MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+    } else {
+        MultiProgress::new()
+    }
 }
 
 pub fn spinner() -> ProgressStyle {
     ProgressStyle::default_spinner()
-        .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ")
+        .tick_chars(if emoji::enabled() {
+            "⠁⠂⠄⡀⢀⠠⠐⠈ "
+        } else {
+            "|/-\\ "
+        })
         .template("{prefix:.bold.dim} {spinner} {wide_msg}")
         .unwrap()
 }
+
+/// The style of a progress with a known number of steps.
+pub fn bar() -> ProgressStyle {
+    ProgressStyle::default_bar()
+        .tick_chars(if emoji::enabled() {
+            "⠁⠂⠄⡀⢀⠠⠐⠈ "
+        } else {
+            "|/-\\ "
+        })
+        .template("{prefix:.bold.dim} {spinner} {wide_msg} [{bar:30}] {pos}/{len}")
+        .unwrap()
+        .progress_chars("=> ")
+}
+
+/// The style of a download, its size unknown.
+pub fn download() -> ProgressStyle {
+    spinner()
+        .template("{prefix:.bold.dim} {spinner} {wide_msg} {bytes} ({bytes_per_sec})")
+        .unwrap()
+}
@@ -1,23 +1,130 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use indexmap::IndexMap;
-use log::info;
+use log::{info, warn};
 use regex::Regex;
+use std::{
+    process::Command,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use thiserror::Error;
 
-use crate::emoji;
+use crate::template_variables::KEYWORDS;
+use crate::{case, emoji};
 use console::style;
 
 use crate::{
+    app_config::RememberedAnswers,
     config::{Config, TemplateSlotsTable},
+    generation::Generation,
     interactive::LIST_SEP,
-    template::TemplateObjectResource,
+    locale,
+    template::{render_config_value, TemplateObjectResource},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TemplateSlots {
     pub(crate) var_name: String,
     pub(crate) var_info: VarInfo,
     pub(crate) prompt: Prompt,
+    /// whether the answer is offered as default the next time, see [`RememberedAnswers`]
+    pub(crate) remember: bool,
+    /// command whose output replaces the default, run only with `--allow-commands`
+    pub(crate) default_from_command: Option<String>,
+}
+
+impl TemplateSlots {
+    /// Replaces the default by the output of `default_from_command`, keeping the default of the
+    /// template if commands are not allowed, or the output is not a valid value.
+    pub(crate) fn resolve_default_from_command(&mut self, allow_commands: bool) {
+        let Some(command) = self.default_from_command.take() else {
+            return;
+        };
+        if !allow_commands {
+            warn!(
+                "{} {} `{}` {}",
+                emoji::WARN,
+                style("Not running the default command of").bold(),
+                style(&self.var_name).bold(),
+                style("without --allow-commands").bold(),
+            );
+            return;
+        }
+
+        match run_default_command(&command) {
+            Ok(output) => {
+                if !self.set_default(&output) {
+                    warn!(
+                        "{} {} `{}`: {output:?}",
+                        emoji::WARN,
+                        style("Ignoring the invalid output of the default command of").bold(),
+                        style(&self.var_name).bold(),
+                    );
+                }
+            }
+            Err(e) => warn!(
+                "{} {} `{}`: {e}",
+                emoji::WARN,
+                style("The default command failed for").bold(),
+                style(&self.var_name).bold(),
+            ),
+        }
+    }
+
+    fn set_default(&mut self, value: &str) -> bool {
+        match &mut self.var_info {
+            VarInfo::Bool { default } => match value.parse() {
+                Ok(value) => *default = Some(value),
+                Err(_) => return false,
+            },
+            VarInfo::String { entry } => {
+                let value = entry.transformed(value.to_string());
+                let is_choice = match &entry.kind {
+                    StringKind::Choices(choices) => choices.contains(&value),
+                    _ => true,
+                };
+                if !is_choice
+                    || entry.regex.as_ref().is_some_and(|r| !r.is_match(&value))
+                    || entry.shape.is_some_and(|shape| shape.rejects(&value).is_some())
+                {
+                    return false;
+                }
+                entry.default = Some(value);
+            }
+            VarInfo::Array { entry } => {
+                let values = value
+                    .split(LIST_SEP)
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+                if !values.iter().all(|v| entry.choices.contains(v)) {
+                    return false;
+                }
+                entry.default = Some(values);
+            }
+        }
+        true
+    }
+}
+
+/// Runs `command` in a shell, returning its trimmed standard output.
+fn run_default_command(command: &str) -> Result<String> {
+    let output = if cfg!(target_os = "windows") {
+        Command::new("cmd").arg("/C").arg(command).output()?
+    } else {
+        Command::new("sh").arg("-c").arg(command).output()?
+    };
+    if !output.status.success() {
+        anyhow::bail!(
+            "`{command}` {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
 #[derive(Debug, Clone)]
@@ -80,6 +187,223 @@ pub struct StringEntry {
     pub(crate) default: Option<String>,
     pub(crate) kind: StringKind,
     pub(crate) regex: Option<Regex>,
+    /// what the `regex` asks for in plain words, told users whose input it rejects
+    pub(crate) regex_description: Option<String>,
+    /// normalizations of the input, applied in order before it is validated
+    pub(crate) transform: Vec<Transform>,
+    /// the built-in validator of placeholders of type `email`, `url` or `ident`
+    pub(crate) shape: Option<Shape>,
+}
+
+impl StringEntry {
+    pub(crate) fn transformed(&self, value: String) -> String {
+        self.transform
+            .iter()
+            .fold(value, |value, transform| transform.apply(&value))
+    }
+}
+
+/// A normalization of the input of a string placeholder, named like the template filter doing
+/// the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Trim,
+    Lower,
+    Upper,
+    KebabCase,
+    LowerCamelCase,
+    PascalCase,
+    ShoutyKebabCase,
+    ShoutySnakeCase,
+    SnakeCase,
+    TitleCase,
+    UpperCamelCase,
+    AsciiSlug,
+}
+
+impl Transform {
+    const NAMES: [(&'static str, Self); 12] = [
+        ("trim", Self::Trim),
+        ("lower", Self::Lower),
+        ("upper", Self::Upper),
+        ("kebab_case", Self::KebabCase),
+        ("lower_camel_case", Self::LowerCamelCase),
+        ("pascal_case", Self::PascalCase),
+        ("shouty_kebab_case", Self::ShoutyKebabCase),
+        ("shouty_snake_case", Self::ShoutySnakeCase),
+        ("snake_case", Self::SnakeCase),
+        ("title_case", Self::TitleCase),
+        ("upper_camel_case", Self::UpperCamelCase),
+        ("ascii_slug", Self::AsciiSlug),
+    ];
+
+    fn apply(self, value: &str) -> String {
+        match self {
+            Self::Trim => value.trim().to_string(),
+            Self::Lower => value.to_lowercase(),
+            Self::Upper => value.to_uppercase(),
+            Self::KebabCase => case::kebab_case(value),
+            Self::LowerCamelCase => case::lower_camel_case(value),
+            Self::PascalCase => case::pascal_case(value),
+            Self::ShoutyKebabCase => case::shouty_kebab_case(value),
+            Self::ShoutySnakeCase => case::shouty_snake_case(value),
+            Self::SnakeCase => case::snake_case(value),
+            Self::TitleCase => case::title_case(value),
+            Self::UpperCamelCase => case::upper_camel_case(value),
+            Self::AsciiSlug => case::ascii_slug(value, "-"),
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, transform)| *transform)
+            .ok_or(())
+    }
+}
+
+/// A common shape of string values, checked by a validator built in, so templates need no regex
+/// for it: the `type` of a placeholder, `email`, `url` or `ident`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Email,
+    Url,
+    Ident,
+}
+
+impl Shape {
+    const NAMES: [(&'static str, Self); 3] = [
+        ("email", Self::Email),
+        ("url", Self::Url),
+        ("ident", Self::Ident),
+    ];
+
+    /// Why `value` is not of the shape, in plain words, if it isn't.
+    pub(crate) fn rejects(self, value: &str) -> Option<String> {
+        let (rule, problem) = match self {
+            Self::Email => (
+                "it must be an email address like `name@example.com`",
+                email_problem(value)?,
+            ),
+            Self::Url => (
+                "it must be a URL like `https://example.com/path`",
+                url_problem(value)?,
+            ),
+            Self::Ident => (
+                "it must be an identifier, a letter or `_` followed by letters, digits or `_`",
+                ident_problem(value)?,
+            ),
+        };
+        Some(format!("{rule}, but {problem}"))
+    }
+}
+
+impl FromStr for Shape {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, shape)| *shape)
+            .ok_or(())
+    }
+}
+
+fn email_problem(value: &str) -> Option<String> {
+    if value.chars().any(char::is_whitespace) {
+        return Some("it contains spaces".into());
+    }
+    let Some((local, domain)) = value.rsplit_once('@') else {
+        return Some("there is no `@`".into());
+    };
+    if local.is_empty() {
+        return Some("nothing comes before the `@`".into());
+    }
+    if let Some(c) = local
+        .chars()
+        .find(|c| !c.is_alphanumeric() && !"!#$%&'*+/=?^_`{|}~.-".contains(*c))
+    {
+        return Some(format!("`{c}` can't come before the `@`"));
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return Some("the part before the `@` can't start or end with `.`, or hold `..`".into());
+    }
+    if domain.is_empty() {
+        return Some("nothing comes after the `@`".into());
+    }
+    if !domain.contains('.') {
+        return Some(format!("the domain `{domain}` has no `.`"));
+    }
+    host_problem(domain)
+}
+
+fn url_problem(value: &str) -> Option<String> {
+    if value.chars().any(char::is_whitespace) {
+        return Some("it contains spaces".into());
+    }
+    let Some((scheme, rest)) = value.split_once("://") else {
+        return Some("it has no scheme like `https://`".into());
+    };
+    if !scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    {
+        return Some(format!("the scheme `{scheme}` is not valid"));
+    }
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_and_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let (host, port) = match host_and_port.rsplit_once(':') {
+        Some((host, port)) if !host_and_port.ends_with(']') => (host, Some(port)),
+        _ => (host_and_port, None),
+    };
+    if host.is_empty() {
+        return Some("it has no host".into());
+    }
+    if let Some(port) = port.filter(|port| port.parse::<u16>().is_err()) {
+        return Some(format!("the port `{port}` is not a number up to 65535"));
+    }
+    if host.starts_with('[') && host.ends_with(']') {
+        // an IPv6 address
+        return None;
+    }
+    host_problem(host)
+}
+
+fn host_problem(host: &str) -> Option<String> {
+    let valid = host.split('.').all(|label| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+    });
+    (!valid).then(|| format!("`{host}` is not a valid host name"))
+}
+
+fn ident_problem(value: &str) -> Option<String> {
+    let Some(first) = value.chars().next() else {
+        return Some("it is empty".into());
+    };
+    if !first.is_alphabetic() && first != '_' {
+        return Some(format!("it starts with `{first}`"));
+    }
+    if let Some(c) = value.chars().find(|c| !c.is_alphanumeric() && *c != '_') {
+        return Some(format!("`{c}` can't be in it"));
+    }
+    if value == "_" {
+        return Some("`_` alone is not one".into());
+    }
+    KEYWORDS
+        .contains(&value)
+        .then(|| format!("`{value}` is a Rust keyword"))
 }
 
 #[derive(Debug, Clone)]
@@ -111,11 +435,17 @@ pub enum ConversionError {
         choices: Vec<String>,
     },
     #[error(
-        "invalid type for variable `{var_name}`: `{value}` possible values are `bool`, `string`, `text` and `editor`"
+        "invalid type for variable `{var_name}`: `{value}` possible values are `bool`, `string`, `text`, `editor`, `array`, `email`, `url` and `ident`"
     )]
     InvalidVariableType { var_name: String, value: String },
     #[error("{var_type} type does not support `choices` field")]
     UnsupportedChoices { var_type: String },
+    #[error("{var_type} type does not support `transform` field")]
+    UnsupportedTransform { var_type: String },
+    #[error(
+        "unknown transform `{transform}` of `{var_name}`, possible values are `trim`, `lower`, `upper`, `ascii_slug` and the case conversion filters like `snake_case`"
+    )]
+    UnknownTransform { var_name: String, transform: String },
     #[error("bool type does not support `regex` field")]
     RegexOnBool { var_name: String },
     #[error(
@@ -124,6 +454,8 @@ pub enum ConversionError {
     MissingDefaultValueForPlaceholderVariable { var_name: String },
     #[error("field `{field}` of variable `{var_name}` does not match configured regex")]
     RegexDoesntMatchField { var_name: String, field: String },
+    #[error("default of variable `{var_name}` is not valid: {reason}")]
+    ShapeDoesntMatchDefault { var_name: String, reason: String },
     #[error("regex of `{var_name}` is not a valid regex. {error}")]
     InvalidRegex {
         var_name: String,
@@ -150,6 +482,105 @@ enum SupportedVarType {
     Array,
 }
 
+/// Variables whose name starts with this prefix are internal to the template: they are rendered
+/// like any other, but never shown to the user nor remembered.
+pub const INTERNAL_PREFIX: char = '_';
+
+pub fn is_internal(name: &str) -> bool {
+    name.starts_with(INTERNAL_PREFIX)
+}
+
+/// Placeholders named with dots, like `database.host`, are nested: the value is the field `host`
+/// of the object `database`, which templates refer to as `{{ database.host }}`.
+pub const NESTING_SEP: char = '.';
+
+/// The group of a nested placeholder, `database` of `database.host`.
+pub fn group_of(name: &str) -> Option<&str> {
+    name.rsplit_once(NESTING_SEP).map(|(group, _)| group)
+}
+
+/// The value of the placeholder `name` in `object`, following the dots of a nested name.
+pub fn nested_value<'a>(
+    object: &'a serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> Option<&'a serde_json::Value> {
+    if let Some(value) = object.get(name) {
+        return Some(value);
+    }
+    let (group, field) = name.rsplit_once(NESTING_SEP)?;
+    nested_value(object, group)?.as_object()?.get(field)
+}
+
+/// Sets the placeholder `name` in `object`, creating the objects of a nested name. Where one of
+/// them is taken by a value that is no object, the name is kept as it is instead.
+///
+/// The field is set under all its [`spellings`], the groups only under the one given.
+pub fn set_nested_value(
+    object: &mut serde_json::Map<String, serde_json::Value>,
+    name: &str,
+    value: serde_json::Value,
+) {
+    let Some((group, field)) = name.split_once(NESTING_SEP) else {
+        insert_variable(object, name, value);
+        return;
+    };
+    let nested = object
+        .entry(group)
+        .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+    match nested.as_object_mut() {
+        Some(nested) => set_nested_value(nested, field, value),
+        None => insert_variable(object, name, value),
+    }
+}
+
+/// The spellings the variable `name` is known by: its own, the snake_case one and the kebab-case
+/// one, so templates refer to `project-name` as `project_name`. Internal variables only have
+/// their own.
+pub fn spellings(name: &str) -> Vec<String> {
+    let mut spellings = vec![name.to_string()];
+    if !is_internal(name) {
+        for spelling in [name.replace('-', "_"), name.replace('_', "-")] {
+            if !spellings.contains(&spelling) {
+                spellings.push(spelling);
+            }
+        }
+    }
+    spellings
+}
+
+/// Whether `a` and `b` are spellings of the same variable.
+pub fn same_variable(a: &str, b: &str) -> bool {
+    a == b || (!is_internal(a) && !is_internal(b) && a.replace('-', "_") == b.replace('-', "_"))
+}
+
+/// Sets the variable `name` in `object` under all its [`spellings`].
+pub fn insert_variable(
+    object: &mut serde_json::Map<String, serde_json::Value>,
+    name: &str,
+    value: serde_json::Value,
+) {
+    for spelling in spellings(name) {
+        object.insert(spelling, value.clone());
+    }
+    CHANGES.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Removes the variable `name` from `object`, under all its [`spellings`].
+pub fn remove_variable(object: &mut serde_json::Map<String, serde_json::Value>, name: &str) {
+    for spelling in spellings(name) {
+        object.remove(&spelling);
+    }
+    CHANGES.fetch_add(1, Ordering::SeqCst);
+}
+
+/// How many variables were set or removed so far, in any object, for what is derived from their
+/// values to notice they changed.
+static CHANGES: AtomicU64 = AtomicU64::new(0);
+
+pub fn changes() -> u64 {
+    CHANGES.load(Ordering::SeqCst)
+}
+
 const RESERVED_NAMES: [&str; 7] = [
     "authors",
     "os-arch",
@@ -160,24 +591,44 @@ const RESERVED_NAMES: [&str; 7] = [
     "is_init",
 ];
 
+/// The built-in variables neither hooks nor values given may overwrite, as the project is generated
+/// according to them. Hooks can still `variable::force_set` them.
+const PROTECTED_NAMES: [&str; 6] = [
+    "project-name",
+    "crate_name",
+    "crate_type",
+    "within_cargo_project",
+    "is_init",
+    "os-arch",
+];
+
+/// Whether the variable `name`, in any spelling, is a built-in protected from being overwritten.
+pub fn is_protected(name: &str) -> bool {
+    PROTECTED_NAMES
+        .iter()
+        .any(|protected| same_variable(protected, name))
+}
+
+/// Whether `name` is a spelling of the project name.
+pub fn is_project_name(name: &str) -> bool {
+    same_variable(name, "project-name")
+}
+
 pub fn show_project_variables_with_value(template_object: &TemplateObjectResource, config: &Config) {
     let template_slots = config
         .placeholders
         .as_ref()
-        .map(try_into_template_slots)
+        .map(|placeholders| try_into_template_slots(placeholders, &RememberedAnswers::default()))
         .unwrap_or_else(|| Ok(IndexMap::new()))
         .unwrap_or_default();
 
     template_slots
         .iter()
-        .filter(|(k, _)| template_object.lock().unwrap().borrow().contains_key(**k))
+        .filter(|(k, _)| !is_internal(k))
+        .filter(|(k, _)| nested_value(&template_object.lock().unwrap().borrow(), k).is_some())
         .for_each(|(k, v)| {
             let name = v.var_name.as_str();
-            let value = template_object
-                .lock()
-                .unwrap()
-                .borrow()
-                .get(*k)
+            let value = nested_value(&template_object.lock().unwrap().borrow(), k)
                 .unwrap()
                 .to_string();
             info!(
@@ -189,40 +640,95 @@ pub fn show_project_variables_with_value(template_object: &TemplateObjectResourc
 }
 
 /// For each defined placeholder, try to add it with value as a variable to the template_object.
+/// Answers to placeholders with `remember = true` are recorded in `remembered`.
+///
+/// The placeholders of a group, like `database.host` and `database.port`, are asked one after the
+/// other, where the first of them is.
 pub fn fill_project_variables(
     template_object: &TemplateObjectResource,
     config: &Config,
-    value_provider: impl Fn(&TemplateSlots) -> Result<serde_json::Value>,
+    remembered: &mut RememberedAnswers,
+    generation: &Arc<Generation>,
+    value_provider: impl Fn(&mut TemplateSlots) -> Result<serde_json::Value>,
 ) -> Result<()> {
-    let template_slots = config
-        .placeholders
-        .as_ref()
-        .map(try_into_template_slots)
-        .unwrap_or_else(|| Ok(IndexMap::new()))?;
+    let Some(TemplateSlotsTable(placeholders)) = config.placeholders.as_ref() else {
+        return Ok(());
+    };
 
-    for (&key, slot) in template_slots.iter() {
+    let mut groups = IndexMap::<Option<&str>, Vec<_>>::new();
+    for (key, values) in placeholders {
+        groups.entry(group_of(key)).or_default().push((key, values));
+    }
+    for (key, values) in groups.into_values().flatten() {
         {
             let template_obj = template_object.lock().unwrap();
             let borrowed = template_obj.borrow();
-            if borrowed.contains_key(key) {
+            if nested_value(&borrowed, key).is_some() {
                 // Key already exists, skip
                 continue;
             }
         }
-        
+
+        // the prompt and default may refer to the values of the placeholders before
+        let values = render_config_values(template_object, key, values, generation)?;
+        let mut slot = try_key_value_into_slot(key, &values, remembered)?;
         // we don't have the variable yet, so we ask for it
-        let value = value_provider(slot)?;
-        template_object.lock().unwrap().borrow_mut().insert(key.to_string(), value);
+        let value = value_provider(&mut slot)?;
+        if slot.remember && !is_internal(key) {
+            remembered.remember(key, &value);
+        }
+        set_nested_value(&mut template_object.lock().unwrap().borrow_mut(), key, value);
     }
     Ok(())
 }
 
-fn try_into_template_slots(
-    TemplateSlotsTable(table): &TemplateSlotsTable,
-) -> Result<IndexMap<&str, TemplateSlots>, ConversionError> {
+/// The definition of the placeholder `key`, with the expressions in its `prompt` and `default`
+/// rendered with the variables known so far.
+fn render_config_values(
+    template_object: &TemplateObjectResource,
+    key: &str,
+    values: &toml::Value,
+    generation: &Arc<Generation>,
+) -> Result<toml::Value> {
+    let mut values = values.clone();
+    if let Some(table) = values.as_table_mut() {
+        let prompt = table
+            .get("prompt")
+            .and_then(toml::Value::as_table)
+            .and_then(locale::localized)
+            .cloned();
+        if let Some(prompt) = prompt {
+            table.insert("prompt".into(), prompt);
+        }
+        for field in ["prompt", "default"] {
+            if let Some(toml::Value::String(value)) = table.get_mut(field) {
+                *value =
+                    render_config_value(template_object, value, generation).with_context(|| {
+                        format!(
+                            "{} {} `{}`",
+                            emoji::ERROR,
+                            style(format!("Failed rendering the {field} of placeholder"))
+                                .bold()
+                                .red(),
+                            style(key).bold().yellow(),
+                        )
+                    })?;
+            }
+        }
+    }
+    Ok(values)
+}
+
+fn try_into_template_slots<'a>(
+    TemplateSlotsTable(table): &'a TemplateSlotsTable,
+    remembered: &RememberedAnswers,
+) -> Result<IndexMap<&'a str, TemplateSlots>, ConversionError> {
     let mut slots = IndexMap::with_capacity(table.len());
     for (key, values) in table.iter() {
-        slots.insert(key.as_str(), try_key_value_into_slot(key, values)?);
+        slots.insert(
+            key.as_str(),
+            try_key_value_into_slot(key, values, remembered)?,
+        );
     }
     Ok(slots)
 }
@@ -230,6 +736,7 @@ fn try_into_template_slots(
 fn try_key_value_into_slot(
     key: &str,
     values: &toml::Value,
+    remembered: &RememberedAnswers,
 ) -> Result<TemplateSlots, ConversionError> {
     if RESERVED_NAMES.contains(&key) {
         return Err(ConversionError::InvalidPlaceholderName {
@@ -244,7 +751,16 @@ fn try_key_value_into_slot(
         })?;
 
     let var_type = extract_type(key, table.get("type"))?;
+    let shape = table
+        .get("type")
+        .and_then(toml::Value::as_str)
+        .and_then(|name| name.parse::<Shape>().ok());
+    let misshapen = |value: &Option<SupportedVarValue>| match (shape, value) {
+        (Some(shape), Some(SupportedVarValue::String(value))) => shape.rejects(value),
+        _ => None,
+    };
     let regex = extract_regex(key, var_type, table.get("regex"))?;
+    let regex_description = extract_regex_description(key, table.get("regex_description"))?;
     let prompt = extract_prompt(key, table.get("prompt"))?;
     let choices = extract_choices(key, var_type, regex.as_ref(), table.get("choices"))?;
     let default_choice = extract_default(
@@ -254,6 +770,36 @@ fn try_key_value_into_slot(
         table.get("default"),
         choices.as_ref(),
     )?;
+    let transform = extract_transform(key, var_type, table.get("transform"))?;
+    let remember = extract_remember(key, table.get("remember"))?;
+    let mut default_from_command =
+        extract_default_from_command(key, table.get("default_from_command"))?;
+    // a remembered answer the placeholder no longer accepts is ignored
+    let remembered_choice = remembered
+        .default_for(key, remember)
+        .and_then(|answer| {
+            extract_default(
+                key,
+                var_type,
+                regex.as_ref(),
+                Some(answer),
+                choices.as_ref(),
+            )
+            .ok()
+            .flatten()
+        })
+        .filter(|answer| misshapen(&Some(answer.clone())).is_none());
+    if remembered_choice.is_some() {
+        // the answer of the user wins over the one derived from their environment
+        default_from_command = None;
+    }
+    if let Some(reason) = misshapen(&default_choice) {
+        return Err(ConversionError::ShapeDoesntMatchDefault {
+            var_name: key.to_string(),
+            reason,
+        });
+    }
+    let default_choice = remembered_choice.or(default_choice);
 
     let var_info = match var_type {
         SupportedVarType::Bool => VarInfo::Bool {
@@ -272,6 +818,9 @@ fn try_key_value_into_slot(
                 },
                 kind: choices.map_or(StringKind::String, StringKind::Choices),
                 regex,
+                regex_description,
+                transform,
+                shape,
             }),
         },
         SupportedVarType::Editor => VarInfo::String {
@@ -283,6 +832,9 @@ fn try_key_value_into_slot(
                 },
                 kind: StringKind::Editor,
                 regex,
+                regex_description,
+                transform,
+                shape: None,
             }),
         },
         SupportedVarType::Array => VarInfo::Array {
@@ -304,6 +856,9 @@ fn try_key_value_into_slot(
                 },
                 kind: StringKind::Text,
                 regex,
+                regex_description,
+                transform,
+                shape: None,
             }),
         },
     };
@@ -311,6 +866,8 @@ fn try_key_value_into_slot(
         var_name: key.to_string(),
         var_info,
         prompt: prompt.into(),
+        remember,
+        default_from_command,
     })
 }
 
@@ -360,6 +917,9 @@ fn extract_type(
         Some(toml::Value::String(value)) if value == "text" => Ok(SupportedVarType::Text),
         Some(toml::Value::String(value)) if value == "bool" => Ok(SupportedVarType::Bool),
         Some(toml::Value::String(value)) if value == "array" => Ok(SupportedVarType::Array),
+        Some(toml::Value::String(value)) if value.parse::<Shape>().is_ok() => {
+            Ok(SupportedVarType::String)
+        }
         Some(toml::Value::String(value)) => Err(ConversionError::InvalidVariableType {
             var_name: var_name.into(),
             value: value.clone(),
@@ -372,23 +932,108 @@ fn extract_type(
     }
 }
 
-fn extract_prompt(
+fn extract_regex_description(
     var_name: &str,
     table_entry: Option<&toml::Value>,
+) -> Result<Option<String>, ConversionError> {
+    table_entry
+        .map(|entry| localized_text(var_name, "regex_description", entry))
+        .transpose()
+}
+
+/// The text of the `parameter` given as a string, or as a table of them by language.
+fn localized_text(
+    var_name: &str,
+    parameter: &str,
+    entry: &toml::Value,
 ) -> Result<String, ConversionError> {
-    match table_entry {
+    let entry = match entry {
+        toml::Value::Table(texts) => locale::localized(texts),
+        entry => Some(entry),
+    };
+    match entry {
         Some(toml::Value::String(value)) => Ok(value.clone()),
-        Some(_) => Err(ConversionError::WrongTypeParameter {
+        _ => Err(ConversionError::WrongTypeParameter {
             var_name: var_name.into(),
-            parameter: "prompt".into(),
+            parameter: parameter.into(),
             correct_type: "String".into(),
         }),
-        None => Err(ConversionError::MissingPrompt {
+    }
+}
+
+fn extract_remember(
+    var_name: &str,
+    table_entry: Option<&toml::Value>,
+) -> Result<bool, ConversionError> {
+    match table_entry {
+        None => Ok(false),
+        Some(toml::Value::Boolean(value)) => Ok(*value),
+        Some(_) => Err(ConversionError::WrongTypeParameter {
             var_name: var_name.into(),
+            parameter: "remember".into(),
+            correct_type: "bool".into(),
         }),
     }
 }
 
+fn extract_default_from_command(
+    var_name: &str,
+    table_entry: Option<&toml::Value>,
+) -> Result<Option<String>, ConversionError> {
+    match table_entry {
+        None => Ok(None),
+        Some(toml::Value::String(command)) => Ok(Some(command.clone())),
+        Some(_) => Err(ConversionError::WrongTypeParameter {
+            var_name: var_name.into(),
+            parameter: "default_from_command".into(),
+            correct_type: "String".into(),
+        }),
+    }
+}
+
+fn extract_transform(
+    var_name: &str,
+    var_type: SupportedVarType,
+    table_entry: Option<&toml::Value>,
+) -> Result<Vec<Transform>, ConversionError> {
+    let wrong_type = || ConversionError::WrongTypeParameter {
+        var_name: var_name.into(),
+        parameter: "transform".into(),
+        correct_type: "String Array".into(),
+    };
+    match (table_entry, var_type) {
+        (None, _) => Ok(Vec::new()),
+        (Some(_), SupportedVarType::Bool | SupportedVarType::Array) => {
+            Err(ConversionError::UnsupportedTransform {
+                var_type: format!("{var_type:?}"),
+            })
+        }
+        (Some(toml::Value::Array(transforms)), _) => transforms
+            .iter()
+            .map(|transform| {
+                let transform = transform.as_str().ok_or_else(wrong_type)?;
+                transform
+                    .parse()
+                    .map_err(|()| ConversionError::UnknownTransform {
+                        var_name: var_name.into(),
+                        transform: transform.into(),
+                    })
+            })
+            .collect(),
+        (Some(_), _) => Err(wrong_type()),
+    }
+}
+
+fn extract_prompt(
+    var_name: &str,
+    table_entry: Option<&toml::Value>,
+) -> Result<String, ConversionError> {
+    let entry = table_entry.ok_or_else(|| ConversionError::MissingPrompt {
+        var_name: var_name.into(),
+    })?;
+    localized_text(var_name, "prompt", entry)
+}
+
 fn extract_default(
     var_name: &str,
     var_type: SupportedVarType,
@@ -1053,6 +1698,15 @@ mod tests {
         )
     }
 
+    #[test]
+    fn prompt_can_be_given_by_language() {
+        let prompts: toml::Value = toml::from_str(r#"en = "License?""#).unwrap();
+        assert_eq!(extract_prompt("foo", Some(&prompts)), Ok("License?".into()));
+
+        let prompts: toml::Value = toml::from_str("en = 0").unwrap();
+        assert!(extract_prompt("foo", Some(&prompts)).is_err());
+    }
+
     #[test]
     fn prompt_must_be_string() {
         let result = extract_prompt("foo", Some(&toml::Value::Integer(0)));
@@ -1156,8 +1810,11 @@ mod tests {
 
     #[test]
     fn block_invalid_key_names() {
-        let result =
-            try_key_value_into_slot("project-name", &toml::Value::Table(Default::default()));
+        let result = try_key_value_into_slot(
+            "project-name",
+            &toml::Value::Table(Default::default()),
+            &RememberedAnswers::default(),
+        );
 
         assert!(result.is_err());
         let result = result.err().unwrap();
@@ -1168,7 +1825,11 @@ mod tests {
             }
         );
 
-        let result = try_key_value_into_slot("crate_name", &toml::Value::Table(Default::default()));
+        let result = try_key_value_into_slot(
+            "crate_name",
+            &toml::Value::Table(Default::default()),
+            &RememberedAnswers::default(),
+        );
 
         assert!(result.is_err());
         let result = result.err().unwrap();
@@ -1182,7 +1843,11 @@ mod tests {
 
     #[test]
     fn only_tables_as_placeholder_values() {
-        let result = try_key_value_into_slot("foo", &toml::Value::Integer(Default::default()));
+        let result = try_key_value_into_slot(
+            "foo",
+            &toml::Value::Integer(Default::default()),
+            &RememberedAnswers::default(),
+        );
 
         assert!(result.is_err());
         let result = result.err().unwrap();
@@ -1193,4 +1858,234 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn remembered_answers_replace_the_default_if_still_valid() {
+        let placeholder = toml::toml! {
+            prompt = "Which one?"
+            choices = ["a", "b"]
+            default = "a"
+            remember = true
+        };
+        let default_with = |answer: &str| {
+            let mut remembered = RememberedAnswers::default();
+            remembered.remember("foo", &serde_json::Value::from(answer));
+            let slot = try_key_value_into_slot(
+                "foo",
+                &toml::Value::Table(placeholder.clone()),
+                &remembered,
+            )
+            .unwrap();
+            assert!(slot.remember);
+            match slot.var_info {
+                VarInfo::String { entry } => entry.default,
+                _ => unreachable!(),
+            }
+        };
+
+        assert_eq!(default_with("b").as_deref(), Some("b"));
+        assert_eq!(default_with("c").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn default_from_command_must_be_a_valid_value() {
+        let placeholder = toml::Value::Table(toml::toml! {
+            prompt = "Which one?"
+            choices = ["a", "b"]
+            default = "a"
+            default_from_command = "echo b"
+        });
+        let mut slot =
+            try_key_value_into_slot("foo", &placeholder, &RememberedAnswers::default()).unwrap();
+        assert_eq!(slot.default_from_command.as_deref(), Some("echo b"));
+
+        assert!(!slot.set_default("c"));
+        assert!(slot.set_default("b"));
+        match slot.var_info {
+            VarInfo::String { entry } => assert_eq!(entry.default.as_deref(), Some("b")),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn shapes_accept_common_values_and_tell_what_is_wrong() {
+        for valid in ["name@example.com", "first.last+tag@mail.example.co.uk"] {
+            assert_eq!(Shape::Email.rejects(valid), None, "{valid}");
+        }
+        for (invalid, problem) in [
+            ("name", "there is no `@`"),
+            ("@example.com", "nothing comes before the `@`"),
+            ("name@", "nothing comes after the `@`"),
+            ("name@localhost", "the domain `localhost` has no `.`"),
+            ("first..last@example.com", "can't start or end with `.`"),
+            ("name@exa_mple.com", "`exa_mple.com` is not a valid host name"),
+        ] {
+            let reason = Shape::Email.rejects(invalid).unwrap();
+            assert!(reason.contains(problem), "{invalid}: {reason}");
+        }
+
+        for valid in [
+            "https://example.com",
+            "http://user@example.com:8080/path?query#fragment",
+            "ssh://[::1]/repo",
+        ] {
+            assert_eq!(Shape::Url.rejects(valid), None, "{valid}");
+        }
+        for (invalid, problem) in [
+            ("example.com", "it has no scheme"),
+            ("https://", "it has no host"),
+            ("https://example.com:http", "the port `http`"),
+            ("1http://example.com", "the scheme `1http`"),
+        ] {
+            let reason = Shape::Url.rejects(invalid).unwrap();
+            assert!(reason.contains(problem), "{invalid}: {reason}");
+        }
+
+        for valid in ["auth", "_private", "Service2"] {
+            assert_eq!(Shape::Ident.rejects(valid), None, "{valid}");
+        }
+        for (invalid, problem) in [
+            ("", "it is empty"),
+            ("2fa", "it starts with `2`"),
+            ("auth-service", "`-` can't be in it"),
+            ("_", "`_` alone"),
+            ("fn", "`fn` is a Rust keyword"),
+        ] {
+            let reason = Shape::Ident.rejects(invalid).unwrap();
+            assert!(reason.contains(problem), "{invalid}: {reason}");
+        }
+    }
+
+    #[test]
+    fn defaults_must_have_the_shape_of_the_placeholder() {
+        let placeholder = toml::Value::Table(toml::toml! {
+            prompt = "Maintainer?"
+            type = "email"
+            default = "nobody"
+        });
+        let err = try_key_value_into_slot("maintainer", &placeholder, &RememberedAnswers::default())
+            .unwrap_err();
+        assert!(matches!(err, ConversionError::ShapeDoesntMatchDefault { .. }));
+    }
+
+    #[test]
+    fn dotted_names_are_nested_and_asked_by_group() {
+        let config = Config::try_from(
+            r#"
+            [placeholders."database.host"]
+            prompt = "Host?"
+            default = "localhost"
+            [placeholders.license]
+            prompt = "License?"
+            default = "MIT"
+            [placeholders."database.port"]
+            prompt = "Port?"
+            default = "5432"
+            "#
+            .to_string(),
+        )
+        .unwrap();
+        let template_object = TemplateObjectResource::default();
+        let asked = std::cell::RefCell::new(vec![]);
+
+        fill_project_variables(
+            &template_object,
+            &config,
+            &mut RememberedAnswers::default(),
+            &Arc::default(),
+            |slot| {
+                asked.borrow_mut().push(slot.var_name.clone());
+                read_default(slot)
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            *asked.borrow(),
+            ["database.host", "database.port", "license"]
+        );
+        let object = template_object.lock().unwrap();
+        let object = object.borrow();
+        assert_eq!(
+            object.get("database"),
+            Some(&serde_json::json!({ "host": "localhost", "port": "5432" }))
+        );
+        assert_eq!(
+            nested_value(&object, "database.port"),
+            Some(&serde_json::json!("5432"))
+        );
+    }
+
+    fn read_default(slot: &mut TemplateSlots) -> Result<serde_json::Value> {
+        match &slot.var_info {
+            VarInfo::String { entry } => Ok(entry.default.clone().unwrap_or_default().into()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn values_of_nested_names_dont_replace_other_values() {
+        let mut object = serde_json::Map::new();
+        object.insert("database".into(), "sqlite".into());
+
+        set_nested_value(&mut object, "database.host", "localhost".into());
+
+        assert_eq!(object.get("database"), Some(&"sqlite".into()));
+        assert_eq!(
+            nested_value(&object, "database.host"),
+            Some(&"localhost".into())
+        );
+    }
+
+    #[test]
+    fn variables_are_set_under_both_spellings() {
+        let mut object = serde_json::Map::new();
+
+        set_nested_value(&mut object, "license-kind", "MIT".into());
+        set_nested_value(&mut object, "ci.run_on", "push".into());
+        set_nested_value(&mut object, "_internal_flag", true.into());
+
+        assert_eq!(object["license_kind"], "MIT");
+        assert_eq!(object["license-kind"], "MIT");
+        assert_eq!(nested_value(&object, "ci.run-on"), Some(&"push".into()));
+        assert_eq!(nested_value(&object, "ci.run_on"), Some(&"push".into()));
+        assert!(!object.contains_key("-internal-flag"));
+        assert_eq!(spellings("a-b_c"), ["a-b_c", "a_b_c", "a-b-c"]);
+        assert!(same_variable("os-arch", "os_arch"));
+        assert!(!same_variable("_a", "-a"));
+    }
+
+    #[test]
+    fn transforms_apply_in_order_and_only_to_strings() {
+        let placeholder = toml::Value::Table(toml::toml! {
+            prompt = "Name?"
+            regex = "^[a-z_]+$"
+            transform = ["trim", "snake_case"]
+        });
+        let slot =
+            try_key_value_into_slot("foo", &placeholder, &RememberedAnswers::default()).unwrap();
+        match &slot.var_info {
+            VarInfo::String { entry } => {
+                assert_eq!(entry.transformed("  My Service ".into()), "my_service")
+            }
+            _ => unreachable!(),
+        }
+
+        let placeholder = toml::Value::Table(toml::toml! {
+            prompt = "Name?"
+            transform = ["reverse"]
+        });
+        let err = try_key_value_into_slot("foo", &placeholder, &RememberedAnswers::default())
+            .unwrap_err();
+        assert!(matches!(err, ConversionError::UnknownTransform { .. }));
+
+        let placeholder = toml::Value::Table(toml::toml! {
+            prompt = "Yes?"
+            type = "bool"
+            transform = ["lower"]
+        });
+        let err = try_key_value_into_slot("foo", &placeholder, &RememberedAnswers::default())
+            .unwrap_err();
+        assert!(matches!(err, ConversionError::UnsupportedTransform { .. }));
+    }
 }
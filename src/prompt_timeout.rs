@@ -0,0 +1,241 @@
+//! `--prompt-timeout`: prompts nobody answers in time take their default, or abort generating if
+//! they have none, so pipelines that don't expect a prompt don't wait for an answer forever.
+//!
+//! Prompts that time out are asked as plain lines, like those of `--accessible`, and their answer
+//! is only read once there is one to read, so nothing is left waiting on the input once the time
+//! is up, to take what is typed for a later prompt. As nobody seems to be there to answer, all
+//! prompts after it take their defaults right away, without asking.
+
+use anyhow::{anyhow, Result};
+use console::style;
+use log::warn;
+use std::{
+    cell::Cell,
+    fmt, io,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::emoji;
+use crate::exit_code::{FailedAt, Failure};
+
+thread_local! {
+    /// When the prompt asked on this thread times out, if it does
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// How long the prompts of a generation wait for an answer.
+#[derive(Debug, Default)]
+pub struct PromptTimeout {
+    /// The seconds to wait for an answer, 0 to wait for as long as it takes
+    seconds: u64,
+    /// Whether a prompt went unanswered
+    timed_out: AtomicBool,
+}
+
+impl PromptTimeout {
+    /// Waits `seconds` for each answer, if given.
+    pub fn new(seconds: Option<u64>) -> Self {
+        Self {
+            seconds: seconds.unwrap_or(0),
+            timed_out: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether prompts time out.
+    pub const fn active(&self) -> bool {
+        self.seconds > 0
+    }
+
+    /// The answer `ask` gets for the placeholder `name`, or `default` if it gets none in time.
+    pub fn ask(
+        &self,
+        name: &str,
+        default: Option<String>,
+        ask: impl FnOnce() -> Result<String>,
+    ) -> Result<String> {
+        let seconds = self.seconds;
+        if !self.timed_out.load(Ordering::SeqCst) {
+            DEADLINE.set(Some(Instant::now() + Duration::from_secs(seconds)));
+            let answer = ask();
+            DEADLINE.set(None);
+            match answer {
+                Err(error) if error.is::<Unanswered>() => {
+                    self.timed_out.store(true, Ordering::SeqCst);
+                }
+                answer => return answer,
+            }
+        }
+
+        let Some(default) = default else {
+            return Err(anyhow!(
+                "{} {} `{}` {}",
+                emoji::ERROR,
+                style("Nobody answered the prompt for").bold().red(),
+                style(name).bold().yellow(),
+                style(format!("within {seconds}s, and it has no default"))
+                    .bold()
+                    .red(),
+            ))
+            .failed_at(Failure::Abort);
+        };
+        warn!(
+            "{} {} `{}` {} `{}`",
+            emoji::WARN,
+            style("Nobody answered the prompt for").bold(),
+            style(name).bold().yellow(),
+            style(format!("within {seconds}s, taking its default")).bold(),
+            style(&default).bold(),
+        );
+        Ok(default)
+    }
+}
+
+/// Whether a prompt that times out is asked on this thread, which is then asked as plain lines.
+pub fn asking() -> bool {
+    DEADLINE.get().is_some()
+}
+
+/// Nothing to read came before the prompt asked timed out.
+#[derive(Debug)]
+struct Unanswered;
+
+impl std::error::Error for Unanswered {}
+
+impl fmt::Display for Unanswered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Nobody answered in time")
+    }
+}
+
+/// Reads a line of the input into `line`, like [`io::Stdin::read_line`], failing if none comes
+/// before the prompt asked times out.
+#[cfg(unix)]
+pub fn read_line(line: &mut String) -> Result<usize> {
+    use std::os::fd::AsRawFd;
+
+    if let Some(deadline) = DEADLINE.get() {
+        wait_for_input(io::stdin().as_raw_fd(), deadline)?;
+    }
+    Ok(io::stdin().read_line(line)?)
+}
+
+/// Waits until there is something to read from `fd`, failing if nothing comes before `deadline`.
+#[cfg(unix)]
+fn wait_for_input(fd: std::os::fd::RawFd, deadline: Instant) -> Result<()> {
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let millis = libc::c_int::try_from(left.as_millis()).unwrap_or(libc::c_int::MAX);
+        let mut input = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `input` is a single valid `pollfd`
+        match unsafe { libc::poll(&mut input, 1, millis) } {
+            // the milliseconds are rounded down
+            0 if Instant::now() < deadline => {}
+            0 => return Err(Unanswered.into()),
+            -1 => {
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(error.into());
+                }
+                crate::interrupt::stop_if_caught()?;
+            }
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// Reads a line of the input into `line`, like [`io::Stdin::read_line`], failing if none comes
+/// before the prompt asked times out.
+///
+/// The input can't be waited for here but by reading it, so the first prompt that times out
+/// starts a single thread reading its lines, which all lines are read from afterwards, that none
+/// is lost.
+#[cfg(not(unix))]
+pub fn read_line(line: &mut String) -> Result<usize> {
+    use std::sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Mutex, OnceLock,
+    };
+    use std::thread;
+
+    static LINES: OnceLock<Mutex<Receiver<io::Result<String>>>> = OnceLock::new();
+
+    let deadline = DEADLINE.get();
+    if deadline.is_none() && LINES.get().is_none() {
+        return Ok(io::stdin().read_line(line)?);
+    }
+    let lines = LINES.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || loop {
+            let mut line = String::new();
+            let read = io::stdin().read_line(&mut line).map(|_| line);
+            let ended = !matches!(&read, Ok(line) if !line.is_empty());
+            if sender.send(read).is_err() || ended {
+                break;
+            }
+        });
+        Mutex::new(receiver)
+    });
+    let lines = lines.lock().map_err(|_| crate::hooks::PoisonError)?;
+    let read = match deadline {
+        Some(deadline) => {
+            match lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(read) => read?,
+                Err(RecvTimeoutError::Timeout) => return Err(Unanswered.into()),
+                // the input has ended
+                Err(RecvTimeoutError::Disconnected) => String::new(),
+            }
+        }
+        None => lines.recv().unwrap_or_else(|_| Ok(String::new()))?,
+    };
+    line.push_str(&read);
+    Ok(read.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unanswered_prompts_take_their_defaults_then_no_more_are_asked() {
+        let timeout = PromptTimeout::new(Some(1));
+        let answer = timeout.ask("license", Some("MIT".into()), || {
+            assert!(asking());
+            Err(Unanswered.into())
+        });
+        assert_eq!(answer.unwrap(), "MIT");
+        assert!(!asking());
+
+        let answer = timeout.ask("owner", None, || unreachable!("asked after a timeout"));
+        assert!(answer.is_err());
+        assert!(!PromptTimeout::new(None).active());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_input_is_waited_for_until_the_deadline() {
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for both ends of the pipe
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let [read, write] = fds;
+
+        let deadline = Instant::now() + Duration::from_millis(100);
+        assert!(wait_for_input(read, deadline)
+            .unwrap_err()
+            .is::<Unanswered>());
+        assert!(Instant::now() >= deadline);
+
+        // SAFETY: `write` is the open end of the pipe, and the byte written is valid
+        assert_eq!(unsafe { libc::write(write, b"\n".as_ptr().cast(), 1) }, 1);
+        assert!(wait_for_input(read, Instant::now() + Duration::from_secs(1)).is_ok());
+        // SAFETY: both ends are open, and closed only here
+        unsafe {
+            libc::close(read);
+            libc::close(write);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! The `regenerate.sh` written into generated projects with `--regenerate-script`: running it
+//! generates the project again, in place, from the same template and ref, with the same answers, so
+//! picking up changes of the template takes a single command.
+//!
+//! Unlike the `.cargo-generate.lock`, it records the answers themselves, to be passed back as a
+//! values file.
+
+use anyhow::Result;
+use std::{fs, path::Path};
+
+use crate::escape::shell_quote;
+use crate::user_parsed_input::{TemplateLocation, UserParsedInput};
+
+pub const SCRIPT_NAME: &str = "regenerate.sh";
+
+/// Writes the script generating the project expanded in `template_dir` again, named
+/// `project_name`, with the `answers` to its placeholders.
+pub fn write(
+    template_dir: &Path,
+    user_parsed_input: &UserParsedInput,
+    project_name: &str,
+    answers: &toml::Table,
+) -> Result<()> {
+    let path = template_dir.join(SCRIPT_NAME);
+    fs::write(
+        &path,
+        script(&command(user_parsed_input, project_name), answers)?,
+    )?;
+    make_executable(&path)
+}
+
+/// The arguments of cargo-generate selecting the template and naming the project, quoted.
+fn command(user_parsed_input: &UserParsedInput, project_name: &str) -> Vec<String> {
+    let mut command = vec!["cargo".to_string(), "generate-mj".to_string()];
+    match user_parsed_input.location() {
+        TemplateLocation::Git(git) => {
+            command.extend(["--git".into(), shell_quote(git.url())]);
+            let reference = [
+                ("--branch", git.branch()),
+                ("--tag", git.tag()),
+                ("--revision", git.revision()),
+            ];
+            for (option, value) in reference {
+                if let Some(value) = value {
+                    command.extend([option.into(), shell_quote(value)]);
+                }
+            }
+        }
+        TemplateLocation::Path(path) => {
+            // the script is run from within the project
+            let path = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+            command.extend(["--path".into(), shell_quote(&path.display().to_string())]);
+        }
+        TemplateLocation::Fetched(url) => command.extend(["--git".into(), shell_quote(url)]),
+    }
+    if let Some(subfolder) = user_parsed_input.subfolder() {
+        command.push(shell_quote(subfolder));
+    }
+    command.extend(["--name".into(), shell_quote(project_name)]);
+    command
+}
+
+fn script(command: &[String], answers: &toml::Table) -> Result<String> {
+    let values = toml::to_string(&toml::Table::from_iter([(
+        "values".to_string(),
+        toml::Value::Table(answers.clone()),
+    )]))?;
+    Ok(format!(
+        r#"#!/bin/sh
+# Generates this project again from its template, with the answers it was generated with,
+# overwriting the files of the template with what they render to now.
+set -e
+cd "$(dirname "$0")"
+values="$(mktemp)"
+trap 'rm -f "$values"' EXIT
+cat > "$values" <<'VALUES'
+{values}VALUES
+{} \
+    --template-values-file "$values" --silent --init --overwrite --regenerate-script "$@"
+"#,
+        command.join(" ")
+    ))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_script_passes_the_answers_as_a_values_file() {
+        let answers: toml::Table = toml::from_str(indoc::indoc! {r#"
+            license = "MIT"
+            "database.host" = "it's local"
+            features = ["cli"]
+        "#})
+        .unwrap();
+        let script = script(
+            &["cargo".into(), "generate-mj".into(), "--name".into(), "foo".into()],
+            &answers,
+        )
+        .unwrap();
+
+        let values = script
+            .split("<<'VALUES'\n")
+            .nth(1)
+            .and_then(|rest| rest.split("VALUES\n").next())
+            .unwrap();
+        let values: toml::Table = toml::from_str(values).unwrap();
+        assert_eq!(values["values"].as_table(), Some(&answers));
+        assert!(script.contains("\ncargo generate-mj --name foo \\\n"));
+    }
+}
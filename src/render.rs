@@ -0,0 +1,43 @@
+//! `render` and `eval`: printing a single file of a template as it would be generated, or the
+//! value of an expression, to check how it comes out without generating the whole project.
+
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::{generate_with, ContextArgs, EvalArgs, GenerateOptions, RenderArgs};
+
+/// What is printed rendered instead of generating a project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenderOnly {
+    /// A file of the template, relative to it
+    File(PathBuf),
+    /// A minijinja expression
+    Expression(String),
+}
+
+/// Resolves the values of the template like generating does, then prints the file of `args`.
+pub fn render(args: &RenderArgs) -> Result<()> {
+    render_only(&args.context, RenderOnly::File(args.file.clone()))
+}
+
+/// Resolves the values of the template like generating does, then prints the value of the
+/// expression of `args`.
+pub fn eval(args: &EvalArgs) -> Result<()> {
+    render_only(
+        &args.context,
+        RenderOnly::Expression(args.expression.clone()),
+    )
+}
+
+fn render_only(context: &ContextArgs, render_only: RenderOnly) -> Result<()> {
+    // hooks running before anything is rendered may write to the project
+    let destination = tempfile::tempdir()?;
+    generate_with(
+        context.generate_args(destination.path().to_path_buf()),
+        GenerateOptions {
+            render_only: Some(render_only),
+            ..GenerateOptions::default()
+        },
+    )?;
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Errors of rendering the files of a template, see [`crate::template::walk_dir`].
+//!
+//! A file that fails to render doesn't stop the others: all errors are collected and reported
+//! at the end, grouped by the phase that failed.
+
+use console::style;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::strict;
+
+/// The step of rendering a file that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderPhase {
+    /// Templating the name of a file or folder
+    Filename,
+    /// Reading and rendering the contents of a file
+    Content,
+    /// Writing, copying or removing a file
+    Write,
+}
+
+impl RenderPhase {
+    const fn heading(self) -> &'static str {
+        match self {
+            Self::Filename => "Failed templating the names of",
+            Self::Content => "Substitution skipped, found invalid syntax in",
+            Self::Write => "Failed writing",
+        }
+    }
+}
+
+/// A file of the template that could not be rendered.
+#[derive(Debug)]
+pub struct RenderError {
+    pub phase: RenderPhase,
+    /// The file, relative to the template
+    pub file: PathBuf,
+    pub cause: anyhow::Error,
+}
+
+impl RenderError {
+    /// Whether the contents of the file would have been left as they are, were rendering not
+    /// strict: they are no invalid syntax to skip then, but a mistake to fix where it is told.
+    fn failed_strictly(&self) -> bool {
+        self.phase == RenderPhase::Content && self.cause.is::<strict::Failed>()
+    }
+
+    fn heading(&self) -> &'static str {
+        if self.failed_strictly() {
+            "Failed rendering strictly"
+        } else {
+            self.phase.heading()
+        }
+    }
+}
+
+pub trait InPhase<T> {
+    /// Attributes the error of a step of rendering `file` to `phase`.
+    fn in_phase(self, phase: RenderPhase, file: &Path) -> Result<T, RenderError>;
+}
+
+impl<T, E: Into<anyhow::Error>> InPhase<T> for Result<T, E> {
+    fn in_phase(self, phase: RenderPhase, file: &Path) -> Result<T, RenderError> {
+        self.map_err(|cause| RenderError {
+            phase,
+            file: file.to_path_buf(),
+            cause: cause.into(),
+        })
+    }
+}
+
+/// All errors of rendering a template, in the order the files were rendered.
+#[derive(Debug)]
+pub struct RenderErrors(pub Vec<RenderError>);
+
+impl std::error::Error for RenderErrors {}
+
+impl fmt::Display for RenderErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut errors = self.0.iter().collect::<Vec<_>>();
+        // stable, so the files of a phase stay in order
+        errors.sort_by_key(|error| error.phase);
+
+        let mut heading = None;
+        for error in errors {
+            if heading != Some(error.heading()) {
+                if heading.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "{}", style(error.heading()).bold().red())?;
+                heading = Some(error.heading());
+            }
+            writeln!(f, "\t{}: {:#}", error.file.display(), error.cause)?;
+        }
+
+        if self
+            .0
+            .iter()
+            .any(|error| error.phase == RenderPhase::Content && !error.failed_strictly())
+        {
+            let hint = style("Consider adding these files to a `cargo-generate.toml` in the template repo to skip substitution on these files.").bold();
+            let read_more =
+                "Learn more: https://github.com/cargo-generate/cargo-generate#include--exclude.";
+            write!(f, "\n{hint}\n\n{read_more}\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors found rendering the names of a template, before anything is written: the files would
+/// not make a valid project, so rendering doesn't even start.
+#[derive(Debug)]
+pub struct InvalidTree(pub RenderErrors);
+
+impl std::error::Error for InvalidTree {}
+
+impl fmt::Display for InvalidTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_are_grouped_by_phase() {
+        let error = |phase, file: &str, cause: &str| RenderError {
+            phase,
+            file: file.into(),
+            cause: anyhow::anyhow!(cause.to_string()),
+        };
+        let report = RenderErrors(vec![
+            error(RenderPhase::Write, "b.txt", "disk full"),
+            error(RenderPhase::Filename, "{{ x }}.rs", "undefined"),
+            error(RenderPhase::Write, "a.txt", "read-only"),
+        ])
+        .to_string();
+        let report = console::strip_ansi_codes(&report);
+
+        assert_eq!(
+            report,
+            "Failed templating the names of\n\t{{ x }}.rs: undefined\n\n\
+             Failed writing\n\tb.txt: disk full\n\ta.txt: read-only\n"
+        );
+    }
+
+    #[test]
+    fn strict_failures_are_no_syntax_to_skip() {
+        let report = RenderErrors(vec![RenderError {
+            phase: RenderPhase::Content,
+            file: "README.md".into(),
+            cause: strict::Failed("undefined value `autor` at line 3, column 7".into()).into(),
+        }])
+        .to_string();
+        let report = console::strip_ansi_codes(&report);
+
+        assert_eq!(
+            report,
+            "Failed rendering strictly\n\tREADME.md: undefined value `autor` at line 3, column 7\n"
+        );
+    }
+}
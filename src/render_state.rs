@@ -0,0 +1,244 @@
+//! State shared by all files rendered for a project, the `state` variable: counters and lists one
+//! file adds to and the next ones read, e.g. `{{ state.incr("service_port", 8000) }}` renders
+//! `8000` in the first file calling it and `8001` in the next, and
+//! `{{ state.append("modules", crate_name) }}` adds to the list `state.modules`.
+//!
+//! Files are rendered in the order they are walked, those matching `render_last` of `[template]`
+//! once all others are, so they see everything the others added, e.g. to render an index of them.
+//! The files generated so far are recorded too, for [`RenderState::mods`] to declare the modules among them.
+
+use indexmap::IndexMap;
+use minijinja::value::{from_args, Enumerator, Object, Value};
+use minijinja::{Error, ErrorKind, State};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The state of the files of a generation, shared by all environments rendering them.
+#[derive(Debug, Default, Clone)]
+pub struct RenderState(Arc<Shared>);
+
+#[derive(Debug, Default)]
+struct Shared {
+    /// The values set so far, by name
+    values: Mutex<IndexMap<String, Value>>,
+    /// The files generated so far, relative to the project
+    generated: Mutex<Vec<PathBuf>>,
+}
+
+impl Shared {
+    fn values(&self) -> MutexGuard<'_, IndexMap<String, Value>> {
+        self.values.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn generated(&self) -> MutexGuard<'_, Vec<PathBuf>> {
+        self.generated.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl RenderState {
+    /// Records that the file at `relative_path` of the project was generated.
+    pub fn add_generated(&self, relative_path: &Path) {
+        self.0.generated().push(relative_path.to_path_buf());
+    }
+
+    /// The files generated so far below the folder `dir` of the project, e.g.
+    /// `{% for file in generated_files("migrations") %}`, with `/` separated paths.
+    pub fn generated_files(&self, dir: &str) -> Vec<String> {
+        let dir = folder(dir);
+        let mut files = self
+            .0
+            .generated()
+            .iter()
+            .filter(|path| path.starts_with(&dir))
+            .map(|path| {
+                let parts = path
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy());
+                parts.collect::<Vec<_>>().join("/")
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    /// Renders a `mod` declaration for each module generated so far in the folder `dir` of the
+    /// project, e.g. `{{ mods("src/services", "pub") }}`: one for every `<name>.rs` in it but
+    /// `mod.rs`, `lib.rs` and `main.rs`, and one for every folder in it with a `mod.rs`. The
+    /// `visibility` given precedes each.
+    pub fn mods(&self, dir: &str, visibility: Option<&str>) -> String {
+        let dir = folder(dir);
+        let mut modules = BTreeSet::new();
+        for path in self.0.generated().iter() {
+            let Ok(path) = path.strip_prefix(&dir) else {
+                continue;
+            };
+            let parts = path
+                .iter()
+                .map(|part| part.to_string_lossy())
+                .collect::<Vec<_>>();
+            match parts.as_slice() {
+                [file] => {
+                    if let Some(name) = file.strip_suffix(".rs") {
+                        if !["mod", "lib", "main"].contains(&name) {
+                            modules.insert(name.to_string());
+                        }
+                    }
+                }
+                [folder, file] if file == "mod.rs" => {
+                    modules.insert(folder.to_string());
+                }
+                _ => {}
+            }
+        }
+        let visibility = visibility
+            .filter(|visibility| !visibility.is_empty())
+            .map(|visibility| format!("{visibility} "))
+            .unwrap_or_default();
+        modules
+            .iter()
+            .map(|name| format!("{visibility}mod {name};\n"))
+            .collect()
+    }
+
+    /// The `state` variable.
+    pub fn value(&self) -> Value {
+        Value::from_object(self.clone())
+    }
+}
+
+/// The folder `dir` of the project, `.` or empty for its root.
+fn folder(dir: &str) -> PathBuf {
+    Path::new(dir)
+        .components()
+        .filter(|part| !matches!(part, Component::CurDir))
+        .collect()
+}
+
+impl Object for RenderState {
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        self.0.values().get(key.as_str()?).cloned()
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        Enumerator::Values(self.0.values().keys().map(Value::from).collect())
+    }
+
+    fn call_method(
+        self: &Arc<Self>,
+        _state: &State,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, Error> {
+        let mut values = self.0.values();
+        match method {
+            // the value of the counter `name`, counting on from `start`, 0 unless given
+            "incr" => {
+                let (name, start): (&str, Option<i64>) = from_args(args)?;
+                let current = match values.get(name) {
+                    None => start.unwrap_or_default(),
+                    Some(value) => i64::try_from(value.clone()).map_err(|_| {
+                        Error::new(
+                            ErrorKind::InvalidOperation,
+                            format!("state `{name}` is not a counter"),
+                        )
+                    })?,
+                };
+                values.insert(name.to_string(), Value::from(current + 1));
+                Ok(Value::from(current))
+            }
+            // adds `value` to the list `name`, rendering nothing
+            "append" => {
+                let (name, value): (&str, Value) = from_args(args)?;
+                let mut items = match values.get(name) {
+                    None => vec![],
+                    Some(list) => list
+                        .try_iter()
+                        .ok()
+                        .filter(|_| list.as_str().is_none())
+                        .ok_or_else(|| {
+                            Error::new(
+                                ErrorKind::InvalidOperation,
+                                format!("state `{name}` is not a list"),
+                            )
+                        })?
+                        .collect(),
+                };
+                items.push(value);
+                values.insert(name.to_string(), Value::from(items));
+                Ok(Value::from(""))
+            }
+            // sets `name` to `value`, rendering nothing
+            "set" => {
+                let (name, value): (&str, Value) = from_args(args)?;
+                values.insert(name.to_string(), value);
+                Ok(Value::from(""))
+            }
+            // the value of `name`, or `default` if nothing set it yet
+            "get" => {
+                let (name, default): (&str, Option<Value>) = from_args(args)?;
+                Ok(values
+                    .get(name)
+                    .cloned()
+                    .or(default)
+                    .unwrap_or(Value::UNDEFINED))
+            }
+            _ => Err(Error::new(
+                ErrorKind::UnknownMethod,
+                format!("state has no method `{method}`"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::Environment;
+
+    #[test]
+    fn renders_share_counters_and_lists() {
+        let state = RenderState::default();
+        let render = |source: &str| {
+            let mut env = Environment::new();
+            env.add_global("state", state.value());
+            env.render_str(source, ()).unwrap()
+        };
+
+        assert_eq!(render(r#"{{ state.incr("port", 8000) }}"#), "8000");
+        assert_eq!(render(r#"{{ state.incr("port", 8000) }}"#), "8001");
+        assert_eq!(render(r#"{{ state.append("mods", "a") }}"#), "");
+        render(r#"{{ state.append("mods", "b") }}"#);
+        assert_eq!(render(r#"{{ state.mods | join(",") }}"#), "a,b");
+        assert_eq!(render(r#"{{ state.get("missing", 1) }}"#), "1");
+        state.0.values().clear();
+        assert_eq!(render(r#"{{ state.incr("port") }}"#), "0");
+    }
+
+    #[test]
+    fn modules_are_declared_for_files_and_folders_with_a_mod_rs() {
+        let state = RenderState::default();
+        for path in [
+            "src/main.rs",
+            "src/services/mod.rs",
+            "src/services/billing.rs",
+            "src/services/auth.rs",
+            "src/services/admin/mod.rs",
+            "src/services/admin/users.rs",
+            "src/services/assets/logo.png",
+            "src/services/README.md",
+        ] {
+            state.add_generated(Path::new(path));
+        }
+
+        assert_eq!(
+            state.mods("./src/services/", Some("pub")),
+            "pub mod admin;\npub mod auth;\npub mod billing;\n"
+        );
+        assert_eq!(state.mods("src", None), "mod services;\n");
+        assert_eq!(
+            state.generated_files("src/services/admin"),
+            ["src/services/admin/mod.rs", "src/services/admin/users.rs"]
+        );
+    }
+}
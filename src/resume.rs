@@ -0,0 +1,342 @@
+//! Getting past network failures: getting a template is retried a few times, waiting longer each
+//! time, and once all placeholders are answered the answers are kept in a state file of the user,
+//! next to the application config, until the project is generated, so `--resume` can reuse them after a hook or
+//! anything else failed, rather than asking for all of them again.
+
+use anyhow::Result;
+use console::style;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::{
+    error::Error,
+    fmt,
+    fs::{self, DirBuilder, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use crate::args::TemplatePath;
+use crate::{emoji, GenerateArgs};
+
+/// How long to wait before each retry.
+const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(4),
+];
+
+/// The kinds of IO errors of networks worth another try.
+const TRANSIENT_IO_ERRORS: [io::ErrorKind; 8] = [
+    io::ErrorKind::TimedOut,
+    io::ErrorKind::ConnectionRefused,
+    io::ErrorKind::ConnectionReset,
+    io::ErrorKind::ConnectionAborted,
+    io::ErrorKind::NotConnected,
+    io::ErrorKind::HostUnreachable,
+    io::ErrorKind::NetworkUnreachable,
+    io::ErrorKind::UnexpectedEof,
+];
+
+/// The HTTP statuses of servers that may answer otherwise another time: timeouts, too many
+/// requests, and bad gateways or unavailability.
+const TRANSIENT_STATUSES: [u16; 5] = [408, 429, 502, 503, 504];
+
+/// The folder next to the application config the state files are kept in.
+const STATE_DIR: &str = "cargo-generate-resume";
+
+/// An error marked as worth another try where it happens, the only place its kind is known,
+/// showing as the error itself.
+#[derive(Debug)]
+pub struct Transient(anyhow::Error);
+
+impl Error for Transient {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl fmt::Display for Transient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Marks `error` as worth another try if `transient`.
+pub fn transient_if(transient: bool, error: anyhow::Error) -> anyhow::Error {
+    if transient {
+        Transient(error).into()
+    } else {
+        error
+    }
+}
+
+/// Whether a server answering with the HTTP `status` may answer otherwise another time.
+pub fn is_transient_status(status: u16) -> bool {
+    TRANSIENT_STATUSES.contains(&status)
+}
+
+/// The answers kept for a template.
+#[derive(Serialize, Deserialize, Debug)]
+struct ResumeState {
+    /// The template, as given
+    template: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project_name: Option<String>,
+    /// The folder created for the project, removed on resuming if the failure left it empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destination: Option<PathBuf>,
+    answers: toml::Table,
+}
+
+/// Runs `get`, trying again after each of [`RETRY_DELAYS`] for as long as it fails the way
+/// networks do.
+pub fn with_retries<T>(what: &str, mut get: impl FnMut() -> Result<T>) -> Result<T> {
+    for delay in RETRY_DELAYS {
+        match get() {
+            Err(error) if is_transient(&error) => {
+                warn!(
+                    "{} {}, {}",
+                    style(format!("Failed to get {what}:")).bold(),
+                    error,
+                    style(format!("retrying in {}s", delay.as_secs())).bold(),
+                );
+                thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+    get()
+}
+
+/// Whether `error` is marked [`Transient`], or comes of a network failing the way it may not
+/// another time.
+fn is_transient(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if cause.is::<Transient>() {
+            return true;
+        }
+        if let Some(error) = cause.downcast_ref::<io::Error>() {
+            return TRANSIENT_IO_ERRORS.contains(&error.kind());
+        }
+        #[cfg(feature = "libgit2")]
+        if let Some(error) = cause.downcast_ref::<git2::Error>() {
+            return error.class() == git2::ErrorClass::Net;
+        }
+        false
+    })
+}
+
+/// Keeps the answers of a project generated from `template_path` next to the application config at
+/// `app_config_path`, `destination` being the folder created for it if any.
+pub fn save(
+    app_config_path: &Path,
+    template_path: &TemplatePath,
+    project_name: &str,
+    destination: Option<PathBuf>,
+    answers: toml::Table,
+) {
+    let Some(template) = template_key(template_path) else {
+        return;
+    };
+    let state = ResumeState {
+        template,
+        project_name: Some(project_name.to_string()),
+        destination,
+        answers,
+    };
+    let saved = toml::to_string(&state)
+        .map_err(anyhow::Error::from)
+        .and_then(|contents| write_state(&state_path(app_config_path, &state.template), &contents));
+    if let Err(error) = saved {
+        warn!(
+            "{} {}",
+            style("The answers can't be resumed, keeping them failed:").bold(),
+            error
+        );
+    }
+}
+
+/// Writes the state file at `path` afresh, readable by the user only as the answers may be
+/// secrets, and never through a link planted in its place.
+fn write_state(path: &Path, contents: &str) -> Result<()> {
+    let mut dir = DirBuilder::new();
+    dir.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut dir, 0o700);
+    if let Some(parent) = path.parent() {
+        dir.create(parent)?;
+    }
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error.into()),
+        _ => {}
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+/// With `--resume`, `args` naming the project as when its answers were kept, unless named
+/// otherwise, and the answers kept.
+pub fn resumed(app_config_path: &Path, args: GenerateArgs) -> (GenerateArgs, toml::Table) {
+    let load = || load(app_config_path, &args.template_path);
+    let Some(state) = args.resume.then(load).flatten() else {
+        return (args, toml::Table::new());
+    };
+    let name = args.name.or(state.project_name);
+    (GenerateArgs { name, ..args }, state.answers)
+}
+
+/// The answers kept for `template_path`.
+fn load(app_config_path: &Path, template_path: &TemplatePath) -> Option<ResumeState> {
+    let state = template_key(template_path)
+        .map(|template| state_path(app_config_path, &template))
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| toml::from_str::<ResumeState>(&contents).ok());
+    let Some(state) = state else {
+        warn!(
+            "{}",
+            style("There are no answers to resume for this template, asking again").bold()
+        );
+        return None;
+    };
+    if let Some(destination) = &state.destination {
+        // only ever empty folders are removed
+        let _ = fs::remove_dir(destination);
+    }
+    Some(state)
+}
+
+/// Forgets the answers kept for `template_path`, once they are no longer needed.
+pub fn clear(app_config_path: &Path, template_path: &TemplatePath) {
+    if let Some(template) = template_key(template_path) {
+        let _ = fs::remove_file(state_path(app_config_path, &template));
+    }
+}
+
+/// Tells how to reuse the answers kept for `template_path`, if there are any.
+pub fn hint(app_config_path: &Path, template_path: &TemplatePath) {
+    let kept = template_key(template_path)
+        .map(|template| state_path(app_config_path, &template))
+        .is_some_and(|path| path.exists());
+    if kept {
+        warn!(
+            "{} {}",
+            emoji::SHRUG,
+            style("The answers are kept, run again with `--resume` to reuse them").bold()
+        );
+    }
+}
+
+/// What tells the template of `template_path` apart, local ones by where they are rather than
+/// how they are given, so `--path .` in different folders are different templates.
+fn template_key(template_path: &TemplatePath) -> Option<String> {
+    let template = template_path
+        .git
+        .as_ref()
+        .or(template_path.path.as_ref())
+        .or(template_path.favorite.as_ref())
+        .or(template_path.auto_path.as_ref())?;
+    let template = fs::canonicalize(template)
+        .map_or_else(|_| template.clone(), |path| path.display().to_string());
+    Some(template_path.subfolder().map_or_else(
+        || template.clone(),
+        |subfolder| format!("{template} {}", subfolder.as_ref()),
+    ))
+}
+
+/// The state file of `template`, one per template so generating several at once can't mix them.
+fn state_path(app_config_path: &Path, template: &str) -> PathBuf {
+    let hash = format!("{:x}", Sha1::digest(template.as_bytes()));
+    app_config_path
+        .with_file_name(STATE_DIR)
+        .join(format!("{}.toml", &hash[..12]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn only_network_failures_are_retried() {
+        let attempts = Cell::new(0);
+        let result: Result<()> = with_retries("nothing", || {
+            attempts.set(attempts.get() + 1);
+            anyhow::bail!("repository not found")
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+
+        let timed_out = anyhow::Error::from(io::Error::from(io::ErrorKind::TimedOut));
+        assert!(is_transient(&timed_out.context("failed to connect")));
+        let not_found = anyhow::Error::from(io::Error::from(io::ErrorKind::NotFound));
+        assert!(!is_transient(&not_found));
+        // a mere mention of a network is no failure of one
+        assert!(!is_transient(&anyhow::anyhow!(
+            "no connection string, 503 lines"
+        )));
+    }
+
+    #[test]
+    fn only_answers_worth_another_try_are_transient() {
+        for (status, transient) in [(503, true), (429, true), (404, false), (401, false)] {
+            let error = transient_if(
+                is_transient_status(status),
+                anyhow::anyhow!("HTTP {status}"),
+            );
+            assert_eq!(is_transient(&error), transient, "{status}");
+            assert_eq!(error.to_string(), format!("HTTP {status}"));
+        }
+    }
+
+    #[test]
+    fn local_templates_are_told_apart_by_where_they_are() {
+        let template = tempfile::tempdir().unwrap();
+        let key = |path: &Path| {
+            template_key(&TemplatePath {
+                path: Some(path.display().to_string()),
+                ..TemplatePath::default()
+            })
+        };
+        let dotted = template.path().join(".");
+        assert_eq!(key(&dotted), key(template.path()));
+        assert_eq!(
+            key(template.path()),
+            Some(
+                template
+                    .path()
+                    .canonicalize()
+                    .unwrap()
+                    .display()
+                    .to_string()
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn state_files_are_the_users_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STATE_DIR).join("state.toml");
+        let elsewhere = dir.path().join("elsewhere");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(&elsewhere, &path).unwrap();
+
+        write_state(&path, "answers = {}").unwrap();
+        assert!(
+            !elsewhere.exists(),
+            "links in place of the state file are not followed"
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "answers = {}");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
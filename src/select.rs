@@ -0,0 +1,241 @@
+//! A prompt choosing one of a list of items, for keyboard-heavy users: besides the arrow keys,
+//! typing the number of an item or the start of its name jumps to it, and long lists are paged.
+
+use anyhow::{bail, Result};
+use dialoguer::console::{Key, Term};
+use dialoguer::theme::Theme;
+use std::ops::Range;
+
+use crate::{accessible, theme};
+
+/// The number of items shown at once, longer lists are paged.
+const PAGE_SIZE: usize = 10;
+
+/// Asks to choose one of `items` with `prompt`, starting at the item `default`, returning the
+/// index of the chosen one.
+pub fn select(prompt: &str, items: &[String], default: usize) -> Result<usize> {
+    if accessible::line_prompts() {
+        return accessible::select(prompt, items, default);
+    }
+    let term = Term::stderr();
+    if !term.is_term() {
+        bail!("not a terminal");
+    }
+    if items.is_empty() {
+        bail!("no items to choose from");
+    }
+
+    let theme = theme::prompt_theme();
+    let mut selection = Selection::new(items, default, PAGE_SIZE);
+    term.hide_cursor()?;
+    let chosen = loop {
+        let lines = render(&selection, &theme, prompt);
+        for line in &lines {
+            term.write_line(line)?;
+        }
+        term.flush()?;
+        let accepted = selection.key(term.read_key()?);
+        term.clear_last_lines(lines.len())?;
+        if accepted {
+            break selection.selected;
+        }
+    };
+    term.show_cursor()?;
+
+    let mut line = String::new();
+    theme.format_select_prompt_selection(&mut line, prompt, &items[chosen])?;
+    term.write_line(&line)?;
+    Ok(chosen)
+}
+
+fn render(selection: &Selection, theme: &dyn Theme, prompt: &str) -> Vec<String> {
+    let mut lines = vec![];
+
+    let mut line = String::new();
+    theme.format_select_prompt(&mut line, prompt).ok();
+    lines.push(format!("{line} {}", selection.typed));
+
+    let width = selection.items.len().to_string().len();
+    for index in selection.page() {
+        let mut line = String::new();
+        let item = format!("{:>width$}) {}", index + 1, selection.items[index]);
+        theme
+            .format_select_prompt_item(&mut line, &item, index == selection.selected)
+            .ok();
+        lines.push(line);
+    }
+
+    let (page, pages) = selection.page_of_pages();
+    if pages > 1 {
+        lines.push(format!("  [page {page}/{pages}, ←/→ to turn pages]"));
+    }
+    lines
+}
+
+/// The state of a [`select`] prompt, apart from the terminal.
+#[derive(Debug)]
+struct Selection<'a> {
+    items: &'a [String],
+    selected: usize,
+    /// What was typed to jump to an item, by its number or the start of its name
+    typed: String,
+    page_size: usize,
+}
+
+impl<'a> Selection<'a> {
+    fn new(items: &'a [String], default: usize, page_size: usize) -> Self {
+        Self {
+            items,
+            selected: default.min(items.len() - 1),
+            typed: String::new(),
+            page_size,
+        }
+    }
+
+    /// Handles `key`, returning whether it accepts the selected item.
+    fn key(&mut self, key: Key) -> bool {
+        let last = self.items.len() - 1;
+        match key {
+            Key::Enter => return true,
+            Key::Char(c) if !c.is_control() => {
+                self.typed.push(c);
+                self.jump();
+                return false;
+            }
+            Key::Backspace => {
+                self.typed.pop();
+                self.jump();
+                return false;
+            }
+            Key::ArrowDown | Key::Tab => {
+                self.selected = (self.selected + 1) % self.items.len();
+            }
+            Key::ArrowUp | Key::BackTab => {
+                self.selected = (self.selected + last) % self.items.len();
+            }
+            Key::ArrowRight | Key::PageDown => {
+                self.selected = (self.selected + self.page_size).min(last);
+            }
+            Key::ArrowLeft | Key::PageUp => {
+                self.selected = self.selected.saturating_sub(self.page_size);
+            }
+            Key::Home => self.selected = 0,
+            Key::End => self.selected = last,
+            _ => return false,
+        }
+        // moving around starts over what's typed
+        self.typed.clear();
+        false
+    }
+
+    /// Selects the item whose number was typed, or the first one starting with what was typed.
+    fn jump(&mut self) {
+        if self.typed.is_empty() {
+            return;
+        }
+        let found = match self.typed.parse::<usize>() {
+            Ok(number) => (1..=self.items.len()).contains(&number).then(|| number - 1),
+            Err(_) => {
+                let typed = self.typed.to_lowercase();
+                self.items
+                    .iter()
+                    .position(|item| item.to_lowercase().starts_with(&typed))
+            }
+        };
+        if let Some(index) = found {
+            self.selected = index;
+        }
+    }
+
+    /// The items on the page of the selected item.
+    fn page(&self) -> Range<usize> {
+        let start = self.selected / self.page_size * self.page_size;
+        start..(start + self.page_size).min(self.items.len())
+    }
+
+    /// The page of the selected item, and the number of pages, counting from 1.
+    const fn page_of_pages(&self) -> (usize, usize) {
+        (
+            self.selected / self.page_size + 1,
+            self.items.len().div_ceil(self.page_size),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn it_starts_at_the_default() {
+        let items = items(&["a", "b", "c"]);
+        assert_eq!(Selection::new(&items, 2, 10).selected, 2);
+        assert_eq!(Selection::new(&items, 7, 10).selected, 2);
+    }
+
+    #[test]
+    fn typing_a_number_jumps_to_that_item() {
+        let items = (1..=12).map(|n| format!("item{n}")).collect::<Vec<_>>();
+        let mut selection = Selection::new(&items, 0, 10);
+
+        assert!(!selection.key(Key::Char('1')));
+        assert_eq!(selection.selected, 0);
+        selection.key(Key::Char('2'));
+        assert_eq!(selection.selected, 11);
+        selection.key(Key::Char('9'));
+        assert_eq!(selection.selected, 11, "there is no item 129");
+        assert!(selection.key(Key::Enter));
+    }
+
+    #[test]
+    fn typing_the_start_of_a_name_jumps_to_the_first_item_starting_so() {
+        let items = items(&["esp32", "esp32c3", "rp2040", "Rp2350"]);
+        let mut selection = Selection::new(&items, 0, 10);
+
+        selection.key(Key::Char('r'));
+        assert_eq!(selection.selected, 2);
+        selection.key(Key::Char('p'));
+        selection.key(Key::Char('2'));
+        selection.key(Key::Char('3'));
+        assert_eq!(selection.selected, 3);
+        selection.key(Key::Backspace);
+        selection.key(Key::Backspace);
+        assert_eq!(selection.selected, 2);
+    }
+
+    #[test]
+    fn moving_around_starts_over_what_is_typed() {
+        let items = items(&["a", "b", "c"]);
+        let mut selection = Selection::new(&items, 0, 10);
+
+        selection.key(Key::Char('c'));
+        selection.key(Key::ArrowUp);
+        assert_eq!(selection.selected, 1);
+        assert!(selection.typed.is_empty());
+        selection.key(Key::ArrowDown);
+        selection.key(Key::ArrowDown);
+        assert_eq!(selection.selected, 0, "moving down wraps around");
+    }
+
+    #[test]
+    fn long_lists_are_paged() {
+        let items = (1..=25).map(|n| n.to_string()).collect::<Vec<_>>();
+        let mut selection = Selection::new(&items, 0, 10);
+        assert_eq!(selection.page(), 0..10);
+        assert_eq!(selection.page_of_pages(), (1, 3));
+
+        selection.key(Key::ArrowRight);
+        selection.key(Key::ArrowRight);
+        assert_eq!(selection.selected, 20);
+        assert_eq!(selection.page(), 20..25);
+        assert_eq!(selection.page_of_pages(), (3, 3));
+        selection.key(Key::ArrowRight);
+        assert_eq!(selection.selected, 24);
+        selection.key(Key::PageUp);
+        assert_eq!(selection.page(), 10..20);
+    }
+}
@@ -0,0 +1,146 @@
+//! Verification of the SSH signatures template authors ship next to `cargo-generate.toml` and the
+//! hook scripts, enforced with `--require-signed`.
+//!
+//! A signature is the `<file>.sig` that `ssh-keygen -Y sign -n cargo-generate` writes, and it is
+//! checked against the public keys trusted in the `[defaults]` of the config file.
+
+use anyhow::{bail, Result};
+use console::style;
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::{emoji, git::tmp_dir};
+
+/// The namespace signatures of templates are made in, so no other signature of the same key fits.
+pub const SIGNATURE_NAMESPACE: &str = "cargo-generate";
+
+/// The principal of all trusted keys in the allowed signers file handed to `ssh-keygen`.
+const PRINCIPAL: &str = "template-author";
+
+fn signature_path(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+/// Fails unless the config file and every hook script of the template is signed by one of
+/// `trusted_keys`.
+pub fn verify_template(
+    config_file: Option<&Path>,
+    template_dir: &Path,
+    hook_files: &[String],
+    trusted_keys: &[String],
+) -> Result<()> {
+    if trusted_keys.is_empty() {
+        bail!(
+            "{} {}",
+            emoji::ERROR,
+            style("Signed templates are required, but no `trusted_keys` are configured")
+                .bold()
+                .red(),
+        );
+    }
+    let Some(config_file) = config_file else {
+        bail!(
+            "{} {}",
+            emoji::ERROR,
+            style("Signed templates are required, but the template has no config file to sign")
+                .bold()
+                .red(),
+        );
+    };
+
+    let signers_dir = tmp_dir()?;
+    let allowed_signers = signers_dir.path().join("allowed_signers");
+    fs::write(
+        &allowed_signers,
+        trusted_keys
+            .iter()
+            .map(|key| format!("{PRINCIPAL} namespaces=\"{SIGNATURE_NAMESPACE}\" {key}\n"))
+            .collect::<String>(),
+    )?;
+
+    let files = std::iter::once(config_file.to_path_buf())
+        .chain(hook_files.iter().map(|file| template_dir.join(file)));
+    for file in files {
+        verify_file(&file, &allowed_signers)?;
+    }
+    Ok(())
+}
+
+fn verify_file(file: &Path, allowed_signers: &Path) -> Result<()> {
+    let signature = signature_path(file);
+    if !signature.exists() {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Signed templates are required, found no signature of")
+                .bold()
+                .red(),
+            style(file.file_name().unwrap_or_default().to_string_lossy()).bold(),
+        );
+    }
+
+    let mut child = Command::new("ssh-keygen")
+        .args([
+            "-Y",
+            "verify",
+            "-n",
+            SIGNATURE_NAMESPACE,
+            "-I",
+            PRINCIPAL,
+            "-f",
+        ])
+        .arg(allowed_signers)
+        .arg("-s")
+        .arg(&signature)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("stdin of ssh-keygen is piped")
+        .write_all(&fs::read(file)?)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "{} {} `{}`: {}",
+            emoji::ERROR,
+            style("Invalid signature of").bold().red(),
+            style(file.file_name().unwrap_or_default().to_string_lossy()).bold(),
+            String::from_utf8_lossy(&output.stderr).trim(),
+        );
+    }
+    Ok(())
+}
+
+/// Removes the signatures of the config file and the hook scripts, they are never part of the
+/// output.
+pub fn remove_signatures(config_file: Option<&Path>, template_dir: &Path, hook_files: &[String]) {
+    let files = config_file
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(hook_files.iter().map(|file| template_dir.join(file)));
+    for file in files {
+        fs::remove_file(signature_path(&file)).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_are_next_to_the_signed_file() {
+        assert_eq!(
+            signature_path(Path::new("hooks/pre.rhai")),
+            Path::new("hooks/pre.rhai.sig")
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Strict rendering, with `--strict` or `strict = true` in `[template]`: a variable that is not
+//! defined fails generating instead of rendering as nothing, and a file or name that fails to
+//! render fails generating instead of being left as it is, telling where it failed.
+//!
+//! Without it, a typo in the name of a variable goes unnoticed until the project is looked at.
+
+use minijinja::{Environment, ErrorKind, Template, UndefinedBehavior, Value};
+use regex::Regex;
+use std::error::Error;
+use std::fmt;
+
+use crate::config::Config;
+
+/// Whether rendering is strict, with `strict`, or as the template in `config` asks for.
+pub fn asked(strict: bool, config: &Config) -> bool {
+    strict || config.template.as_ref().and_then(|t| t.strict) == Some(true)
+}
+
+/// Fails `env` on undefined variables if `strict`.
+pub fn apply(env: &mut Environment, strict: bool) {
+    if strict {
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+    }
+}
+
+/// Whether `env` renders strictly, failing where it would leave a file as it is otherwise.
+pub fn renders_strictly(env: &Environment) -> bool {
+    env.undefined_behavior() == UndefinedBehavior::Strict
+}
+
+/// A file that failed to render strictly, with what failed and where, see [`describe`].
+#[derive(Debug)]
+pub struct Failed(pub String);
+
+impl Error for Failed {}
+
+impl fmt::Display for Failed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// What failed rendering the template `main` of the `source` with `env` and `variables`, and
+/// where, e.g. ``undefined value `projct_name` at line 2, column 12``, naming the template it was
+/// found in unless it is `main`.
+pub fn describe(
+    error: &minijinja::Error,
+    env: &Environment,
+    main: &str,
+    source: &str,
+    variables: &Value,
+) -> String {
+    // the error of an included template is the source of the error including it
+    let mut error = error;
+    while let Some(source) = error
+        .source()
+        .and_then(|source| source.downcast_ref::<minijinja::Error>())
+    {
+        error = source;
+    }
+    let template = error.name().and_then(|name| {
+        if name == main {
+            env.template_from_named_str(main, source).ok()
+        } else {
+            env.get_template(name).ok()
+        }
+    });
+
+    let mut description = error.kind().to_string();
+    if let Some(detail) = error.detail() {
+        description.push_str(&format!(": {detail}"));
+    }
+    let Some((template, line)) = template.zip(error.line()) else {
+        return description;
+    };
+    let source = template.source();
+    let undefined = (error.kind() == ErrorKind::UndefinedError)
+        .then(|| {
+            let text = source.lines().nth(line - 1).unwrap_or_default();
+            first_undefined(&template, env, variables, text)
+        })
+        .flatten();
+    let spanned = error
+        .range()
+        .filter(|range| range.end <= source.len())
+        .map(|range| {
+            let before = &source[..range.start];
+            let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+            (column, source[range].trim().to_string())
+        });
+
+    if let Some((_, name)) = &undefined {
+        description.push_str(&format!(" `{name}`"));
+    }
+    if template.name() != main {
+        description.push_str(&format!(" in `{}`", template.name()));
+    }
+    description.push_str(&format!(" at line {line}"));
+    // what is undefined is where it is first used on the line, not where it failed
+    match (undefined, spanned) {
+        (Some((column, _)), _) => description.push_str(&format!(", column {column}")),
+        (None, Some((column, code))) => {
+            description.push_str(&format!(", column {column}: {code}"));
+        }
+        (None, None) => {}
+    }
+    description
+}
+
+/// The column of the first variable of `template` on its line `text` that is not defined, as
+/// neither one of the `variables` nor a function of `env`, with its name.
+fn first_undefined(
+    template: &Template,
+    env: &Environment,
+    variables: &Value,
+    text: &str,
+) -> Option<(usize, String)> {
+    let is_defined = |name: &str| {
+        env.compile_expression(name)
+            .and_then(|expression| expression.eval(variables))
+            .is_ok_and(|value| !value.is_undefined())
+    };
+    template
+        .undeclared_variables(false)
+        .into_iter()
+        .filter(|name| !is_defined(name))
+        .filter_map(|name| {
+            let word = Regex::new(&format!(r"\b{}\b", regex::escape(&name))).ok()?;
+            let start = word.find(text)?.start();
+            Some((text[..start].chars().count() + 1, name))
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::context;
+
+    fn render_error(source: &str, includes: &[(&str, &str)]) -> String {
+        let mut env = Environment::new();
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        for (name, included) in includes {
+            env.add_template(name, included).unwrap();
+        }
+        let variables = context! { project_name => "foobar" };
+        let error = env
+            .template_from_named_str("file", source)
+            .unwrap()
+            .render(&variables)
+            .unwrap_err();
+        describe(&error, &env, "file", source, &variables)
+    }
+
+    #[test]
+    fn errors_tell_the_line_and_column() {
+        assert_eq!(
+            render_error("[package]\nname = \"{{ projct_name }}\"\n", &[]),
+            "undefined value `projct_name` at line 2, column 12"
+        );
+        assert_eq!(
+            render_error(
+                "{% for x in range(2) %}{{ x }}{% endfor %}\n{{ project_name }} {{ license | upper }}",
+                &[]
+            ),
+            "undefined value `license` at line 2, column 23"
+        );
+    }
+
+    #[test]
+    fn errors_in_includes_tell_the_include() {
+        assert_eq!(
+            render_error(
+                "{% include \"header.md\" %}",
+                &[("header.md", "# {{ titel }}")]
+            ),
+            "undefined value `titel` in `header.md` at line 1, column 6"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Files for some platforms only, declared in `[target.'cfg(...)'.template]` sections, like the
+//! `[target]` tables of Cargo:
+//!
+//! ```toml
+//! [target.'cfg(windows)'.template]
+//! ignore = ["run.sh"]
+//!
+//! [target.'cfg(not(windows))'.template]
+//! ignore = ["run.bat"]
+//! ```
+//!
+//! The `cfg` expressions are those of Cargo, checked against the platform of the built-in
+//! `platform` variable: `windows`, `unix`, and `target_os`, `target_arch`, `target_family` and
+//! `target_endian` compared to a value, combined with `all`, `any` and `not`. Other names never
+//! match, as in Cargo.
+
+use anyhow::{anyhow, Result};
+use console::style;
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::config::ConditionalConfig;
+use crate::emoji;
+use crate::template_variables::get_platform;
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TargetConfig {
+    /// The globs added to those of `[template]` on the platforms of the target
+    pub template: Option<TargetTemplateConfig>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TargetTemplateConfig {
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub ignore: Option<Vec<String>>,
+}
+
+/// A parsed `cfg` expression.
+#[derive(Debug, PartialEq)]
+enum Cfg {
+    Name(String),
+    KeyValue(String, String),
+    All(Vec<Self>),
+    Any(Vec<Self>),
+    Not(Box<Self>),
+}
+
+/// The `[template]` sections of the `targets` matching this platform, as conditionals, with the
+/// names of their sections.
+pub fn matching(
+    targets: Option<IndexMap<String, TargetConfig>>,
+) -> Result<Vec<(String, ConditionalConfig)>> {
+    let platform = get_platform();
+    let mut matching = vec![];
+    for (key, target) in targets.into_iter().flatten() {
+        let cfg = parse(&key).map_err(|cause| {
+            anyhow!(
+                "{} {} `{}`{} {cause}",
+                emoji::ERROR,
+                style("Invalid target").bold().red(),
+                style(&key).bold().yellow(),
+                style(":").bold().red(),
+            )
+        })?;
+        let Some(template) = target.template.filter(|_| cfg.matches(&platform)) else {
+            continue;
+        };
+        let conditional = ConditionalConfig {
+            include: template.include,
+            exclude: template.exclude,
+            ignore: template.ignore,
+            placeholders: None,
+        };
+        matching.push((format!("[target.'{key}'.template]"), conditional));
+    }
+    Ok(matching)
+}
+
+impl Cfg {
+    /// Whether the expression holds for the `platform`, the value of the `platform` variable.
+    fn matches(&self, platform: &serde_json::Value) -> bool {
+        let field = |name: &str| platform[name].as_str().unwrap_or_default();
+        match self {
+            Self::Name(name) => {
+                matches!(name.as_str(), "windows" | "unix") && field("family") == name
+            }
+            Self::KeyValue(key, value) => match key.as_str() {
+                "target_os" => field("os") == value,
+                "target_arch" => field("arch") == value,
+                "target_family" => field("family") == value,
+                "target_endian" => field("endianness") == value,
+                _ => false,
+            },
+            Self::All(cfgs) => cfgs.iter().all(|cfg| cfg.matches(platform)),
+            Self::Any(cfgs) => cfgs.iter().any(|cfg| cfg.matches(platform)),
+            Self::Not(cfg) => !cfg.matches(platform),
+        }
+    }
+}
+
+/// Parses the key of a target, `cfg(...)` around a single expression.
+fn parse(key: &str) -> Result<Cfg, String> {
+    let mut parser = Parser { rest: key };
+    if parser.name() != Some("cfg") || !parser.eat('(') {
+        return Err("only `cfg(...)` expressions are supported".into());
+    }
+    let cfg = parser.expression()?;
+    if !parser.eat(')') {
+        return Err(parser.unexpected());
+    }
+    if !parser.rest.trim().is_empty() {
+        return Err(parser.unexpected());
+    }
+    Ok(cfg)
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn expression(&mut self) -> Result<Cfg, String> {
+        let name = self.name().ok_or_else(|| self.unexpected())?;
+        if self.eat('=') {
+            let value = self.string().ok_or_else(|| self.unexpected())?;
+            return Ok(Cfg::KeyValue(name.into(), value.into()));
+        }
+        if !self.eat('(') {
+            return Ok(Cfg::Name(name.into()));
+        }
+        let mut cfgs = vec![];
+        while !self.eat(')') {
+            cfgs.push(self.expression()?);
+            if !self.eat(',') && !self.rest.trim_start().starts_with(')') {
+                return Err(self.unexpected());
+            }
+        }
+        match name {
+            "all" => Ok(Cfg::All(cfgs)),
+            "any" => Ok(Cfg::Any(cfgs)),
+            "not" if cfgs.len() == 1 => Ok(Cfg::Not(Box::new(cfgs.remove(0)))),
+            "not" => Err("`not` takes exactly one expression".into()),
+            _ => Err(format!("`{name}` is none of `all`, `any` and `not`")),
+        }
+    }
+
+    fn name(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start();
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let (name, rest) = rest.split_at(end);
+        self.rest = rest;
+        (!name.is_empty()).then_some(name)
+    }
+
+    fn string(&mut self) -> Option<&'a str> {
+        let (value, rest) = self.rest.trim_start().strip_prefix('"')?.split_once('"')?;
+        self.rest = rest;
+        Some(value)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.trim_start().strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn unexpected(&self) -> String {
+        match self.rest.trim_start() {
+            "" => "the expression ends too early".into(),
+            rest => format!("unexpected `{rest}`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cfg_expressions_are_parsed() {
+        assert_eq!(parse("cfg(windows)"), Ok(Cfg::Name("windows".into())));
+        assert_eq!(
+            parse(r#"cfg(any(unix, target_os = "windows"))"#),
+            Ok(Cfg::Any(vec![
+                Cfg::Name("unix".into()),
+                Cfg::KeyValue("target_os".into(), "windows".into()),
+            ]))
+        );
+        assert_eq!(
+            parse("cfg( not( all(unix,) ) )"),
+            Ok(Cfg::Not(Box::new(Cfg::All(vec![Cfg::Name("unix".into())]))))
+        );
+        assert!(parse("x86_64-pc-windows-msvc").is_err());
+        assert!(parse("cfg(windows").is_err());
+        assert!(parse("cfg(not(unix, windows))").is_err());
+        assert!(parse("cfg(some(unix))").is_err());
+        assert!(parse("cfg(target_os = linux)").is_err());
+        assert!(parse("cfg(unix) and more").is_err());
+    }
+
+    #[test]
+    fn cfg_expressions_are_checked_against_the_platform() {
+        let platform = serde_json::json!({
+            "os": "linux", "arch": "aarch64", "family": "unix", "endianness": "little",
+        });
+        let matches = |key: &str| parse(key).unwrap().matches(&platform);
+
+        assert!(matches("cfg(unix)"));
+        assert!(!matches("cfg(windows)"));
+        assert!(matches(
+            r#"cfg(all(target_os = "linux", target_arch = "aarch64"))"#
+        ));
+        assert!(matches(
+            r#"cfg(any(target_family = "windows", target_endian = "little"))"#
+        ));
+        assert!(matches(r#"cfg(not(target_os = "macos"))"#));
+        assert!(!matches("cfg(debug_assertions)"));
+        assert!(!matches("cfg(linux)"));
+    }
+}
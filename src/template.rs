@@ -1,36 +1,123 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use console::style;
+use ignore::gitignore::GitignoreBuilder;
+use indexmap::IndexMap;
 use indicatif::{MultiProgress, ProgressBar};
-use minijinja::Environment;
+use log::{info, warn};
+use minijinja::value::{Enumerator, Object, Value};
+use minijinja::{Environment, UndefinedBehavior};
 use std::sync::{Arc, Mutex};
 use std::{
     cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
 };
 use walkdir::{DirEntry, WalkDir};
 
 use crate::config::TemplateConfig;
+use crate::copy::copy_files_recursively;
 use crate::emoji;
-use crate::filenames::substitute_filename;
+use crate::explain_var::Source;
+use crate::filenames::{expand_each_markers, long_path, substitute_filename};
+use crate::generation::Generation;
 use crate::hooks::PoisonError;
 use crate::include_exclude::*;
+use crate::interrupt;
+use crate::keep_source::KeepSource;
+use crate::limits::{Budget, LimitExceeded};
+use crate::merge::MergeRules;
+use crate::profile::{self, Stage};
+use crate::project_variables::{self, insert_variable};
 use crate::progressbar::spinner;
+use crate::render_error::{InPhase, InvalidTree, RenderError, RenderErrors, RenderPhase};
+use crate::strict;
+use crate::template_includes;
 use crate::template_variables::{
-    get_authors, get_os_arch, Authors, CrateName, ProjectDir, ProjectName,
+    date_variables, get_authors, get_lazy_variable, get_os_arch, get_platform, get_repository_url,
+    Authors, CrateName, ProjectDir, ProjectName,
 };
 use crate::user_parsed_input::UserParsedInput;
 
 pub type TemplateObjectResource = Arc<Mutex<RefCell<serde_json::Map<String, serde_json::Value>>>>;
 
-pub fn create_minijinja_engine(
+/// The variables templates are rendered with.
+///
+/// Each variable is looked up in the template object, and converted, only once a template
+/// references it, so rendering a file doesn't copy all variables. Variables that aren't in the
+/// template object may be lazy built-ins, see [`get_lazy_variable`].
+///
+/// The converted variables are kept until any variable changes, so a context made once renders
+/// all files of a generation, converting each variable once.
+#[derive(Debug)]
+pub struct TemplateContext {
+    template_object: TemplateObjectResource,
+    /// The generation rendering, whose aliases stand for variables too
+    generation: Arc<Generation>,
+    /// The variables converted since the [`project_variables::changes`] counted
+    converted: Mutex<(u64, HashMap<String, Value>)>,
+}
+
+impl TemplateContext {
+    pub fn value(template_object: &TemplateObjectResource, generation: &Arc<Generation>) -> Value {
+        Value::from_object(Self {
+            template_object: template_object.clone(),
+            generation: Arc::clone(generation),
+            converted: Mutex::new((project_variables::changes(), HashMap::new())),
+        })
+    }
+
+    fn lookup(&self, name: &str) -> Option<Value> {
+        {
+            // the lock must not be held while rendering, as filters may need it
+            let template_object = self.template_object.lock().ok()?;
+            let template_object = template_object.borrow();
+            if let Some(value) = template_object.get(name) {
+                return Some(Value::from_serialize(value));
+            }
+            let aliased = self.generation.aliases.aliased(name);
+            if let Some(value) = aliased.and_then(|aliased| template_object.get(aliased)) {
+                return Some(Value::from_serialize(value));
+            }
+        }
+        get_lazy_variable(name).map(Value::from_serialize)
+    }
+}
+
+impl Object for TemplateContext {
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        let name = key.as_str()?;
+        let mut converted = self.converted.lock().ok()?;
+        let changes = project_variables::changes();
+        if converted.0 != changes {
+            *converted = (changes, HashMap::new());
+        }
+        if let Some(value) = converted.1.get(name) {
+            return Some(value.clone());
+        }
+        let value = self.lookup(name)?;
+        converted.1.insert(name.to_string(), value.clone());
+        Some(value)
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        self.template_object
+            .lock()
+            .map_or(Enumerator::Empty, |template_object| {
+                Enumerator::Values(template_object.borrow().keys().map(Value::from).collect())
+            })
+    }
+}
+
+pub fn create_minijinja_engine<'source>(
     template_dir: PathBuf,
     template_object: TemplateObjectResource,
     allow_commands: bool,
     silent: bool,
     rhai_filter_files: Arc<Mutex<Vec<PathBuf>>>,
     preserve_whitespace: bool,
-) -> Environment<'static> {
+    generation: &Arc<Generation>,
+) -> Environment<'source> {
     let mut env = Environment::new();
     
     // Enable automatic whitespace stripping for cleaner output (unless preserve_whitespace is true)
@@ -49,7 +136,10 @@ pub fn create_minijinja_engine(
         allow_commands,
         silent,
         rhai_filter_files,
+        generation,
     );
+    strict::apply(&mut env, generation.strict);
+    env.set_loader(template_includes::loader(generation.include_roots.clone()));
     
     env
 }
@@ -59,29 +149,26 @@ pub fn create_template_object(user_parsed_input: &UserParsedInput) -> Result<Tem
     let authors: Authors = get_authors()?;
     let os_arch = get_os_arch();
 
-    let mut template_object = serde_json::Map::new();
-
+    let mut variables = vec![];
     if let Some(name) = user_parsed_input.name() {
-        let name_value = serde_json::Value::from(name.to_owned());
-        template_object.insert("project-name".to_string(), name_value.clone());
-        template_object.insert("project_name".to_string(), name_value);
+        variables.push(("project-name", serde_json::Value::from(name.to_owned())));
     }
+    variables.extend([
+        (
+            "crate_type",
+            serde_json::Value::from(user_parsed_input.crate_type().to_string()),
+        ),
+        ("authors", serde_json::Value::from(authors.author)),
+        ("username", serde_json::Value::from(authors.username)),
+        ("os-arch", serde_json::Value::from(os_arch)),
+        ("platform", get_platform()),
+        ("is_init", serde_json::Value::from(user_parsed_input.init())),
+    ]);
 
-    template_object.insert(
-        "crate_type".to_string(),
-        serde_json::Value::from(user_parsed_input.crate_type().to_string()),
-    );
-    template_object.insert("authors".to_string(), serde_json::Value::from(authors.author));
-    template_object.insert("username".to_string(), serde_json::Value::from(authors.username));
-    
-    let os_arch_value = serde_json::Value::from(os_arch);
-    template_object.insert("os-arch".to_string(), os_arch_value.clone());
-    template_object.insert("os_arch".to_string(), os_arch_value);
-
-    template_object.insert(
-        "is_init".to_string(),
-        serde_json::Value::from(user_parsed_input.init()),
-    );
+    let mut template_object = serde_json::Map::new();
+    for (name, value) in variables {
+        insert_variable(&mut template_object, name, value);
+    }
 
     Ok(Arc::new(Mutex::new(RefCell::new(template_object))))
 }
@@ -90,24 +177,190 @@ pub fn set_project_name_variables(
     template_object: &TemplateObjectResource,
     project_dir: &ProjectDir,
     project_name: &ProjectName,
-    crate_name: &CrateName,
+    crate_name: Option<&CrateName>,
+    generation: &Generation,
 ) -> Result<()> {
     let ref_cell = template_object.lock().map_err(|_| PoisonError)?;
     let mut template_obj = ref_cell.borrow_mut();
 
-    let project_name_value = serde_json::Value::from(project_name.as_ref().to_owned());
-    template_obj.insert("project-name".to_string(), project_name_value.clone());
-    template_obj.insert("project_name".to_string(), project_name_value);
+    let mut variables = vec![(
+        "project-name",
+        serde_json::Value::from(project_name.as_ref().to_owned()),
+    )];
+    // generic projects are no crates
+    if let Some(crate_name) = crate_name {
+        variables.push((
+            "crate_name",
+            serde_json::Value::from(crate_name.as_ref().to_owned()),
+        ));
+        variables.push((
+            "within_cargo_project",
+            serde_json::Value::from(is_within_cargo_project(project_dir.as_ref())),
+        ));
+    }
+    for (name, value) in variables {
+        generation.audit.record(name, &value, Source::BuiltIn);
+        insert_variable(&mut template_obj, name, value);
+    }
+
+    Ok(())
+}
+
+/// Sets the `repository` variable to the inferred repository URL, unless the template or the
+/// user already provided one.
+pub fn set_repository_variable(
+    template_object: &TemplateObjectResource,
+    project_dir: &ProjectDir,
+    project_name: &ProjectName,
+    generation: &Generation,
+) -> Result<()> {
+    let ref_cell = template_object.lock().map_err(|_| PoisonError)?;
+    let mut template_obj = ref_cell.borrow_mut();
+    if template_obj.contains_key("repository") {
+        return Ok(());
+    }
+
+    if let Some(url) = get_repository_url(project_dir.as_ref(), project_name.as_ref()) {
+        generation.audit.record("repository", &url, Source::BuiltIn);
+        insert_variable(&mut template_obj, "repository", serde_json::Value::from(url));
+    }
+
+    Ok(())
+}
+
+/// Sets the variables of the `[computed]` section, in order, each rendered with all variables
+/// known at that point, including the computed ones before it.
+pub fn set_computed_variables(
+    template_dir: &Path,
+    template_object: &TemplateObjectResource,
+    computed: &IndexMap<String, String>,
+    user_parsed_input: &UserParsedInput,
+    generation: &Arc<Generation>,
+) -> Result<()> {
+    let mut env = create_minijinja_engine(
+        template_dir.to_owned(),
+        template_object.clone(),
+        user_parsed_input.allow_commands(),
+        user_parsed_input.silent(),
+        Arc::new(Mutex::new(vec![])),
+        false,
+        generation,
+    );
+    // a typo in an expression should not silently compute an empty value
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+
+    for (name, expression) in computed {
+        if template_object
+            .lock()
+            .map_err(|_| PoisonError)?
+            .borrow()
+            .contains_key(name)
+        {
+            bail!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("Computed variable clashes with an existing variable:")
+                    .bold()
+                    .red(),
+                style(name).bold().yellow(),
+            );
+        }
+        let value = env
+            .render_str(
+                expression,
+                TemplateContext::value(template_object, generation),
+            )
+            .with_context(|| {
+                format!(
+                    "{} {} `{}`",
+                    emoji::ERROR,
+                    style("Failed computing variable").bold().red(),
+                    style(name).bold().yellow(),
+                )
+            })?;
+        generation.audit.record(name, &value, Source::Computed);
+        insert_variable(
+            &mut template_object.lock().map_err(|_| PoisonError)?.borrow_mut(),
+            name,
+            serde_json::Value::from(value),
+        );
+    }
+
+    Ok(())
+}
+
+/// Renders the expressions in a value of `cargo-generate.toml`, like a prompt, with the variables
+/// known so far. Values without any are returned as they are.
+pub fn render_config_value(
+    template_object: &TemplateObjectResource,
+    value: &str,
+    generation: &Arc<Generation>,
+) -> Result<String> {
+    if !value.contains("{{") && !value.contains("{%") {
+        return Ok(value.to_string());
+    }
 
-    template_obj.insert(
-        "crate_name".to_string(),
-        serde_json::Value::from(crate_name.as_ref().to_owned()),
+    let mut env = Environment::new();
+    crate::template_filters::register_all_filters(
+        &mut env,
+        PathBuf::new(),
+        template_object.clone(),
+        false,
+        false,
+        Arc::new(Mutex::new(Vec::new())),
+        generation,
     );
+    // a variable that is not known yet should not silently render as empty
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+
+    Ok(env.render_str(value, TemplateContext::value(template_object, generation))?)
+}
 
-    template_obj.insert(
-        "within_cargo_project".to_string(),
-        serde_json::Value::from(is_within_cargo_project(project_dir.as_ref())),
+/// Evaluates the minijinja `expression`, with the custom filters and functions, against the
+/// variables known so far.
+pub fn eval_expression(
+    template_object: &TemplateObjectResource,
+    expression: &str,
+    generation: &Arc<Generation>,
+) -> Result<Value> {
+    let mut env = Environment::new();
+    crate::template_filters::register_all_filters(
+        &mut env,
+        PathBuf::new(),
+        template_object.clone(),
+        false,
+        false,
+        Arc::new(Mutex::new(Vec::new())),
+        generation,
     );
+    // a typo in a variable name should not silently evaluate to nothing
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+
+    let value = env
+        .compile_expression(expression)?
+        .eval(TemplateContext::value(template_object, generation))?;
+    if value.is_undefined() {
+        bail!(
+            "{} `{}` {}",
+            emoji::ERROR,
+            style(expression).bold(),
+            style("is undefined").bold().red(),
+        );
+    }
+    Ok(value)
+}
+
+/// Sets the `current_*` date variables, all describing the same moment `now`.
+pub fn set_date_variables(
+    template_object: &TemplateObjectResource,
+    generation: &Generation,
+) -> Result<()> {
+    let ref_cell = template_object.lock().map_err(|_| PoisonError)?;
+    let mut template_obj = ref_cell.borrow_mut();
+    for (name, value) in date_variables(generation.generated_at) {
+        generation.audit.record(name, &value, Source::BuiltIn);
+        insert_variable(&mut template_obj, name, value);
+    }
 
     Ok(())
 }
@@ -124,10 +377,12 @@ pub fn walk_dir(
     project_dir: &Path,
     hook_files: &[String],
     template_object: &TemplateObjectResource,
-    rhai_engine: Environment,
+    engine: &Environment,
     rhai_filter_files: &Arc<Mutex<Vec<PathBuf>>>,
     mp: &mut MultiProgress,
     quiet: bool,
+    verbose: bool,
+    generation: &Arc<Generation>,
 ) -> Result<()> {
     fn is_git_metadata(entry: &DirEntry) -> bool {
         entry
@@ -136,21 +391,86 @@ pub fn walk_dir(
             .any(|c| c == std::path::Component::Normal(".git".as_ref()))
     }
 
-    let preserve_whitespace = template_config.preserve_whitespace.unwrap_or(false);
+    fn is_hidden(entry: &DirEntry) -> bool {
+        entry.file_name().to_string_lossy().starts_with('.')
+    }
+
+    // deep trees of rendered names easily exceed the length Windows accepts by default
+    let project_dir = &long_path(project_dir);
+    if template_config.follow_symlinks.unwrap_or(false) {
+        copy_symlinked_dirs(project_dir)?;
+    }
+    let max_depth = template_config.max_depth.unwrap_or(usize::MAX);
+    let render_hidden = template_config.render_hidden.unwrap_or(true);
     let matcher = Matcher::new(template_config, project_dir, hook_files)?;
     let spinner_style = spinner();
 
-    let mut files_with_errors = Vec::new();
-    let files = WalkDir::new(project_dir)
-        .sort_by_file_name()
-        .contents_first(true)
+    let renderer = Renderer {
+        project_dir,
+        matcher: &matcher,
+        template_object,
+        variables: TemplateContext::value(template_object, generation),
+        engine,
+        budget: &generation.budget,
+        generation,
+    };
+
+    // skipped folders are not even descended into, which `contents_first` would; instead the
+    // names are sorted backwards, so reversing the entries lists the contents of each folder
+    // before the folder itself, with names in order
+    let mut files = WalkDir::new(project_dir)
+        .min_depth(1)
+        .max_depth(max_depth)
+        .sort_by(|a, b| b.file_name().cmp(a.file_name()))
         .into_iter()
+        .filter_entry(|e| !is_git_metadata(e) && (render_hidden || !is_hidden(e)))
         .filter_map(Result::ok)
-        .filter(|e| !is_git_metadata(e))
-        .filter(|e| e.path() != project_dir)
         .collect::<Vec<_>>();
-    let total = files.len().to_string();
-    for (progress, entry) in files.into_iter().enumerate() {
+    files.reverse();
+    // nothing is written unless every name renders, and the names make a valid tree
+    let skipped = rhai_filter_files.lock().map_err(|_| PoisonError)?.clone();
+    renderer
+        .budget
+        .add_files(renderer.output_count(&files, &skipped))?;
+    let errors = renderer.check_output_tree(&files, &skipped);
+    if !errors.is_empty() {
+        return Err(InvalidTree(RenderErrors(errors)).into());
+    }
+    if verbose {
+        for entry in files.iter().filter(|entry| entry.file_type().is_file()) {
+            let relative_path = renderer.relative(entry.path());
+            if skipped.iter().any(|skipped| skipped == relative_path) {
+                info!(
+                    "Skipping: {}, a rhai filter script",
+                    relative_path.display()
+                );
+                continue;
+            }
+            let (should_include, rule) = matcher.decide(relative_path);
+            let decision = match should_include {
+                ShouldInclude::Include => "Rendering",
+                ShouldInclude::Exclude => "Copying",
+                ShouldInclude::Ignore => "Skipping",
+            };
+            info!("{decision}: {}, {rule}", relative_path.display());
+        }
+    }
+    let keep_source = KeepSource::new(project_dir, template_config.keep_source.as_ref())?;
+    for entry in files.iter().filter(|entry| entry.file_type().is_file()) {
+        let relative_path = renderer.relative(entry.path());
+        let rendered = matches!(matcher.should_include(relative_path), ShouldInclude::Include)
+            && !skipped.iter().any(|skipped| skipped == relative_path);
+        if rendered && keep_source.keeps(relative_path) {
+            keep_source.keep(project_dir, relative_path)?;
+        }
+    }
+    let (last, files) = renderer.split_render_last(files, template_config.render_last.as_deref())?;
+    let last_from = files.len();
+
+    let mut errors = Vec::new();
+    let total = (files.len() + last.len()).to_string();
+    for (progress, entry) in files.into_iter().chain(last).enumerate() {
+        interrupt::stop_if_caught()?;
         let pb = mp.add(ProgressBar::new(50));
         pb.set_style(spinner_style.clone());
         pb.set_prefix(format!(
@@ -167,11 +487,7 @@ pub fn walk_dir(
         let filename = entry.path();
         let relative_path = filename.strip_prefix(project_dir)?;
         let filename_display = relative_path.display();
-        // Attempt to NOT process files used as liquid rhai filters.
-        // Only works if filter file has been used before an attempt to process it!
-        if rhai_filter_files
-            .lock()
-            .map_err(|_| PoisonError)?
+        if skipped
             .iter()
             .any(|rhai_filter| relative_path.eq(rhai_filter.as_path()))
         {
@@ -182,174 +498,549 @@ pub fn walk_dir(
         }
 
         pb.set_message(format!("Processing: {filename_display}"));
+        let rendered = if progress < last_from {
+            renderer.render_entry(&entry, relative_path, &pb, &mut errors)
+        } else {
+            renderer.render_last(&entry, relative_path, &pb)
+        };
+        match rendered {
+            Ok(message) => pb.finish_with_message(message),
+            // the files after it would exceed the limit as well
+            Err(error) if error.cause.is::<LimitExceeded>() => {
+                pb.finish_with_message(format!("Failed: {filename_display}"));
+                return Err(error.cause);
+            }
+            Err(error) => {
+                pb.finish_with_message(format!("Failed: {filename_display}"));
+                errors.push(error);
+            }
+        }
+    }
 
-        match matcher.should_include(relative_path) {
-            ShouldInclude::Include => {
-                if entry.file_type().is_file() {
-                    match template_process_file(template_object, &rhai_engine, filename, preserve_whitespace) {
-                        Err(e) => {
-                            files_with_errors
-                                .push((relative_path.display().to_string(), e.to_string()));
-                        }
-                        Ok(new_contents) => {
-                            let new_filename =
-                                substitute_filename(filename, &rhai_engine, template_object, preserve_whitespace)
-                                    .with_context(|| {
-                                        format!(
-                                            "{} {} `{}`",
-                                            emoji::ERROR,
-                                            style("Error templating a filename").bold().red(),
-                                            style(filename.display()).bold()
-                                        )
-                                    })?;
-                            pb.inc(25);
-                            let relative_path = new_filename.strip_prefix(project_dir)?;
-                            let f = relative_path.display();
-                            fs::create_dir_all(new_filename.parent().unwrap()).unwrap();
-                            fs::write(new_filename.as_path(), new_contents).with_context(|| {
-                                format!(
-                                    "{} {} `{}`",
-                                    emoji::ERROR,
-                                    style("Error writing rendered file.").bold().red(),
-                                    style(new_filename.display()).bold()
-                                )
-                            })?;
-                            if filename != new_filename {
-                                fs::remove_file(filename)?;
-                            }
-                            pb.inc(50);
-                            pb.finish_with_message(format!("Done: {f}"));
-                        }
-                    }
-                } else {
-                    let new_filename = substitute_filename(filename, &rhai_engine, template_object, preserve_whitespace)?;
-                    let relative_path = new_filename.strip_prefix(project_dir)?;
-                    let f = relative_path.display();
-                    pb.inc(50);
-                    if filename != new_filename {
-                        fs::remove_dir_all(filename)?;
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(RenderErrors(errors).into())
+    }
+}
+
+/// The bytes of the file at `path`, none if they can't be told, as copying it fails then anyway.
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+/// Replaces the symlinked folders below `project_dir` that point into it by copies of their
+/// targets. Others are left alone, as copying the project skips symbolic links.
+fn copy_symlinked_dirs(project_dir: &Path) -> Result<()> {
+    let root = project_dir.canonicalize()?;
+    let links = WalkDir::new(project_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.path_is_symlink() && e.path().is_dir())
+        .map(|e| e.into_path())
+        .collect::<Vec<_>>();
+
+    for link in links {
+        let target = link.canonicalize()?;
+        if !target.starts_with(&root) {
+            warn!(
+                "{} {} `{}`",
+                emoji::WARN,
+                style("Not following symbolic link out of the template:").bold(),
+                style(link.display()).bold(),
+            );
+            continue;
+        }
+        fs::remove_file(&link).or_else(|_| fs::remove_dir(&link))?;
+        fs::create_dir(&link)?;
+        copy_files_recursively(&target, &link, false, &MergeRules::default())?;
+    }
+
+    Ok(())
+}
+
+/// What [`walk_dir`] needs to render the entries of the template.
+struct Renderer<'a> {
+    project_dir: &'a Path,
+    matcher: &'a Matcher,
+    template_object: &'a TemplateObjectResource,
+    /// The context all files are rendered with, but those expanded by `each` markers
+    variables: Value,
+    engine: &'a Environment<'a>,
+    /// What the files rendered may still take
+    budget: &'a Budget,
+    generation: &'a Arc<Generation>,
+}
+
+impl Renderer<'_> {
+    /// Renders the names of all `entries`, except those `skipped`, without writing anything, and
+    /// checks the tree they make: every name must render to a non-empty one, no two files may end
+    /// up at the same path, or a file where a folder is, and nothing may be written where a file
+    /// or folder is that is renamed, as it could be written before that one is moved away.
+    fn check_output_tree(&self, entries: &[DirEntry], skipped: &[PathBuf]) -> Vec<RenderError> {
+        let mut errors = Vec::new();
+        let mut planned = Vec::new();
+        for entry in entries {
+            let Ok(relative_path) = entry.path().strip_prefix(self.project_dir) else {
+                continue;
+            };
+            if skipped.iter().any(|file| file == relative_path)
+                || matches!(
+                    self.matcher.should_include(relative_path),
+                    ShouldInclude::Ignore
+                )
+            {
+                continue;
+            }
+            match self.output_paths(entry, relative_path) {
+                Ok(outputs) => planned.push((relative_path, entry.file_type().is_file(), outputs)),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        let renamed = planned
+            .iter()
+            .filter(|(source, _, outputs)| !outputs.iter().any(|output| output == source))
+            .map(|(source, _, _)| *source)
+            .collect::<HashSet<_>>();
+        let error = |file: &Path, cause| RenderError {
+            phase: RenderPhase::Filename,
+            file: file.to_path_buf(),
+            cause,
+        };
+        // the template file each rendered file comes from
+        let mut files = BTreeMap::<&Path, &Path>::new();
+        let mut folders = HashSet::new();
+        for (source, is_file, outputs) in &planned {
+            for output in outputs {
+                if let Some(taken) = output
+                    .ancestors()
+                    .find(|path| path != source && renamed.contains(path))
+                {
+                    errors.push(error(
+                        source,
+                        anyhow!(
+                            "renders to `{}`, where `{}` is until it is renamed",
+                            output.display(),
+                            taken.display()
+                        ),
+                    ));
+                    continue;
+                }
+                folders.extend(output.ancestors().skip(1));
+                if !is_file {
+                    folders.insert(output);
+                    continue;
+                }
+                if let Some(other) = files.get(output.as_path()) {
+                    let cause = if other == output {
+                        anyhow!("would overwrite `{}`", output.display())
+                    } else if source == output {
+                        anyhow!("would be overwritten by `{}`", other.display())
+                    } else {
+                        anyhow!(
+                            "renders to `{}`, as does `{}`",
+                            output.display(),
+                            other.display()
+                        )
+                    };
+                    errors.push(error(source, cause));
+                    continue;
+                }
+                files.insert(output, source);
+            }
+        }
+
+        for (output, source) in &files {
+            if folders.contains(output) {
+                errors.push(error(
+                    source,
+                    anyhow!("renders to `{}`, which is a folder", output.display()),
+                ));
+            }
+        }
+
+        // a folder whose name fails, fails for all its contents, once is enough
+        let failed = errors
+            .iter()
+            .map(|error| error.file.clone())
+            .collect::<HashSet<_>>();
+        errors.retain(|error| !error.file.ancestors().skip(1).any(|a| failed.contains(a)));
+        errors
+    }
+
+    /// How many files the file `entries`, but those `skipped` or ignored, make, counting those
+    /// with `each` markers once per iteration, without rendering any name.
+    fn output_count(&self, entries: &[DirEntry], skipped: &[PathBuf]) -> u64 {
+        entries
+            .iter()
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| entry.path().strip_prefix(self.project_dir).ok())
+            .filter(|relative_path| {
+                !skipped.iter().any(|file| file == relative_path)
+                    && !matches!(
+                        self.matcher.should_include(relative_path),
+                        ShouldInclude::Ignore
+                    )
+            })
+            .map(|relative_path| {
+                match expand_each_markers(relative_path, self.template_object) {
+                    Ok(Some(iterations)) => iterations.len() as u64,
+                    // names that fail to expand are reported by `check_output_tree`
+                    Ok(None) | Err(_) => 1,
+                }
+            })
+            .sum()
+    }
+
+    /// Where `entry` ends up, relative to the project: once per iteration for files with `each`
+    /// markers, nowhere for such folders, which only hold the files expanded.
+    fn output_paths(
+        &self,
+        entry: &DirEntry,
+        relative_path: &Path,
+    ) -> Result<Vec<PathBuf>, RenderError> {
+        let iterations = expand_each_markers(relative_path, self.template_object)
+            .in_phase(RenderPhase::Filename, relative_path)?;
+        let Some(iterations) = iterations else {
+            let new_filename = self.new_filename(entry.path(), relative_path)?;
+            return Ok(vec![self.relative(&new_filename).to_path_buf()]);
+        };
+        if !entry.file_type().is_file() {
+            return Ok(vec![]);
+        }
+        iterations
+            .into_iter()
+            .map(|(iteration_path, context)| {
+                substitute_filename(
+                    &iteration_path,
+                    self.engine,
+                    &TemplateContext::value(&context, self.generation),
+                )
+                .in_phase(RenderPhase::Filename, &iteration_path)
+            })
+            .collect()
+    }
+
+    /// Renders the file or folder `entry` in place, returning the message to finish its progress
+    /// bar with. The contents of `each` iterations that fail to render are added to `errors`,
+    /// without stopping the other iterations.
+    fn render_entry(
+        &self,
+        entry: &DirEntry,
+        relative_path: &Path,
+        pb: &ProgressBar,
+        errors: &mut Vec<RenderError>,
+    ) -> Result<String, RenderError> {
+        let _profiled = profile::file_scope(relative_path);
+        let filename = entry.path();
+        let filename_display = relative_path.display();
+
+        let iterations = expand_each_markers(relative_path, self.template_object)
+            .in_phase(RenderPhase::Filename, relative_path)?;
+        if let Some(iterations) = iterations {
+            let render = match self.matcher.should_include(relative_path) {
+                ShouldInclude::Include => true,
+                ShouldInclude::Exclude => false,
+                ShouldInclude::Ignore => return Ok(format!("Ignored: {filename_display}")),
+            };
+
+            if !entry.file_type().is_file() {
+                // the files within have been expanded already
+                fs::remove_dir_all(filename).in_phase(RenderPhase::Write, relative_path)?;
+                return Ok(format!("Done: {filename_display}"));
+            }
+            let count = iterations.len();
+            for (iteration_path, context) in iterations {
+                let variables = TemplateContext::value(&context, self.generation);
+                let new_filename = substitute_filename(
+                    &self.project_dir.join(&iteration_path),
+                    self.engine,
+                    &variables,
+                )
+                .in_phase(RenderPhase::Filename, &iteration_path)?;
+                fs::create_dir_all(new_filename.parent().unwrap())
+                    .in_phase(RenderPhase::Write, &iteration_path)?;
+                if !render {
+                    self.budget
+                        .write(self.relative(&new_filename), file_size(filename))
+                        .in_phase(RenderPhase::Write, &iteration_path)?;
+                    fs::copy(filename, &new_filename)
+                        .in_phase(RenderPhase::Write, &iteration_path)?;
+                    self.generation
+                        .render_state
+                        .add_generated(self.relative(&new_filename));
+                    continue;
+                }
+                match template_process_file(&variables, self.engine, filename) {
+                    Err(cause) => errors.push(RenderError {
+                        phase: RenderPhase::Content,
+                        file: iteration_path,
+                        cause,
+                    }),
+                    Ok(new_contents) => {
+                        self.budget
+                            .write(self.relative(&new_filename), new_contents.len() as u64)
+                            .in_phase(RenderPhase::Write, &iteration_path)?;
+                        profile::time_stage(Stage::Write, || fs::write(&new_filename, new_contents))
+                            .in_phase(RenderPhase::Write, &iteration_path)?;
+                        self.generation
+                            .render_state
+                            .add_generated(self.relative(&new_filename));
                     }
-                    pb.inc(50);
-                    pb.finish_with_message(format!("Done: {f}"));
                 }
             }
+            fs::remove_file(filename).in_phase(RenderPhase::Write, relative_path)?;
+            return Ok(format!("Done: {filename_display} ({count} times)"));
+        }
+
+        match self.matcher.should_include(relative_path) {
+            ShouldInclude::Include if entry.file_type().is_file() => {
+                let new_filename = self.new_filename(filename, relative_path)?;
+                self.render_file_to(filename, &new_filename, relative_path, pb)
+            }
+            ShouldInclude::Include | ShouldInclude::Exclude if entry.file_type().is_dir() => {
+                let new_filename = self.new_filename(filename, relative_path)?;
+                pb.inc(50);
+                if filename != new_filename {
+                    self.rename_dir(filename, &new_filename, relative_path)?;
+                }
+                pb.inc(50);
+                Ok(format!("Done: {}", self.display(&new_filename)))
+            }
+            ShouldInclude::Include => {
+                // a symbolic link, which the project doesn't get anyway
+                if filename != self.new_filename(filename, relative_path)? {
+                    fs::remove_file(filename).in_phase(RenderPhase::Write, relative_path)?;
+                }
+                Ok(format!("Skipped: {filename_display}"))
+            }
             ShouldInclude::Exclude => {
-                let new_filename = substitute_filename(filename, &rhai_engine, template_object, preserve_whitespace)?;
-                let mut f = filename_display;
+                let new_filename = self.new_filename(filename, relative_path)?;
+                self.budget
+                    .write(self.relative(&new_filename), file_size(filename))
+                    .in_phase(RenderPhase::Write, relative_path)?;
                 // Check if the file to exclude is in a templated path
                 // If it is, we need to copy it to the new location
-                if filename != new_filename {
-                    let relative_path = new_filename.strip_prefix(project_dir)?;
-                    f = relative_path.display();
-                    fs::create_dir_all(new_filename.parent().unwrap()).unwrap();
-                    fs::copy(filename, new_filename.as_path()).with_context(|| {
-                        format!(
-                            "{} {} `{}`",
-                            emoji::ERROR,
-                            style("Error copying file.").bold().red(),
-                            style(new_filename.display()).bold()
-                        )
-                    })?;
-                    pb.inc(50);
-                    fs::remove_file(filename)?;
-                    pb.inc(50);
+                self.generation
+                    .render_state
+                    .add_generated(self.relative(&new_filename));
+                if filename == new_filename {
+                    return Ok(format!("Skipped: {filename_display}"));
                 }
-                pb.finish_with_message(format!("Skipped: {f}"));
+                fs::create_dir_all(new_filename.parent().unwrap())
+                    .in_phase(RenderPhase::Write, relative_path)?;
+                fs::copy(filename, &new_filename).in_phase(RenderPhase::Write, relative_path)?;
+                pb.inc(50);
+                fs::remove_file(filename).in_phase(RenderPhase::Write, relative_path)?;
+                pb.inc(50);
+                Ok(format!("Skipped: {}", self.display(&new_filename)))
             }
-            ShouldInclude::Ignore => {
-                pb.finish_with_message(format!("Ignored: {filename_display}"));
+            ShouldInclude::Ignore => Ok(format!("Ignored: {filename_display}")),
+        }
+    }
+
+    /// Renders the contents of the file `filename` into `new_filename`, removing `filename` if
+    /// they differ.
+    fn render_file_to(
+        &self,
+        filename: &Path,
+        new_filename: &Path,
+        relative_path: &Path,
+        pb: &ProgressBar,
+    ) -> Result<String, RenderError> {
+        let new_contents = template_process_file(&self.variables, self.engine, filename)
+        .in_phase(RenderPhase::Content, relative_path)?;
+        self.budget
+            .write(self.relative(new_filename), new_contents.len() as u64)
+            .in_phase(RenderPhase::Write, relative_path)?;
+        pb.inc(25);
+        fs::create_dir_all(new_filename.parent().unwrap())
+            .in_phase(RenderPhase::Write, relative_path)?;
+        profile::time_stage(Stage::Write, || fs::write(new_filename, new_contents))
+            .in_phase(RenderPhase::Write, relative_path)?;
+        self.generation
+            .render_state
+            .add_generated(self.relative(new_filename));
+        if filename != new_filename {
+            fs::remove_file(filename).in_phase(RenderPhase::Write, relative_path)?;
+        }
+        pb.inc(50);
+        Ok(format!("Done: {}", self.display(new_filename)))
+    }
+
+    /// Splits the files matching the `render_last` globs off `entries`, keeping the order of
+    /// both. Only files rendered as they are qualify, not those expanded by `each` markers.
+    fn split_render_last(
+        &self,
+        entries: Vec<DirEntry>,
+        render_last: Option<&[String]>,
+    ) -> Result<(Vec<DirEntry>, Vec<DirEntry>)> {
+        let Some(render_last) = render_last.filter(|globs| !globs.is_empty()) else {
+            return Ok((vec![], entries));
+        };
+        let mut builder = GitignoreBuilder::new(self.project_dir);
+        for glob in render_last {
+            builder.add_line(None, glob)?;
+        }
+        let globs = builder.build()?;
+
+        let mut last = vec![];
+        let mut others = vec![];
+        for entry in entries {
+            let relative_path = self.relative(entry.path());
+            let is_last = entry.file_type().is_file()
+                && globs
+                    .matched_path_or_any_parents(relative_path, /* is_dir */ false)
+                    .is_ignore()
+                && matches!(
+                    self.matcher.should_include(relative_path),
+                    ShouldInclude::Include
+                )
+                && matches!(expand_each_markers(relative_path, self.template_object), Ok(None));
+            if is_last {
+                last.push(entry);
+            } else {
+                others.push(entry);
             }
         }
+        Ok((last, others))
     }
 
-    if files_with_errors.is_empty() {
-        Ok(())
-    } else {
-        bail!(print_files_with_errors_warning(files_with_errors))
+    /// Renders the file `entry` matching `render_last`, once all others are rendered: renaming
+    /// the folder it is in has moved it along, so it is rendered from where it was moved to.
+    fn render_last(
+        &self,
+        entry: &DirEntry,
+        relative_path: &Path,
+        pb: &ProgressBar,
+    ) -> Result<String, RenderError> {
+        let _profiled = profile::file_scope(relative_path);
+        let filename = entry.path();
+        let moved_to = match filename.parent() {
+            Some(folder) if folder != self.project_dir => self
+                .new_filename(folder, relative_path)?
+                .join(entry.file_name()),
+            _ => filename.to_path_buf(),
+        };
+        let new_filename = self.new_filename(filename, relative_path)?;
+        self.render_file_to(&moved_to, &new_filename, relative_path, pb)
+    }
+
+    /// Finishes renaming the folder `filename` to `new_filename`, once all of its contents are
+    /// rendered: what is left in it, like the files not walked, is moved over, and the then empty
+    /// folder removed. Nothing is removed along with it, so files already rendered can't be lost.
+    fn rename_dir(
+        &self,
+        filename: &Path,
+        new_filename: &Path,
+        relative_path: &Path,
+    ) -> Result<(), RenderError> {
+        fs::create_dir_all(new_filename).in_phase(RenderPhase::Write, relative_path)?;
+        for left in fs::read_dir(filename).in_phase(RenderPhase::Write, relative_path)? {
+            let left = left.in_phase(RenderPhase::Write, relative_path)?;
+            let destination = new_filename.join(left.file_name());
+            let left_path = relative_path.join(left.file_name());
+            if destination.exists() {
+                return Err(RenderError {
+                    phase: RenderPhase::Write,
+                    file: left_path,
+                    cause: anyhow!("`{}` exists already", self.display(&destination)),
+                });
+            }
+            fs::rename(left.path(), destination).in_phase(RenderPhase::Write, &left_path)?;
+        }
+        fs::remove_dir(filename).in_phase(RenderPhase::Write, relative_path)
+    }
+
+    fn new_filename(&self, filename: &Path, relative_path: &Path) -> Result<PathBuf, RenderError> {
+        substitute_filename(filename, self.engine, &self.variables)
+        .in_phase(RenderPhase::Filename, relative_path)
+    }
+
+    fn display<'p>(&self, path: &'p Path) -> std::path::Display<'p> {
+        self.relative(path).display()
+    }
+
+    fn relative<'p>(&self, path: &'p Path) -> &'p Path {
+        path.strip_prefix(self.project_dir).unwrap_or(path)
     }
 }
 
-fn template_process_file(
-    context: &TemplateObjectResource,
-    parser: &Environment,
+/// Renders the contents of `file` of the template in `template_dir` as generating the project
+/// would, without writing anything.
+pub fn render_file(
+    template_dir: &Path,
     file: &Path,
+    context: &TemplateObjectResource,
+    engine: &Environment,
+    generation: &Arc<Generation>,
+) -> Result<String> {
+    let path = template_dir.join(file);
+    if !path.is_file() {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("The template has no file").bold().red(),
+            style(file.display()).bold(),
+        );
+    }
+    template_process_file(&TemplateContext::value(context, generation), engine, &path)
+}
+
+/// Renders `content` like the files of the template in `template_dir`, with its current variables,
+/// failing where files would be left as they are.
+pub fn render_string(
+    template_dir: &Path,
+    template_object: &TemplateObjectResource,
+    allow_commands: bool,
+    silent: bool,
     preserve_whitespace: bool,
+    content: &str,
+    generation: &Arc<Generation>,
 ) -> Result<String> {
+    let env = create_minijinja_engine(
+        template_dir.to_owned(),
+        template_object.clone(),
+        allow_commands,
+        silent,
+        Arc::default(),
+        preserve_whitespace,
+        generation,
+    );
+    Ok(env.render_str(content, TemplateContext::value(template_object, generation))?)
+}
+
+fn template_process_file(variables: &Value, engine: &Environment, file: &Path) -> Result<String> {
     let content = fs::read_to_string(file)
         .with_context(|| format!("Failed to read file: {}", file.display()))?;
-    render_string_gracefully(context, parser, content.as_str(), preserve_whitespace)
+    render_string_gracefully(variables, engine, content.as_str())
 }
 
+/// The name of the file being rendered, as errors rendering it tell.
+const TEMPLATE_NAME: &str = "__temp_template__";
+
+/// Renders `content` with the `variables` and the `engine` of the generation, returning `content`
+/// as it is if it fails to render, unless the `engine` renders [`strict`]ly.
 pub fn render_string_gracefully(
-    context: &TemplateObjectResource,
-    _parser: &Environment,
+    variables: &Value,
+    engine: &Environment,
     content: &str,
-    preserve_whitespace: bool,
 ) -> Result<String> {
-    // Get the context values
-    let ref_cell = context.lock().map_err(|_| PoisonError)?;
-    let object_map = ref_cell.borrow();
-    
-    // Create a minijinja context from serde_json::Value
-    let context_obj = serde_json::Value::Object(object_map.clone());
-    
-    // Try to render using minijinja's render macro-like behavior
-    // For simple template strings, we can use compile_expression-like behavior or add/get pattern
-    // Create a temporary template
-    let template_name = "__temp_template__";
-    
-    // Clone the parser and add template
-    let mut env = Environment::new();
-    
-    // Enable automatic whitespace stripping for cleaner output (unless preserve_whitespace is true)
-    if !preserve_whitespace {
-        env.set_trim_blocks(true);
-        env.set_lstrip_blocks(true);
-    }
-    
-    // Register filters from the original environment
-    crate::template_filters::register_all_filters(
-        &mut env,
-        std::path::PathBuf::new(),
-        context.clone(),
-        false,
-        false,
-        Arc::new(Mutex::new(Vec::new())),
-    );
-    
-    // Add and compile the template
-    env.add_template(template_name, content)
-        .with_context(|| "Failed to add template".to_string())?;
-    
-    let template = env.get_template(template_name)
-        .with_context(|| "Failed to get template".to_string())?;
-    
-    // Evaluate the template
-    match template.render(context_obj) {
+    let template = profile::time_stage(Stage::Parse, || {
+        engine.template_from_named_str(TEMPLATE_NAME, content)
+    })
+    .with_context(|| "Failed to add template".to_string())?;
+    let rendered = profile::time_stage(Stage::Render, || template.render(variables));
+    match rendered {
         Ok(result) => Ok(result),
-        Err(_e) => {
-            // Gracefully handle errors - always return original content if rendering fails
-            // (either due to undefined variables or other template errors)
-            Ok(content.to_string())
+        Err(e) if strict::renders_strictly(engine) => {
+            let description = strict::describe(&e, engine, TEMPLATE_NAME, content, variables);
+            Err(strict::Failed(description).into())
         }
+        // left as it is, like any file that is no template at all
+        Err(_) => Ok(content.to_string()),
     }
 }
 
-fn print_files_with_errors_warning(files_with_errors: Vec<(String, String)>) -> String {
-    let mut msg = format!(
-        "{}",
-        style("Substitution skipped, found invalid syntax in\n")
-            .bold()
-            .red(),
-    );
-    for file_error in files_with_errors {
-        msg.push('\t');
-        msg.push_str(&file_error.0);
-        msg.push('\n');
-    }
-    let read_more =
-        "Learn more: https://github.com/cargo-generate/cargo-generate#include--exclude.\n\n";
-    let hint = style("Consider adding these files to a `cargo-generate.toml` in the template repo to skip substitution on these files.").bold();
-
-    format!("{msg}\n{hint}\n\n{read_more}")
-}
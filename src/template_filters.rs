@@ -1,16 +1,23 @@
 #![allow(clippy::box_default)]
 
-use heck::{
-    ToKebabCase, ToLowerCamelCase, ToPascalCase, ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase,
-    ToTitleCase, ToUpperCamelCase,
-};
-use minijinja::Environment;
+use minijinja::{Environment, Error, ErrorKind, State, Value};
+use ignore::gitignore::GitignoreBuilder;
+use regex::Regex;
 use std::{
-    path::PathBuf,
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex},
 };
+use walkdir::WalkDir;
 
+use crate::case;
+use crate::dates;
+use crate::escape;
+use crate::generation::Generation;
+use crate::gitignore;
+use crate::interrupt;
+use crate::profile;
 use crate::template::TemplateObjectResource;
+use crate::template_functions;
 use log::warn;
 
 // This file is now mainly a placeholder since minijinja filters are registered
@@ -25,60 +32,244 @@ pub fn register_all_filters(
     _allow_commands: bool,
     _silent: bool,
     _rhai_filter_files: Arc<Mutex<Vec<PathBuf>>>,
+    generation: &Arc<Generation>,
 ) {
     // Register case conversion filters
-    env.add_filter("kebab_case", |s: String| -> String { s.to_kebab_case() });
-    env.add_filter("lower_camel_case", |s: String| -> String { s.to_lower_camel_case() });
-    env.add_filter("pascal_case", |s: String| -> String { s.to_pascal_case() });
-    env.add_filter("shouty_kebab_case", |s: String| -> String { s.to_shouty_kebab_case() });
-    env.add_filter("shouty_snake_case", |s: String| -> String { s.to_shouty_snake_case() });
-    env.add_filter("snake_case", |s: String| -> String { s.to_snake_case() });
-    env.add_filter("title_case", |s: String| -> String { s.to_title_case() });
-    env.add_filter("upper_camel_case", |s: String| -> String { s.to_upper_camel_case() });
+    env.add_filter("kebab_case", case::kebab_case);
+    env.add_filter("lower_camel_case", case::lower_camel_case);
+    env.add_filter("pascal_case", case::pascal_case);
+    env.add_filter("shouty_kebab_case", case::shouty_kebab_case);
+    env.add_filter("shouty_snake_case", case::shouty_snake_case);
+    env.add_filter("snake_case", case::snake_case);
+    env.add_filter("title_case", case::title_case);
+    env.add_filter("upper_camel_case", case::upper_camel_case);
+    env.add_filter("ascii_slug", |s: &str, separator: Option<&str>| -> String {
+        case::ascii_slug(s, separator.unwrap_or("-"))
+    });
+
+    // Register escaping filters - `"{{ description | toml_escape }}"`
+    env.add_filter("toml_escape", escape::toml_escape);
+    env.add_filter("json_escape", escape::json_escape);
+    env.add_filter("yaml_escape", escape::yaml_escape);
+    env.add_filter("rust_string_escape", escape::rust_string_escape);
+    env.add_filter("shell_quote", escape::shell_quote);
     
-    // Register date filter - simple implementation extracting year from date string
-    env.add_filter("date", date_filter);
+    // Register zero_pad filter - `{{ loop_index | zero_pad(4) }}` for numbered files
+    env.add_filter("zero_pad", zero_pad);
+
+    // Register date filter and now function - `{{ now() | date("%B %-d, %Y") }}`
+    dates::add_to(env, generation.generated_at);
     
+    // Register badge function - Markdown badges for READMEs
+    env.add_function("badge", badge);
+
+    // Register get function and filter - `{{ get("ci.provider", "github") }}` for optional values
+    env.add_function("get", get);
+    env.add_filter("get", |value: &Value, path: &str, default: Option<Value>| {
+        get_path(value.clone(), path.split('.'), default)
+    });
+
+    // Register gitignore function - `.gitignore` files from bundled fragments
+    env.add_function("gitignore", gitignore::gitignore);
+
+    // Register the functions of the template - Rhai scripts, `{{ make_port(index) }}`
+    template_functions::add_to(env, &generation.functions);
+
+    // Register the state shared by all files - `{{ state.incr("port", 8000) }}`
+    let render_state = &generation.render_state;
+    env.add_global("state", render_state.value());
+
+    // Register the files generated so far - `{{ mods("src/services", "pub") }}`
+    let state = render_state.clone();
+    env.add_function("generated_files", move |dir: &str| {
+        state.generated_files(dir)
+    });
+    let state = render_state.clone();
+    env.add_function("mods", move |dir: &str, visibility: Option<&str>| {
+        state.mods(dir, visibility)
+    });
+
+    // Register platform tests - `{% if platform is windows %}`
+    for name in ["windows", "linux", "macos", "unix"] {
+        env.add_test(name, move |value: &Value| is_platform(value, name));
+    }
+
     // Register rhai filter - execute rhai scripts
     let template_dir_clone = template_dir.clone();
+    let no_scripts = generation.no_scripts;
     env.add_filter("rhai", move |filename: String| -> String { 
-        rhai_filter(&filename, &template_dir_clone)
+        profile::time_filter(&format!("rhai: {filename}"), || {
+            rhai_filter(&filename, &template_dir_clone, no_scripts)
+        })
     });
 }
 
-fn date_filter(date_str: String, format_str: String) -> String {
-    // Simple date filter that extracts portions of the date string
-    // For now, just handle the common case of extracting the year (%Y)
-    match format_str.as_str() {
-        "%Y" => {
-            // Extract year (first 4 digits)
-            date_str.chars().take(4).collect()
+/// Pads the number `n` with leading zeros to `width` digits, e.g. `{{ 7 | zero_pad(3) }}` is `007`.
+fn zero_pad(n: i64, width: usize) -> String {
+    format!("{n:0width$}")
+}
+
+/// Whether `value`, the `platform` variable or one of its fields, names the operating system or
+/// OS family `name`.
+fn is_platform(value: &Value, name: &str) -> bool {
+    value.as_str().map_or_else(
+        || {
+            ["os", "family"].iter().any(|field| {
+                value
+                    .get_attr(field)
+                    .is_ok_and(|field| field.as_str() == Some(name))
+            })
         },
-        "%m" => {
-            // Extract month (characters 5-6 in YYYY-MM-DD format)
-            if date_str.len() >= 7 {
-                date_str.chars().skip(5).take(2).collect()
+        |value| value == name,
+    )
+}
+
+/// The value at the dotted `path` of the context, e.g. `{{ get("ci.provider", "github") }}`, or
+/// `default`, if given, when any part of it is missing or none.
+///
+/// Parts that are numbers index lists, `{{ get("authors.0") }}`.
+fn get(state: &State, path: &str, default: Option<Value>) -> Value {
+    let mut parts = path.split('.');
+    let root = parts
+        .next()
+        .and_then(|name| state.lookup(name))
+        .unwrap_or_default();
+    get_path(root, parts, default)
+}
+
+fn get_path<'a>(
+    mut value: Value,
+    parts: impl IntoIterator<Item = &'a str>,
+    default: Option<Value>,
+) -> Value {
+    for part in parts {
+        if value.is_undefined() || value.is_none() {
+            break;
+        }
+        value = match part.parse::<i64>() {
+            Ok(index) if value.as_str().is_none() => value.get_item(&Value::from(index)),
+            _ => value.get_attr(part),
+        }
+        .unwrap_or_default();
+    }
+    if value.is_undefined() || value.is_none() {
+        default.unwrap_or(Value::from(()))
+    } else {
+        value
+    }
+}
+
+/// Renders a Markdown badge, e.g. `{{ badge("crates-io", crate_name) }}`.
+///
+/// The crate badges (`crates-io`, `docs-rs`, `downloads`, `license`) take a crate name, the `ci`
+/// badge takes a GitHub or GitLab repository URL, or an `owner/repo` slug for GitHub.
+fn badge(kind: &str, target: &str) -> Result<String, Error> {
+    let badge = match kind {
+        "crates-io" => format!(
+            "[![Crates.io](https://img.shields.io/crates/v/{target}.svg)](https://crates.io/crates/{target})"
+        ),
+        "docs-rs" => format!(
+            "[![Documentation](https://docs.rs/{target}/badge.svg)](https://docs.rs/{target})"
+        ),
+        "downloads" => format!(
+            "[![Downloads](https://img.shields.io/crates/d/{target}.svg)](https://crates.io/crates/{target})"
+        ),
+        "license" => format!(
+            "[![License](https://img.shields.io/crates/l/{target}.svg)](https://crates.io/crates/{target})"
+        ),
+        "ci" => {
+            let url = target.trim_end_matches('/');
+            let url = if url.contains("://") {
+                url.to_string()
             } else {
-                date_str
-            }
-        },
-        "%d" => {
-            // Extract day (characters 8-9 in YYYY-MM-DD format)
-            if date_str.len() >= 10 {
-                date_str.chars().skip(8).take(2).collect()
+                format!("https://github.com/{url}")
+            };
+            if url.starts_with("https://github.com/") {
+                format!("[![CI]({url}/actions/workflows/ci.yml/badge.svg)]({url}/actions/workflows/ci.yml)")
+            } else if url.starts_with("https://gitlab.com/") {
+                format!("[![CI]({url}/badges/main/pipeline.svg)]({url}/-/pipelines)")
             } else {
-                date_str
+                return Err(Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("no CI badge known for repository `{target}`"),
+                ));
             }
-        },
+        }
         _ => {
-            // For unsupported formats, return the original string
-            date_str
+            return Err(Error::new(
+                ErrorKind::InvalidOperation,
+                format!(
+                    "unknown badge `{kind}`, must be one of `crates-io`, `docs-rs`, `downloads`, `license` or `ci`"
+                ),
+            ))
+        }
+    };
+    Ok(badge)
+}
+
+/// Matches the script name of a `rhai` filter applied to a string literal, `{{ "x.rhai" | rhai }}`.
+static RHAI_FILTER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?:"([^"]+)"|'([^']+)')\s*\|\s*rhai\b"#).unwrap());
+
+/// The scripts below `template_dir` used as `rhai` filters, relative to it, so they can be left
+/// out before any file is rendered: those matching the `declared` globs of
+/// `[template.rhai_filters]`, else those any of its files, the config included, is found to use.
+pub fn rhai_filter_files(
+    template_dir: &Path,
+    declared: Option<&Vec<String>>,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let files = template_files(template_dir);
+    let Some(declared) = declared else {
+        let mut used = vec![];
+        for source in files.filter_map(|file| std::fs::read_to_string(file.path()).ok()) {
+            for name in rhai_filter_names(&source) {
+                let file = PathBuf::from(name);
+                if template_dir.join(&file).is_file() && !used.contains(&file) {
+                    used.push(file);
+                }
+            }
         }
+        return Ok(used);
+    };
+
+    let mut builder = GitignoreBuilder::new(template_dir);
+    for glob in declared {
+        builder.add_line(None, glob)?;
     }
+    let matcher = builder.build()?;
+    Ok(files
+        .filter_map(|file| file.path().strip_prefix(template_dir).ok().map(Path::to_path_buf))
+        .filter(|file| {
+            matcher
+                .matched_path_or_any_parents(file, /* is_dir */ false)
+                .is_ignore()
+        })
+        .collect())
 }
 
-fn rhai_filter(filename: &str, template_dir: &PathBuf) -> String {
+fn template_files(template_dir: &Path) -> impl Iterator<Item = walkdir::DirEntry> {
+    WalkDir::new(template_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+}
+
+fn rhai_filter_names(source: &str) -> impl Iterator<Item = &str> {
+    RHAI_FILTER
+        .captures_iter(source)
+        .filter_map(|cap| cap.get(1).or_else(|| cap.get(2)))
+        .map(|name| name.as_str())
+}
+
+fn rhai_filter(filename: &str, template_dir: &Path, no_scripts: bool) -> String {
     use std::fs;
+
+    if no_scripts {
+        // like a script that is not found, the filter expression remains visible
+        return filename.to_string();
+    }
     
     // Construct the full path to the rhai script file
     let script_path = template_dir.join(filename);
@@ -112,7 +303,155 @@ fn rhai_filter(filename: &str, template_dir: &PathBuf) -> String {
 fn execute_rhai_script(script_content: &str) -> Result<String, Box<dyn std::error::Error>> {
     use rhai::Engine;
 
-    let engine = Engine::new();
+    let mut engine = Engine::new();
+    interrupt::stop_when(&mut engine, interrupt::caught);
     let result: rhai::Dynamic = engine.eval(script_content)?;
     Ok(result.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rhai_filters_of_string_literals_are_found() {
+        let source = r#"{{ "a.rhai" | rhai }} {{'scripts/b.rhai'|rhai}} {{ name | rhai }}"#;
+
+        assert_eq!(
+            rhai_filter_names(source).collect::<Vec<_>>(),
+            ["a.rhai", "scripts/b.rhai"]
+        );
+        assert_eq!(rhai_filter_names(r#"{{ "a" | rhai_like }}"#).count(), 0);
+    }
+
+    #[test]
+    fn badge_renders_crate_and_ci_badges() {
+        assert_eq!(
+            badge("docs-rs", "foo").unwrap(),
+            "[![Documentation](https://docs.rs/foo/badge.svg)](https://docs.rs/foo)"
+        );
+        assert_eq!(
+            badge("ci", "foo/bar").unwrap(),
+            badge("ci", "https://github.com/foo/bar/").unwrap()
+        );
+        assert!(badge("ci", "https://gitlab.com/foo/bar")
+            .unwrap()
+            .contains("https://gitlab.com/foo/bar/badges/main/pipeline.svg"));
+        assert!(badge("ci", "https://example.com/foo/bar").is_err());
+        assert!(badge("coverage", "foo").is_err());
+    }
+
+    #[test]
+    fn badge_is_available_in_templates() {
+        let mut env = Environment::new();
+        register_all_filters(
+            &mut env,
+            PathBuf::new(),
+            TemplateObjectResource::default(),
+            false,
+            true,
+            Default::default(),
+            &Arc::default(),
+        );
+
+        let rendered = env
+            .render_str(
+                r#"{{ badge("crates-io", name) }}"#,
+                minijinja::context! { name => "foo" },
+            )
+            .unwrap();
+        assert_eq!(
+            rendered,
+            "[![Crates.io](https://img.shields.io/crates/v/foo.svg)](https://crates.io/crates/foo)"
+        );
+    }
+
+    #[test]
+    fn gitignore_is_available_in_templates() {
+        let mut env = Environment::new();
+        register_all_filters(
+            &mut env,
+            PathBuf::new(),
+            TemplateObjectResource::default(),
+            false,
+            true,
+            Default::default(),
+            &Arc::default(),
+        );
+
+        let rendered = env
+            .render_str(
+                r#"{{ gitignore(["rust", "macos"]) }}"#,
+                minijinja::context! {},
+            )
+            .unwrap();
+        assert!(rendered.starts_with("# rust\n"), "{rendered}");
+        assert!(rendered.contains("\n# macos\n.DS_Store\n"), "{rendered}");
+    }
+
+    #[test]
+    fn get_walks_nested_values_with_a_default() {
+        let mut env = Environment::new();
+        register_all_filters(
+            &mut env,
+            PathBuf::new(),
+            TemplateObjectResource::default(),
+            false,
+            true,
+            Default::default(),
+            &Arc::default(),
+        );
+        let ctx = minijinja::context! {
+            ci => minijinja::context! { provider => "gitlab", os => vec!["linux", "macos"] },
+            empty => (),
+        };
+
+        for (template, expected) in [
+            (r#"{{ get("ci.provider", "github") }}"#, "gitlab"),
+            (r#"{{ get("ci.os.1") }}"#, "macos"),
+            (r#"{{ get("ci.branch", "main") }}"#, "main"),
+            (r#"{{ get("cd.provider", "github") }}"#, "github"),
+            (r#"{{ get("empty.value", 1) }}"#, "1"),
+            (r#"{{ get("ci.branch") is none }}"#, "true"),
+            (r#"{{ ci | get("os.0", "windows") }}"#, "linux"),
+        ] {
+            assert_eq!(env.render_str(template, &ctx).unwrap(), expected, "{template}");
+        }
+    }
+
+    #[test]
+    fn zero_pad_pads_to_the_width() {
+        assert_eq!(zero_pad(7, 4), "0007");
+        assert_eq!(zero_pad(12345, 4), "12345");
+        assert_eq!(zero_pad(-7, 3), "-07");
+    }
+
+    #[test]
+    fn platform_tests_match_os_and_family() {
+        let mut env = Environment::new();
+        register_all_filters(
+            &mut env,
+            PathBuf::new(),
+            TemplateObjectResource::default(),
+            false,
+            true,
+            Default::default(),
+            &Arc::default(),
+        );
+        let template = "{% if platform is windows %}bat{% elif platform is unix %}sh{% endif %}";
+
+        for (os, family, expected) in [
+            ("windows", "windows", "bat"),
+            ("linux", "unix", "sh"),
+            ("macos", "unix", "sh"),
+        ] {
+            let platform = minijinja::context! { os => os, family => family };
+            let rendered = env
+                .render_str(template, minijinja::context! { platform => platform })
+                .unwrap();
+            assert_eq!(rendered, expected);
+        }
+        assert!(is_platform(&Value::from("linux"), "linux"));
+        assert!(!is_platform(&Value::from("linux"), "unix"));
+    }
+}
@@ -0,0 +1,172 @@
+//! Template functions implemented in Rhai, declared in `[template.functions]`, e.g.
+//! `make_port = "hooks/make_port.rhai"`: `{{ make_port(service_index) }}` calls the function
+//! `make_port` of the script with the arguments, and renders the value it returns.
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use minijinja::value::{Rest, Value, ValueKind};
+use minijinja::{Environment, Error, ErrorKind};
+use rhai::{Array, Dynamic, Engine, Map, Scope};
+use std::path::Path;
+
+use crate::{interrupt, profile};
+
+/// A function of a template, the function of its name in a script of the template.
+#[derive(Debug, Clone)]
+pub struct TemplateFunction {
+    name: String,
+    /// The script, relative to the template
+    script: String,
+    source: String,
+}
+
+/// The `functions` of the template in `template_dir`, reading their scripts.
+pub fn load(
+    template_dir: &Path,
+    functions: Option<&IndexMap<String, String>>,
+) -> Result<Vec<TemplateFunction>> {
+    let mut loaded = vec![];
+    for (name, script) in functions.into_iter().flatten() {
+        let source = std::fs::read_to_string(template_dir.join(script)).with_context(|| {
+            format!("Failed to read the script of the function `{name}`: {script}")
+        })?;
+        loaded.push(TemplateFunction {
+            name: name.clone(),
+            script: script.clone(),
+            source,
+        });
+    }
+    Ok(loaded)
+}
+
+/// Adds the `functions` of the template to `env`.
+pub fn add_to(env: &mut Environment, functions: &[TemplateFunction]) {
+    for function in functions.iter().cloned() {
+        env.add_function(function.name.clone(), move |args: Rest<Value>| {
+            profile::time_filter(&format!("{}()", function.name), || function.call(&args))
+        });
+    }
+}
+
+impl TemplateFunction {
+    fn call(&self, args: &[Value]) -> Result<Value, Error> {
+        let failed = |cause: String| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                format!(
+                    "function `{}` of {} failed: {cause}",
+                    self.name, self.script
+                ),
+            )
+        };
+        let mut engine = Engine::new();
+        interrupt::stop_when(&mut engine, interrupt::caught);
+        let ast = engine
+            .compile(&self.source)
+            .map_err(|e| failed(e.to_string()))?;
+        let args = args.iter().map(to_dynamic).collect::<Vec<_>>();
+        let result = engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &ast, &self.name, args)
+            .map_err(|e| failed(e.to_string()))?;
+        Ok(from_dynamic(result))
+    }
+}
+
+fn to_dynamic(value: &Value) -> Dynamic {
+    match value.kind() {
+        ValueKind::Undefined | ValueKind::None => Dynamic::UNIT,
+        ValueKind::Bool => Dynamic::from(value.is_true()),
+        ValueKind::Number => i64::try_from(value.clone()).map_or_else(
+            |_| Dynamic::from(f64::try_from(value.clone()).unwrap_or_default()),
+            Dynamic::from,
+        ),
+        ValueKind::Seq => value
+            .try_iter()
+            .map(|items| Dynamic::from(items.map(|item| to_dynamic(&item)).collect::<Array>()))
+            .unwrap_or(Dynamic::UNIT),
+        ValueKind::Map => value
+            .try_iter()
+            .map(|keys| {
+                let map = keys
+                    .map(|key| {
+                        let item = value.get_item(&key).unwrap_or_default();
+                        (key.to_string().into(), to_dynamic(&item))
+                    })
+                    .collect::<Map>();
+                Dynamic::from(map)
+            })
+            .unwrap_or(Dynamic::UNIT),
+        _ => Dynamic::from(
+            value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string),
+        ),
+    }
+}
+
+fn from_dynamic(value: Dynamic) -> Value {
+    if value.is_unit() {
+        return Value::from(());
+    }
+    if let Ok(b) = value.as_bool() {
+        return Value::from(b);
+    }
+    if let Ok(i) = value.as_int() {
+        return Value::from(i);
+    }
+    if let Ok(f) = value.as_float() {
+        return Value::from(f);
+    }
+    if value.is_array() {
+        let items = value.cast::<Array>();
+        return items.into_iter().map(from_dynamic).collect();
+    }
+    if value.is_map() {
+        let map = value.cast::<Map>();
+        return map
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), from_dynamic(value)))
+            .collect();
+    }
+    Value::from(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_are_called_with_the_arguments() {
+        let function = TemplateFunction {
+            name: "make_port".into(),
+            script: "make_port.rhai".into(),
+            source: r#"
+                fn make_port(index, service) {
+                    #{ port: 8000 + index, name: service.name + "-svc", tags: [true, ()] }
+                }
+            "#
+            .into(),
+        };
+        let service = Value::from_iter([("name", "auth")]);
+
+        let value = function.call(&[Value::from(2), service]).unwrap();
+
+        assert_eq!(value.get_attr("port").unwrap(), Value::from(8002));
+        assert_eq!(value.get_attr("name").unwrap(), Value::from("auth-svc"));
+        assert_eq!(
+            value.get_attr("tags").unwrap(),
+            Value::from(vec![Value::from(true), Value::from(())])
+        );
+    }
+
+    #[test]
+    fn missing_functions_are_errors() {
+        let function = TemplateFunction {
+            name: "make_port".into(),
+            script: "make_port.rhai".into(),
+            source: "fn other() { 1 }".into(),
+        };
+        let error = function.call(&[]).unwrap_err();
+        assert!(error.to_string().contains("make_port.rhai"), "{error}");
+    }
+}
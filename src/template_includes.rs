@@ -0,0 +1,187 @@
+//! Templates referenced by others, e.g. `{% include "partials/header.html" %}`, looked up in the
+//! template itself and in the include roots declared in `[template.include_roots]` or given with
+//! `--include-root`, the first root having a file of that name winning.
+//!
+//! The template is set aside before any of it is rendered or removed, so snippets the template
+//! ignores can still be included, as they are. Templates are read from the roots by name only as
+//! rendering asks for them, so names computed from variables, e.g. `{% extends layout %}`, are
+//! resolved as well.
+
+use anyhow::{bail, Result};
+use console::style;
+use minijinja::path_loader;
+use std::{
+    fmt, fs,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+use crate::emoji;
+use crate::git::tmp_dir;
+
+type Loader = Box<dyn Fn(&str) -> Result<Option<String>, minijinja::Error> + Send + Sync>;
+
+/// The include roots of a template.
+pub struct IncludeRoots {
+    /// A loader for each root, in order
+    loaders: Vec<Loader>,
+    /// Where the template was set aside, for the roots within it
+    _set_aside: TempDir,
+}
+
+impl fmt::Debug for IncludeRoots {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncludeRoots")
+            .field("roots", &self.loaders.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl IncludeRoots {
+    fn load(&self, name: &str) -> Result<Option<String>, minijinja::Error> {
+        for loader in &self.loaders {
+            if let Some(source) = loader(name)? {
+                return Ok(Some(source));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The include roots of the template in `template_dir`.
+///
+/// The `configured` roots are relative to the template and must stay within the repository
+/// `template_base_dir`, `.` being the template itself, which comes first unless listed. The
+/// `given` roots, from the command line, come last.
+pub fn roots(
+    template_base_dir: &Path,
+    template_dir: &Path,
+    configured: Option<&Vec<String>>,
+    given: &[PathBuf],
+) -> Result<IncludeRoots> {
+    let mut roots = vec![];
+    let configured = configured.map(Vec::as_slice).unwrap_or_default();
+    if !configured.iter().any(|root| is_template_itself(root)) {
+        roots.push(template_dir.to_path_buf());
+    }
+    for root in configured {
+        let path = template_dir.join(root);
+        let escapes = fs::canonicalize(&path)
+            .ok()
+            .zip(fs::canonicalize(template_base_dir).ok())
+            .is_none_or(|(path, base)| !path.starts_with(base));
+        if escapes {
+            bail!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("Include roots must be folders of the template repository, not")
+                    .bold()
+                    .red(),
+                style(root).bold().yellow(),
+            );
+        }
+        roots.push(path);
+    }
+    for root in given {
+        if !root.is_dir() {
+            bail!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("The include root is no folder:").bold().red(),
+                style(root.display()).bold().yellow(),
+            );
+        }
+        roots.push(root.clone());
+    }
+
+    // the template is rendered in place, the roots within it are read where it was set aside
+    let set_aside = set_aside(template_dir)?;
+    let template_dir = fs::canonicalize(template_dir)?;
+    let loaders = roots
+        .iter()
+        .map(|root| {
+            let root = fs::canonicalize(root)?;
+            let set_aside_root = root
+                .strip_prefix(&template_dir)
+                .map(|within| set_aside.path().join(within));
+            Ok(Box::new(path_loader(set_aside_root.unwrap_or(root))) as Loader)
+        })
+        .collect::<Result<_>>()?;
+    Ok(IncludeRoots {
+        loaders,
+        _set_aside: set_aside,
+    })
+}
+
+/// The loader of the templates of [`minijinja::Environment`]s rendering a template, looking them
+/// up in its include `roots`, if known.
+pub fn loader(
+    roots: Option<Arc<IncludeRoots>>,
+) -> impl Fn(&str) -> Result<Option<String>, minijinja::Error> + Send + Sync {
+    move |name| roots.as_ref().map_or(Ok(None), |roots| roots.load(name))
+}
+
+fn is_template_itself(root: &str) -> bool {
+    Path::new(root)
+        .components()
+        .all(|component| component == Component::CurDir)
+}
+
+/// Copies the files of `template_dir` but its git metadata to a folder of their own.
+fn set_aside(template_dir: &Path) -> Result<TempDir> {
+    let set_aside = tmp_dir()?;
+    for entry in WalkDir::new(template_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .filter_map(Result::ok)
+    {
+        let Ok(relative_path) = entry.path().strip_prefix(template_dir) else {
+            continue;
+        };
+        let copy = set_aside.path().join(relative_path);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(copy)?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), copy)?;
+        }
+    }
+    Ok(set_aside)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn the_template_itself_is_a_root() {
+        assert!(is_template_itself("."));
+        assert!(is_template_itself("./"));
+        assert!(!is_template_itself("./shared"));
+    }
+
+    #[test]
+    fn templates_are_loaded_from_the_first_root_having_them() {
+        let repository = tempdir().unwrap();
+        let template = repository.path().join("app");
+        fs::create_dir_all(template.join("layouts")).unwrap();
+        fs::create_dir_all(repository.path().join("shared/layouts")).unwrap();
+        fs::write(template.join("layouts/plain.md"), "own").unwrap();
+        fs::write(repository.path().join("shared/layouts/plain.md"), "shared").unwrap();
+        fs::write(repository.path().join("shared/layouts/fancy.md"), "fancy").unwrap();
+
+        let configured = vec!["../shared".to_string()];
+        let roots = roots(repository.path(), &template, Some(&configured), &[]).unwrap();
+        let load = loader(Some(Arc::new(roots)));
+        // rendering the template in place leaves what is included as it was
+        fs::remove_file(template.join("layouts/plain.md")).unwrap();
+
+        assert_eq!(load("layouts/plain.md").unwrap().as_deref(), Some("own"));
+        assert_eq!(load("layouts/fancy.md").unwrap().as_deref(), Some("fancy"));
+        assert_eq!(load("layouts/missing.md").unwrap(), None);
+        assert_eq!(load("../shared/layouts/fancy.md").unwrap(), None);
+    }
+}
@@ -1,7 +1,8 @@
 use anyhow::Result;
-use git2::{Config as GitConfig, Repository as GitRepository};
 use std::env;
 
+use crate::git;
+
 pub struct Authors {
     pub author: String,
     pub username: String,
@@ -16,8 +17,8 @@ pub fn get_authors() -> Result<Authors> {
     }
 
     fn discover_author() -> Result<(String, Option<String>)> {
-        let git_config = find_real_git_config();
-        let git_config = git_config.as_ref();
+        let cwd = env::current_dir().ok();
+        let git_config = |key| git::backend().config_value(cwd.as_deref(), key);
 
         let name_variables = [
             "CARGO_NAME",
@@ -28,7 +29,7 @@ pub fn get_authors() -> Result<Authors> {
             "NAME",
         ];
         let name = get_environment_variable(&name_variables[0..3])
-            .or_else(|| git_config.and_then(|g| g.get_string("user.name").ok()))
+            .or_else(|| git_config("user.name"))
             .or_else(|| get_environment_variable(&name_variables[3..]));
 
         let name = match name {
@@ -48,7 +49,7 @@ pub fn get_authors() -> Result<Authors> {
             "EMAIL",
         ];
         let email = get_environment_variable(&email_variables[0..3])
-            .or_else(|| git_config.and_then(|g| g.get_string("user.email").ok()))
+            .or_else(|| git_config("user.email"))
             .or_else(|| get_environment_variable(&email_variables[3..]));
 
         let name = name.trim().to_string();
@@ -67,18 +68,6 @@ pub fn get_authors() -> Result<Authors> {
         Ok((name, email))
     }
 
-    fn find_real_git_config() -> Option<GitConfig> {
-        env::current_dir().map_or_else(
-            |_| GitConfig::open_default().ok(),
-            |cwd| {
-                GitRepository::discover(cwd)
-                    .and_then(|repo| repo.config())
-                    .or_else(|_| GitConfig::open_default())
-                    .ok()
-            },
-        )
-    }
-
     let author = match discover_author()? {
         (name, Some(email)) => Authors {
             author: format!("{name} <{email}>"),
@@ -1,19 +1,82 @@
 use std::fmt::Display;
 
-use heck::ToSnakeCase;
+use anyhow::Result;
+use console::style;
+use dialoguer::console::Term;
+use log::warn;
 
 use super::ProjectNameInput;
+use crate::{case, interactive, prompt_timeout::PromptTimeout};
+
+/// Keywords of all editions, strict and reserved, none of which can name a crate.
+pub const KEYWORDS: [&str; 51] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Crates every project can use already, which a crate of the same name would shadow.
+const CORE_CRATES: [&str; 5] = ["alloc", "core", "proc_macro", "std", "test"];
 
 #[derive(Debug)]
 pub struct CrateName(String);
 
 impl From<&ProjectNameInput> for CrateName {
     fn from(project_name_input: &ProjectNameInput) -> Self {
-        let crate_name = project_name_input.as_ref().to_snake_case();
+        let crate_name = case::crate_name(project_name_input.as_ref());
         Self(crate_name)
     }
 }
 
+impl CrateName {
+    /// Why the crate name would not compile, if it wouldn't.
+    pub fn unusable_because(&self) -> Option<&'static str> {
+        if KEYWORDS.contains(&self.0.as_str()) {
+            Some("is a Rust keyword")
+        } else if CORE_CRATES.contains(&self.0.as_str()) {
+            Some("is the name of a crate of the standard library")
+        } else {
+            None
+        }
+    }
+}
+
+/// Makes sure the crate name derived from `project_name_input` compiles: an unusable one is
+/// warned about and replaced by another name, asked for unless `silent`, or else made by
+/// appending `-rs`.
+pub fn usable_project_name(
+    mut project_name_input: ProjectNameInput,
+    silent: bool,
+    timeout: &PromptTimeout,
+) -> Result<ProjectNameInput> {
+    loop {
+        let crate_name = CrateName::from(&project_name_input);
+        let Some(reason) = crate_name.unusable_because() else {
+            return Ok(project_name_input);
+        };
+        let fallback = format!("{project_name_input}-rs");
+        warn!(
+            "{} `{}` {}, {}",
+            style("The crate name").bold(),
+            style(&crate_name).bold().yellow(),
+            style(reason).bold(),
+            style("the project needs another name").bold(),
+        );
+        project_name_input = if silent || !Term::stderr().is_term() {
+            warn!(
+                "{} `{}`",
+                style("Using the project name").bold(),
+                style(&fallback).bold().green(),
+            );
+            ProjectNameInput(fallback)
+        } else {
+            ProjectNameInput(interactive::another_name(&fallback, timeout)?)
+        };
+    }
+}
+
 impl AsRef<str> for CrateName {
     fn as_ref(&self) -> &str {
         self.0.as_ref()
@@ -25,3 +88,31 @@ impl Display for CrateName {
         self.0.fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords_and_core_crates_are_unusable() {
+        let crate_name = |name: &str| CrateName::from(&ProjectNameInput(name.to_string()));
+
+        assert!(crate_name("match").unusable_because().is_some());
+        assert!(crate_name("Type").unusable_because().is_some());
+        assert!(crate_name("proc-macro").unusable_because().is_some());
+        assert!(crate_name("matcher").unusable_because().is_none());
+        assert!(crate_name("my-std").unusable_because().is_none());
+    }
+
+    #[test]
+    fn unusable_names_fall_back_to_a_suffix_when_silent() {
+        let timeout = PromptTimeout::default();
+        let project_name =
+            usable_project_name(ProjectNameInput("type".into()), true, &timeout).unwrap();
+        assert_eq!(project_name.as_ref(), "type-rs");
+
+        let project_name =
+            usable_project_name(ProjectNameInput("typed".into()), true, &timeout).unwrap();
+        assert_eq!(project_name.as_ref(), "typed");
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{anyhow, Result};
+use console::style;
+use time::{OffsetDateTime, UtcOffset};
+
+use crate::emoji;
+
+/// Environment variable pinning the date of reproducible builds, see
+/// <https://reproducible-builds.org/specs/source-date-epoch/>
+pub const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// The moment the project is generated at, shared by all date variables of a run.
+///
+/// Taken from `timestamp` if given, else from `$SOURCE_DATE_EPOCH`, else from the clock, unless
+/// `reproducible` is set, in which case it falls back to the unix epoch. The offset defaults to
+/// UTC, as the local offset cannot be determined soundly in a multi-threaded process.
+pub fn get_generation_time(
+    timestamp: Option<i64>,
+    utc_offset: Option<&str>,
+    reproducible: bool,
+) -> Result<OffsetDateTime> {
+    let timestamp = match timestamp {
+        Some(timestamp) => Some(timestamp),
+        None => std::env::var(SOURCE_DATE_EPOCH)
+            .ok()
+            .map(|epoch| {
+                epoch.trim().parse::<i64>().map_err(|_| {
+                    anyhow!(
+                        "{} {} `{epoch}`",
+                        emoji::ERROR,
+                        style(format!("{SOURCE_DATE_EPOCH} is not a unix timestamp:"))
+                            .bold()
+                            .red(),
+                    )
+                })
+            })
+            .transpose()?,
+    };
+    let now = match timestamp {
+        Some(timestamp) => OffsetDateTime::from_unix_timestamp(timestamp)?,
+        None if reproducible => OffsetDateTime::UNIX_EPOCH,
+        None => OffsetDateTime::now_utc(),
+    };
+    let offset = utc_offset.map(parse_utc_offset).transpose()?;
+
+    Ok(now.to_offset(offset.unwrap_or(UtcOffset::UTC)))
+}
+
+/// Parses `Z`, `UTC`, `+HH`, `+HH:MM` or `+HHMM` (and the same with `-`).
+fn parse_utc_offset(offset: &str) -> Result<UtcOffset> {
+    let invalid = || {
+        anyhow!(
+            "{} {} `{offset}`",
+            emoji::ERROR,
+            style("Invalid UTC offset, expected e.g. `+02:00`:")
+                .bold()
+                .red(),
+        )
+    };
+
+    let trimmed = offset.trim();
+    if trimmed.eq_ignore_ascii_case("z") || trimmed.eq_ignore_ascii_case("utc") {
+        return Ok(UtcOffset::UTC);
+    }
+    let (sign, digits) = match trimmed.split_at_checked(1) {
+        Some(("+", digits)) => (1, digits),
+        Some(("-", digits)) => (-1, digits),
+        _ => return Err(invalid()),
+    };
+    let digits = digits.replace(':', "");
+    if !digits.chars().all(|c| c.is_ascii_digit()) || !matches!(digits.len(), 2 | 4) {
+        return Err(invalid());
+    }
+    let hours = digits[..2].parse::<i8>().map_err(|_| invalid())?;
+    let minutes = match &digits[2..] {
+        "" => 0,
+        minutes => minutes.parse::<i8>().map_err(|_| invalid())?,
+    };
+
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0).map_err(|_| invalid())
+}
+
+/// The `current_*` variables describing `now`.
+pub fn date_variables(now: OffsetDateTime) -> [(&'static str, serde_json::Value); 4] {
+    let date = format!(
+        "{:04}-{:02}-{:02}",
+        now.year(),
+        u8::from(now.month()),
+        now.day()
+    );
+    let offset = now.offset();
+    let offset = if offset.is_utc() {
+        "Z".to_string()
+    } else {
+        let (hours, minutes, _) = offset.as_hms();
+        let sign = if offset.is_negative() { '-' } else { '+' };
+        format!("{sign}{:02}:{:02}", hours.abs(), minutes.abs())
+    };
+    let datetime = format!(
+        "{date}T{:02}:{:02}:{:02}{offset}",
+        now.hour(),
+        now.minute(),
+        now.second()
+    );
+
+    [
+        ("current_datetime", datetime.into()),
+        ("current_date_iso", date.into()),
+        ("current_year", now.year().into()),
+        ("current_timestamp", now.unix_timestamp().into()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_variables_in_the_requested_offset() {
+        let now = get_generation_time(Some(1_704_067_199), Some("+02:00"), false).unwrap();
+        let variables = date_variables(now);
+
+        assert_eq!(variables[0].1, "2024-01-01T01:59:59+02:00");
+        assert_eq!(variables[1].1, "2024-01-01");
+        assert_eq!(variables[2].1, 2024);
+        assert_eq!(variables[3].1, 1_704_067_199);
+
+        let now = get_generation_time(Some(1_704_067_199), None, false).unwrap();
+        assert_eq!(date_variables(now)[0].1, "2023-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn parses_utc_offsets() {
+        assert_eq!(parse_utc_offset("Z").unwrap(), UtcOffset::UTC);
+        assert_eq!(
+            parse_utc_offset("-0530").unwrap(),
+            UtcOffset::from_hms(-5, -30, 0).unwrap()
+        );
+        assert_eq!(
+            parse_utc_offset("+09").unwrap(),
+            UtcOffset::from_hms(9, 0, 0).unwrap()
+        );
+        assert!(parse_utc_offset("02:00").is_err());
+        assert!(parse_utc_offset("+2").is_err());
+        assert!(parse_utc_offset("+30:00").is_err());
+    }
+}
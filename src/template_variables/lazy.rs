@@ -0,0 +1,60 @@
+//! Built-in variables that are costly to compute, like those running a command, so they are only
+//! computed once a template references them.
+
+use std::{process::Command, sync::OnceLock};
+
+struct LazyVariable {
+    name: &'static str,
+    compute: fn() -> Option<serde_json::Value>,
+    value: OnceLock<Option<serde_json::Value>>,
+}
+
+impl LazyVariable {
+    const fn new(name: &'static str, compute: fn() -> Option<serde_json::Value>) -> Self {
+        Self {
+            name,
+            compute,
+            value: OnceLock::new(),
+        }
+    }
+}
+
+static LAZY_VARIABLES: [LazyVariable; 1] = [LazyVariable::new("rust_version", get_rust_version)];
+
+/// The value of the lazy built-in variable `name`, computed the first time it is asked for.
+///
+/// Returns `None` if there is no such variable, or if its value can't be determined here.
+pub fn get_lazy_variable(name: &str) -> Option<serde_json::Value> {
+    let variable = LAZY_VARIABLES
+        .iter()
+        .find(|variable| variable.name == name)?;
+    variable.value.get_or_init(variable.compute).clone()
+}
+
+/// The version of the `rustc` found on the `PATH`, like `1.80.1`.
+fn get_rust_version() -> Option<serde_json::Value> {
+    let output = Command::new("rustc").arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8(output.stdout).ok()?;
+    version
+        .split_whitespace()
+        .nth(1)
+        .map(serde_json::Value::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lazy_variables_are_computed_once() {
+        assert_eq!(get_lazy_variable("unknown"), None);
+        assert_eq!(
+            get_lazy_variable("rust_version"),
+            get_lazy_variable("rust_version")
+        );
+        assert!(LAZY_VARIABLES[0].value.get().is_some());
+    }
+}
@@ -1,12 +1,16 @@
 mod authors;
 mod crate_name;
 mod crate_type;
+mod date;
+mod lazy;
 mod os_arch;
 mod project_dir;
 mod project_name;
 mod project_name_input;
+mod repository;
 
-use crate::{emoji, GenerateArgs};
+use crate::explain_var::{Audit, Source};
+use crate::{emoji, project_variables::is_internal, GenerateArgs};
 
 use anyhow::Result;
 use console::style;
@@ -14,18 +18,21 @@ use indexmap::IndexMap;
 use log::info;
 use regex::Regex;
 use serde::Deserialize;
-use std::{collections::HashMap, fmt::Display, fs, path::Path};
+use std::{collections::HashMap, fmt::Display, fs, path::Path, process::Command};
 use toml::Value;
 
 pub use authors::{get_authors, Authors};
-pub use crate_name::CrateName;
+pub use crate_name::{usable_project_name, CrateName, KEYWORDS};
 pub use crate_type::CrateType;
-pub use os_arch::get_os_arch;
+pub use date::{date_variables, get_generation_time, SOURCE_DATE_EPOCH};
+pub use lazy::get_lazy_variable;
+pub use os_arch::{get_os_arch, get_platform};
 pub use project_dir::ProjectDir;
 pub use project_name::ProjectName;
 pub use project_name_input::ProjectNameInput;
+pub use repository::get_repository_url;
 
-fn load_env_template_values() -> Result<IndexMap<String, toml::Value>> {
+fn load_env_template_values(audit: &Audit) -> Result<IndexMap<String, toml::Value>> {
     //FIXME: use this variable to be in sync with args
     let mut values = std::env::var("CARGO_GENERATE_TEMPLATE_VALUES_FILE")
         .ok()
@@ -38,10 +45,14 @@ fn load_env_template_values() -> Result<IndexMap<String, toml::Value>> {
             .map(|key| (key.to_lowercase(), Value::from(value)))
     }));
 
+    record_values(audit, &values, Source::Environment);
     Ok(values)
 }
 
-fn load_args_template_values(args: &GenerateArgs) -> Result<IndexMap<String, toml::Value>> {
+fn load_args_template_values(
+    args: &GenerateArgs,
+    audit: &Audit,
+) -> Result<IndexMap<String, toml::Value>> {
     let mut values = args
         .template_values_file
         .as_ref()
@@ -49,16 +60,30 @@ fn load_args_template_values(args: &GenerateArgs) -> Result<IndexMap<String, tom
         .map_or(Ok(Default::default()), |path| {
             read_template_values_file(path)
         })?;
+    record_values(audit, &values, Source::ValuesFile);
 
-    values.extend(read_template_values_from_definitions(&args.define)?);
+    let defined = read_template_values_from_definitions(&args.define)?;
+    record_values(audit, &defined, Source::Define);
+    values.extend(defined);
     Ok(values)
 }
 
+fn record_values<'a>(
+    audit: &Audit,
+    values: impl IntoIterator<Item = (&'a String, &'a Value)>,
+    source: Source,
+) {
+    for (key, value) in values {
+        audit.record(key, value, source.clone());
+    }
+}
+
 pub fn load_env_and_args_template_values(
     args: &GenerateArgs,
+    audit: &Audit,
 ) -> Result<IndexMap<String, toml::Value>> {
-    let mut template_variables = load_env_template_values()?;
-    template_variables.extend(load_args_template_values(args)?);
+    let mut template_variables = load_env_template_values(audit)?;
+    template_variables.extend(load_args_template_values(args, audit)?);
     Ok(template_variables)
 }
 
@@ -77,11 +102,42 @@ fn read_template_values_file(path: &Path) -> Result<IndexMap<String, Value>> {
     }
 }
 
+/// Downloads the template values published at `url`, with `curl` so any URL it supports works.
+pub fn load_remote_template_values(url: &str) -> Result<IndexMap<String, Value>> {
+    let error = |cause: &dyn Display| {
+        anyhow::anyhow!(
+            "{} {} \"{}\": {}",
+            emoji::ERROR,
+            style("Values URL Error:").bold().red(),
+            style(url).bold().red(),
+            style(cause).bold().red(),
+        )
+    };
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", url])
+        .output()
+        .map_err(|e| error(&e))?;
+    if !output.status.success() {
+        return Err(error(&String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    parse_template_values(&String::from_utf8_lossy(&output.stdout)).map_err(|e| error(&e))
+}
+
+/// Parses values in the format of the values file, or the same structure in JSON.
+fn parse_template_values(contents: &str) -> Result<IndexMap<String, Value>> {
+    let values = if contents.trim_start().starts_with('{') {
+        serde_json::from_str::<TemplateValuesToml>(contents)?
+    } else {
+        toml::from_str::<TemplateValuesToml>(contents)?
+    };
+    Ok(values.values)
+}
+
 fn read_template_values_from_definitions(
     definitions: &[impl AsRef<str> + Display],
 ) -> Result<HashMap<String, toml::Value>> {
     let mut values = HashMap::with_capacity(definitions.len());
-    let key_value_regex = Regex::new(r"^([a-zA-Z]+[a-zA-Z0-9\-_]*)\s*=\s*((.+))?$").unwrap();
+    let key_value_regex = Regex::new(r"^(_?[a-zA-Z]+[a-zA-Z0-9\-_]*(?:\.[a-zA-Z_][a-zA-Z0-9\-_]*)*)\s*=\s*((.+))?$").unwrap();
 
     definitions
         .iter()
@@ -99,11 +155,13 @@ fn read_template_values_from_definitions(
                     let key = cap.get(1).unwrap().as_str().to_string();
                     let value = cap.get(2).map(|s| s.as_str()).unwrap_or("").to_owned();
 
-                    info!(
-                        "{} {} (value from CLI)",
-                        emoji::WRENCH,
-                        style(format!("{key}: {value:?}")).bold(),
-                    );
+                    if !is_internal(&key) {
+                        info!(
+                            "{} {} (value from CLI)",
+                            emoji::WRENCH,
+                            style(format!("{key}: {value:?}")).bold(),
+                        );
+                    }
                     template_values.insert(key, Value::from(value));
                     Ok(template_values)
                 },
@@ -119,7 +177,7 @@ struct TemplateValuesToml {
 
 #[cfg(test)]
 mod test {
-    use super::read_template_values_from_definitions;
+    use super::{parse_template_values, read_template_values_from_definitions};
 
     #[test]
     fn names_must_start_with_word_char() {
@@ -135,11 +193,20 @@ mod test {
         let result = read_template_values_from_definitions(&definitions);
         assert!(result.is_err());
 
-        let definitions = vec!["_key=42"];
+        let definitions = vec!["_0key=42"];
         let result = read_template_values_from_definitions(&definitions);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn internal_names_start_with_underscore() {
+        let definitions = vec!["_key=42"];
+        let result = read_template_values_from_definitions(&definitions).unwrap();
+
+        let val = result["_key"].as_str().unwrap();
+        assert_eq!(val, "42");
+    }
+
     #[test]
     fn names_may_contain_digits() {
         let definitions = vec!["my0123456789key=42"];
@@ -182,4 +249,13 @@ mod test {
         let val = result["key"].as_str().unwrap();
         assert_eq!(val, "42");
     }
+
+    #[test]
+    fn values_can_be_given_in_toml_or_json() {
+        let toml = parse_template_values("[values]\nregistry = \"internal\"\n").unwrap();
+        let json = parse_template_values(r#"{ "values": { "registry": "internal" } }"#).unwrap();
+
+        assert_eq!(toml, json);
+        assert_eq!(json["registry"].as_str(), Some("internal"));
+    }
 }
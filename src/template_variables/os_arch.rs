@@ -5,3 +5,13 @@ pub type OsArch = String;
 pub fn get_os_arch() -> OsArch {
     format!("{}-{}", env::consts::OS, env::consts::ARCH)
 }
+
+/// The operating system, architecture, OS family and byte order, as the `platform` variable.
+pub fn get_platform() -> serde_json::Value {
+    serde_json::json!({
+        "os": env::consts::OS,
+        "arch": env::consts::ARCH,
+        "family": env::consts::FAMILY,
+        "endianness": if cfg!(target_endian = "big") { "big" } else { "little" },
+    })
+}
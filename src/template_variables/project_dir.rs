@@ -35,17 +35,35 @@ impl TryFrom<(&ProjectNameInput, &UserParsedInput)> for ProjectDir {
     fn try_from(
         (project_name_input, user_parsed_input): (&ProjectNameInput, &UserParsedInput),
     ) -> Result<Self, Self::Error> {
+        let name = user_parsed_input
+            .name()
+            .unwrap_or_else(|| project_name_input.as_ref());
+        Self::named(name, user_parsed_input)
+    }
+}
+
+impl ProjectDir {
+    /// The directory for a project the user gave another name than `--name`, e.g. as the one
+    /// given was not usable.
+    pub fn renamed(
+        project_name_input: &ProjectNameInput,
+        user_parsed_input: &UserParsedInput,
+    ) -> anyhow::Result<Self> {
+        Self::named(project_name_input.as_ref(), user_parsed_input)
+    }
+
+    fn named(name: &str, user_parsed_input: &UserParsedInput) -> anyhow::Result<Self> {
         let base_path = user_parsed_input.destination();
 
         if user_parsed_input.init() {
             return Ok(Self(base_path.to_owned()));
         }
 
-        let name = user_parsed_input
-            .name()
-            .map_or_else(|| project_name_input.as_ref().to_owned(), String::from);
-
-        let dir_name = if user_parsed_input.force() {
+        let name = name.to_owned();
+        let dir_name = if user_parsed_input.generic() {
+            check_folder_name(&name)?;
+            name.clone()
+        } else if user_parsed_input.force() {
             name.clone()
         } else {
             {
@@ -78,9 +96,7 @@ impl TryFrom<(&ProjectNameInput, &UserParsedInput)> for ProjectDir {
 
         Ok(Self(project_dir))
     }
-}
 
-impl ProjectDir {
     pub fn create(&self) -> anyhow::Result<()> {
         let path = self.0.as_path();
         if path.exists() {
@@ -99,6 +115,22 @@ impl ProjectDir {
     }
 }
 
+/// Refuses names of generic projects that would not name a single folder in the destination.
+fn check_folder_name(name: &str) -> anyhow::Result<()> {
+    if name.trim().is_empty() || matches!(name, "." | "..") || name.contains(['/', '\\']) {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("The project name does not name a folder:")
+                .bold()
+                .red(),
+            style(name).bold().yellow(),
+        );
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,4 +172,23 @@ mod tests {
         let project_dir = ProjectDir::try_from((&input, &args)).unwrap();
         assert!(project_dir.0.as_path().ends_with("lockFirmware"));
     }
+
+    #[test]
+    fn test_kept_as_given_when_generic() {
+        let input = ProjectNameInput("My Site".to_string());
+        let args = UserParsedInputBuilder::for_testing().with_generic().build();
+
+        let project_dir = ProjectDir::try_from((&input, &args)).unwrap();
+        assert!(project_dir.0.as_path().ends_with("My Site"));
+    }
+
+    #[test]
+    fn test_generic_names_must_name_a_folder() {
+        let args = UserParsedInputBuilder::for_testing().with_generic().build();
+
+        for name in ["", "..", "site/docs"] {
+            let input = ProjectNameInput(name.to_string());
+            assert!(ProjectDir::try_from((&input, &args)).is_err());
+        }
+    }
 }
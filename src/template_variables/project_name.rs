@@ -13,11 +13,13 @@ impl From<(&ProjectNameInput, &UserParsedInput)> for ProjectName {
     fn from(
         (project_name_input, user_parsed_input): (&ProjectNameInput, &UserParsedInput),
     ) -> Self {
-        Self(if user_parsed_input.force() {
-            project_name_input.as_ref().to_owned()
-        } else {
-            sanitize_project_name(project_name_input.as_ref())
-        })
+        Self(
+            if user_parsed_input.force() || user_parsed_input.generic() {
+                project_name_input.as_ref().to_owned()
+            } else {
+                sanitize_project_name(project_name_input.as_ref())
+            },
+        )
     }
 }
 
@@ -96,4 +98,13 @@ mod tests {
         let project_name = ProjectName::from((&input, &args));
         assert_eq!(project_name, ProjectName("lockFirmware".into()));
     }
+
+    #[test]
+    fn test_not_converted_to_dash_case_when_generic() {
+        let input = ProjectNameInput("Lock Firmware".to_string());
+        let args = UserParsedInputBuilder::for_testing().with_generic().build();
+
+        let project_name = ProjectName::from((&input, &args));
+        assert_eq!(project_name, ProjectName("Lock Firmware".into()));
+    }
 }
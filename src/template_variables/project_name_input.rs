@@ -4,27 +4,31 @@ use anyhow::anyhow;
 use console::style;
 
 use crate::{
-    emoji, interactive, template::TemplateObjectResource, user_parsed_input::UserParsedInput,
+    emoji, interactive, prompt_timeout::PromptTimeout, template::TemplateObjectResource,
+    user_parsed_input::UserParsedInput,
 };
 use log::warn;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProjectNameInput(pub(crate) String);
 
-impl TryFrom<(&TemplateObjectResource, &UserParsedInput)> for ProjectNameInput {
+impl TryFrom<(&TemplateObjectResource, &UserParsedInput, &PromptTimeout)> for ProjectNameInput {
     type Error = anyhow::Error;
 
     fn try_from(
-        (template_object, user_parsed_input): (&TemplateObjectResource, &UserParsedInput),
+        (template_object, user_parsed_input, timeout): (
+            &TemplateObjectResource,
+            &UserParsedInput,
+            &PromptTimeout,
+        ),
     ) -> Result<Self, Self::Error> {
         let name_str = {
             let guard = template_object.lock().unwrap();
             let borrowed_obj = guard.borrow();
             
-            // Try underscore version first (set by init hooks) then fall back to hyphenated version (from CLI)
+            // init hooks set both spellings, whichever they use
             borrowed_obj
-                .get("project_name")
-                .or_else(|| borrowed_obj.get("project-name"))
+                .get("project-name")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string())
         };
@@ -53,7 +57,7 @@ impl TryFrom<(&TemplateObjectResource, &UserParsedInput)> for ProjectNameInput {
             None => {
                 match std::env::var("CARGO_GENERATE_VALUE_PROJECT_NAME") {
                     Ok(name) => Ok(Self(name)),
-                    Err(_) if !user_parsed_input.silent() => Ok(Self(interactive::name()?)),
+                    Err(_) if !user_parsed_input.silent() => Ok(Self(interactive::name(timeout)?)),
                     Err(_) => Err(anyhow!(
                         "{} {} {}",
                         emoji::ERROR,
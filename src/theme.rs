@@ -0,0 +1,99 @@
+//! Colors of the output: whether there are any, and which ones.
+//!
+//! `console` and `indicatif` share one switch for colors, `dialoguer` has its own, as it depends
+//! on another version of `console`; both are set together here.
+
+use dialoguer::theme::ColorfulTheme;
+use log::Level;
+use std::sync::RwLock;
+
+use crate::app_config::ThemeConfig;
+use crate::ColorChoice;
+
+/// Turns colors off if set to a non-empty value, see <https://no-color.org>.
+pub const NO_COLOR_ENV: &str = "NO_COLOR";
+
+static THEME: RwLock<ThemeConfig> = RwLock::new(ThemeConfig {
+    accent: None,
+    warning: None,
+    error: None,
+});
+
+pub fn set_color_choice(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto if std::env::var_os(NO_COLOR_ENV).is_some_and(|v| !v.is_empty()) => false,
+        // left to the detection of the terminal capabilities
+        ColorChoice::Auto => return,
+    };
+    console::set_colors_enabled(enabled);
+    console::set_colors_enabled_stderr(enabled);
+    dialoguer::console::set_colors_enabled(enabled);
+    dialoguer::console::set_colors_enabled_stderr(enabled);
+}
+
+pub fn set_theme(theme: Option<&ThemeConfig>) {
+    if let Ok(mut current) = THEME.write() {
+        *current = theme.cloned().unwrap_or_default();
+    }
+}
+
+/// The theme of the interactive prompts, using the accent color for defaults, values and the
+/// selected item.
+pub fn prompt_theme() -> ColorfulTheme {
+    use dialoguer::console::Style;
+
+    let mut prompt_theme = ColorfulTheme::default();
+    let accent = THEME.read().ok().and_then(|theme| theme.accent.clone());
+    if let Some(accent) = accent {
+        let accent = Style::from_dotted_str(&accent).for_stderr();
+        prompt_theme.prompt_prefix = accent.apply_to("?".to_string());
+        prompt_theme.defaults_style = accent.clone();
+        prompt_theme.values_style = accent.clone();
+        prompt_theme.active_item_style = accent.clone();
+        prompt_theme.active_item_prefix = accent.apply_to("❯".to_string());
+        prompt_theme.picked_item_prefix = accent.apply_to("❯".to_string());
+    }
+    prompt_theme
+}
+
+/// Renders a warning or error line in the style of the theme, replacing the inline styling of
+/// the message.
+pub fn style_log_line(level: Level, line: String) -> String {
+    let style = THEME.read().ok().and_then(|theme| match level {
+        Level::Error => theme.error.clone(),
+        Level::Warn => theme.warning.clone(),
+        _ => None,
+    });
+    match style {
+        Some(style) => console::Style::from_dotted_str(&style)
+            .apply_to(console::strip_ansi_codes(&line))
+            .to_string(),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn themed_lines_drop_their_inline_styling() {
+        let colors_enabled = console::colors_enabled();
+        console::set_colors_enabled(true);
+        let line = console::style("boom").red().to_string();
+        set_theme(Some(&ThemeConfig {
+            warning: Some("magenta".into()),
+            ..Default::default()
+        }));
+
+        let warning = style_log_line(Level::Warn, line.clone());
+        let error = style_log_line(Level::Error, line.clone());
+        set_theme(None);
+        console::set_colors_enabled(colors_enabled);
+
+        assert_eq!(warning, "\u{1b}[35mboom\u{1b}[0m");
+        assert_eq!(error, line, "no style configured for errors");
+    }
+}
@@ -0,0 +1,172 @@
+//! `undo`: removing the project generated last, for when the wrong answers were given.
+//!
+//! After each generation, the directory of the new project and the checksums of its files are
+//! recorded next to the application config. The project is only removed while it is still exactly
+//! as it was generated.
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{app_config::app_config_path, emoji, git::try_get_commit_from_path, package, UndoArgs};
+
+pub const LAST_GENERATION_FILE_NAME: &str = "cargo-generate-last.toml";
+
+/// The project generated last, as it was generated.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct LastGeneration {
+    project_dir: PathBuf,
+    /// The `Cargo.toml` of the workspace the project was added to, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workspace_manifest: Option<PathBuf>,
+    /// The commit of the project's git repository, if it has any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    files: BTreeMap<String, String>,
+}
+
+/// Records the project just generated in `project_dir`, to be removed again by `undo`, next to
+/// the application config at `app_config_path`. Nothing is recorded without a folder for it.
+pub fn record(
+    app_config_path: &Path,
+    project_dir: &Path,
+    workspace_manifest: Option<&Path>,
+) -> Result<()> {
+    let Some(config_dir) = app_config_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+    else {
+        return Ok(());
+    };
+    let last = LastGeneration {
+        project_dir: project_dir.canonicalize()?,
+        workspace_manifest: workspace_manifest.map(Path::to_path_buf),
+        commit: try_get_commit_from_path(project_dir),
+        files: package::checksums(project_dir)?,
+    };
+    fs::create_dir_all(config_dir)?;
+    fs::write(
+        config_dir.join(LAST_GENERATION_FILE_NAME),
+        toml::to_string(&last)?,
+    )?;
+    Ok(())
+}
+
+/// Removes the project generated last, unless it changed since.
+pub fn undo(args: &UndoArgs) -> Result<()> {
+    let path = app_config_path(&args.config)?.with_file_name(LAST_GENERATION_FILE_NAME);
+    if !path.exists() {
+        bail!(
+            "{} {}",
+            emoji::ERROR,
+            style("Nothing to undo, no project was generated since the last undo")
+                .bold()
+                .red(),
+        );
+    }
+    let last: LastGeneration = toml::from_str(&fs::read_to_string(&path)?).with_context(|| {
+        format!(
+            "Invalid record of the last generation in {}",
+            path.display()
+        )
+    })?;
+
+    let project_dir = &last.project_dir;
+    if !project_dir.is_dir() {
+        fs::remove_file(&path)?;
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("The project generated last is gone already:")
+                .bold()
+                .red(),
+            style(project_dir.display()).bold(),
+        );
+    }
+    check_untouched(&last)?;
+
+    fs::remove_dir_all(project_dir)?;
+    fs::remove_file(&path)?;
+    info!(
+        "{} {} `{}`",
+        emoji::SPARKLE,
+        style("Removed").bold().green(),
+        style(project_dir.display()).bold(),
+    );
+    if let Some(workspace_manifest) = &last.workspace_manifest {
+        warn!(
+            "{} `{}`",
+            style("The project is still a member of the workspace in").bold(),
+            style(workspace_manifest.display()).bold().yellow(),
+        );
+    }
+    Ok(())
+}
+
+/// Fails unless the project of `last` is still as it was generated.
+fn check_untouched(last: &LastGeneration) -> Result<()> {
+    let files = package::checksums(&last.project_dir)?;
+    let changed = package::first_difference(&last.files, &files)
+        .cloned()
+        .or_else(|| {
+            (try_get_commit_from_path(&last.project_dir) != last.commit).then(|| ".git".to_string())
+        });
+    if let Some(changed) = changed {
+        bail!(
+            "{} {} `{}`, {}",
+            emoji::ERROR,
+            style("The project changed since it was generated at")
+                .bold()
+                .red(),
+            style(changed).bold(),
+            style("not removing it").bold().red(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_untouched_projects_are_removed() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let config = config_dir.path().join("cargo-generate.toml");
+        let projects = tempfile::tempdir().unwrap();
+        let project = projects.path().join("project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("src/main.rs"), "fn main() {}").unwrap();
+        let args = UndoArgs {
+            config: Some(config.clone()),
+        };
+        fs::write(&config, "").unwrap();
+
+        record(&config, &project, None).unwrap();
+        fs::write(project.join("src/lib.rs"), "").unwrap();
+        let error = undo(&args).unwrap_err();
+        assert!(error.to_string().contains("src/lib.rs"), "{error}");
+        assert!(project.exists());
+
+        fs::remove_file(project.join("src/lib.rs")).unwrap();
+        undo(&args).unwrap();
+        assert!(!project.exists());
+        assert!(undo(&args).is_err());
+    }
+
+    #[test]
+    fn nothing_is_recorded_without_a_folder_for_it() {
+        let projects = tempfile::tempdir().unwrap();
+        let project = projects.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+
+        record(Path::new("cargo-generate.toml"), &project, None).unwrap();
+        assert!(!Path::new(LAST_GENERATION_FILE_NAME).exists());
+    }
+}
@@ -10,7 +10,7 @@ use crate::absolute_path::AbsolutePathExt;
 use console::style;
 use regex::Regex;
 
-use crate::{app_config::AppConfig, template_variables::CrateType, GenerateArgs, Vcs};
+use crate::{app_config::AppConfig, fetchers, package, template_variables::CrateType, GenerateArgs, Vcs};
 use log::warn;
 
 #[derive(Debug)]
@@ -44,6 +44,11 @@ impl UserParsedInputBuilder {
         self
     }
 
+    pub const fn with_generic(mut self) -> Self {
+        self.subject.generic = true;
+        self
+    }
+
     pub fn build(self) -> UserParsedInput {
         self.subject
     }
@@ -69,6 +74,8 @@ pub struct UserParsedInput {
 
     vcs: Vcs,
     pub init: bool,
+    /// Set by templates of a `generic` project type, see [`crate::config::ProjectType`]
+    pub generic: bool,
     overwrite: bool,
     crate_type: CrateType,
     allow_commands: bool,
@@ -138,6 +145,7 @@ impl UserParsedInput {
                 template_values: default_values,
                 vcs: args.vcs.unwrap_or(DEFAULT_VCS),
                 init: args.init,
+                generic: false,
                 overwrite: args.overwrite,
                 crate_type: CrateType::from(args),
                 allow_commands: args.allow_commands,
@@ -161,6 +169,7 @@ impl UserParsedInput {
                 template_values: default_values,
                 vcs: args.vcs.unwrap_or(DEFAULT_VCS),
                 init: args.init,
+                generic: false,
                 overwrite: args.overwrite,
                 crate_type: CrateType::from(args),
                 allow_commands: args.allow_commands,
@@ -230,6 +239,7 @@ impl UserParsedInput {
                     .then_some(true)
                     .or(fav_cfg.init)
                     .unwrap_or_default(),
+                generic: false,
                 overwrite: args
                     .overwrite
                     .then_some(true)
@@ -289,6 +299,9 @@ impl UserParsedInput {
             TemplateLocation::Path(path) => {
                 format!("local path: {}", style(path.display()).bold())
             }
+            TemplateLocation::Fetched(url) => {
+                format!("fetched template: {}", style(url).bold())
+            }
         };
         warn!(
             "Favorite `{}` not found in config, using it as a {}",
@@ -306,6 +319,7 @@ impl UserParsedInput {
             template_values: default_values,
             vcs: args.vcs.unwrap_or(DEFAULT_VCS),
             init: args.init,
+            generic: false,
             overwrite: args.overwrite,
             crate_type: CrateType::from(args),
             allow_commands: args.allow_commands,
@@ -345,6 +359,10 @@ impl UserParsedInput {
         self.init
     }
 
+    pub const fn generic(&self) -> bool {
+        self.generic
+    }
+
     pub const fn overwrite(&self) -> bool {
         self.overwrite
     }
@@ -365,6 +383,11 @@ impl UserParsedInput {
         self.destination.as_path()
     }
 
+    /// Generates into `destination` instead, as answered when it was asked for.
+    pub fn set_destination(&mut self, destination: PathBuf) {
+        self.destination = destination;
+    }
+
     pub const fn force(&self) -> bool {
         self.force
     }
@@ -410,7 +433,7 @@ pub fn abbreviated_github(fav: &str) -> Option<String> {
 
 pub fn local_path(fav: &str) -> Option<PathBuf> {
     let path = PathBuf::from(fav);
-    (path.exists() && path.is_dir()).then_some(path)
+    (path.is_dir() || package::is_archive(&path)).then_some(path)
 }
 
 // Template should be cloned with git
@@ -494,11 +517,17 @@ impl GitUserInput {
 pub enum TemplateLocation {
     Git(GitUserInput),
     Path(PathBuf),
+    /// A URL with a scheme a fetcher is registered for
+    Fetched(String),
 }
 
 impl From<GitUserInput> for TemplateLocation {
     fn from(source: GitUserInput) -> Self {
-        Self::Git(source)
+        if fetchers::has_fetcher(source.url()) {
+            Self::Fetched(source.url)
+        } else {
+            Self::Git(source)
+        }
     }
 }
 
@@ -0,0 +1,53 @@
+//! Other names for variables, the built-ins mostly, given in `[template.variable_aliases]`, so
+//! templates coming from other generators keep working with the names they used:
+//!
+//! ```toml
+//! [template.variable_aliases]
+//! app_name = "project-name"
+//! ```
+//!
+//! An alias is looked up when a variable of its name is not set, and has the value its variable
+//! has at that time.
+
+use indexmap::IndexMap;
+
+use crate::project_variables::same_variable;
+
+/// The aliases of a template, with the variables they stand for.
+#[derive(Debug, Default)]
+pub struct Aliases(Vec<(String, String)>);
+
+impl Aliases {
+    pub fn new(aliases: Option<&IndexMap<String, String>>) -> Self {
+        Self(
+            aliases
+                .into_iter()
+                .flatten()
+                .map(|(alias, name)| (alias.clone(), name.clone()))
+                .collect(),
+        )
+    }
+
+    /// The name of the variable `name` is an alias of, if it is one.
+    pub fn aliased(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(alias, _)| same_variable(alias, name))
+            .map(|(_, aliased)| aliased.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_are_looked_up_by_any_spelling() {
+        let aliases = IndexMap::from([("app_name".to_string(), "project-name".to_string())]);
+        let aliases = Aliases::new(Some(&aliases));
+        assert_eq!(aliases.aliased("app-name"), Some("project-name"));
+        assert_eq!(aliases.aliased("app_name"), Some("project-name"));
+        assert_eq!(aliases.aliased("project-name"), None);
+        assert_eq!(Aliases::new(None).aliased("app_name"), None);
+    }
+}
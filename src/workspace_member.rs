@@ -5,8 +5,12 @@ use std::{
 };
 
 use anyhow::{anyhow, bail, Context, Result};
+use cargo_util_schemas::manifest::TomlDependency;
+use console::{style, Term};
 use log::warn;
 
+use crate::{case, emoji, interactive, prompt_timeout::PromptTimeout};
+
 #[derive(Debug, PartialEq)]
 pub enum WorkspaceMemberStatus {
     Added(PathBuf),
@@ -26,6 +30,59 @@ pub fn add_to_workspace(member_path: &Path) -> Result<WorkspaceMemberStatus> {
     Ok(WorkspaceMemberStatus::Added(workspace.cargo_toml_path))
 }
 
+/// Asks for another name while the crate name derived from `project_name` is the name of a
+/// package of the workspace in `workspace_dir`, the folder the project is generated in, returning
+/// the name to go on with. Unless there is a terminal to ask on, a name taken is an error, as the
+/// workspace would no longer build.
+pub fn check_name_unique(
+    mut project_name: String,
+    workspace_dir: &Path,
+    silent: bool,
+    timeout: &PromptTimeout,
+) -> Result<String> {
+    let Some(workspace) = Workspace::at(workspace_dir)? else {
+        return Ok(project_name);
+    };
+    let taken = workspace.package_names(workspace_dir);
+    loop {
+        let crate_name = case::crate_name(&project_name);
+        if !taken
+            .iter()
+            .any(|name| case::crate_name(name) == crate_name)
+        {
+            return Ok(project_name);
+        }
+        if silent || !Term::stderr().is_term() {
+            bail!(
+                "{} {} `{}` {} `{}`",
+                emoji::ERROR,
+                style("The crate name").bold().red(),
+                style(&crate_name).bold(),
+                style("is already taken in the workspace").bold().red(),
+                style(workspace.cargo_toml_path.display()).bold(),
+            );
+        }
+        warn!(
+            "{} `{}` {}",
+            style("The crate name").bold(),
+            style(&crate_name).bold().yellow(),
+            style("is already taken in the workspace").bold(),
+        );
+        project_name = interactive::another_name(&project_name, timeout)?;
+    }
+}
+
+/// Make the given members part of the workspace rooted at `workspace_dir`.
+///
+/// Creates the `Cargo.toml`, or its `[workspace]` section, if the template does not provide them.
+pub fn stitch_workspace(workspace_dir: &Path, members: &[String]) -> Result<()> {
+    let mut workspace = Workspace::open_or_create(workspace_dir)?;
+    for name in members {
+        workspace.add_member(WorkspaceMember { name: name.clone() })?;
+    }
+    workspace.save()
+}
+
 struct Workspace {
     manifest: TomlManifest,
     cargo_toml_path: PathBuf,
@@ -36,26 +93,90 @@ impl Workspace {
     ///
     /// Returns `None` if no workspace project is found.
     pub fn try_new(member_path: &Path) -> Result<Option<Self>> {
-        if let Some(parent) = member_path.parent() {
-            let cargo_toml_path = parent.join("Cargo.toml");
-            if cargo_toml_path.exists() {
-                let content = fs::read_to_string(&cargo_toml_path)?;
-                let manifest: TomlManifest = toml::from_str(&content)
-                    .with_context(|| format!("Failed to parse {}", cargo_toml_path.display()))?;
-                if manifest.workspace.is_some()
-                    && manifest.workspace.as_ref().unwrap().members.is_some()
-                {
-                    return Ok(Some(Self {
-                        manifest,
-                        cargo_toml_path,
-                    }));
-                }
+        member_path.parent().map_or(Ok(None), Self::at)
+    }
+
+    /// The workspace project in `workspace_dir`, if it is one.
+    fn at(workspace_dir: &Path) -> Result<Option<Self>> {
+        let cargo_toml_path = workspace_dir.join("Cargo.toml");
+        if cargo_toml_path.exists() {
+            let content = fs::read_to_string(&cargo_toml_path)?;
+            let manifest: TomlManifest = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", cargo_toml_path.display()))?;
+            if manifest.workspace.is_some()
+                && manifest.workspace.as_ref().unwrap().members.is_some()
+            {
+                return Ok(Some(Self {
+                    manifest,
+                    cargo_toml_path,
+                }));
             }
         }
 
         Ok(None)
     }
 
+    /// The names of the packages of the workspace in `workspace_dir`: those of its members, with
+    /// `folder/*` standing for all folders in `folder`, and those of the path dependencies of
+    /// `[workspace.dependencies]`. Members without a readable manifest are left out.
+    fn package_names(&self, workspace_dir: &Path) -> Vec<String> {
+        let Some(workspace) = self.manifest.workspace.as_ref() else {
+            return vec![];
+        };
+        let mut member_dirs = vec![];
+        for member in workspace.members.iter().flatten() {
+            match member.strip_suffix("/*") {
+                Some(folder) => member_dirs.extend(
+                    fs::read_dir(workspace_dir.join(folder))
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|entry| Some(entry.ok()?.path())),
+                ),
+                None => member_dirs.push(workspace_dir.join(member)),
+            }
+        }
+        let members = member_dirs
+            .iter()
+            .filter_map(|dir| WorkspaceMember::try_new(dir).ok())
+            .map(|member| member.name);
+        let path_dependencies = workspace
+            .dependencies
+            .iter()
+            .flatten()
+            .filter_map(|(name, dependency)| match dependency {
+                TomlDependency::Detailed(detailed) if detailed.path.is_some() => Some(
+                    detailed
+                        .package
+                        .as_ref()
+                        .unwrap_or(name)
+                        .to_string(),
+                ),
+                _ => None,
+            });
+        members.chain(path_dependencies).collect()
+    }
+
+    /// Read the manifest at the root of a workspace template, defaulting to an empty workspace.
+    fn open_or_create(workspace_dir: &Path) -> Result<Self> {
+        let cargo_toml_path = workspace_dir.join("Cargo.toml");
+        let mut manifest = if cargo_toml_path.exists() {
+            let content = fs::read_to_string(&cargo_toml_path)?;
+            toml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", cargo_toml_path.display()))?
+        } else {
+            TomlManifest::default()
+        };
+
+        let workspace = manifest.workspace.get_or_insert_with(Default::default);
+        workspace.members.get_or_insert_with(Vec::new);
+        workspace.resolver.get_or_insert_with(|| "2".to_string());
+
+        Ok(Self {
+            manifest,
+            cargo_toml_path,
+        })
+    }
+
     /// Add a new member to the workspace, if it is not already a member.
     /// The member list will be sorted alphabetically.
     pub fn add_member(&mut self, member: WorkspaceMember) -> Result<()> {
@@ -0,0 +1,253 @@
+//! Templates that generate a whole workspace.
+//!
+//! A workspace template lists its members in `[workspace_template]`. Every member is a template of
+//! its own, living in a subfolder of the workspace template, and may come with its own
+//! `cargo-generate.toml` declaring further placeholders. Members are expanded with a copy of the
+//! workspace context, where `project-name` and `crate_name` refer to the member, and are then
+//! added to the `[workspace]` section of the root `Cargo.toml`.
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use fs_err as fs;
+use indicatif::MultiProgress;
+use log::{info, warn};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tempfile::TempDir;
+
+use crate::config::{Config, WorkspaceMemberConfig, WorkspaceTemplateConfig, CONFIG_FILE_NAME};
+use crate::generation::Generation;
+use crate::hooks::PoisonError;
+use crate::project_variables::insert_variable;
+use crate::template::{self, create_minijinja_engine, TemplateObjectResource};
+use crate::user_parsed_input::UserParsedInput;
+use crate::{case, emoji, ignore_me, workspace_member, GenerateArgs, GenerateOptions};
+
+/// The expanded members, parked outside of the workspace template until it is expanded itself.
+pub struct ExpandedMembers {
+    staging: TempDir,
+    members: Vec<String>,
+}
+
+/// Expands all members of the workspace template in `template_dir`.
+///
+/// The members are moved out of `template_dir`, so that they are not expanded again along with
+/// the rest of the workspace template; [`ExpandedMembers::finish`] moves them back into place.
+#[allow(clippy::too_many_arguments)]
+pub fn expand_members(
+    workspace: &WorkspaceTemplateConfig,
+    template_dir: &Path,
+    template_object: &TemplateObjectResource,
+    user_parsed_input: &UserParsedInput,
+    args: &GenerateArgs,
+    options: &GenerateOptions,
+    pbar: &mut MultiProgress,
+    generation: &Arc<Generation>,
+) -> Result<ExpandedMembers> {
+    let staging = crate::git::tmp_dir()?;
+    let mut members = Vec::with_capacity(workspace.members.len());
+
+    for member in &workspace.members {
+        let member_path = expand_member(
+            member,
+            template_dir,
+            staging.path(),
+            template_object,
+            user_parsed_input,
+            args,
+            options,
+            pbar,
+            generation,
+        )?;
+        members.push(member_path);
+    }
+
+    Ok(ExpandedMembers { staging, members })
+}
+
+impl ExpandedMembers {
+    /// Moves the expanded members into the expanded workspace template and adds them to the root
+    /// `Cargo.toml`.
+    pub fn finish(self, template_dir: &Path) -> Result<()> {
+        for member in &self.members {
+            let destination = template_dir.join(member);
+            if destination.exists() {
+                bail!(
+                    "{} {} `{}`",
+                    emoji::ERROR,
+                    style("Workspace member collides with a file of the workspace template:")
+                        .bold()
+                        .red(),
+                    style(member).bold().red(),
+                );
+            }
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(self.staging.path().join(member), destination)?;
+        }
+
+        workspace_member::stitch_workspace(template_dir, &self.members)
+    }
+}
+
+/// Expands a single member, returning the path it ends up at within the workspace.
+#[allow(clippy::too_many_arguments)]
+fn expand_member(
+    member: &WorkspaceMemberConfig,
+    template_dir: &Path,
+    staging_dir: &Path,
+    template_object: &TemplateObjectResource,
+    user_parsed_input: &UserParsedInput,
+    args: &GenerateArgs,
+    options: &GenerateOptions,
+    pbar: &mut MultiProgress,
+    generation: &Arc<Generation>,
+) -> Result<String> {
+    let mut context = template_object
+        .lock()
+        .map_err(|_| PoisonError)?
+        .borrow()
+        .clone();
+
+    let name = context
+        .get(&member.name_placeholder)
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            anyhow!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("Missing name of workspace member, define a string placeholder named")
+                    .bold()
+                    .red(),
+                style(&member.name_placeholder).bold().red(),
+            )
+        })?;
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        bail!(
+            "{} {} `{name}`",
+            emoji::ERROR,
+            style("Invalid name of workspace member:").bold().red(),
+        );
+    }
+
+    info!(
+        "{} {} `{}`{}",
+        emoji::WRENCH,
+        style("Generating workspace member").bold(),
+        style(&name).bold().yellow(),
+        style("...").bold()
+    );
+
+    let member_dir =
+        crate::resolve_template_dir_subfolder(template_dir, Some(member.template.as_str()))?;
+    let directory = member.directory.as_deref().map_or_else(
+        || {
+            Path::new(&member.template)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default()
+        },
+        PathBuf::from,
+    );
+    let member_path = directory.join(&name).to_string_lossy().replace('\\', "/");
+
+    insert_variable(
+        &mut context,
+        "project-name",
+        serde_json::Value::from(name.clone()),
+    );
+    insert_variable(
+        &mut context,
+        "crate_name",
+        serde_json::Value::from(case::crate_name(&name)),
+    );
+    let member_object: TemplateObjectResource = Arc::new(Mutex::new(RefCell::new(context)));
+
+    let mut config = Config::from_path(&Some(member_dir.join(CONFIG_FILE_NAME)))?;
+    if config.has_hooks() {
+        warn!(
+            "{} {} `{}`",
+            emoji::WARN,
+            style("Hooks of workspace members are not supported, ignoring those of").bold(),
+            style(&member.template).bold(),
+        );
+    }
+    crate::fill_placeholders_and_merge_conditionals(
+        &mut config,
+        &member_object,
+        user_parsed_input.template_values(),
+        args,
+        options,
+        generation,
+    )?;
+
+    let mut template_config = config.template.take().unwrap_or_default();
+    let preserve_whitespace = template_config.preserve_whitespace.unwrap_or(false);
+    ignore_me::remove_unneeded_files(&member_dir, &template_config, args.verbose)?;
+
+    let rhai_filter_files = Arc::new(Mutex::new(vec![]));
+    let engine = create_minijinja_engine(
+        member_dir.clone(),
+        member_object.clone(),
+        user_parsed_input.allow_commands(),
+        user_parsed_input.silent(),
+        rhai_filter_files.clone(),
+        preserve_whitespace,
+        generation,
+    );
+    let result = template::walk_dir(
+        &mut template_config,
+        &member_dir,
+        &[],
+        &member_object,
+        &engine,
+        &rhai_filter_files,
+        pbar,
+        args.quiet,
+        args.verbose,
+        generation,
+    );
+    if let Err(e) = result {
+        // Don't print the error twice
+        if !args.quiet && args.continue_on_error {
+            warn!("{e}");
+        }
+        if !args.continue_on_error {
+            return Err(e);
+        }
+    }
+
+    let staged = staging_dir.join(&member_path);
+    if staged.exists() {
+        bail!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Two workspace members would be generated into")
+                .bold()
+                .red(),
+            style(&member_path).bold().red(),
+        );
+    }
+    if let Some(parent) = staged.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(&member_dir, &staged)?;
+
+    // don't leave the now empty folders the member templates were grouped in behind
+    let template_dir = fs::canonicalize(template_dir)?;
+    for folder in member_dir.ancestors().skip(1) {
+        if !folder.starts_with(&template_dir) || folder == template_dir {
+            break;
+        }
+        if std::fs::remove_dir(folder).is_err() {
+            break;
+        }
+    }
+
+    Ok(member_path)
+}
@@ -126,6 +126,75 @@ fn it_substitutes_os_arch() {
     )));
 }
 
+#[test]
+fn it_exposes_the_platform_to_templates_and_conditionals() {
+    let template = tempdir()
+        .file(
+            "platform.txt",
+            indoc! {r#"
+                {{ platform.os }}/{{ platform.arch }}/{{ platform.family }}
+                {% if platform is windows %}run.bat{% else %}run.sh{% endif %}
+            "#},
+        )
+        .file("windows-only.txt", "")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [conditional.'platform.family != "windows"']
+                ignore = ["windows-only.txt"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let script = if cfg!(windows) { "run.bat" } else { "run.sh" };
+    assert_eq!(
+        dir.read("foobar-project/platform.txt"),
+        format!(
+            "{}/{}/{}\n{script}",
+            env::consts::OS,
+            env::consts::ARCH,
+            env::consts::FAMILY
+        )
+    );
+    assert_eq!(dir.exists("foobar-project/windows-only.txt"), cfg!(windows));
+}
+
+#[test]
+fn it_determines_the_rust_version_when_referenced() {
+    let template = tempdir()
+        .file("rust-version.txt", "{{ rust_version }}")
+        .file("defined.txt", "{{ rust_version | default('unset') }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let output = Command::new("rustc").arg("--version").output().unwrap();
+    let version = String::from_utf8(output.stdout).unwrap();
+    let version = version.split_whitespace().nth(1).unwrap();
+    assert_eq!(dir.read("foobar-project/rust-version.txt"), version);
+    assert_eq!(dir.read("foobar-project/defined.txt"), version);
+}
+
 #[test]
 fn it_keeps_snake_case_projectname() {
     let template = tempdir().init_default_template().build();
@@ -338,6 +407,32 @@ fn it_can_overwrite_files() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_keeps_existing_files_that_fail_to_render_on_init() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("README.md", "{% if %} broken")
+        .file("src/main.rs", "// {{ project_name }}")
+        .init_git()
+        .build();
+    let dir = tempdir().file("README.md", "original").build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("overwritten-proj")
+        .arg_branch("main")
+        .flag_init()
+        .arg("--overwrite")
+        .arg("--continue-on-error")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Not writing `README.md`").from_utf8());
+
+    assert_eq!(dir.read("README.md"), "original");
+    assert_eq!(dir.read("src/main.rs"), "// overwritten-proj");
+}
+
 #[test]
 fn it_allows_user_defined_projectname_when_passing_force_flag() {
     let template = tempdir().init_default_template().build();
@@ -941,3 +1036,571 @@ fn error_message_for_invalid_repo_or_user() {
                 .from_utf8(),
         );
 }
+
+#[test]
+fn it_pins_date_variables_to_the_given_timestamp() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "dates.txt",
+            "{{ current_datetime }} {{ current_date_iso }} {{ current_year }} {{ current_timestamp }}",
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("from-flag")
+        .arg_branch("main")
+        .args(["--timestamp", "1704067199", "--utc-offset", "+02:00"])
+        .current_dir(dir.path())
+        .env("SOURCE_DATE_EPOCH", "0")
+        .assert()
+        .success();
+    binary()
+        .arg_git(template.path())
+        .arg_name("from-env")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .env("SOURCE_DATE_EPOCH", "1704067199")
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("from-flag/dates.txt"),
+        "2024-01-01T01:59:59+02:00 2024-01-01 2024 1704067199"
+    );
+    assert_eq!(
+        dir.read("from-env/dates.txt"),
+        "2023-12-31T23:59:59Z 2023-12-31 2023 1704067199"
+    );
+}
+
+#[test]
+fn it_generates_identical_output_in_reproducible_mode() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("dates.txt", "{{ current_datetime }} {{ current_year }}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators]
+                license = true
+            "#},
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    for name in ["first", "second"] {
+        binary()
+            .arg_git(template.path())
+            .arg_name(name)
+            .arg_branch("main")
+            .arg("--silent")
+            .arg("--reproducible")
+            .args(["--define", "license=MIT"])
+            .current_dir(dir.path())
+            .env_remove("SOURCE_DATE_EPOCH")
+            .assert()
+            .success();
+    }
+
+    assert_eq!(dir.read("first/dates.txt"), "1970-01-01T00:00:00Z 1970");
+    for file in ["dates.txt", "LICENSE", "Cargo.toml"] {
+        assert_eq!(
+            dir.read(&format!("first/{file}"))
+                .replace("first", "second"),
+            dir.read(&format!("second/{file}")),
+            "{file}"
+        );
+    }
+}
+
+#[test]
+fn it_prints_plain_ascii_without_emoji() {
+    let template = tempdir().with_default_manifest().init_git().build();
+    let dir = tempdir().build();
+
+    for (name, flag, env) in [
+        ("from-flag", "--no-emoji", ""),
+        ("from-env", "--verbose", "1"),
+    ] {
+        let output = binary()
+            .arg_git(template.path())
+            .arg_name(name)
+            .arg_branch("main")
+            .arg(flag)
+            .current_dir(dir.path())
+            .env("CARGO_GENERATE_NO_EMOJI", env)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        let stdout = String::from_utf8(output).unwrap();
+        assert!(stdout.contains("Done!"), "{stdout}");
+        assert!(stdout.is_ascii(), "{stdout}");
+    }
+}
+
+#[test]
+fn it_uses_colors_as_requested() {
+    let template = tempdir().with_default_manifest().init_git().build();
+    let dir = tempdir().build();
+
+    for (name, color, no_color, colored) in [
+        ("always", "always", "", true),
+        ("never", "never", "", false),
+        ("no-color", "auto", "1", false),
+    ] {
+        let output = binary()
+            .arg_git(template.path())
+            .arg_name(name)
+            .arg_branch("main")
+            .args(["--color", color])
+            .current_dir(dir.path())
+            .env("CLICOLOR_FORCE", "1")
+            .env("NO_COLOR", no_color)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        let stdout = String::from_utf8(output).unwrap();
+        assert_eq!(stdout.contains('\u{1b}'), colored, "{name}: {stdout}");
+    }
+}
+
+#[test]
+fn it_derives_an_ascii_crate_name_from_an_international_project_name() {
+    let template = tempdir()
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "{{ crate_name }}"
+                description = "{{ project_name | ascii_slug }} {{ '日本Project' | snake_case }}"
+            "#},
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("Straße-Café")
+        .arg_branch("main")
+        .arg("--force")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let cargo_toml = dir.read("Straße-Café/Cargo.toml");
+    assert!(
+        cargo_toml.contains(r#"name = "strasse_cafe""#),
+        "{cargo_toml}"
+    );
+    assert!(
+        cargo_toml.contains(r#"description = "strasse-cafe 日本_project""#),
+        "{cargo_toml}"
+    );
+}
+
+#[test]
+fn it_prints_the_slowest_files_and_hooks_with_profile_render() {
+    let template = tempdir()
+        .file("slow.txt", "{% for i in range(1000) %}{{ i }}{% endfor %}")
+        .file("pre.rhai", r#"variable::set("x", "y");"#)
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                pre = ["pre.rhai"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--profile-render", "1"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("Slowest files (parse / render / write):")
+                .and(predicates::str::contains("slow.txt"))
+                .and(predicates::str::contains("Slowest hooks:"))
+                .and(predicates::str::contains("pre.rhai"))
+                .from_utf8(),
+        );
+}
+
+#[test]
+fn it_records_the_provenance_of_the_project_in_a_lockfile() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.flavor]
+                type = "string"
+                prompt = "Flavor?"
+            "#},
+        )
+        .file("README.md", "{{ flavor }}")
+        .init_git()
+        .build();
+    let commit = Repository::open(template.path())
+        .unwrap()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .id()
+        .to_string();
+
+    let dir = tempdir().build();
+    let generate = |name: &str, flavor: &str| {
+        binary()
+            .arg_git(template.path())
+            .arg_name(name)
+            .arg_branch("main")
+            .args(["--lockfile", "--timestamp", "1704067199"])
+            .args(["-d", &format!("flavor={flavor}")])
+            .current_dir(dir.path())
+            .assert()
+            .success();
+        cargo_generate::GenerationLock::read(&dir.path().join(name)).unwrap()
+    };
+
+    let lock = generate("vanilla-project", "vanilla");
+    assert_eq!(lock.template.source, template.path().display().to_string());
+    assert_eq!(lock.template.branch.as_deref(), Some("main"));
+    assert_eq!(lock.template.commit, Some(commit));
+    assert_eq!(lock.tool_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(lock.generated_at, "2023-12-31T23:59:59Z");
+
+    let same_answers = generate("other-vanilla-project", "vanilla");
+    let other_answers = generate("chocolate-project", "chocolate");
+    assert_eq!(lock.placeholders_hash, same_answers.placeholders_hash);
+    assert_ne!(lock.placeholders_hash, other_answers.placeholders_hash);
+}
+
+#[test]
+fn it_tells_where_a_project_came_from_by_its_lockfile() {
+    let template = tempdir().init_default_template().init_git().build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--lockfile", "--timestamp", "1704067199"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    binary()
+        .arg("info")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains(template.path().display().to_string())
+                .and(predicates::str::contains("Branch: main"))
+                .and(predicates::str::contains("2023-12-31T23:59:59Z"))
+                .from_utf8(),
+        );
+    binary()
+        .arg("info")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("without `--lockfile`").from_utf8());
+}
+
+#[test]
+fn it_writes_no_lockfile_unless_asked_to() {
+    let template = tempdir().with_default_manifest().init_git().build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(dir.exists("foobar-project/Cargo.toml"));
+    assert!(!dir.exists("foobar-project/.cargo-generate.lock"));
+}
+
+#[test]
+fn it_renames_a_project_whose_crate_name_is_a_keyword() {
+    let template = tempdir().init_default_template().init_git().build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("match")
+        .arg_branch("main")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("is a Rust keyword").from_utf8());
+
+    assert!(!dir.exists("match"));
+    assert!(dir
+        .read("match-rs/Cargo.toml")
+        .contains(r#"name = "match-rs""#));
+}
+
+#[test]
+fn it_exits_with_a_code_telling_what_failed() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.port]
+                prompt = "Port?"
+                regex = "^[0-9]+$"
+
+                [hooks]
+                pre = ["pre-script.rhai"]
+            "#},
+        )
+        .file(
+            "pre-script.rhai",
+            r#"if variable::get("port") == "0" { abort("no port"); }"#,
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(dir.path().join("missing"))
+        .arg_name("fetched-project")
+        .current_dir(dir.path())
+        .assert()
+        .code(3);
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("validated-project")
+        .args(["--define", "port=http"])
+        .current_dir(dir.path())
+        .assert()
+        .code(4);
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("hooked-project")
+        .args(["--define", "port=0"])
+        .current_dir(dir.path())
+        .assert()
+        .code(5)
+        .stderr(predicates::str::contains("no port").from_utf8());
+}
+
+#[test]
+fn it_explains_what_set_a_variable_with_explain_var() {
+    let template = tempdir()
+        .file("pre-script.rhai", r#"variable::set("license", "Apache-2.0");"#)
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.license]
+                type = "string"
+                prompt = "License?"
+
+                [hooks]
+                pre = ["pre-script.rhai"]
+            "#},
+        )
+        .file("LICENSE", "{{ license }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "license=MIT"])
+        .args(["--explain-var", "license", "--explain-var", "nothing"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("`license` was set by:")
+                .and(predicates::str::contains(r#"--define: "MIT""#))
+                .and(predicates::str::contains(
+                    r#"the hook `pre-script.rhai`: "Apache-2.0", overwriting "MIT""#,
+                ))
+                .and(predicates::str::contains("`nothing` was never set"))
+                .from_utf8(),
+        );
+
+    assert_eq!(dir.read("foobar-project/LICENSE"), "Apache-2.0");
+}
+
+#[cfg(unix)]
+#[test]
+fn it_writes_a_script_regenerating_the_project_with_the_same_answers() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.flavor]
+                type = "string"
+                prompt = "Flavor?"
+            "#},
+        )
+        .file("README.md", "{{ flavor }}")
+        .build();
+
+    let dir = tempdir().build();
+    binary()
+        .arg_path(template.path())
+        .arg_name("foobar-project")
+        .args(["--regenerate-script", "-d", "flavor=it's vanilla"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert_eq!(dir.read("foobar-project/README.md"), "it's vanilla");
+
+    fs::write(template.path().join("README.md"), "# {{ flavor }}").unwrap();
+    // `cargo generate-mj` runs the binary under test
+    let bin_dir = assert_cmd::cargo::cargo_bin("cargo-generate-mj")
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    let path = env::join_paths(
+        std::iter::once(bin_dir).chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())),
+    )
+    .unwrap();
+    let cargo_home = tempdir().build();
+    Command::new("sh")
+        .arg(dir.path().join("foobar-project/regenerate.sh"))
+        .env("PATH", path)
+        .env("CARGO_HOME", cargo_home.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/README.md"), "# it's vanilla");
+    assert!(dir.exists("foobar-project/regenerate.sh"));
+}
+
+#[test]
+fn it_fails_on_more_files_than_the_limits_allow() {
+    let template = tempdir()
+        .init_default_template()
+        .file("LICENSE", "MIT")
+        .init_git()
+        .build();
+    let config = tempdir()
+        .file("cargo-generate.toml", "[limits]\nmax_files = 1\n")
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--config")
+        .arg(config.path().join("cargo-generate.toml"))
+        .current_dir(dir.path())
+        .assert()
+        .code(6)
+        .stderr(predicates::str::contains("max_files").from_utf8());
+    assert!(!dir.exists("foobar-project/Cargo.toml"));
+}
+
+#[test]
+fn it_lists_the_templates_to_choose_from_without_a_terminal() {
+    let template = tempdir()
+        .file(
+            "cli/cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                description = "A command line tool"
+            "#},
+        )
+        .file("cli/README.md", "cli")
+        .file("web/cargo-generate.toml", "")
+        .file("web/README.md", "web")
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Several templates were found")
+                .and(predicates::str::contains("cli: A command line tool"))
+                .and(predicates::str::contains("    web"))
+                .from_utf8(),
+        );
+
+    binary()
+        .arg_git(template.path())
+        .arg("web")
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert_eq!(dir.read("foobar-project/README.md"), "web");
+}
+
+#[test]
+fn it_formats_dates_with_strftime() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "dates.txt",
+            indoc! {r#"
+                {{ now() | date("%A, %B %-d %Y %H:%M %:z") }}
+                {{ now("Z") }}
+                {{ now("Europe/Paris") }}
+                {{ current_timestamp | date("%F") }}
+                {{ "31/12/2023" | date("%j", "%d/%m/%Y") }}"#},
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--timestamp", "1704067199", "--utc-offset", "+02:00"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/dates.txt"),
+        "Monday, January 1 2024 01:59 +02:00\n2023-12-31T23:59:59+00:00\n\
+         2024-01-01T00:59:59+01:00\n2024-01-01\n365"
+    );
+}
@@ -0,0 +1,78 @@
+use crate::helpers::prelude::*;
+
+/// A config declaring a fetcher for `tpl://`, copying the template from `template` and noting
+/// the URL it was asked for.
+fn config_fetching_from(template: &Project) -> Project {
+    tempdir()
+        .file(
+            "cargo-generate",
+            format!(
+                indoc! {r#"
+                    [fetchers.tpl]
+                    command = ["sh", "-c", "cp -R '{}'/. \"$1\" && echo \"$0\" > \"$1/url.txt\""]
+                "#},
+                template.path().display().to_string().escape_default()
+            ),
+        )
+        .build()
+}
+
+#[test]
+fn it_fetches_templates_with_the_fetcher_of_their_scheme() {
+    let template = tempdir().file("README.md", "{{ project_name }}").build();
+    let config = config_fetching_from(&template);
+    let dir = tempdir().build();
+
+    binary()
+        .arg("--config")
+        .arg(config.path().join("cargo-generate"))
+        .arg("tpl://templates/readme")
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/README.md"), "foobar-project");
+    assert_eq!(
+        dir.read("foobar-project/url.txt").trim(),
+        "tpl://templates/readme"
+    );
+
+    binary()
+        .arg("--config")
+        .arg(config.path().join("cargo-generate"))
+        .arg_git("tpl://templates/readme")
+        .arg_name("other-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("other-project/README.md"), "other-project");
+}
+
+#[test]
+fn it_fails_when_the_fetcher_fails() {
+    let config = tempdir()
+        .file(
+            "cargo-generate",
+            indoc! {r#"
+                [fetchers.tpl]
+                command = ["sh", "-c", "exit 3"]
+            "#},
+        )
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg("--config")
+        .arg(config.path().join("cargo-generate"))
+        .arg("tpl://templates/readme")
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Fetcher failed to fetch `tpl://templates/readme`")
+                .from_utf8(),
+        );
+}
@@ -1,3 +1,5 @@
 mod defaults;
 mod favorites;
+mod fetchers;
+mod remembered;
 mod values;
@@ -0,0 +1,84 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_offers_remembered_answers_as_default() {
+    let config_dir = tempdir().file("cargo-generate.toml", "").build();
+    let template_dir = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.github_org]
+                type = "string"
+                prompt = "GitHub organization?"
+                default = "template-org"
+                remember = true
+            "#},
+        )
+        .file("org.txt", "{{ github_org }}")
+        .init_git()
+        .build();
+    let working_dir = tempdir().build();
+
+    binary()
+        .arg("--config")
+        .arg(config_dir.path().join("cargo-generate.toml"))
+        .arg_name("first")
+        .arg_git(template_dir.path())
+        .arg("--silent")
+        .args(["--define", "github_org=my-org"])
+        .current_dir(working_dir.path())
+        .assert()
+        .success();
+    assert!(config_dir
+        .read("cargo-generate-remembered.toml")
+        .contains(r#"github_org = "my-org""#));
+
+    binary()
+        .arg("--config")
+        .arg(config_dir.path().join("cargo-generate.toml"))
+        .arg_name("second")
+        .arg_git(template_dir.path())
+        .arg("--silent")
+        .current_dir(working_dir.path())
+        .assert()
+        .success();
+    assert_eq!(working_dir.read("second/org.txt"), "my-org");
+    assert_eq!(
+        config_dir.read("cargo-generate.toml"),
+        "",
+        "the application config is left alone"
+    );
+}
+
+#[test]
+fn it_does_not_remember_internal_answers() {
+    let config_dir = tempdir().file("cargo-generate.toml", "").build();
+    let template_dir = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders._token]
+                type = "string"
+                prompt = "Token?"
+                remember = true
+            "#},
+        )
+        .file("token.txt", "{{ _token }}")
+        .init_git()
+        .build();
+    let working_dir = tempdir().build();
+
+    binary()
+        .arg("--config")
+        .arg(config_dir.path().join("cargo-generate.toml"))
+        .arg_name("first")
+        .arg_git(template_dir.path())
+        .arg("--silent")
+        .args(["--define", "_token=secret"])
+        .current_dir(working_dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("secret").not().from_utf8());
+    assert_eq!(working_dir.read("first/token.txt"), "secret");
+    assert!(!config_dir.exists("cargo-generate-remembered.toml"));
+}
@@ -415,3 +415,115 @@ fn cli_values_are_checked_via_regex() {
         .assert()
         .failure();
 }
+
+#[test]
+fn it_accepts_template_values_from_a_url() {
+    let values_dir = tempdir()
+        .file(
+            "defaults.json",
+            r#"{ "values": { "registry": "intranet", "prefix": "acme" } }"#,
+        )
+        .build();
+    let template = tempdir()
+        .file(
+            "random.toml",
+            indoc! {r#"
+                registry = "{{registry}}"
+                prefix = "{{prefix}}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_name("foobar-project")
+        .arg_git(template.path())
+        .arg("--values-url")
+        .arg(format!(
+            "file://{}",
+            values_dir.path().join("defaults.json").display()
+        ))
+        .arg("-d")
+        .arg("prefix=cli-value")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let random_toml = dir.read("foobar-project/random.toml");
+    assert!(random_toml.contains(r#"registry = "intranet""#));
+    assert!(random_toml.contains(r#"prefix = "cli-value""#));
+}
+
+#[test]
+fn it_accepts_a_values_url_from_the_config() {
+    let values_dir = tempdir()
+        .file(
+            "defaults.toml",
+            indoc! {r#"
+                [values]
+                registry = "intranet"
+                prefix = "acme"
+            "#},
+        )
+        .build();
+    let config_dir = tempdir()
+        .file(
+            "cargo-generate",
+            format!(
+                indoc! {r#"
+                    [defaults]
+                    values_url = "file://{}"
+
+                    [values]
+                    prefix = "mine"
+                "#},
+                values_dir.path().join("defaults.toml").display()
+            ),
+        )
+        .build();
+    let template = tempdir()
+        .file(
+            "random.toml",
+            indoc! {r#"
+                registry = "{{registry}}"
+                prefix = "{{prefix}}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg("--config")
+        .arg(config_dir.path().join("cargo-generate"))
+        .arg_name("foobar-project")
+        .arg_git(template.path())
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let random_toml = dir.read("foobar-project/random.toml");
+    assert!(random_toml.contains(r#"registry = "intranet""#));
+    assert!(random_toml.contains(r#"prefix = "mine""#));
+}
+
+#[test]
+fn it_fails_when_the_values_url_cannot_be_fetched() {
+    let template = tempdir().file("random.toml", "").init_git().build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_name("foobar-project")
+        .arg_git(template.path())
+        .arg("--values-url")
+        .arg(format!("file://{}", dir.path().join("missing.json").display()))
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Values URL Error").from_utf8());
+}
@@ -0,0 +1,83 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+use crate::helpers::prelude::*;
+
+/// Starts an API on this machine knowing only the crate `taken`, returning its URL.
+fn start_api() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request_line = String::new();
+            BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .unwrap();
+            let status = if request_line.contains("/crates/taken ") {
+                "200 OK"
+            } else {
+                "404 Not Found"
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}"
+            )
+            .unwrap();
+        }
+    });
+    format!("http://{address}/api/v1")
+}
+
+#[test]
+fn it_warns_about_crate_names_taken_on_crates_io() {
+    let api = start_api();
+    let template = tempdir().init_default_template().init_git().build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("taken")
+        .arg_branch("main")
+        .arg("--allow-network")
+        .current_dir(dir.path())
+        .env("CARGO_GENERATE_CRATES_IO_API", &api)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("is already taken on crates.io").from_utf8());
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("still-free")
+        .arg_branch("main")
+        .arg("--allow-network")
+        .current_dir(dir.path())
+        .env("CARGO_GENERATE_CRATES_IO_API", &api)
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("is already taken on crates.io")
+                .not()
+                .from_utf8(),
+        );
+    assert!(dir.exists("taken/Cargo.toml"));
+    assert!(dir.exists("still-free/Cargo.toml"));
+}
+
+#[test]
+fn it_does_not_check_crate_names_without_allow_network() {
+    let template = tempdir().init_default_template().init_git().build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("taken")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .env("CARGO_GENERATE_CRATES_IO_API", "http://127.0.0.1:1/api/v1")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("crates.io").not().from_utf8());
+}
@@ -113,3 +113,130 @@ fn assert_liquid_paths(location: Location) {
         "project should not contain foobar-project/README.md.liquid"
     );
 }
+
+#[test]
+fn it_expands_files_and_folders_for_each_element_of_a_list() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                services = { type = "array", prompt = "Services?", choices = ["auth", "billing", "search"], default = ["auth", "billing"] }
+            "#},
+        )
+        .file(
+            "src/services/{{ each service in services }}.rs",
+            "pub struct {{ service | upper_camel_case }}Service;",
+        )
+        .file(
+            "handlers/{{ each services }}/mod.rs",
+            "// handles {{ item }} for {{ project_name }}",
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "services=auth,billing"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(
+        dir.read("foobar-project/src/services/auth.rs"),
+        "pub struct AuthService;"
+    );
+    assert_eq!(
+        dir.read("foobar-project/src/services/billing.rs"),
+        "pub struct BillingService;"
+    );
+    assert!(!dir.exists("foobar-project/src/services/search.rs"));
+    assert_eq!(
+        dir.read("foobar-project/handlers/billing/mod.rs"),
+        "// handles billing for foobar-project"
+    );
+    assert_eq!(
+        std::fs::read_dir(dir.path().join("foobar-project/handlers"))
+            .unwrap()
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn it_numbers_files_expanded_for_each_element_of_a_list() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                migrations = { type = "array", prompt = "Migrations?", choices = ["init", "users", "orders"], default = ["init"] }
+            "#},
+        )
+        .file(
+            "migrations/{{ loop_index | zero_pad(4) }}_{{ each migration in migrations }}.sql",
+            "-- migration {{ loop_index }}: {{ loop_value }}",
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "migrations=init,users"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/migrations/0001_init.sql"),
+        "-- migration 1: init"
+    );
+    assert_eq!(
+        dir.read("foobar-project/migrations/0002_users.sql"),
+        "-- migration 2: users"
+    );
+}
+
+#[test]
+fn it_reports_all_invalid_filenames_before_writing_any_file() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("{{ project_name }}.rs", "// rendered")
+        .file("foobar-project.rs", "// kept")
+        .file("{{ nothing }}/lib.rs", "// nowhere")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Failed templating the names of")
+                .and(predicates::str::contains(
+                    "{{ project_name }}.rs: would overwrite `foobar-project.rs`",
+                ))
+                .and(predicates::str::contains("renders to an empty one"))
+                .and(predicates::str::contains("{{ nothing }}/lib.rs").not())
+                .from_utf8(),
+        );
+
+    assert!(!dir.exists("foobar-project/foobar-project.rs"));
+    assert!(!dir.exists("foobar-project/Cargo.toml"));
+}
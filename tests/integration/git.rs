@@ -180,3 +180,51 @@ fn should_find_them_all() {
         }
     }
 }
+
+#[test]
+fn the_git_command_line_tool_can_clone_a_branch_and_init_the_project() {
+    let template = tempdir().init_default_template().branch("bak").build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_branch("bak")
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .env("CARGO_GENERATE_VCS_BACKEND", "git-cli")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("foobar-project/Cargo.toml")
+        .contains("foobar-project"));
+    let repo = Repository::open(dir.path().join("foobar-project")).unwrap();
+    assert_eq!(0, repo.references().unwrap().count());
+}
+
+#[test]
+fn the_git_command_line_tool_can_clone_a_tag_or_revision() {
+    let template = tempdir().init_default_template().tag("v1.0").build();
+    // the tagged commit, the template gets another one after the tag
+    let commit_sha = template.commit_shas().last().unwrap().to_string();
+    let dir = tempdir().build();
+
+    for (name, flag, value) in [
+        ("tagged-project", "--tag", "v1.0".to_string()),
+        ("revision-project", "--revision", commit_sha),
+    ] {
+        binary()
+            .arg(flag)
+            .arg(value)
+            .arg_git(template.path())
+            .arg_name(name)
+            .current_dir(dir.path())
+            .env("CARGO_GENERATE_VCS_BACKEND", "git-cli")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Done!").from_utf8());
+
+        assert!(dir.read(&format!("{name}/Cargo.toml")).contains(name));
+    }
+}
@@ -1,2 +1,34 @@
 use crate::helpers::prelude::*;
 
+#[test]
+fn it_clones_the_template_the_gitconfig_puts_instead_of_the_url() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("README.md", "{{project_name}} from the mirror")
+        .init_git()
+        .build();
+    let home = tempdir()
+        .file(
+            ".gitconfig",
+            format!(
+                indoc! {r#"
+                    [url "{}"]
+                        insteadOf = https://git.example.invalid/templates/
+                "#},
+                template.path().join("").display()
+            ),
+        )
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git("https://git.example.invalid/templates/")
+        .arg_gitconfig(home.path().join(".gitconfig"))
+        .arg_name("mirrored")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("mirrored/README.md"), "mirrored from the mirror");
+}
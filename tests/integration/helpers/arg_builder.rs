@@ -2,17 +2,25 @@ use assert_cmd::prelude::*;
 use std::ffi::OsStr;
 use std::path::Path;
 use std::process::Command;
+use tempfile::TempDir;
 
 pub fn binary() -> CargoGenerateArgBuilder {
     CargoGenerateArgBuilder::new()
 }
 
-pub struct CargoGenerateArgBuilder(Command);
+/// The command, and the `$CARGO_HOME` of its own it runs with, so the records, resume states and
+/// remembered answers of a test never end up with the developer's.
+pub struct CargoGenerateArgBuilder(Command, TempDir);
 
 impl CargoGenerateArgBuilder {
     pub fn new() -> Self {
-        let mut builder = Self(Command::cargo_bin("cargo-generate-mj").unwrap());
+        let cargo_home = tempfile::Builder::new()
+            .prefix("cargo-generate-home")
+            .tempdir()
+            .unwrap();
+        let mut builder = Self(Command::cargo_bin("cargo-generate-mj").unwrap(), cargo_home);
         builder.0.arg("generate-mj");
+        builder.0.env("CARGO_HOME", builder.1.path());
 
         builder
     }
@@ -112,6 +112,43 @@ fn it_runs_system_commands() {
     assert!(dir.exists("script-project/touched_file"));
 }
 
+#[cfg(unix)]
+#[test]
+fn hooks_run_commands_where_declared_with_their_environment() {
+    let template = tempdir()
+        .file(
+            "system-script.rhai",
+            indoc! {r#"
+                print(system::command("echo", ["$(pwd)", "$CRATE"]));
+            "#},
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+            [hooks]
+            post = [
+                { script = "system-script.rhai", cwd = "dest", env = { CRATE = "{{ crate_name }}" } },
+            ]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("script-project")
+        .arg("--allow-commands")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(format!(
+            "{} script_project",
+            dir.path().canonicalize().unwrap().join("script-project").display()
+        )));
+}
+
 #[test]
 fn it_fails_to_prompt_for_system_commands_in_silent_mode() {
     let template = tempdir()
@@ -144,6 +181,70 @@ fn it_fails_to_prompt_for_system_commands_in_silent_mode() {
         .stderr(predicates::str::contains("--allow-commands").from_utf8());
 }
 
+#[test]
+fn it_uses_the_defaults_of_prompt_or_in_silent_mode() {
+    let template = tempdir()
+        .file(
+            "pre-script.rhai",
+            indoc! {r#"
+                variable::set("license", variable::prompt_or("License?", "MIT"));
+            "#},
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+            [hooks]
+            pre = ["pre-script.rhai"]
+            "#},
+        )
+        .file("LICENSE", "{{license}}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("script-project")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("script-project/LICENSE"), "MIT");
+}
+
+#[test]
+fn it_fails_to_prompt_from_hooks_in_silent_mode() {
+    let template = tempdir()
+        .file(
+            "pre-script.rhai",
+            indoc! {r#"
+                variable::set("license", variable::prompt("License?", "MIT"));
+            "#},
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+            [hooks]
+            pre = ["pre-script.rhai"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("script-project")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("variable::prompt_or").from_utf8());
+}
+
 #[test]
 fn it_fails_when_a_system_command_returns_non_zero_exit_code() {
     let template = tempdir()
@@ -226,6 +327,75 @@ fn it_fails_when_it_cant_execute_system_command() {
         );
 }
 
+#[test]
+fn hooks_can_render_strings() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+            [hooks]
+            post = ["post-script.rhai"]
+            "#},
+        )
+        .file(
+            "post-script.rhai",
+            indoc! {r#"
+                variable::set("features", ["cli", "tui"]);
+                file::write("FEATURES", render("{% for f in features %}{{ f | upper }};{% endfor %}"));
+                print(render("Generated {{ project_name }}"));
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("script-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Generated script-project"));
+
+    assert_eq!(dir.read("script-project/FEATURES"), "CLI;TUI;");
+}
+
+#[test]
+fn aliases_have_the_values_of_their_variables() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+            [template.variable_aliases]
+            app_name = "project-name"
+            [hooks]
+            pre = ["pre-script.rhai"]
+            "#},
+        )
+        .file(
+            "pre-script.rhai",
+            r#"variable::set("greeting", "Hello from " + variable::get("app_name"));"#,
+        )
+        .file("{{app_name}}.txt", "{{ greeting }}, {{ app_name | upper }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("script-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("script-project/script-project.txt"),
+        "Hello from script-project, SCRIPT-PROJECT"
+    );
+}
+
 #[test]
 fn it_can_change_case() {
     let template = tempdir()
@@ -519,6 +689,125 @@ fn date_works() {
     );
 }
 
+#[test]
+fn hooks_see_the_time_of_the_generation() {
+    let template = tempdir()
+        .file(
+            "init.rhai",
+            indoc! {r#"
+                let dt = system::date();
+                variable::set("date", `${dt.year}-${dt.month}-${dt.day}`);
+                print(`epoch: ${system::command("echo", ["${SOURCE_DATE_EPOCH:-unset}"])}`);
+            "#},
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                init = ["init.rhai"]
+            "#},
+        )
+        .file("generated.txt", "{{date}}")
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    for (name, reproducible, epoch) in [
+        ("pinned", false, "unset"),
+        ("reproducible", true, "1704067199"),
+    ] {
+        let mut command = binary();
+        if reproducible {
+            command.arg("--reproducible");
+        }
+        command
+            .arg_git(template.path())
+            .arg_name(name)
+            .args(["--timestamp", "1704067199", "--utc-offset", "+02:00"])
+            .arg("--allow-commands")
+            .current_dir(dir.path())
+            .env_remove("SOURCE_DATE_EPOCH")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(format!("epoch: {epoch}")).from_utf8());
+
+        assert_eq!(dir.read(&format!("{name}/generated.txt")), "2023-12-31");
+    }
+}
+
+#[test]
+fn rhai_filter_files_are_left_out_wherever_they_are_used() {
+    let template = tempdir()
+        .file(
+            "filters/a.rhai",
+            indoc! {r#"
+                "{{ from a }}"
+            "#},
+        )
+        .file("filters/b.rhai", r#""from b""#)
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+            [placeholders.greeting]
+            type = "string"
+            prompt = "Greeting?"
+            default = "{{ 'filters/b.rhai' | rhai }}"
+            "#},
+        )
+        .file("z.txt", r#"{{ "filters/a.rhai" | rhai }} {{ greeting }}"#)
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("filter-project")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("filter-project/z.txt"), "{{ from a }} from b");
+    assert!(!dir.exists("filter-project/filters/a.rhai"));
+    assert!(!dir.exists("filter-project/filters/b.rhai"));
+}
+
+#[test]
+fn scripts_can_be_declared_in_the_template_table() {
+    let template = tempdir()
+        .file("filters/shout.rhai", r#""{{ not rendered }}""#)
+        .file("scripts/pre.rhai", r#"variable::set("greeting", "hello");"#)
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+            [template]
+            rhai_filters = ["filters/*.rhai"]
+            hooks = { pre = ["scripts/pre.rhai"] }
+            "#},
+        )
+        .file("greeting.txt", "{{ greeting }} {{ filter | rhai }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("script-project")
+        .args(["--define", "filter=filters/shout.rhai"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("script-project/greeting.txt"),
+        "hello {{ not rendered }}"
+    );
+    assert!(!dir.exists("script-project/filters/shout.rhai"));
+    assert!(!dir.exists("script-project/scripts/pre.rhai"));
+}
+
 #[test]
 fn missing_rhai_filter_fails_prints_warnings() {
     let template = tempdir()
@@ -546,3 +835,583 @@ fn missing_rhai_filter_fails_prints_warnings() {
         .read("filter-project/file_to_expand.txt")
         .contains("filter-script.rhai"));
 }
+
+#[test]
+fn post_hook_can_generate_sub_projects() {
+    let examples_template = tempdir()
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "{{project_name}}"
+                description = "Examples of {{parent}}"
+            "#},
+        )
+        .init_git()
+        .build();
+    let template = tempdir()
+        .file(
+            "post-script.rhai",
+            format!(
+                "let examples = generate::run(`{}`, \"examples\", #{{ parent: variable::get(\"project-name\") }});\n\
+                 file::write(\"examples.txt\", examples);",
+                examples_template.path().display()
+            ),
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                post = ["post-script.rhai"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("parent-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("parent-project/examples/Cargo.toml")
+        .contains(r#"description = "Examples of parent-project""#));
+    assert_eq!(dir.read("parent-project/examples.txt"), "examples");
+    assert!(!dir.exists("parent-project/examples/.git"));
+}
+
+#[test]
+fn nested_generation_is_limited() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                post = ["post-script.rhai"]
+            "#},
+        )
+        .build();
+    std::fs::write(
+        template.path().join("post-script.rhai"),
+        format!("generate::run(`{}`, \"again\");", template.path().display()),
+    )
+    .unwrap();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_path(template.path())
+        .arg_name("recursive-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("nested more than 4 levels deep").from_utf8());
+}
+
+#[test]
+fn nested_generation_from_remote_templates_needs_allow_network() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                post = ["post-script.rhai"]
+            "#},
+        )
+        .file(
+            "post-script.rhai",
+            r#"generate::run("gh:me/examples-template", "examples");"#,
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("outer-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("needs `--allow-network`").from_utf8());
+    assert!(!dir.exists("outer-project/examples"));
+}
+
+#[test]
+fn pre_hook_generating_a_sub_project_leaves_the_template_as_it_was() {
+    let template = tempdir()
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "{{project_name}}"
+                {% if false %}publish = false{% endif %}
+            "#},
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                ignore = ["member"]
+
+                [template.functions]
+                answer = "answer.rhai"
+
+                [hooks]
+                pre = ["pre-script.rhai"]
+            "#},
+        )
+        .file("answer.rhai", "fn answer() { 42 }")
+        .file("pre-script.rhai", r#"generate::run("member", "nested");"#)
+        .file("README.md", "{{ answer() }}")
+        .file(
+            "member/Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "{{project_name}}"
+                # {{crate_type}}
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("outer-project")
+        .arg("--bin")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Favorite").not().from_utf8());
+
+    assert_eq!(dir.read("outer-project/README.md"), "42");
+    assert!(dir
+        .read("outer-project/nested/Cargo.toml")
+        .contains("# bin"));
+}
+
+#[test]
+fn templates_rendering_strictly_stay_strict_after_generating_sub_projects() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                strict = true
+                ignore = ["member"]
+
+                [hooks]
+                pre = ["pre-script.rhai"]
+            "#},
+        )
+        .file("pre-script.rhai", r#"generate::run("member", "nested");"#)
+        .file("README.md", "{{ autor }}")
+        .file("member/README.md", "{{ autor }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("outer-project")
+        .current_dir(dir.path())
+        .assert()
+        .code(6)
+        .stderr(predicates::str::contains("undefined value `autor`").from_utf8());
+}
+
+#[test]
+fn the_answers_are_reused_with_resume_after_a_hook_failed() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.license]
+                prompt = "License?"
+
+                [hooks]
+                post = ["post-script.rhai"]
+            "#},
+        )
+        .file(
+            "post-script.rhai",
+            indoc! {r#"
+                if variable::is_set("offline") {
+                    throw "the registry is unreachable";
+                }
+            "#},
+        )
+        .file("LICENSE", "{{ license }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+    let cargo_home = tempdir().build();
+    let state_files = || {
+        std::fs::read_dir(cargo_home.path().join("cargo-generate-resume"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>()
+    };
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("resumed-project")
+        .arg_branch("main")
+        .args(["--define", "license=MIT", "--define", "offline=true"])
+        .current_dir(dir.path())
+        .env("CARGO_HOME", cargo_home.path())
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("--resume").from_utf8());
+
+    let kept = state_files();
+    assert_eq!(kept.len(), 1);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&kept[0]).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "the answers are the user's only");
+    }
+
+    // neither the name nor the license are given again, and there is no terminal to ask on
+    binary()
+        .arg_git(template.path())
+        .arg_branch("main")
+        .arg("--resume")
+        .current_dir(dir.path())
+        .env("CARGO_HOME", cargo_home.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("resumed-project/LICENSE"), "MIT");
+    assert!(
+        state_files().is_empty(),
+        "the answers are forgotten once used"
+    );
+}
+
+/// Generates from a template with a pre hook never finishing, pressing Ctrl-C while it runs.
+#[cfg(unix)]
+fn interrupt_a_hook(args: &[&str]) -> (Project, std::process::Output) {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                pre = ["pre-script.rhai"]
+            "#},
+        )
+        .file("pre-script.rhai", "loop {}")
+        .build();
+
+    let dir = tempdir().build();
+
+    let generation = binary()
+        .arg_path(template.path())
+        .arg_name("interrupted-project")
+        .args(args)
+        .current_dir(dir.path())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // the folder of the project is made right before the pre hooks run
+    while !dir.exists("interrupted-project") {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    Command::new("kill")
+        .args(["-INT", &generation.id().to_string()])
+        .status()
+        .unwrap();
+
+    let output = generation.wait_with_output().unwrap();
+    (dir, output)
+}
+
+#[cfg(unix)]
+#[test]
+fn ctrl_c_stops_a_hook_and_leaves_nothing_behind() {
+    let (dir, output) = interrupt_a_hook(&[]);
+
+    assert_eq!(output.status.code(), Some(130));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Interrupted"));
+    assert!(!dir.exists("interrupted-project"));
+}
+
+#[cfg(unix)]
+#[test]
+fn ctrl_c_keeps_the_partial_project_if_asked_to() {
+    let (dir, output) = interrupt_a_hook(&["--keep-partial"]);
+
+    assert_eq!(output.status.code(), Some(130));
+    assert!(dir.exists("interrupted-project"));
+}
+
+#[test]
+fn it_runs_nothing_of_the_template_with_no_scripts() {
+    let template = tempdir()
+        .file(
+            "pre-script.rhai",
+            indoc! {r#"
+                variable::set("greeting", "hello");
+                file::write("HOOKED", "ran");
+            "#},
+        )
+        .file("upper.rhai", "content.to_upper()")
+        .file("filter.rhai", r#""filtered""#)
+        .file(
+            "README.md",
+            r#"{{ greeting | default("none") }} {{ "filter.rhai" | rhai }}"#,
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template.postprocess]
+                "README.md" = ["upper.rhai"]
+
+                [hooks]
+                pre = ["pre-script.rhai"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--no-scripts")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("pre-script.rhai, upper.rhai")
+                .and(predicates::str::contains("may be missing: greeting"))
+                .from_utf8(),
+        );
+
+    assert_eq!(dir.read("foobar-project/README.md"), "none filter.rhai");
+    assert!(!dir.exists("foobar-project/HOOKED"));
+    assert!(!dir.exists("foobar-project/pre-script.rhai"));
+}
+
+#[test]
+fn hooks_can_ignore_files_and_show_messages() {
+    let template = tempdir()
+        .file(
+            "pre-script.rhai",
+            r#"#{ ignore: ["docs", "book.toml"], message: "Skipping the docs" }"#,
+        )
+        .file("post-script.rhai", r#"#{ ignore: "CHANGELOG.md" }"#)
+        .file("docs/index.md", "docs")
+        .file("book.toml", "[book]")
+        .file("CHANGELOG.md", "changes")
+        .file("README.md", "{{project_name}}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                pre = ["pre-script.rhai"]
+                post = ["post-script.rhai"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Skipping the docs").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/README.md"), "foobar-project");
+    assert!(!dir.exists("foobar-project/docs"));
+    assert!(!dir.exists("foobar-project/book.toml"));
+    assert!(!dir.exists("foobar-project/CHANGELOG.md"));
+}
+
+#[test]
+fn a_hook_can_abort_generating() {
+    let template = tempdir()
+        .file(
+            "pre-script.rhai",
+            r#"#{ abort: "this template needs a database" }"#,
+        )
+        .file("after-script.rhai", r#"print("after the abort");"#)
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                pre = ["pre-script.rhai", "after-script.rhai"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("this template needs a database").from_utf8())
+        .stdout(predicates::str::contains("after the abort").not().from_utf8());
+}
+
+/// A template whose pre hook sets `crate_type` with `function`.
+fn template_setting_crate_type(function: &str) -> Project {
+    tempdir()
+        .file(
+            "pre-script.rhai",
+            format!(r#"variable::{function}("crate_type", "bin");"#),
+        )
+        .file("README.md", "{{ crate_type }}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                pre = ["pre-script.rhai"]
+            "#},
+        )
+        .init_git()
+        .build()
+}
+
+#[test]
+fn hooks_can_only_force_built_in_variables() {
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template_setting_crate_type("set").path())
+        .arg_name("set-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Variable crate_type is built in").from_utf8());
+
+    binary()
+        .arg_git(template_setting_crate_type("force_set").path())
+        .arg_name("forced-project")
+        .arg_branch("main")
+        .args(["--define", "crate_name=other"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("built-in variable `crate_name` is ignored")
+                .and(predicates::str::contains(
+                    "A hook overwrites the built-in variable `crate_type`",
+                ))
+                .from_utf8(),
+        );
+
+    assert_eq!(dir.read("forced-project/README.md"), "bin");
+}
+
+#[test]
+fn hooks_can_show_their_progress() {
+    let template = tempdir()
+        .file(
+            "post-script.rhai",
+            indoc! {r#"
+                progress::start("Downloading assets", 2);
+                for asset in ["logo.png", "icon.svg"] {
+                    file::write(asset, asset);
+                    progress::inc();
+                }
+                progress::finish("Downloaded assets");
+                progress::start("Building");
+            "#},
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                post = ["post-script.rhai"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/logo.png"), "logo.png");
+    assert_eq!(dir.read("foobar-project/icon.svg"), "icon.svg");
+}
+
+#[test]
+fn hooks_can_download_with_allow_network() {
+    let assets = tempdir().file("model.json", "{}").build();
+    let url = url::Url::from_file_path(assets.path().join("model.json")).unwrap();
+    let template = tempdir()
+        .file(
+            "post-script.rhai",
+            format!(
+                r#"http::download("{url}", "assets/model.json", "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a");"#
+            ),
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                post = ["post-script.rhai"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("offline-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .code(5)
+        .stderr(predicates::str::contains("needs `--allow-network`").from_utf8());
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("online-project")
+        .arg_branch("main")
+        .arg("--allow-network")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("online-project/assets/model.json"), "{}");
+}
@@ -3,6 +3,7 @@ mod helpers;
 // test modules go here
 mod basics;
 mod conditionals;
+mod crates_io;
 mod config_file;
 mod filenames;
 mod git;
@@ -10,7 +11,11 @@ mod git_instead_of;
 #[cfg(e2e_tests_with_ssh_key)]
 mod git_over_ssh;
 mod hooks_and_rhai;
+mod oci;
+mod package;
 mod public_api;
+mod render;
 mod template_config_file;
 mod template_filters;
+mod undo;
 mod workspace_member;
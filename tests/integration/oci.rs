@@ -0,0 +1,138 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::helpers::prelude::*;
+
+type Store = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+/// Starts a registry on this machine keeping blobs and manifests in memory, returning its
+/// address.
+fn start_registry() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let store = Store::default();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            handle(stream, &store);
+        }
+    });
+    address
+}
+
+fn handle(stream: TcpStream, store: &Store) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap(), parts.next().unwrap());
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut store = store.lock().unwrap();
+    let (status, headers, response) = match method {
+        "POST" if path.ends_with("/blobs/uploads/") => (
+            "202 Accepted",
+            format!("Location: {path}upload\r\n"),
+            vec![],
+        ),
+        "PUT" if path.contains("/blobs/uploads/") => {
+            let digest = query.strip_prefix("digest=").unwrap().replace("%3A", ":");
+            store.insert(digest, body);
+            ("201 Created", String::new(), vec![])
+        }
+        "PUT" => {
+            store.insert(path.to_string(), body);
+            ("201 Created", String::new(), vec![])
+        }
+        "GET" => {
+            let key = path
+                .split_once("/blobs/")
+                .map_or(path, |(_, digest)| digest)
+                .to_string();
+            match store.get(&key) {
+                Some(data) => ("200 OK", String::new(), data.clone()),
+                None => ("404 Not Found", String::new(), vec![]),
+            }
+        }
+        _ => ("405 Method Not Allowed", String::new(), vec![]),
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.len()
+    )
+    .unwrap();
+    stream.write_all(&response).unwrap();
+}
+
+#[test]
+fn it_generates_from_a_published_template() {
+    let registry = start_registry();
+    let template = tempdir()
+        .file("README.md", "{{ project_name }}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                flavor = { type = "string", prompt = "Flavor?", default = "plain" }
+            "#},
+        )
+        .file("flavor.txt", "{{ flavor }}")
+        .build();
+
+    binary()
+        .arg("publish")
+        .arg("--oci")
+        .arg(format!("{registry}/templates/readme:1.0.0"))
+        .current_dir(template.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Published template as").from_utf8());
+
+    let dir = tempdir().build();
+    binary()
+        .arg(format!("oci://{registry}/templates/readme:1.0.0"))
+        .arg_name("foobar-project")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/README.md"), "foobar-project");
+    assert_eq!(dir.read("foobar-project/flavor.txt"), "plain");
+}
+
+#[test]
+fn it_fails_for_unknown_tags() {
+    let registry = start_registry();
+    let dir = tempdir().build();
+
+    binary()
+        .arg(format!("oci://{registry}/templates/readme:9.9.9"))
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Failed to fetch the manifest of").from_utf8());
+}
@@ -0,0 +1,62 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_generates_from_a_packaged_template() {
+    let template = tempdir()
+        .file("README.md", "{{ project_name }}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                post = ["post.rhai"]
+            "#},
+        )
+        .file("post.rhai", r#"file::write("hooked.txt", "yes");"#)
+        .build();
+    let out = tempdir().build();
+
+    binary()
+        .arg("package")
+        .arg(template.path())
+        .arg("--output")
+        .arg(out.path().join("template.tar.gz"))
+        .current_dir(out.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Packaged template into").from_utf8());
+
+    let dir = tempdir().build();
+    binary()
+        .arg_path(out.path().join("template.tar.gz"))
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/README.md"), "foobar-project");
+    assert_eq!(dir.read("foobar-project/hooked.txt"), "yes");
+    assert!(!dir.exists("foobar-project/cargo-generate.sha256"));
+}
+
+#[test]
+fn it_refuses_to_package_a_template_without_its_hooks() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                pre = ["pre.rhai"]
+            "#},
+        )
+        .build();
+
+    binary()
+        .arg("package")
+        .arg(template.path())
+        .arg("--output")
+        .arg(template.path().join("template.tar.gz"))
+        .current_dir(template.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("pre.rhai").from_utf8());
+}
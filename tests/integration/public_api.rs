@@ -1,6 +1,6 @@
 use crate::helpers::prelude::*;
 
-use cargo_generate::{generate, GenerateArgs, TemplatePath};
+use cargo_generate::{generate, ColorChoice, GenerateArgs, TemplatePath};
 
 #[test]
 fn it_allows_generate_call_with_public_args_and_returns_the_generated_path() {
@@ -9,6 +9,8 @@ fn it_allows_generate_call_with_public_args_and_returns_the_generated_path() {
     let template = tempdir().init_default_template().init_git().build();
 
     let dir = tempdir().build().root.into_path();
+    // the generation and its answers are kept next to the config, which is none of the developer's
+    let config = tempdir().file("cargo-generate.toml", "").build();
 
     let args_exposed: GenerateArgs = GenerateArgs {
         template_path: TemplatePath {
@@ -27,23 +29,46 @@ fn it_allows_generate_call_with_public_args_and_returns_the_generated_path() {
         vcs: None,
         verbose: true,
         template_values_file: None,
+        values_url: None,
         silent: false,
+        defaults: false,
+        resume: false,
+        prompt_timeout: None,
+        lang: None,
+        accessible: false,
         continue_on_error: false,
+        strict: false,
         quiet: false,
         list_favorites: false,
-        config: None,
+        config: Some(config.path().join("cargo-generate.toml")),
         bin: true,
         lib: false,
         ssh_identity: None,
         gitconfig: None,
         define: vec![],
+        include_root: vec![],
         init: false,
         destination: Some(dir.clone()),
         force_git_init: false,
         allow_commands: false,
+        no_scripts: false,
+        allow_network: false,
+        preview: false,
+        keep_partial: false,
+        require_signed: false,
         overwrite: false,
         other_args: None,
+        command: None,
         skip_submodules: false,
+        timestamp: None,
+        utc_offset: None,
+        reproducible: false,
+        no_emoji: false,
+        lockfile: false,
+        regenerate_script: false,
+        profile_render: None,
+        explain_var: vec![],
+        color: ColorChoice::Auto,
     };
 
     assert_eq!(
@@ -57,6 +82,12 @@ fn it_allows_generate_call_with_public_args_and_returns_the_generated_path() {
             .contains("foobar_project")
     );
 
+    let resume_dir = config.path().join("cargo-generate-resume");
+    assert!(
+        std::fs::read_dir(resume_dir).unwrap().next().is_none(),
+        "the answers are forgotten once the project is generated"
+    );
+
     let cwd_after = std::env::current_dir().unwrap();
     assert!(cwd_after == cwd_before);
 }
@@ -0,0 +1,105 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_prints_a_single_rendered_file() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                license = { type = "string", prompt = "License?", default = "MIT" }
+            "#},
+        )
+        .file(
+            "src/main.rs",
+            "// {{ project_name }} under {{ license }} by {{ team }}",
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg("render")
+        .args(["--file", "src/main.rs"])
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "team=core"])
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout("// foobar-project under MIT by core");
+
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_fails_to_render_a_file_the_template_lacks() {
+    let template = tempdir().init_default_template().init_git().build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg("render")
+        .args(["--file", "src/nope.rs"])
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("The template has no file").from_utf8());
+}
+
+#[test]
+fn it_prints_the_value_of_an_expression() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                license = { type = "string", prompt = "License?", default = "MIT" }
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg("eval")
+        .arg("project_name | upper_camel_case ~ ' ' ~ (license == 'MIT') ~ ' ' ~ x")
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "x=1"])
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout("FoobarProject true 1\n");
+}
+
+#[test]
+fn it_fails_to_evaluate_unknown_variables() {
+    let template = tempdir().init_default_template().init_git().build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg("eval")
+        .arg("nope")
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("undefined").from_utf8());
+}
@@ -0,0 +1,98 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_derives_computed_variables_from_other_variables() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "src/lib.rs",
+            indoc! {r#"
+                pub const NAME: &str = "{{ crate_name_upper }}";
+                pub const GREETING: &str = "{{ greeting }}";
+            "#},
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                salutation = { type = "string", prompt = "Salutation?" }
+
+                [computed]
+                crate_name_upper = "{{ crate_name | shouty_snake_case }}"
+                greeting = "{{ salutation }}, {{ crate_name_upper }}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--define")
+        .arg("salutation=Hello")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let lib = dir.read("foobar-project/src/lib.rs");
+    assert!(lib.contains(r#"NAME: &str = "FOOBAR_PROJECT""#), "{lib}");
+    assert!(
+        lib.contains(r#"GREETING: &str = "Hello, FOOBAR_PROJECT""#),
+        "{lib}"
+    );
+}
+
+#[test]
+fn it_fails_on_computed_variables_using_unknown_variables() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [computed]
+                crate_name_upper = "{{ crate_nam | shouty_snake_case }}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Failed computing variable").from_utf8());
+}
+
+#[test]
+fn it_fails_on_computed_variables_shadowing_variables() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [computed]
+                crate_name = "{{ project_name | upper }}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("clashes with an existing variable").from_utf8());
+}
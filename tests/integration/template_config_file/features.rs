@@ -0,0 +1,70 @@
+use crate::helpers::prelude::*;
+
+fn template_with_features() -> Project {
+    tempdir()
+        .with_default_manifest()
+        .file("src/cli.rs", "// cli")
+        .file(
+            "README.md",
+            "{{ features | join(\",\") }} {{ format | default(\"none\") }}",
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template.features.serde]
+                default = true
+                placeholders.format = { type = "string", prompt = "Format?", default = "json" }
+                cargo.dependencies.serde = { version = "1", features = ["derive"] }
+                cargo.features.serde = ["dep:serde"]
+
+                [template.features.cli]
+                files = ["src/cli.rs"]
+                cargo.dependencies.clap = "4"
+            "#},
+        )
+        .init_git()
+        .build()
+}
+
+#[test]
+fn it_applies_the_default_features() {
+    let template = template_with_features();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let manifest = dir.read("foobar-project/Cargo.toml");
+    assert!(manifest.contains("[dependencies.serde]"), "{manifest}");
+    assert!(manifest.contains(r#"serde = ["dep:serde"]"#), "{manifest}");
+    assert!(!manifest.contains("clap"), "{manifest}");
+    assert!(!dir.exists("foobar-project/src/cli.rs"));
+    assert_eq!(dir.read("foobar-project/README.md"), "serde json");
+}
+
+#[test]
+fn it_applies_the_features_picked() {
+    let template = template_with_features();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "features=cli", "--silent"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let manifest = dir.read("foobar-project/Cargo.toml");
+    assert!(manifest.contains(r#"clap = "4""#), "{manifest}");
+    assert!(!manifest.contains("serde"), "{manifest}");
+    assert!(dir.exists("foobar-project/src/cli.rs"));
+    assert_eq!(dir.read("foobar-project/README.md"), "cli none");
+}
@@ -0,0 +1,48 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_renders_values_returned_by_template_functions() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template.functions]
+                make_port = "hooks/make_port.rhai"
+            "#},
+        )
+        .file(
+            "hooks/make_port.rhai",
+            indoc! {r#"
+                fn make_port(index) {
+                    8000 + index
+                }
+            "#},
+        )
+        .file(
+            "config.toml",
+            indoc! {r#"
+                {% for service in ["auth", "billing"] %}
+                {{ service }} = {{ make_port(loop.index) }}
+                {% endfor %}
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/config.toml"),
+        "auth = 8001\nbilling = 8002\n"
+    );
+    assert!(!dir.exists("foobar-project/hooks/make_port.rhai"));
+}
@@ -0,0 +1,295 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_generates_license_files_from_the_license_placeholder() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators]
+                license = true
+                code_of_conduct = true
+                contributing = true
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .args(["--define", "license=MIT"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("foobar-project/LICENSE")
+        .contains("Permission is hereby granted"));
+    assert!(!dir.exists("foobar-project/LICENSE-APACHE"));
+    assert!(dir.exists("foobar-project/CODE_OF_CONDUCT.md"));
+    assert!(dir
+        .read("foobar-project/CONTRIBUTING.md")
+        .starts_with("# Contributing to foobar-project"));
+}
+
+#[test]
+fn it_keeps_license_files_provided_by_the_template() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("LICENSE-MIT", "our very own license")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators]
+                license = true
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/LICENSE-MIT"),
+        "our very own license"
+    );
+    assert!(dir.exists("foobar-project/LICENSE-APACHE"));
+}
+
+#[test]
+fn it_generates_a_ci_pipeline_for_the_crate_type() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators.ci]
+                provider = "woodpecker"
+                features = ["clippy", "release"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--lib")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let pipeline = dir.read("foobar-project/.woodpecker.yml");
+    assert!(pipeline.contains("cargo clippy"));
+    assert!(pipeline.contains("cargo publish"));
+    assert!(!pipeline.contains("cargo test"));
+}
+
+#[test]
+fn it_takes_the_generated_files_from_the_limits() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators]
+                code_of_conduct = true
+                contributing = true
+            "#},
+        )
+        .init_git()
+        .build();
+    let config = tempdir()
+        .file("cargo-generate.toml", "[limits]\nmax_files = 2\n")
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--config")
+        .arg(config.path().join("cargo-generate.toml"))
+        .current_dir(dir.path())
+        .assert()
+        .code(6)
+        .stderr(predicates::str::contains("max_files").from_utf8());
+    assert!(!dir.exists("foobar-project/Cargo.toml"));
+}
+
+#[test]
+fn it_keeps_the_files_of_an_existing_project_on_init() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators]
+                code_of_conduct = true
+            "#},
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().file("CODE_OF_CONDUCT.md", "Be nice").build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .flag_init()
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("CODE_OF_CONDUCT.md"), "Be nice");
+}
+
+#[test]
+fn it_installs_a_git_pre_commit_hook_when_asked_to() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators]
+                pre_commit = "git-hook"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(dir
+        .read("foobar-project/.githooks/pre-commit")
+        .contains("cargo clippy"));
+    assert!(dir
+        .read("foobar-project/.git/config")
+        .contains("hooksPath = .githooks"));
+}
+
+#[test]
+fn it_activates_the_git_hook_in_the_repository_the_project_is_generated_into() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators]
+                pre_commit = "git-hook"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let repo = tempdir().file("member/.keep", "").init_git().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .flag_init()
+        .arg("--silent")
+        .current_dir(repo.path().join("member"))
+        .assert()
+        .success();
+
+    assert!(repo.exists("member/.githooks/pre-commit"));
+    assert!(repo
+        .read(".git/config")
+        .contains("hooksPath = member/.githooks"));
+}
+
+#[test]
+fn it_skips_the_pre_commit_hook_when_declined() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators]
+                pre_commit = "pre-commit"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "pre_commit=false"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(!dir.exists("foobar-project/.pre-commit-config.yaml"));
+}
+
+#[test]
+fn it_generates_container_files_when_asked_to() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [generators]
+                container = true
+
+                [placeholders]
+                rust_version = { type = "string", prompt = "Rust version?", default = "1.80" }
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .args(["--define", "container=true"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(dir
+        .read("foobar-project/Dockerfile")
+        .starts_with("FROM rust:1.80 AS build"));
+    assert!(dir.exists("foobar-project/.dockerignore"));
+    assert!(dir
+        .read("foobar-project/.devcontainer/devcontainer.json")
+        .contains(r#""name": "foobar-project""#));
+}
@@ -0,0 +1,71 @@
+use crate::helpers::prelude::*;
+
+fn generic_template() -> Project {
+    tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                project_type = "generic"
+            "#},
+        )
+        .file(
+            "index.html",
+            "<h1>{{ project_name }}</h1>{% if crate_name is defined %}{{ crate_name }}{% endif %}",
+        )
+        .init_git()
+        .build()
+}
+
+#[test]
+fn it_keeps_the_name_of_a_generic_project_as_given() {
+    let template = generic_template();
+    let dir = tempdir()
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [workspace]
+                members = []
+            "#},
+        )
+        .build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("My Site")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("My Site/index.html"), "<h1>My Site</h1>");
+    assert!(!dir.read("Cargo.toml").contains("My Site"));
+}
+
+#[test]
+fn it_refuses_generic_project_names_that_name_no_folder() {
+    let template = generic_template();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("site/docs")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("does not name a folder").from_utf8());
+}
+
+#[test]
+fn it_refuses_to_test_a_generic_project() {
+    let template = generic_template();
+
+    binary()
+        .arg_name("site")
+        .arg("--test")
+        .current_dir(template.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("`--test` is not allowed").from_utf8());
+}
@@ -0,0 +1,165 @@
+use crate::helpers::prelude::*;
+
+fn repository_with_shared_snippets(include_roots: &str) -> Project {
+    tempdir()
+        .file("shared/partials/header.md", "# {{ project_name }} (shared)")
+        .file(
+            "shared/macros.jinja",
+            "{% macro shout(s) %}{{ s | upper }}!{% endmacro %}",
+        )
+        .file(
+            "app/cargo-generate.toml",
+            format!(
+                indoc! {r#"
+                    [template]
+                    include_roots = {}
+                    ignore = ["partials"]
+                "#},
+                include_roots
+            ),
+        )
+        .file("app/partials/header.md", "# {{ project_name }} (own)")
+        .file(
+            "app/README.md",
+            indoc! {r#"
+                {% include "partials/header.md" %}
+                {% from "macros.jinja" import shout %}
+                {{ shout("hi") }}
+            "#},
+        )
+        .init_git()
+        .build()
+}
+
+#[test]
+fn it_includes_from_the_template_before_its_include_roots() {
+    let template = repository_with_shared_snippets(r#"["../shared"]"#);
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg("app")
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let readme = dir.read("foobar-project/README.md");
+    assert!(readme.contains("# foobar-project (own)"), "{readme}");
+    assert!(readme.contains("HI!"), "{readme}");
+    assert!(!dir.exists("foobar-project/partials"));
+}
+
+#[test]
+fn it_includes_from_the_include_roots_listed_before_the_template() {
+    let template = repository_with_shared_snippets(r#"["../shared", "."]"#);
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg("app")
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let readme = dir.read("foobar-project/README.md");
+    assert!(readme.contains("# foobar-project (shared)"), "{readme}");
+}
+
+#[test]
+fn it_includes_from_the_include_roots_given() {
+    let template = tempdir()
+        .file("README.md", r#"{% include "company/footer.md" %}"#)
+        .init_git()
+        .build();
+    let snippets = tempdir()
+        .file("company/footer.md", "Made at ACME by {{ project_name }}")
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--include-root")
+        .arg(snippets.path())
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/README.md"),
+        "Made at ACME by foobar-project"
+    );
+}
+
+#[test]
+fn it_refuses_include_roots_outside_of_the_template_repository() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                include_roots = ["../.."]
+            "#},
+        )
+        .file("README.md", "")
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Include roots must be folders of the template").from_utf8(),
+        );
+}
+
+#[test]
+fn it_extends_layouts_named_by_variables() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                ignore = ["layouts"]
+            "#},
+        )
+        .file(
+            "layouts/fancy.md",
+            "# {{ project_name }}\n{% block body %}{% endblock %}",
+        )
+        .file(
+            "README.md",
+            indoc! {r#"
+                {% extends "layouts/" ~ flavor ~ ".md" %}
+                {% block body %}A {{ flavor }} project{% endblock %}
+            "#},
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["-d", "flavor=fancy"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/README.md"),
+        "# foobar-project\nA fancy project"
+    );
+    assert!(!dir.exists("foobar-project/layouts"));
+}
@@ -158,3 +158,154 @@ fn it_warns_on_include_and_exclude_in_config() {
         .read("foobar-project/not-actually-excluded")
         .contains("foobar-project"));
 }
+
+#[test]
+fn verbose_output_tells_which_rule_decided_for_each_file() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("README.md", "{{project_name}}")
+        .file("notes.txt", "{{project_name}}")
+        .file("docs/guide.md", "{{project_name}}")
+        .file(".genignore", "*.txt\n")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                exclude = ["docs/**"]
+
+                [conditional.'true']
+                exclude = ["README.md"]
+            "#},
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg("--verbose")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("notes.txt, matching `*.txt` of `.genignore`")
+                .and(predicates::str::contains(
+                    "Copying: docs/guide.md, matching `docs/**` of `exclude` in `[template]`",
+                ))
+                .and(predicates::str::contains(
+                    "Copying: README.md, matching `README.md` of `exclude` in `[conditional.'true']`",
+                ))
+                .and(predicates::str::contains(
+                    "Rendering: Cargo.toml, matching no pattern of `exclude`",
+                ))
+                .from_utf8(),
+        );
+}
+
+#[test]
+fn braces_expand_and_negations_take_files_back() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("src/bin/main.rs", "{{project_name}}")
+        .file("src/lib/mod.rs", "{{project_name}}")
+        .file("src/lib/generated/schema.rs", "{{project_name}}")
+        .file("src/other/mod.rs", "{{project_name}}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                exclude = ["src/{bin,lib}/**", "!src/lib/{mod,lib}.rs"]
+            "#},
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/src/bin/main.rs"),
+        "{{project_name}}"
+    );
+    assert_eq!(
+        dir.read("foobar-project/src/lib/generated/schema.rs"),
+        "{{project_name}}"
+    );
+    assert_eq!(dir.read("foobar-project/src/lib/mod.rs"), "foobar-project");
+    assert_eq!(
+        dir.read("foobar-project/src/other/mod.rs"),
+        "foobar-project"
+    );
+}
+
+#[test]
+fn target_sections_only_apply_on_their_platforms() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("run.sh", "{{project_name}}")
+        .file("run.bat", "{{project_name}}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [target.'cfg(windows)'.template]
+                ignore = ["run.sh"]
+
+                [target.'cfg(not(windows))'.template]
+                ignore = ["run.bat"]
+
+                [target.'cfg(any(unix, windows))'.template]
+                exclude = ["run.*"]
+            "#},
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let (kept, ignored) = if cfg!(windows) {
+        ("run.bat", "run.sh")
+    } else {
+        ("run.sh", "run.bat")
+    };
+    assert_eq!(
+        dir.read(&format!("foobar-project/{kept}")),
+        "{{project_name}}"
+    );
+    assert!(!dir.exists(&format!("foobar-project/{ignored}")));
+}
+
+#[test]
+fn target_sections_must_be_cfg_expressions() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [target.'x86_64-pc-windows-msvc'.template]
+                ignore = ["run.sh"]
+            "#},
+        )
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("only `cfg(...)` expressions").from_utf8());
+}
@@ -0,0 +1,66 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_merges_generated_files_into_existing_ones() {
+    let template = tempdir()
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "{{ project_name }}"
+                edition = "2021"
+
+                [dependencies]
+                serde = "1"
+            "#},
+        )
+        .file(".gitignore", "*.swp\n")
+        .file("README.md", "# {{ project_name }}\n")
+        .file("src/main.rs", "fn main() {}\n")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template.merge]
+                "Cargo.toml" = "merge-toml"
+                ".gitignore" = "append"
+                "README.md" = "skip-if-exists"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir()
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "existing"
+                version = "0.1.0"
+
+                [dependencies]
+                anyhow = "1"
+            "#},
+        )
+        .file(".gitignore", "/target\n")
+        .file("README.md", "# Existing\n")
+        .build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("my-proj")
+        .arg_branch("main")
+        .flag_init()
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let cargo_toml: toml::Table = toml::from_str(&dir.read("Cargo.toml")).unwrap();
+    assert_eq!(cargo_toml["package"]["name"].as_str(), Some("existing"));
+    assert_eq!(cargo_toml["package"]["edition"].as_str(), Some("2021"));
+    assert_eq!(cargo_toml["dependencies"]["anyhow"].as_str(), Some("1"));
+    assert_eq!(cargo_toml["dependencies"]["serde"].as_str(), Some("1"));
+    assert_eq!(dir.read(".gitignore").trim_end(), "/target\n*.swp");
+    assert_eq!(dir.read("README.md"), "# Existing\n");
+    assert!(dir.exists("src/main.rs"));
+}
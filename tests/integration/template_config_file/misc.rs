@@ -113,3 +113,128 @@ fn vsc_none_can_be_specified_in_the_template() {
         .contains("foobar-project"));
     assert!(Repository::open(dir.path().join("foobar-project")).is_err());
 }
+
+#[test]
+fn liquid_era_configs_are_read_and_migrated() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                binary = { type = "string", prompt = "Binary?", default = "{{crate-name | upcase}}" }
+                "#},
+        )
+        .file("bin.txt", "{{ binary }}")
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("migrate-config").count(1).from_utf8());
+    assert_eq!(dir.read("foobar-project/bin.txt"), "FOOBAR_PROJECT");
+
+    binary()
+        .arg("migrate-config")
+        .current_dir(template.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("from version 1 to 2").from_utf8());
+    assert_eq!(
+        template.read("cargo-generate.toml"),
+        indoc! {r#"
+            config_version = 2
+
+            [placeholders]
+            binary = { type = "string", prompt = "Binary?", default = "{{crate_name | upper}}" }
+            "#}
+    );
+}
+
+#[test]
+fn configs_of_newer_versions_are_rejected() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("cargo-generate.toml", "config_version = 3\n")
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("update cargo-generate").from_utf8());
+}
+
+#[test]
+fn undefined_variables_fail_strict_rendering() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("README.md", "# {{ project_name }}\n\nBy {{ autor }}\n")
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("lenient-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert_eq!(
+        dir.read("lenient-project/README.md"),
+        "# lenient-project\n\nBy "
+    );
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("strict-project")
+        .arg("--strict")
+        .current_dir(dir.path())
+        .assert()
+        .code(6)
+        .stderr(
+            predicates::str::contains("Failed rendering strictly")
+                .and(predicates::str::contains(
+                    "README.md: undefined value `autor` at line 3, column 7",
+                ))
+                .and(predicates::str::contains("Substitution skipped").not())
+                .from_utf8(),
+        );
+}
+
+#[test]
+fn templates_can_ask_for_strict_rendering() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                strict = true
+            "#},
+        )
+        .file("{{ kind }}.rs", "fn main() {}")
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .code(6)
+        .stderr(
+            predicates::str::contains("undefined value `kind` at line 1, column 4").from_utf8(),
+        );
+}
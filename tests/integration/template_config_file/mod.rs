@@ -1,3 +1,16 @@
+mod computed;
+mod features;
+mod functions;
+mod generic;
+mod generators;
+mod includes;
 mod includes_and_excludes;
+mod merge;
 mod misc;
+mod overlay;
 mod placeholders;
+mod postprocess;
+mod signed;
+mod state;
+mod walk;
+mod workspace_template;
@@ -0,0 +1,97 @@
+use crate::helpers::prelude::*;
+
+fn overlay_template() -> Project {
+    tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                kind = "overlay"
+            "#},
+        )
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [dev-dependencies]
+                criterion = "0.5"
+            "#},
+        )
+        .file("README.md", "Overlay readme")
+        .file(
+            "benches/{{ crate_name }}.rs",
+            "// benches of {{ crate_name }}",
+        )
+        .file("src/lib.rs", "// replaced")
+        .init_git()
+        .build()
+}
+
+#[test]
+fn it_adds_an_overlay_to_an_existing_project() {
+    let template = overlay_template();
+    let project = tempdir()
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "existing"
+            "#},
+        )
+        .file("README.md", "Existing readme")
+        .file("src/lib.rs", "// existing")
+        .build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("existing")
+        .arg_branch("main")
+        .current_dir(project.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Skipping `src`").from_utf8());
+
+    let manifest = project.read("Cargo.toml");
+    assert!(manifest.contains(r#"name = "existing""#), "{manifest}");
+    assert!(manifest.contains(r#"criterion = "0.5""#), "{manifest}");
+    assert_eq!(project.read("README.md"), "Existing readme");
+    assert_eq!(project.read("src/lib.rs"), "// existing");
+    assert_eq!(
+        project.read("benches/existing.rs"),
+        "// benches of existing"
+    );
+    assert!(!project.exists(".git"));
+}
+
+#[test]
+fn it_refuses_to_add_an_overlay_outside_of_a_project() {
+    let template = overlay_template();
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("existing")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Overlay templates are added to existing projects")
+                .from_utf8(),
+        );
+}
+
+#[test]
+fn it_refuses_to_overwrite_files_with_an_overlay() {
+    let template = overlay_template();
+    let project = tempdir().file("Cargo.toml", "").build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("existing")
+        .arg_branch("main")
+        .arg("--overwrite")
+        .current_dir(project.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Overlay templates never replace files").from_utf8());
+}
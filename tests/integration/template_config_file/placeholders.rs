@@ -186,3 +186,635 @@ fn it_renders_arrays_as_list() {
         r#"["esp32", "esp32c6"]"#
     );
 }
+
+#[test]
+fn it_takes_the_default_from_a_command_if_commands_are_allowed() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("email.txt", "{{ email }}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.email]
+                type = "string"
+                prompt = "Your email?"
+                default = "nobody@example.com"
+                default_from_command = "echo someone@example.com"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    for (name, allow_commands, email) in [
+        ("allowed", true, "someone@example.com"),
+        ("denied", false, "nobody@example.com"),
+    ] {
+        let mut cmd = binary();
+        cmd.arg_git(template.path())
+            .arg_name(name)
+            .arg_branch("main")
+            .arg("--silent");
+        if allow_commands {
+            cmd.arg("--allow-commands");
+        }
+        cmd.current_dir(dir.path()).assert().success();
+
+        assert_eq!(dir.read(&format!("{name}/email.txt")), email);
+    }
+}
+
+#[test]
+fn it_transforms_placeholder_values_before_validating_them() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("service.txt", "{{ service }}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.service]
+                type = "string"
+                prompt = "Service name?"
+                regex = "^[a-z][a-z0-9_]*$"
+                transform = ["trim", "snake_case"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--define")
+        .arg("service=  Billing Service ")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/service.txt"), "billing_service");
+}
+
+#[test]
+fn it_renders_the_defaults_of_placeholders_with_the_values_before() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("description.txt", "{{ description }}")
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.kind]
+                type = "string"
+                prompt = "Kind?"
+                choices = ["library", "service"]
+                default = "library"
+
+                [placeholders.description]
+                type = "string"
+                prompt = "Description of the {{ kind }}?"
+                default = "The {{ project_name }} {{ kind }}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .arg("--define")
+        .arg("kind=service")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/description.txt"),
+        "The foobar-project service"
+    );
+}
+
+#[test]
+fn it_fails_rendering_prompts_with_unknown_variables() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.description]
+                type = "string"
+                prompt = "Description of the {{ kind }}?"
+                default = "none"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Failed rendering the prompt of placeholder")
+                .and(predicates::str::contains("description"))
+                .from_utf8(),
+        );
+}
+
+#[test]
+fn it_tells_which_rule_rejected_a_value() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.port]
+                type = "string"
+                prompt = "Port?"
+                regex = "^[0-9]{2,5}$"
+                regex_description = "a port has between 2 and 5 digits"
+
+                [placeholders.edition]
+                type = "string"
+                prompt = "Edition?"
+                regex = "^20[0-9]{2}$"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "port=8"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains(
+            "is not a valid value for port: a port has between 2 and 5 digits",
+        ));
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("other-project")
+        .arg_branch("main")
+        .args(["--define", "port=8080", "--define", "edition=21"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains(
+            "is not a valid value for edition: it must match `^20[0-9]{2}$`",
+        ));
+}
+
+#[test]
+fn it_validates_email_url_and_ident_placeholders() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.maintainer]
+                type = "email"
+                prompt = "Maintainer?"
+
+                [placeholders.homepage]
+                type = "url"
+                prompt = "Homepage?"
+
+                [placeholders.service]
+                type = "ident"
+                prompt = "Service?"
+                transform = ["snake_case"]
+            "#},
+        )
+        .file("info.txt", "{{ maintainer }} {{ homepage }} {{ service }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "maintainer=me@localhost"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains("the domain `localhost` has no `.`"));
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("other-project")
+        .arg_branch("main")
+        .args(["--define", "maintainer=me@example.com"])
+        .args(["--define", "homepage=https://example.com:8080/docs"])
+        .args(["--define", "service=Auth Service"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("other-project/info.txt"),
+        "me@example.com https://example.com:8080/docs auth_service"
+    );
+}
+
+#[test]
+fn it_fails_without_a_terminal_on_a_rejected_bool() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.docker]
+                type = "bool"
+                prompt = "Docker?"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "docker=maybe"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains(
+            "is not a valid value for docker: it must be `true` or `false`",
+        ));
+}
+
+#[test]
+fn it_lists_the_choices_of_a_rejected_value() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.mcu]
+                type = "string"
+                prompt = "Which MCU to target?"
+                choices = ["esp32", "rp2040"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "mcu=avr"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains("it must be one of `esp32`, `rp2040`"));
+}
+
+#[test]
+fn it_nests_the_values_of_dotted_placeholders() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders."database.host"]
+                prompt = "Host?"
+                default = "localhost"
+
+                [placeholders."database.port"]
+                prompt = "Port?"
+                default = "5432"
+            "#},
+        )
+        .file("config.toml", "url = \"{{ database.host }}:{{ database.port }}\"")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "database.host=db.example.com"])
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/config.toml"),
+        "url = \"db.example.com:5432\""
+    );
+}
+
+#[test]
+fn it_takes_the_defaults_and_asks_only_for_the_others() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.license]
+                prompt = "License?"
+                default = "MIT"
+
+                [placeholders.author]
+                prompt = "Author?"
+            "#},
+        )
+        .file("LICENSE", "{{ license }} by {{ author }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--defaults", "--define", "author=me"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(contains("license: \"MIT\" (default value from template)"));
+
+    assert_eq!(dir.read("foobar-project/LICENSE"), "MIT by me");
+
+    // without a terminal, the author can't be asked for
+    binary()
+        .arg_git(template.path())
+        .arg_name("other-project")
+        .arg_branch("main")
+        .arg("--defaults")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains("not a terminal"));
+}
+
+#[test]
+fn placeholders_and_hook_variables_have_both_spellings() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.license-kind]
+                type = "string"
+                prompt = "License?"
+
+                [hooks]
+                pre = ["pre-script.rhai"]
+            "#},
+        )
+        .file(
+            "pre-script.rhai",
+            indoc! {r#"
+                variable::set("ci_provider", variable::get("license_kind") + "-ci");
+                variable::set("os-family", variable::get("os_arch"));
+            "#},
+        )
+        .file(
+            "README.md",
+            "{{ license_kind }} {{ ci_provider }} {{ os_family == os_arch }}",
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "license-kind=MIT"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/README.md"), "MIT MIT-ci true");
+}
+
+#[test]
+fn it_tells_the_rule_in_the_language_asked_in() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.port]
+                type = "string"
+                prompt.en = "Port?"
+                prompt.de = "Port?"
+                regex = "^[0-9]{2,5}$"
+                regex_description.en = "a port has between 2 and 5 digits"
+                regex_description.de = "ein Port hat 2 bis 5 Ziffern"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    for (name, lang, locale, description) in [
+        ("de-project", None, "de_DE.UTF-8", "ein Port hat 2 bis 5 Ziffern"),
+        ("at-project", Some("de-AT"), "fr_FR.UTF-8", "ein Port hat 2 bis 5 Ziffern"),
+        ("fr-project", None, "fr_FR.UTF-8", "a port has between 2 and 5 digits"),
+    ] {
+        binary()
+            .arg_git(template.path())
+            .arg_name(name)
+            .arg_branch("main")
+            .args(["--define", "port=8"])
+            .args(lang.map(|lang| format!("--lang={lang}")))
+            .current_dir(dir.path())
+            .env("LC_ALL", locale)
+            .assert()
+            .failure()
+            .stderr(contains(format!(
+                "is not a valid value for port: {description}"
+            )));
+    }
+}
+
+#[test]
+fn it_asks_plain_questions_with_numbered_choices_when_accessible() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.owner]
+                type = "string"
+                prompt = "Owner?"
+
+                [placeholders.license]
+                type = "string"
+                prompt = "License?"
+                choices = ["MIT", "Apache-2.0", "GPL-3.0"]
+                default = "MIT"
+
+                [placeholders.ci]
+                type = "bool"
+                prompt = "CI?"
+                default = true
+
+                [placeholders.platforms]
+                type = "array"
+                prompt = "Platforms?"
+                choices = ["linux", "macos", "windows"]
+            "#},
+        )
+        .file(
+            "answers.txt",
+            "{{ owner }} {{ license }} {{ ci }} {{ platforms | join(\",\") }}",
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+    let cargo_home = tempdir().build();
+
+    // answered on stdin, as no terminal is needed to ask plain questions
+    assert_cmd::Command::cargo_bin("cargo-generate-mj")
+        .unwrap()
+        .arg("generate-mj")
+        .env("CARGO_HOME", cargo_home.path())
+        .arg("--git")
+        .arg(template.path())
+        .args(["--branch", "main", "--accessible"])
+        .current_dir(dir.path())
+        .write_stdin("foobar-project\nAcme\n7\n2\n\nlinux, 3\n")
+        .assert()
+        .success()
+        .stderr(contains("1) MIT\n2) Apache-2.0\n3) GPL-3.0\n"))
+        .stderr(contains("7 is not a choice"))
+        .stderr(contains("⠁").not());
+
+    assert_eq!(
+        dir.read("foobar-project/answers.txt"),
+        "Acme Apache-2.0 true linux,windows"
+    );
+}
+
+#[test]
+fn it_takes_the_defaults_of_prompts_nobody_answers_in_time() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.license]
+                type = "string"
+                prompt = "License?"
+                choices = ["MIT", "Apache-2.0"]
+                default = "Apache-2.0"
+
+                [placeholders.owner]
+                type = "string"
+                prompt = "Owner?"
+                default = "Acme"
+            "#},
+        )
+        .file("answers.txt", "{{ license }} {{ owner }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+    let cargo_home = tempdir().build();
+
+    // the prompts wait on an input that stays open, but is never written to
+    let mut child = Command::cargo_bin("cargo-generate-mj")
+        .unwrap()
+        .arg("generate-mj")
+        .env("CARGO_HOME", cargo_home.path())
+        .arg("--git")
+        .arg(template.path())
+        .args(["--branch", "main", "--name", "foobar-project"])
+        .args(["--accessible", "--prompt-timeout", "1"])
+        .current_dir(dir.path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let _stdin = child.stdin.take();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Nobody answered the prompt for `license` within 1s"),
+        "{stdout}"
+    );
+    assert_eq!(dir.read("foobar-project/answers.txt"), "Apache-2.0 Acme");
+}
+
+#[test]
+fn it_reads_the_answers_of_prompts_that_time_out_a_line_at_a_time() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders.license]
+                type = "string"
+                prompt = "License?"
+                choices = ["MIT", "Apache-2.0"]
+                default = "Apache-2.0"
+
+                [placeholders.owner]
+                type = "string"
+                prompt = "Owner?"
+                default = "Acme"
+            "#},
+        )
+        .file("answers.txt", "{{ license }} {{ owner }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+    let cargo_home = tempdir().build();
+
+    // asked as plain questions, with no terminal needed
+    assert_cmd::Command::cargo_bin("cargo-generate-mj")
+        .unwrap()
+        .arg("generate-mj")
+        .env("CARGO_HOME", cargo_home.path())
+        .arg("--git")
+        .arg(template.path())
+        .args(["--branch", "main", "--name", "foobar-project"])
+        .args(["--prompt-timeout", "10"])
+        .current_dir(dir.path())
+        .write_stdin("1\nInitech\n")
+        .assert()
+        .success()
+        .stderr(contains("1) MIT\n2) Apache-2.0\n"));
+
+    assert_eq!(dir.read("foobar-project/answers.txt"), "MIT Initech");
+}
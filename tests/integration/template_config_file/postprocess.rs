@@ -0,0 +1,69 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_postprocesses_rendered_files_by_glob() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file("data.json", r#"{"name":"{{ project_name }}"}"#)
+        .file("notes.md", "# {{ project_name }}   \nsome notes\t")
+        .file(
+            "shout.rhai",
+            r#"if path.ends_with(".md") { content.to_upper() } else { content }"#,
+        )
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template.postprocess]
+                "*.json" = ["json-pretty"]
+                "*.md" = ["strip-trailing-whitespace", "ensure-final-newline", "shout.rhai"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/data.json"),
+        "{\n  \"name\": \"foobar-project\"\n}\n"
+    );
+    assert_eq!(
+        dir.read("foobar-project/notes.md"),
+        "# FOOBAR-PROJECT\nSOME NOTES\n"
+    );
+    assert!(!dir.exists("foobar-project/shout.rhai"));
+}
+
+#[test]
+fn it_fails_on_unknown_postprocessors() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template.postprocess]
+                "*.ts" = ["prettier"]
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown postprocessor `prettier`").from_utf8());
+}
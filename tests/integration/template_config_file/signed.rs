@@ -0,0 +1,171 @@
+use std::path::Path;
+
+use crate::helpers::prelude::*;
+
+/// Creates a key pair in `dir`, returning the public key.
+fn create_key(dir: &Path) -> String {
+    let key = dir.join("key");
+    let status = Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "author", "-f"])
+        .arg(&key)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::read_to_string(key.with_extension("pub"))
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+fn sign(key_dir: &Path, template: &Path, files: &[&str]) {
+    let status = Command::new("ssh-keygen")
+        .args(["-q", "-Y", "sign", "-n", "cargo-generate", "-f"])
+        .arg(key_dir.join("key"))
+        .args(files)
+        .current_dir(template)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn signed_template() -> Project {
+    tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [hooks]
+                pre = ["pre.rhai"]
+            "#},
+        )
+        .file("pre.rhai", r#"file::write("hooked.txt", "yes");"#)
+        .file("README.md", "{{ project_name }}")
+        .build()
+}
+
+fn config_trusting(key: &str) -> Project {
+    tempdir()
+        .file(
+            "cargo-generate",
+            format!(
+                indoc! {r#"
+                    [defaults]
+                    trusted_keys = ["{}"]
+                "#},
+                key
+            ),
+        )
+        .build()
+}
+
+#[test]
+fn it_runs_the_hooks_of_signed_templates() {
+    let keys = tempdir().build();
+    let key = create_key(keys.path());
+    let config = config_trusting(&key);
+    let template = signed_template();
+    sign(
+        keys.path(),
+        template.path(),
+        &["cargo-generate.toml", "pre.rhai"],
+    );
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg("--config")
+        .arg(config.path().join("cargo-generate"))
+        .arg_path(template.path())
+        .arg_name("foobar-project")
+        .arg("--require-signed")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/hooked.txt"), "yes");
+    assert!(!dir.exists("foobar-project/cargo-generate.toml.sig"));
+    assert!(!dir.exists("foobar-project/pre.rhai.sig"));
+}
+
+#[test]
+fn it_refuses_unsigned_hooks() {
+    let keys = tempdir().build();
+    let key = create_key(keys.path());
+    let config = config_trusting(&key);
+    let template = signed_template();
+    sign(keys.path(), template.path(), &["cargo-generate.toml"]);
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg("--config")
+        .arg(config.path().join("cargo-generate"))
+        .arg_path(template.path())
+        .arg_name("foobar-project")
+        .arg("--require-signed")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("found no signature of `pre.rhai`").from_utf8());
+
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_refuses_templates_changed_after_signing() {
+    let keys = tempdir().build();
+    let key = create_key(keys.path());
+    let config = config_trusting(&key);
+    let template = signed_template();
+    sign(
+        keys.path(),
+        template.path(),
+        &["cargo-generate.toml", "pre.rhai"],
+    );
+    fs::write(
+        template.path().join("pre.rhai"),
+        r#"file::write("hooked.txt", "tampered");"#,
+    )
+    .unwrap();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg("--config")
+        .arg(config.path().join("cargo-generate"))
+        .arg_path(template.path())
+        .arg_name("foobar-project")
+        .arg("--require-signed")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid signature of `pre.rhai`").from_utf8());
+}
+
+#[test]
+fn it_refuses_templates_signed_by_untrusted_keys() {
+    let keys = tempdir().build();
+    create_key(keys.path());
+    let other_keys = tempdir().build();
+    let config = config_trusting(&create_key(other_keys.path()));
+    let template = signed_template();
+    sign(
+        keys.path(),
+        template.path(),
+        &["cargo-generate.toml", "pre.rhai"],
+    );
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg("--config")
+        .arg(config.path().join("cargo-generate"))
+        .arg_path(template.path())
+        .arg_name("foobar-project")
+        .arg("--require-signed")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Invalid signature of `cargo-generate.toml`").from_utf8(),
+        );
+}
@@ -0,0 +1,93 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_renders_files_matching_render_last_with_the_state_of_all_others() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                render_last = ["**/all.rs"]
+            "#},
+        )
+        .file(
+            "{{crate_name}}_services/all.rs",
+            indoc! {r#"
+                {% for service in state.services %}
+                mod {{ service }};
+                {% endfor %}
+            "#},
+        )
+        .file(
+            "{{crate_name}}_services/auth.rs",
+            indoc! {r#"
+                {{ state.append("services", "auth") }}
+                const PORT: u16 = {{ state.incr("port", 8000) }};
+            "#},
+        )
+        .file(
+            "{{crate_name}}_services/billing.rs",
+            indoc! {r#"
+                {{ state.append("services", "billing") }}
+                const PORT: u16 = {{ state.incr("port", 8000) }};
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/foobar_project_services/all.rs"),
+        "mod auth;\nmod billing;\n"
+    );
+    assert_eq!(
+        dir.read("foobar-project/foobar_project_services/billing.rs"),
+        "\nconst PORT: u16 = 8001;"
+    );
+    assert!(!dir.exists("foobar-project/{{crate_name}}_services"));
+}
+
+#[test]
+fn it_declares_the_modules_generated_in_a_folder() {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                render_last = ["src/services/mod.rs"]
+            "#},
+        )
+        .file("src/services/mod.rs", "{{ mods(\"src/services\") }}")
+        .file("src/services/{{ each service in services }}.rs", "")
+        .file("src/services/admin/mod.rs", "")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--define")
+        .arg("services=auth,billing")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/src/services/mod.rs"),
+        "mod admin;\nmod auth;\nmod billing;\n"
+    );
+}
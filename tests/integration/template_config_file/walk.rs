@@ -0,0 +1,201 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_copies_files_below_max_depth_as_they_are() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                max_depth = 2
+            "#},
+        )
+        .file("top.txt", "{{ project_name }}")
+        .file("a/middle.txt", "{{ project_name }}")
+        .file("a/b/deep.txt", "{{ project_name }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/top.txt"), "foobar-project");
+    assert_eq!(dir.read("foobar-project/a/middle.txt"), "foobar-project");
+    assert_eq!(
+        dir.read("foobar-project/a/b/deep.txt"),
+        "{{ project_name }}"
+    );
+}
+
+#[test]
+fn it_copies_hidden_files_as_they_are_unless_rendered() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                render_hidden = false
+            "#},
+        )
+        .file("visible.txt", "{{ project_name }}")
+        .file(".hidden.txt", "{{ project_name }}")
+        .file(".vendor/lib.rs", "{{ project_name }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/visible.txt"), "foobar-project");
+    assert_eq!(dir.read("foobar-project/.hidden.txt"), "{{ project_name }}");
+    assert_eq!(
+        dir.read("foobar-project/.vendor/lib.rs"),
+        "{{ project_name }}"
+    );
+}
+
+#[test]
+fn it_moves_files_not_walked_along_with_their_renamed_folder() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                render_hidden = false
+            "#},
+        )
+        .file("{{ project_name }}/src/lib.rs", "// {{ project_name }}")
+        .file("{{ project_name }}/.hidden.txt", "{{ project_name }}")
+        .file("{{ project_name }}/src/.hidden.txt", "{{ project_name }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/foobar-project/src/lib.rs"),
+        "// foobar-project"
+    );
+    assert_eq!(
+        dir.read("foobar-project/foobar-project/.hidden.txt"),
+        "{{ project_name }}"
+    );
+    assert_eq!(
+        dir.read("foobar-project/foobar-project/src/.hidden.txt"),
+        "{{ project_name }}"
+    );
+    assert!(!dir.exists("foobar-project/{{ project_name }}"));
+}
+
+#[cfg(unix)]
+#[test]
+fn it_renders_symlinked_folders_when_following_symlinks() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                follow_symlinks = true
+            "#},
+        )
+        .file("shared/name.txt", "{{ project_name }}")
+        .build();
+    std::os::unix::fs::symlink("shared", template.path().join("linked")).unwrap();
+    for args in [
+        &["init"][..],
+        &["add", "--all"],
+        &[
+            "-c",
+            "user.name=Foo",
+            "-c",
+            "user.email=foo@bar.com",
+            "commit",
+            "-m",
+            "initial",
+        ],
+    ] {
+        Command::new("git")
+            .args(args)
+            .current_dir(template.path())
+            .assert()
+            .success();
+    }
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/shared/name.txt"), "foobar-project");
+    assert_eq!(dir.read("foobar-project/linked/name.txt"), "foobar-project");
+}
+
+#[test]
+fn it_keeps_the_template_files_of_the_keep_source_globs() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [template]
+                exclude = ["config/raw.toml"]
+
+                [template.keep_source]
+                "config/local.toml" = false
+                "config/*.toml" = true
+            "#},
+        )
+        .file("config/{{ project_name }}.toml", "name = \"{{ project_name }}\"")
+        .file("config/local.toml", "{{ project_name }}")
+        .file("config/raw.toml", "{{ project_name }}")
+        .file("README.md", "{{ project_name }}")
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/config/foobar-project.toml"),
+        "name = \"foobar-project\""
+    );
+    assert_eq!(
+        dir.read("foobar-project/.template/config/{{ project_name }}.toml"),
+        "name = \"{{ project_name }}\""
+    );
+    assert!(!dir.exists("foobar-project/.template/config/local.toml"));
+    assert!(!dir.exists("foobar-project/.template/config/raw.toml"));
+    assert!(!dir.exists("foobar-project/.template/README.md"));
+}
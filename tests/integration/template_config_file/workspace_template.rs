@@ -0,0 +1,83 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_generates_all_members_of_a_workspace_template() {
+    let template = tempdir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [workspace_template]
+                members = [
+                    { template = "members/core", name_placeholder = "core_name", directory = "crates" },
+                    { template = "members/cli", name_placeholder = "cli_name", directory = "crates" },
+                ]
+
+                [placeholders]
+                core_name = { type = "string", prompt = "Name of the core crate?", default = "core" }
+                cli_name = { type = "string", prompt = "Name of the cli crate?", default = "cli" }
+            "#},
+        )
+        .file("README.md", "# {{ project_name }}")
+        .file(
+            "members/core/Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "{{ project_name }}"
+                version = "0.1.0"
+            "#},
+        )
+        .file("members/core/src/lib.rs", "// {{ crate_name }}")
+        .file(
+            "members/cli/cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                greeting = { type = "string", prompt = "Greeting?", default = "hello" }
+            "#},
+        )
+        .file(
+            "members/cli/Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "{{ project_name }}"
+                version = "0.1.0"
+
+                [dependencies]
+                {{ core_name }} = { path = "../{{ core_name }}" }
+            "#},
+        )
+        .file(
+            "members/cli/src/main.rs",
+            r#"fn main() { println!("{{ greeting }} from {{ crate_name }} of {{ project_name }}"); }"#,
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--silent")
+        .args(["--define", "cli_name=foobar-cli"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/README.md"), "# foobar-project");
+    assert_eq!(dir.read("foobar-project/crates/core/src/lib.rs"), "// core");
+    assert!(dir
+        .read("foobar-project/crates/foobar-cli/Cargo.toml")
+        .contains(r#"core = { path = "../core" }"#));
+    assert!(dir
+        .read("foobar-project/crates/foobar-cli/src/main.rs")
+        .contains("hello from foobar_cli of foobar-cli"));
+    assert!(!dir.exists("foobar-project/members"));
+    assert!(!dir.exists("foobar-project/crates/foobar-cli/cargo-generate.toml"));
+
+    let manifest = dir.read("foobar-project/Cargo.toml");
+    assert!(manifest.contains("[workspace]"));
+    assert!(manifest.contains(r#""crates/core""#));
+    assert!(manifest.contains(r#""crates/foobar-cli""#));
+}
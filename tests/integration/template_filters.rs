@@ -134,3 +134,113 @@ without_suffix = {{crate_name | split("_") | first}}
     assert!(cargo_toml.contains("upper_camel_case = SomeText"));
     assert!(!cargo_toml.contains("without_suffix = foobar_project"));
 }
+
+#[test]
+fn it_escapes_values_embedded_in_strings() {
+    let template = tempdir()
+        .file(
+            "settings.toml",
+            r#"description = "{{ description | toml_escape }}""#,
+        )
+        .file(
+            "settings.json",
+            r#"{ "description": "{{ description | json_escape }}" }"#,
+        )
+        .file("run.sh", "cd {{ description | shell_quote }}")
+        .init_git()
+        .build();
+    let dir = tempdir().build();
+    let description = "say \"hi\", it's C:\\";
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--define")
+        .arg(format!("description={description}"))
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let settings: toml::Table = toml::from_str(&dir.read("foobar-project/settings.toml")).unwrap();
+    assert_eq!(settings["description"].as_str(), Some(description));
+    let settings: serde_json::Value =
+        serde_json::from_str(&dir.read("foobar-project/settings.json")).unwrap();
+    assert_eq!(settings["description"].as_str(), Some(description));
+    assert_eq!(
+        dir.read("foobar-project/run.sh"),
+        "cd 'say \"hi\", it'\\''s C:\\'"
+    );
+}
+
+#[test]
+fn it_renders_badges_for_the_inferred_repository() -> anyhow::Result<()> {
+    let template = tempdir()
+        .with_default_manifest()
+        .file(
+            "README.md",
+            indoc! {r#"
+                {{ badge("crates-io", crate_name) }}
+                {{ badge("ci", repository) }}
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+    let outer_repo = git2::Repository::init(dir.path())?;
+    outer_repo.remote("origin", "git@github.com:foo/workspace.git")?;
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let readme = dir.read("foobar-project/README.md");
+    assert!(readme.contains("(https://crates.io/crates/foobar_project)"));
+    assert!(readme.contains("(https://github.com/foo/workspace/actions/workflows/ci.yml)"));
+    Ok(())
+}
+
+#[test]
+fn get_falls_back_to_the_default_of_missing_nested_values() {
+    let template = tempdir()
+        .file(
+            "ci.toml",
+            indoc! {r#"
+                provider = "{{ get("ci.provider", "github") }}"
+                runner = "{{ get("ci.runner.os", "ubuntu-latest") }}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tempdir().build();
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .args(["--define", "ci.provider=gitlab"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    binary()
+        .arg_git(template.path())
+        .arg_name("other-project")
+        .arg_branch("main")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("foobar-project/ci.toml"),
+        "provider = \"gitlab\"\nrunner = \"ubuntu-latest\""
+    );
+    assert_eq!(
+        dir.read("other-project/ci.toml"),
+        "provider = \"github\"\nrunner = \"ubuntu-latest\""
+    );
+}
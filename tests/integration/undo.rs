@@ -0,0 +1,59 @@
+use crate::helpers::prelude::*;
+
+#[test]
+fn it_removes_the_project_generated_last() {
+    let template = tempdir().init_default_template().init_git().build();
+    let config = tempdir().file("cargo-generate.toml", "").build();
+    let config_path = config.path().join("cargo-generate.toml");
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--config")
+        .arg(&config_path)
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(dir.exists("foobar-project/Cargo.toml"));
+
+    binary()
+        .arg("undo")
+        .arg("--config")
+        .arg(&config_path)
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Removed").from_utf8());
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_keeps_a_project_changed_since_it_was_generated() {
+    let template = tempdir().init_default_template().init_git().build();
+    let config = tempdir().file("cargo-generate.toml", "").build();
+    let config_path = config.path().join("cargo-generate.toml");
+    let dir = tempdir().build();
+
+    binary()
+        .arg_git(template.path())
+        .arg_name("foobar-project")
+        .arg_branch("main")
+        .arg("--config")
+        .arg(&config_path)
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    std::fs::write(dir.path().join("foobar-project/notes.txt"), "").unwrap();
+
+    binary()
+        .arg("undo")
+        .arg("--config")
+        .arg(&config_path)
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("notes.txt").from_utf8());
+    assert!(dir.exists("foobar-project/Cargo.toml"));
+}
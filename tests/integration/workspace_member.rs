@@ -49,3 +49,60 @@ fn it_should_add_a_new_project_to_the_workspace_members() {
             "c",
         ]"#}));
 }
+
+#[test]
+fn it_refuses_crate_names_taken_in_the_workspace() {
+    let workspace_project = tempdir()
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [workspace]
+                members = ["crates/*"]
+
+                [workspace.dependencies]
+                shared = { path = "../shared" }
+            "#},
+        )
+        .file(
+            "crates/core/Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "my-core"
+                version = "0.1.0"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let template = tempdir()
+        .file(
+            "Cargo.toml",
+            indoc! {r#"
+                [package]
+                name = "{{project_name}}"
+                version = "0.1.0"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    for taken in ["my_core", "shared"] {
+        binary()
+            .arg_name(taken)
+            .arg_path(template.path())
+            .arg("--silent")
+            .current_dir(workspace_project.path())
+            .assert()
+            .code(4)
+            .stderr(predicates::str::contains("is already taken in the workspace").from_utf8());
+        assert!(!workspace_project.exists(taken));
+    }
+
+    binary()
+        .arg_name("my-cli")
+        .arg_path(template.path())
+        .arg("--silent")
+        .current_dir(workspace_project.path())
+        .assert()
+        .success();
+}